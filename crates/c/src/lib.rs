@@ -7,11 +7,13 @@ use std::fmt::Write;
 use std::mem;
 use wit_bindgen_core::abi::{self, AbiVariant, Bindgen, Bitcast, Instruction, LiftLower, WasmType};
 use wit_bindgen_core::{
-    dealias, uwrite, uwriteln, wit_parser::*, AnonymousTypeGenerator, Direction, Files,
-    InterfaceGenerator as _, Ns, WorldGenerator,
+    dealias, uwrite, uwriteln, wit_parser::*, AnonymousTypeGenerator, Case, Direction, Files,
+    InterfaceGenerator as _, Keywords, Ns, Renames, WorldGenerator,
 };
 use wit_component::StringEncoding;
 
+pub use wit_bindgen_core::AsyncConfig;
+
 #[derive(Default)]
 struct C {
     src: Source,
@@ -26,15 +28,21 @@ struct C {
     needs_union_float_int32: bool,
     needs_union_int64_double: bool,
     needs_union_double_int64: bool,
+    needs_error_context: bool,
+    needs_async: bool,
     prim_names: HashSet<String>,
     world: String,
     sizes: SizeAlign,
-    renamed_interfaces: HashMap<WorldKey, String>,
+    renames: Renames,
 
     world_id: Option<WorldId>,
     dtor_funcs: HashMap<TypeId, String>,
     type_names: HashMap<TypeId, String>,
     resources: HashMap<TypeId, ResourceInfo>,
+
+    /// Futures and streams whose helper functions have already been defined,
+    /// by type name.
+    payload_helpers: HashSet<String>,
 }
 
 #[derive(Default)]
@@ -62,6 +70,37 @@ impl std::fmt::Display for Enabled {
     }
 }
 
+/// How the generated bindings allocate and free memory.
+#[derive(Default, Debug, Eq, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Allocator {
+    /// Use `malloc`, `realloc` and `free` from libc.
+    #[default]
+    Libc,
+    /// Use the `WIT_MALLOC` and `WIT_FREE` macros, which default to calling
+    /// the user-provided `wit_malloc` and `wit_free` functions.
+    ///
+    /// `WIT_MALLOC(size, align)` must return memory aligned to `align`, which
+    /// is always a power of two; canonical ABI values may require more than
+    /// the alignment of `max_align_t`. `WIT_FREE(ptr)` is only passed pointers
+    /// returned by `WIT_MALLOC`.
+    Custom,
+    /// Bump-allocate from an arena whose chunks are obtained like `Custom`.
+    /// Generated frees do nothing; memory is released in bulk by
+    /// `<world>_arena_reset`.
+    Arena,
+}
+
+impl std::fmt::Display for Allocator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Libc => write!(f, "libc"),
+            Self::Custom => write!(f, "custom"),
+            Self::Arena => write!(f, "arena"),
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 #[cfg_attr(feature = "clap", derive(clap::Args))]
 pub struct Opts {
@@ -83,8 +122,12 @@ pub struct Opts {
     #[cfg_attr(feature = "clap", arg(long, default_value_t = false))]
     pub no_object_file: bool,
 
-    /// Rename the interface `K` to `V` in the generated source code.
-    #[cfg_attr(feature = "clap", arg(long, name = "K=V", value_parser = parse_rename))]
+    /// Rename the interface or item `K` to `V` in the generated source code.
+    ///
+    /// `K` is either an interface such as `foo:bar/baz`, an item within an
+    /// interface such as `foo:bar/baz.qux`, or an item defined in the world.
+    /// Renamed items are still prefixed with the name of their interface.
+    #[cfg_attr(feature = "clap", arg(long, name = "K=V", value_parser = Renames::parse))]
     pub rename: Vec<(String, String)>,
 
     /// Rename the world in the generated source code and file names.
@@ -99,16 +142,44 @@ pub struct Opts {
     /// Configure the autodropping of borrows in exported functions.
     #[cfg_attr(feature = "clap", arg(long, default_value_t = Enabled::default()))]
     pub autodrop_borrows: Enabled,
-}
 
-#[cfg(feature = "clap")]
-fn parse_rename(name: &str) -> Result<(String, String)> {
-    let mut parts = name.splitn(2, '=');
-    let to_rename = parts.next().unwrap();
-    match parts.next() {
-        Some(part) => Ok((to_rename.to_string(), part.to_string())),
-        None => anyhow::bail!("`--rename` option must have an `=` in it (e.g. `--rename a=b`)"),
-    }
+    /// Determines which functions to lift or lower `async`, if any.
+    ///
+    /// Accepted values are:
+    ///     - none
+    ///     - all
+    ///     - some=<value>[,<value>...], where each <value> is of the form:
+    ///         - import:<selector> or
+    ///         - export:<selector>
+    ///
+    /// Async imports return the status of the subtask they start and take
+    /// pointers to the caller's argument and result buffers, whose values are
+    /// lowered and lifted as needed. Results, and values read from futures and
+    /// streams, may only be delivered once `<world>_task_wait` or the callback
+    /// of an async export sees the corresponding event. Async exports return a
+    /// context pointer for the `*_callback` function they require and deliver
+    /// their results through the generated `*_return` function.
+    #[cfg_attr(feature = "clap", arg(long = "async", value_parser = AsyncConfig::parse, default_value = "none"))]
+    pub async_: AsyncConfig,
+
+    /// Configure how `cabi_realloc`, string helpers and generated `*_free`
+    /// functions allocate and release memory.
+    ///
+    /// With `arena`, results returned from exports must themselves be
+    /// allocated through `<world>_arena_alloc` (or helpers such as
+    /// `<world>_string_dup`), and the arena should be reset once lifted
+    /// values are no longer needed and no async operations are in progress.
+    #[cfg_attr(feature = "clap", arg(long, default_value_t = Allocator::default()))]
+    pub allocator: Allocator,
+
+    /// Validate values lifted from the canonical ABI.
+    ///
+    /// Out-of-range integers, invalid `char` and `bool` values and invalid
+    /// enum, variant, option or result discriminants invoke
+    /// `WIT_LIFT_ERROR(message)`, which aborts unless the macro is defined
+    /// when compiling the generated C file.
+    #[cfg_attr(feature = "clap", arg(long, default_value_t = Enabled::default()))]
+    pub checked_lifts: Enabled,
 }
 
 impl Opts {
@@ -125,6 +196,7 @@ struct Return {
     retptrs: Vec<Type>,
 }
 
+#[derive(Default)]
 struct CSig {
     name: String,
     sig: String,
@@ -151,23 +223,10 @@ impl WorldGenerator for C {
         self.sizes.fill(resolve);
         self.world_id = Some(world);
 
-        let mut interfaces = HashMap::new();
-        let world = &resolve.worlds[world];
-        for (key, _item) in world.imports.iter().chain(world.exports.iter()) {
-            let name = resolve.name_world_key(key);
-            interfaces.insert(name, key.clone());
-        }
-
         for (from, to) in self.opts.rename.iter() {
-            match interfaces.get(from) {
-                Some(key) => {
-                    self.renamed_interfaces.insert(key.clone(), to.clone());
-                }
-                None => {
-                    eprintln!("warning: rename of `{from}` did not match any interfaces");
-                }
-            }
+            self.renames.insert(from, to);
         }
+        self.renames.warn_unmatched(resolve, world);
     }
 
     fn import_interface(
@@ -281,6 +340,23 @@ impl WorldGenerator for C {
         gen.gen.src.append(&gen.src);
     }
 
+    fn export_types(
+        &mut self,
+        resolve: &Resolve,
+        _world: WorldId,
+        types: &[(&str, TypeId)],
+        _files: &mut Files,
+    ) -> Result<()> {
+        let mut gen = self.interface(resolve, false, None);
+        let mut live = LiveTypes::default();
+        for (_, id) in types {
+            live.add_type_id(resolve, *id);
+        }
+        gen.define_live_types(live);
+        gen.gen.src.append(&gen.src);
+        Ok(())
+    }
+
     fn finish(&mut self, resolve: &Resolve, id: WorldId, files: &mut Files) -> Result<()> {
         let linking_symbol = component_type_object::linking_symbol(&self.world);
         self.c_include("<stdlib.h>");
@@ -299,7 +375,27 @@ impl WorldGenerator for C {
            ",
         );
 
+        self.print_allocator();
+        if self.opts.checked_lifts == Enabled::Yes {
+            uwrite!(
+                self.src.c_defs,
+                "
+                   #ifndef WIT_LIFT_ERROR
+                   #define WIT_LIFT_ERROR(message) abort()
+                   #endif
+                ",
+            );
+        }
         self.print_intrinsics();
+        if self.needs_async {
+            self.print_async_runtime();
+        }
+        if self.needs_error_context {
+            self.print_error_context_helpers();
+        }
+        if self.needs_async {
+            self.print_async_helpers();
+        }
 
         if self.needs_string {
             self.c_include("<string.h>");
@@ -328,6 +424,7 @@ impl WorldGenerator for C {
                 StringEncoding::CompactUTF16 => unimplemented!(),
             };
             let ty = self.char_type();
+            let free = self.free_fn();
             let c_string_ty = match self.opts.string_encoding {
                 StringEncoding::UTF8 => "char",
                 StringEncoding::UTF16 => "char16_t",
@@ -364,7 +461,7 @@ impl WorldGenerator for C {
 
                    void {snake}_string_free({snake}_string_t *ret) {{
                        if (ret->len > 0) {{
-                           free(ret->ptr);
+                           {free}(ret->ptr);
                        }}
                        ret->ptr = NULL;
                        ret->len = 0;
@@ -441,6 +538,54 @@ impl WorldGenerator for C {
                 ty = self.char_type(),
             );
         }
+        if self.needs_error_context {
+            uwriteln!(
+                h_str,
+                "
+                typedef struct {snake}_error_context_t {{\n\
+                  int32_t __handle;\n\
+                }} {snake}_error_context_t;",
+            );
+        }
+        if self.needs_async {
+            let shouty = self.world.to_shouty_snake_case();
+            uwrite!(
+                h_str,
+                "
+                // Events delivered by `{snake}_task_wait` and to the callbacks of
+                // async exports, along with the waitable they're about and a
+                // payload.
+                #define {shouty}_EVENT_CALL_STARTING 0
+                #define {shouty}_EVENT_CALL_STARTED 1
+                #define {shouty}_EVENT_CALL_RETURNED 2
+                #define {shouty}_EVENT_CALL_DONE 3
+                #define {shouty}_EVENT_YIELDED 4
+                #define {shouty}_EVENT_STREAM_READ 5
+                #define {shouty}_EVENT_STREAM_WRITE 6
+                #define {shouty}_EVENT_FUTURE_READ 7
+                #define {shouty}_EVENT_FUTURE_WRITE 8
+
+                // Async imports return the status of the subtask they start in
+                // the upper two bits and its handle in the rest. Arguments must
+                // remain valid until the subtask has started, and the results
+                // buffer until it has returned, which may only be seen through
+                // `{snake}_task_wait` or the callback of an async export.
+                #define {shouty}_SUBTASK_STARTING 0
+                #define {shouty}_SUBTASK_STARTED 1
+                #define {shouty}_SUBTASK_RETURNED 2
+                #define {shouty}_SUBTASK_DONE 3
+                #define {shouty}_SUBTASK_STATUS(ret) ((ret) >> 30)
+                #define {shouty}_SUBTASK_HANDLE(ret) ((ret) & 0x3fffffff)
+
+                // Future and stream reads and writes return either the number of
+                // values transferred or one of these. Buffers must remain valid
+                // until a blocked operation completes or is canceled.
+                #define {shouty}_ASYNC_BLOCKED 0xffffffff
+                #define {shouty}_ASYNC_CLOSED 0x80000000
+                #define {shouty}_ASYNC_CANCELED 0
+                ",
+            );
+        }
         if self.src.h_defs.len() > 0 {
             h_str.push_str(&self.src.h_defs);
         }
@@ -534,7 +679,19 @@ impl C {
     }
 
     fn c_include(&mut self, s: &str) {
-        self.c_includes.push(s.to_string());
+        if !self.c_includes.iter().any(|i| i == s) {
+            self.c_includes.push(s.to_string());
+        }
+    }
+
+    /// Name of the function or macro that generated code uses to release
+    /// memory.
+    fn free_fn(&self) -> String {
+        match self.opts.allocator {
+            Allocator::Libc => "free".to_string(),
+            Allocator::Custom => "WIT_FREE".to_string(),
+            Allocator::Arena => format!("{}_ARENA_FREE", self.world.to_shouty_snake_case()),
+        }
     }
 
     fn char_type(&self) -> &'static str {
@@ -663,7 +820,7 @@ pub fn imported_types_used_by_exported_interfaces(
                 exported_interfaces.insert(*id);
                 live_export_types.add_interface(resolve, *id)
             }
-            WorldItem::Type(_) => unreachable!(),
+            WorldItem::Type(id) => live_export_types.add_type_id(resolve, *id),
         }
     }
 
@@ -709,6 +866,9 @@ fn is_prim_type_id(resolve: &Resolve, id: TypeId) -> bool {
 
         TypeDefKind::Type(ty) => is_prim_type(resolve, ty),
 
+        // There's only one error-context type, defined by the world.
+        TypeDefKind::ErrorContext => true,
+
         TypeDefKind::Record(_)
         | TypeDefKind::Resource
         | TypeDefKind::Handle(_)
@@ -718,7 +878,6 @@ fn is_prim_type_id(resolve: &Resolve, id: TypeId) -> bool {
         | TypeDefKind::Result(_)
         | TypeDefKind::Future(_)
         | TypeDefKind::Stream(_)
-        | TypeDefKind::ErrorContext
         | TypeDefKind::Unknown => false,
     }
 }
@@ -780,9 +939,21 @@ pub fn push_ty_name(resolve: &Resolve, ty: &Type, src: &mut String) {
                     src.push_str("list_");
                     push_ty_name(resolve, ty, src);
                 }
-                TypeDefKind::Future(_) => todo!(),
-                TypeDefKind::Stream(_) => todo!(),
-                TypeDefKind::ErrorContext => todo!(),
+                TypeDefKind::Future(ty) => {
+                    src.push_str("future");
+                    if let Some(ty) = ty {
+                        src.push('_');
+                        push_ty_name(resolve, ty, src);
+                    }
+                }
+                TypeDefKind::Stream(ty) => {
+                    src.push_str("stream");
+                    if let Some(ty) = ty {
+                        src.push('_');
+                        push_ty_name(resolve, ty, src);
+                    }
+                }
+                TypeDefKind::ErrorContext => src.push_str("error_context"),
                 TypeDefKind::Handle(Handle::Own(resource)) => {
                     src.push_str("own_");
                     push_ty_name(resolve, &Type::Id(*resource), src);
@@ -803,14 +974,14 @@ pub fn owner_namespace<'a>(
     world: String,
     resolve: &Resolve,
     id: TypeId,
-    renamed_interfaces: &HashMap<WorldKey, String>,
+    renames: &Renames,
 ) -> String {
     let ty = &resolve.types[id];
     match (ty.owner, interface) {
         // If this type is owned by an interface, then we must be generating
         // bindings for that interface to proceed.
         (TypeOwner::Interface(a), Some((b, key))) if a == b => {
-            interface_identifier(key, resolve, !in_import, renamed_interfaces)
+            interface_identifier(key, resolve, !in_import, renames)
         }
         (TypeOwner::Interface(_), None) => unreachable!(),
         (TypeOwner::Interface(_), Some(_)) => unreachable!(),
@@ -823,7 +994,7 @@ pub fn owner_namespace<'a>(
         // If this type has no owner then it's an anonymous type. Here it's
         // assigned to whatever we happen to be generating bindings for.
         (TypeOwner::None, Some((_, key))) => {
-            interface_identifier(key, resolve, !in_import, renamed_interfaces)
+            interface_identifier(key, resolve, !in_import, renames)
         }
         (TypeOwner::None, None) => world.to_snake_case(),
     }
@@ -833,9 +1004,9 @@ fn interface_identifier(
     interface_id: &WorldKey,
     resolve: &Resolve,
     in_export: bool,
-    renamed_interfaces: &HashMap<WorldKey, String>,
+    renames: &Renames,
 ) -> String {
-    if let Some(rename) = renamed_interfaces.get(interface_id) {
+    if let Some(rename) = renames.interface(resolve, interface_id) {
         let mut ns = String::new();
         if in_export && matches!(interface_id, WorldKey::Interface(_)) {
             ns.push_str("exports_");
@@ -885,16 +1056,11 @@ pub fn c_func_name(
     world: &str,
     interface_id: Option<&WorldKey>,
     func: &Function,
-    renamed_interfaces: &HashMap<WorldKey, String>,
+    renames: &Renames,
 ) -> String {
     let mut name = String::new();
     match interface_id {
-        Some(id) => name.push_str(&interface_identifier(
-            id,
-            resolve,
-            !in_import,
-            renamed_interfaces,
-        )),
+        Some(id) => name.push_str(&interface_identifier(id, resolve, !in_import, renames)),
         None => {
             if !in_import {
                 name.push_str("exports_");
@@ -903,7 +1069,10 @@ pub fn c_func_name(
         }
     }
     name.push_str("_");
-    name.push_str(&func.name.to_snake_case().replace('.', "_"));
+    match renames.item(resolve, interface_id, &func.name) {
+        Some(rename) => name.push_str(rename),
+        None => name.push_str(&func.name.to_snake_case().replace('.', "_")),
+    }
     name
 }
 
@@ -922,16 +1091,312 @@ impl C {
         // overridden from some other symbol.
         self.src.c_fns("\n// Canonical ABI intrinsics");
         self.src.c_fns("\n");
-        self.src.c_fns(
-            r#"
-                __attribute__((__weak__, __export_name__("cabi_realloc")))
-                void *cabi_realloc(void *ptr, size_t old_size, size_t align, size_t new_size) {
+        let realloc = match self.opts.allocator {
+            Allocator::Libc => {
+                r#"
                     (void) old_size;
                     if (new_size == 0) return (void*) align;
                     void *ret = realloc(ptr, new_size);
                     if (!ret) abort();
                     return ret;
-                }
+                "#
+            }
+            Allocator::Custom => {
+                r#"
+                    if (new_size == 0) return (void*) align;
+                    void *ret = WIT_MALLOC(new_size, align);
+                    if (!ret) abort();
+                    if (old_size > 0) {
+                        memcpy(ret, ptr, old_size < new_size ? old_size : new_size);
+                        WIT_FREE(ptr);
+                    }
+                    return ret;
+                "#
+            }
+            Allocator::Arena => {
+                let snake = self.world.to_snake_case();
+                &format!(
+                    r#"
+                        if (new_size == 0) return (void*) align;
+                        void *ret = {snake}_arena_alloc(new_size, align);
+                        if (old_size > 0) {{
+                            memcpy(ret, ptr, old_size < new_size ? old_size : new_size);
+                        }}
+                        return ret;
+                    "#
+                )
+            }
+        };
+        self.src.c_fns(
+            r#"
+                __attribute__((__weak__, __export_name__("cabi_realloc")))
+                void *cabi_realloc(void *ptr, size_t old_size, size_t align, size_t new_size) {
+            "#,
+        );
+        self.src.c_fns(realloc.trim_start());
+        self.src.c_fns("}\n");
+    }
+
+    fn print_allocator(&mut self) {
+        if self.opts.allocator == Allocator::Libc {
+            return;
+        }
+        self.c_include("<string.h>");
+        uwrite!(
+            self.src.h_fns,
+            "
+               // Allocation hooks used by the bindings unless `WIT_MALLOC` and
+               // `WIT_FREE` are defined when compiling the generated C file.
+               // `wit_malloc` must return `size` bytes aligned to `align`.
+               extern void *wit_malloc(size_t size, size_t align);
+               extern void wit_free(void *ptr);
+            ",
+        );
+        uwrite!(
+            self.src.c_defs,
+            "
+               #ifndef WIT_MALLOC
+               #define WIT_MALLOC(size, align) wit_malloc(size, align)
+               #endif
+               #ifndef WIT_FREE
+               #define WIT_FREE(ptr) wit_free(ptr)
+               #endif
+            ",
+        );
+        if self.opts.allocator != Allocator::Arena {
+            return;
+        }
+
+        let snake = self.world.to_snake_case();
+        let shouty = self.world.to_shouty_snake_case();
+        uwrite!(
+            self.src.h_fns,
+            "
+               // Allocates `size` bytes aligned to `align` from the arena backing
+               // all memory handed out by the bindings.
+               extern void *{snake}_arena_alloc(size_t size, size_t align);
+
+               // Releases all memory allocated from the arena, invalidating
+               // every value lifted or allocated since the last reset.
+               extern void {snake}_arena_reset(void);
+            ",
+        );
+        uwrite!(
+            self.src.c_defs,
+            "
+               #ifndef WIT_ARENA_CHUNK_SIZE
+               #define WIT_ARENA_CHUNK_SIZE 65536
+               #endif
+
+               // Arena memory is only released in bulk by `{snake}_arena_reset`.
+               #define {shouty}_ARENA_FREE(ptr) ((void) (ptr))
+
+               typedef struct {snake}_arena_chunk_t {{
+                   struct {snake}_arena_chunk_t *next;
+                   size_t size;
+                   size_t used;
+               }} {snake}_arena_chunk_t;
+
+               static {snake}_arena_chunk_t *{snake}_arena = NULL;
+
+               static void *{snake}_arena_bump({snake}_arena_chunk_t *chunk, size_t size, size_t align) {{
+                   uintptr_t base = (uintptr_t) (chunk + 1);
+                   uintptr_t start = (base + chunk->used + align - 1) & ~((uintptr_t) align - 1);
+                   if (start + size > base + chunk->size) return NULL;
+                   chunk->used = start + size - base;
+                   return (void*) start;
+               }}
+
+               void *{snake}_arena_alloc(size_t size, size_t align) {{
+                   if ({snake}_arena != NULL) {{
+                       void *ret = {snake}_arena_bump({snake}_arena, size, align);
+                       if (ret != NULL) return ret;
+                   }}
+                   size_t chunk_size = size + align > WIT_ARENA_CHUNK_SIZE ? size + align : WIT_ARENA_CHUNK_SIZE;
+                   {snake}_arena_chunk_t *chunk = ({snake}_arena_chunk_t*) WIT_MALLOC(sizeof({snake}_arena_chunk_t) + chunk_size, _Alignof({snake}_arena_chunk_t));
+                   if (!chunk) abort();
+                   chunk->next = {snake}_arena;
+                   chunk->size = chunk_size;
+                   chunk->used = 0;
+                   {snake}_arena = chunk;
+                   return {snake}_arena_bump(chunk, size, align);
+               }}
+
+               void {snake}_arena_reset(void) {{
+                   while ({snake}_arena != NULL) {{
+                       {snake}_arena_chunk_t *chunk = {snake}_arena;
+                       {snake}_arena = chunk->next;
+                       WIT_FREE(chunk);
+                   }}
+               }}
+            ",
+        );
+    }
+
+    fn print_error_context_helpers(&mut self) {
+        let snake = self.world.to_snake_case();
+        let encoding = match self.opts.string_encoding {
+            StringEncoding::UTF8 => "utf8",
+            StringEncoding::UTF16 => "utf16",
+            StringEncoding::CompactUTF16 => "latin1+utf16",
+        };
+        uwrite!(
+            self.src.h_helpers,
+            "
+               // Creates an error-context whose debug message is a copy of `message`.
+               extern {snake}_error_context_t {snake}_error_context_new({snake}_string_t *message);
+
+               // Stores a copy of the debug message of `err` into `ret`.
+               extern void {snake}_error_context_debug_message({snake}_error_context_t err, {snake}_string_t *ret);
+
+               extern void {snake}_error_context_drop({snake}_error_context_t err);
+            ",
+        );
+        uwrite!(
+            self.src.c_helpers,
+            r#"
+               __attribute__((__import_module__("$root"), __import_name__("[error-context-new;encoding={encoding}]")))
+               extern int32_t __wasm_import_{snake}_error_context_new(uint8_t *, size_t);
+
+               __attribute__((__import_module__("$root"), __import_name__("[error-context-debug-message;encoding={encoding};realloc=cabi_realloc]")))
+               extern void __wasm_import_{snake}_error_context_debug_message(int32_t, uint8_t *);
+
+               __attribute__((__import_module__("$root"), __import_name__("[error-context-drop]")))
+               extern void __wasm_import_{snake}_error_context_drop(int32_t);
+
+               {snake}_error_context_t {snake}_error_context_new({snake}_string_t *message) {{
+                   return ({snake}_error_context_t) {{ __wasm_import_{snake}_error_context_new((uint8_t *) message->ptr, message->len) }};
+               }}
+
+               void {snake}_error_context_debug_message({snake}_error_context_t err, {snake}_string_t *ret) {{
+                   __wasm_import_{snake}_error_context_debug_message(err.__handle, (uint8_t *) ret);
+               }}
+
+               void {snake}_error_context_drop({snake}_error_context_t err) {{
+                   __wasm_import_{snake}_error_context_drop(err.__handle);
+               }}
+            "#,
+        );
+    }
+
+    /// Prints the bookkeeping of async operations whose values are converted
+    /// to and from their canonical ABI representation in buffers of their
+    /// own, which are lifted and released once the operation completes.
+    fn print_async_runtime(&mut self) {
+        let snake = self.world.to_snake_case();
+        let shouty = self.world.to_shouty_snake_case();
+        let free = self.free_fn();
+        uwrite!(
+            self.src.c_fns,
+            "
+               typedef struct {snake}_pending_t {{
+                   struct {snake}_pending_t *next;
+                   int32_t event;
+                   uint32_t waitable;
+                   uint8_t *buf;
+                   size_t size;
+                   void *dst;
+                   void (*lift)(uint8_t *buf, void *dst, size_t count);
+               }} {snake}_pending_t;
+
+               static {snake}_pending_t *{snake}_pending = NULL;
+
+               uint8_t *{snake}_async_alloc(size_t size, size_t align) {{
+                   return (uint8_t *) cabi_realloc(NULL, 0, align, size);
+               }}
+
+               void {snake}_async_free(uint8_t *buf, size_t size) {{
+                   if (size > 0) {free}(buf);
+               }}
+
+               // Records that `buf` must be kept until `event` is delivered for
+               // `waitable`, at which point `lift` lifts its values into `dst`.
+               void {snake}_async_defer(int32_t event, uint32_t waitable, uint8_t *buf, size_t size, void *dst, void (*lift)(uint8_t *, void *, size_t)) {{
+                   {snake}_pending_t *pending = ({snake}_pending_t *) cabi_realloc(NULL, 0, _Alignof({snake}_pending_t), sizeof({snake}_pending_t));
+                   *pending = ({snake}_pending_t) {{ {snake}_pending, event, waitable, buf, size, dst, lift }};
+                   {snake}_pending = pending;
+               }}
+
+               // Completes the operation on `waitable` waiting for `event`, if
+               // any. Subtasks may skip states, so an event about a subtask also
+               // completes operations waiting for an earlier state.
+               void {snake}_async_event(int32_t event, uint32_t waitable, uint32_t payload) {{
+                   for ({snake}_pending_t **p = &{snake}_pending; *p; p = &(*p)->next) {{
+                       {snake}_pending_t *pending = *p;
+                       bool subtask = event <= {shouty}_EVENT_CALL_DONE && pending->event <= event;
+                       if (pending->waitable != waitable || (pending->event != event && !subtask)) {{
+                           continue;
+                       }}
+                       *p = pending->next;
+                       if (pending->lift) {{
+                           pending->lift(pending->buf, pending->dst, payload & ~{shouty}_ASYNC_CLOSED);
+                       }}
+                       {snake}_async_free(pending->buf, pending->size);
+                       {free}(pending);
+                       return;
+                   }}
+               }}
+            ",
+        );
+    }
+
+    fn print_async_helpers(&mut self) {
+        let snake = self.world.to_snake_case();
+        let shouty = self.world.to_shouty_snake_case();
+        uwrite!(
+            self.src.h_helpers,
+            "
+               // Waits for an event for the current task, returning its
+               // `{shouty}_EVENT_*` code and storing the waitable it's about and its
+               // payload.
+               extern int32_t {snake}_task_wait(uint32_t *waitable, uint32_t *payload);
+
+               // Lets other tasks make progress before resuming this one.
+               extern void {snake}_task_yield(void);
+
+               // Enables or disables backpressure, which prevents new calls to
+               // exports from starting.
+               extern void {snake}_task_backpressure(bool enabled);
+
+               // Drops a subtask once it's `{shouty}_SUBTASK_DONE`.
+               extern void {snake}_subtask_drop(uint32_t subtask);
+            ",
+        );
+        uwrite!(
+            self.src.c_helpers,
+            r#"
+               __attribute__((__import_module__("$root"), __import_name__("[task-wait]")))
+               extern int32_t __wasm_import_{snake}_task_wait(int32_t *);
+
+               __attribute__((__import_module__("$root"), __import_name__("[task-yield]")))
+               extern void __wasm_import_{snake}_task_yield(void);
+
+               __attribute__((__import_module__("$root"), __import_name__("[task-backpressure]")))
+               extern void __wasm_import_{snake}_task_backpressure(int32_t);
+
+               __attribute__((__import_module__("$root"), __import_name__("[subtask-drop]")))
+               extern void __wasm_import_{snake}_subtask_drop(int32_t);
+
+               int32_t {snake}_task_wait(uint32_t *waitable, uint32_t *payload) {{
+                   int32_t event[2];
+                   int32_t ret = __wasm_import_{snake}_task_wait(event);
+                   *waitable = (uint32_t) event[0];
+                   *payload = (uint32_t) event[1];
+                   {snake}_async_event(ret, *waitable, *payload);
+                   return ret;
+               }}
+
+               void {snake}_task_yield(void) {{
+                   __wasm_import_{snake}_task_yield();
+               }}
+
+               void {snake}_task_backpressure(bool enabled) {{
+                   __wasm_import_{snake}_task_backpressure(enabled ? 1 : 0);
+               }}
+
+               void {snake}_subtask_drop(uint32_t subtask) {{
+                   __wasm_import_{snake}_subtask_drop((int32_t) subtask);
+               }}
             "#,
         );
     }
@@ -960,7 +1425,12 @@ impl Return {
             TypeDefKind::Type(t) => return self.return_single(resolve, t, orig_ty, sig_flattening),
 
             // Flags are returned as their bare values, and enums and handles are scalars
-            TypeDefKind::Flags(_) | TypeDefKind::Enum(_) | TypeDefKind::Handle(_) => {
+            TypeDefKind::Flags(_)
+            | TypeDefKind::Enum(_)
+            | TypeDefKind::Handle(_)
+            | TypeDefKind::Future(_)
+            | TypeDefKind::Stream(_)
+            | TypeDefKind::ErrorContext => {
                 self.scalar = Some(Scalar::Type(*orig_ty));
                 return;
             }
@@ -997,9 +1467,6 @@ impl Return {
             | TypeDefKind::List(_)
             | TypeDefKind::Variant(_) => {}
 
-            TypeDefKind::Future(_) => todo!("return_single for future"),
-            TypeDefKind::Stream(_) => todo!("return_single for stream"),
-            TypeDefKind::ErrorContext => todo!("return_single for error-context"),
             TypeDefKind::Resource => todo!("return_single for resource"),
             TypeDefKind::Unknown => unreachable!(),
         }
@@ -1017,11 +1484,11 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.src.h_defs("\n");
         self.docs(docs, SourceType::HDefs);
         self.start_typedef_struct(id);
-        for field in record.fields.iter() {
+        for (field, name) in record.fields.iter().zip(field_names(record)) {
             self.docs(&field.docs, SourceType::HDefs);
             self.print_ty(SourceType::HDefs, &field.ty);
             self.src.h_defs(" ");
-            self.src.h_defs(&to_c_ident(&field.name));
+            self.src.h_defs(&name);
             self.src.h_defs(";\n");
         }
         self.finish_typedef_struct(id);
@@ -1053,7 +1520,7 @@ extern void {ns}_{snake}_drop_own({own} handle);
         } else {
             let module = match self.interface {
                 Some((_, key)) => self.resolve.name_world_key(key),
-                None => unimplemented!("resource exports from worlds"),
+                None => "$root".to_string(),
             };
             format!("[export]{module}")
         };
@@ -1128,8 +1595,13 @@ extern {borrow} {ns}_borrow_{snake}({own} handle);
             self.src.h_defs(&ty_name);
             self.src.h_defs(" ");
             self.print_typedef_target(id);
-            let (_, key) = self.interface.unwrap();
-            let module = self.resolve.name_world_key(key);
+
+            // Destructors of resources exported from a world aren't prefixed
+            // with an interface, just like the world's functions.
+            let dtor_prefix = match self.interface {
+                Some((_, key)) => format!("{}#", self.resolve.name_world_key(key)),
+                None => String::new(),
+            };
 
             // Exported resources use a different representation than imports
             // for borrows which is a raw pointer to the struct declared just
@@ -1155,10 +1627,10 @@ void {ns}_{snake}_destructor({ty_name} *rep);
 
             self.src.c_helpers(&format!(
                 r#"
-__attribute__(( __import_module__("[export]{module}"), __import_name__("[resource-new]{name}")))
+__attribute__(( __import_module__("{import_module}"), __import_name__("[resource-new]{name}")))
 extern int32_t __wasm_import_{ns}_{snake}_new(int32_t);
 
-__attribute__((__import_module__("[export]{module}"), __import_name__("[resource-rep]{name}")))
+__attribute__((__import_module__("{import_module}"), __import_name__("[resource-rep]{name}")))
 extern int32_t __wasm_import_{ns}_{snake}_rep(int32_t);
 
 {own} {ns}_{snake}_new({ty_name} *rep) {{
@@ -1169,7 +1641,7 @@ extern int32_t __wasm_import_{ns}_{snake}_rep(int32_t);
     return ({ns}_{snake}_t*) __wasm_import_{ns}_{snake}_rep(handle.__handle);
 }}
 
-__attribute__((__export_name__("{module}#[dtor]{snake}")))
+__attribute__((__export_name__("{dtor_prefix}[dtor]{snake}")))
 void __wasm_export_{ns}_{snake}_dtor({ns}_{snake}_t* arg) {{
     {ns}_{snake}_destructor(arg);
 }}
@@ -1216,13 +1688,13 @@ void __wasm_export_{ns}_{snake}_dtor({ns}_{snake}_t* arg) {{
             self.src.h_defs("\n");
         }
         let ns = self.owner_namespace(id).to_shouty_snake_case();
-        for (i, flag) in flags.flags.iter().enumerate() {
+        let constants = case_constants(flags.flags.iter().map(|f| f.name.as_str()));
+        for (i, (flag, constant)) in flags.flags.iter().zip(constants).enumerate() {
             self.docs(&flag.docs, SourceType::HDefs);
             uwriteln!(
                 self.src.h_defs,
-                "#define {ns}_{}_{} (1 << {i})",
+                "#define {ns}_{}_{constant} (1 << {i})",
                 name.to_shouty_snake_case(),
-                flag.name.to_shouty_snake_case(),
             );
         }
     }
@@ -1238,7 +1710,8 @@ void __wasm_export_{ns}_{snake}_dtor({ns}_{snake}_t* arg) {{
             variant
                 .cases
                 .iter()
-                .filter_map(|case| case.ty.as_ref().map(|ty| (&case.name, ty))),
+                .zip(case_names(variant))
+                .filter_map(|(case, name)| case.ty.as_ref().map(|ty| (name, ty))),
         );
 
         if !cases_with_data.is_empty() {
@@ -1246,7 +1719,7 @@ void __wasm_export_{ns}_{snake}_dtor({ns}_{snake}_t* arg) {{
             for (name, ty) in cases_with_data {
                 self.print_ty(SourceType::HDefs, ty);
                 self.src.h_defs(" ");
-                self.src.h_defs(&to_c_ident(name));
+                self.src.h_defs(&name);
                 self.src.h_defs(";\n");
             }
             self.src.h_defs("} val;\n");
@@ -1257,13 +1730,13 @@ void __wasm_export_{ns}_{snake}_dtor({ns}_{snake}_t* arg) {{
             self.src.h_defs("\n");
         }
         let ns = self.owner_namespace(id).to_shouty_snake_case();
-        for (i, case) in variant.cases.iter().enumerate() {
+        let constants = case_constants(variant.cases.iter().map(|c| c.name.as_str()));
+        for (i, (case, constant)) in variant.cases.iter().zip(constants).enumerate() {
             self.docs(&case.docs, SourceType::HDefs);
             uwriteln!(
                 self.src.h_defs,
-                "#define {ns}_{}_{} {i}",
+                "#define {ns}_{}_{constant} {i}",
                 name.to_shouty_snake_case(),
-                case.name.to_shouty_snake_case(),
             );
         }
     }
@@ -1309,13 +1782,13 @@ void __wasm_export_{ns}_{snake}_dtor({ns}_{snake}_t* arg) {{
             self.src.h_defs("\n");
         }
         let ns = self.owner_namespace(id).to_shouty_snake_case();
-        for (i, case) in enum_.cases.iter().enumerate() {
+        let constants = case_constants(enum_.cases.iter().map(|c| c.name.as_str()));
+        for (i, (case, constant)) in enum_.cases.iter().zip(constants).enumerate() {
             self.docs(&case.docs, SourceType::HDefs);
             uwriteln!(
                 self.src.h_defs,
-                "#define {ns}_{}_{} {i}",
+                "#define {ns}_{}_{constant} {i}",
                 name.to_shouty_snake_case(),
-                case.name.to_shouty_snake_case(),
             );
         }
     }
@@ -1347,19 +1820,26 @@ void __wasm_export_{ns}_{snake}_dtor({ns}_{snake}_t* arg) {{
         self.finish_typedef_struct(id);
     }
 
-    fn type_future(&mut self, id: TypeId, name: &str, ty: &Option<Type>, docs: &Docs) {
-        _ = (id, name, ty, docs);
-        todo!()
+    fn type_future(&mut self, id: TypeId, _name: &str, _ty: &Option<Type>, docs: &Docs) {
+        self.src.h_defs("\n");
+        self.docs(docs, SourceType::HDefs);
+        self.define_waitable(id);
     }
 
-    fn type_stream(&mut self, id: TypeId, name: &str, ty: &Option<Type>, docs: &Docs) {
-        _ = (id, name, ty, docs);
-        todo!()
+    fn type_stream(&mut self, id: TypeId, _name: &str, _ty: &Option<Type>, docs: &Docs) {
+        self.src.h_defs("\n");
+        self.docs(docs, SourceType::HDefs);
+        self.define_waitable(id);
     }
 
-    fn type_error_context(&mut self, id: TypeId, name: &str, docs: &Docs) {
-        _ = (id, name, docs);
-        todo!()
+    fn type_error_context(&mut self, id: TypeId, _name: &str, docs: &Docs) {
+        self.gen.needs_error_context = true;
+        self.gen.needs_string = true;
+        self.src.h_defs("\n");
+        self.docs(docs, SourceType::HDefs);
+        let snake = self.gen.world.to_snake_case();
+        uwrite!(self.src.h_defs, "typedef {snake}_error_context_t ");
+        self.print_typedef_target(id);
     }
 
     fn type_builtin(&mut self, id: TypeId, name: &str, ty: &Type, docs: &Docs) {
@@ -1446,16 +1926,20 @@ impl<'a> wit_bindgen_core::AnonymousTypeGenerator<'a> for InterfaceGenerator<'a>
         self.print_typedef_target(id);
     }
 
-    fn anonymous_type_future(&mut self, _id: TypeId, _ty: &Option<Type>, _docs: &Docs) {
-        todo!("print_anonymous_type for future");
+    fn anonymous_type_future(&mut self, id: TypeId, _ty: &Option<Type>, _docs: &Docs) {
+        self.src.h_defs("\n");
+        self.define_waitable(id);
     }
 
-    fn anonymous_type_stream(&mut self, _id: TypeId, _ty: &Option<Type>, _docs: &Docs) {
-        todo!("print_anonymous_type for stream");
+    fn anonymous_type_stream(&mut self, id: TypeId, _ty: &Option<Type>, _docs: &Docs) {
+        self.src.h_defs("\n");
+        self.define_waitable(id);
     }
 
     fn anonymous_type_error_context(&mut self) {
-        todo!("print_anonymous_type for error-context");
+        // The type itself is defined along with the world's string type.
+        self.gen.needs_error_context = true;
+        self.gen.needs_string = true;
     }
 
     fn anonymous_type_type(&mut self, _id: TypeId, _ty: &Type, _docs: &Docs) {
@@ -1510,7 +1994,15 @@ impl InterfaceGenerator<'_> {
             let (info, encoded) = gen_type_name(&self.resolve, ty);
             match info {
                 CTypeNameInfo::Named { name } => {
-                    let typedef_name = format!("{}_{encoded}_t", self.owner_namespace(ty));
+                    let owner = match self.resolve.types[ty].owner {
+                        TypeOwner::Interface(_) => self.interface.map(|(_, key)| key),
+                        _ => None,
+                    };
+                    let wit_name = self.resolve.types[ty].name.as_deref().unwrap();
+                    let typedef_name = match self.gen.renames.item(self.resolve, owner, wit_name) {
+                        Some(rename) => format!("{}_{rename}_t", self.owner_namespace(ty)),
+                        None => format!("{}_{encoded}_t", self.owner_namespace(ty)),
+                    };
                     let prev = self.gen.type_names.insert(ty, typedef_name.clone());
                     assert!(prev.is_none());
 
@@ -1573,8 +2065,8 @@ impl InterfaceGenerator<'_> {
             TypeDefKind::Enum(_) => {}
 
             TypeDefKind::Record(r) => {
-                for field in r.fields.iter() {
-                    self.free(&field.ty, &format!("&ptr->{}", to_c_ident(&field.name)));
+                for (field, name) in r.fields.iter().zip(field_names(r)) {
+                    self.free(&field.ty, &format!("&ptr->{name}"));
                 }
             }
 
@@ -1595,16 +2087,17 @@ impl InterfaceGenerator<'_> {
                     .c_helpers("for (size_t i = 0; i < list_len; i++) {\n");
                 self.free(t, "&list_ptr[i]");
                 self.src.c_helpers("}\n");
-                uwriteln!(self.src.c_helpers, "free(list_ptr);");
+                let free = self.gen.free_fn();
+                uwriteln!(self.src.c_helpers, "{free}(list_ptr);");
                 uwriteln!(self.src.c_helpers, "}}");
             }
 
             TypeDefKind::Variant(v) => {
                 self.src.c_helpers("switch ((int32_t) ptr->tag) {\n");
-                for (i, case) in v.cases.iter().enumerate() {
+                for (i, (case, name)) in v.cases.iter().zip(case_names(v)).enumerate() {
                     if let Some(ty) = &case.ty {
                         uwriteln!(self.src.c_helpers, "case {}: {{", i);
-                        let expr = format!("&ptr->val.{}", to_c_ident(&case.name));
+                        let expr = format!("&ptr->val.{name}");
                         self.free(ty, &expr);
                         self.src.c_helpers("break;\n");
                         self.src.c_helpers("}\n");
@@ -1630,9 +2123,8 @@ impl InterfaceGenerator<'_> {
                 }
                 self.src.c_helpers("}\n");
             }
-            TypeDefKind::Future(_) => todo!("print_dtor for future"),
-            TypeDefKind::Stream(_) => todo!("print_dtor for stream"),
-            TypeDefKind::ErrorContext => todo!("print_dtor for error-context"),
+            // Like handles, these are closed or dropped explicitly.
+            TypeDefKind::Future(_) | TypeDefKind::Stream(_) | TypeDefKind::ErrorContext => {}
             TypeDefKind::Resource => {}
             TypeDefKind::Handle(Handle::Borrow(id) | Handle::Own(id)) => {
                 self.free(&Type::Id(*id), "*ptr");
@@ -1682,11 +2174,22 @@ impl InterfaceGenerator<'_> {
             &self.gen.world,
             interface_id,
             func,
-            &self.gen.renamed_interfaces,
+            &self.gen.renames,
         )
     }
 
     fn import(&mut self, interface_name: Option<&WorldKey>, func: &Function) {
+        self.define_payload_helpers(interface_name, func);
+        if self
+            .gen
+            .opts
+            .async_
+            .is_async(self.resolve, interface_name, func, true)
+        {
+            self.import_async(interface_name, func);
+            return;
+        }
+
         self.docs(&func.docs, SourceType::HFns);
         let sig = self.resolve.wasm_signature(AbiVariant::GuestImport, func);
 
@@ -1729,7 +2232,12 @@ impl InterfaceGenerator<'_> {
         // Print the public facing signature into the header, and since that's
         // what we are defining also print it into the C file.
         self.src.h_fns("extern ");
-        let c_sig = self.print_sig(interface_name, func, !self.gen.opts.no_sig_flattening);
+        let c_sig = self.print_sig(
+            interface_name,
+            func,
+            !self.gen.opts.no_sig_flattening,
+            false,
+        );
         self.src.c_adapters("\n");
         self.src.c_adapters(&c_sig.sig);
         self.src.c_adapters(" {\n");
@@ -1802,16 +2310,39 @@ impl InterfaceGenerator<'_> {
     }
 
     fn export(&mut self, func: &Function, interface_name: Option<&WorldKey>) {
-        let sig = self.resolve.wasm_signature(AbiVariant::GuestExport, func);
+        self.define_payload_helpers(interface_name, func);
+        let async_ = self
+            .gen
+            .opts
+            .async_
+            .is_async(self.resolve, interface_name, func, false);
+        let sig = self.resolve.wasm_signature(
+            if async_ {
+                AbiVariant::GuestExportAsync
+            } else {
+                AbiVariant::GuestExport
+            },
+            func,
+        );
 
         self.src.c_fns("\n");
 
         let core_module_name = interface_name.map(|s| self.resolve.name_world_key(s));
         let export_name = func.legacy_core_export_name(core_module_name.as_deref());
+        let export_name = if async_ {
+            format!("[async]{export_name}")
+        } else {
+            export_name.into_owned()
+        };
 
         // Print the actual header for this function into the header file, and
         // it's what we'll be calling.
-        let h_sig = self.print_sig(interface_name, func, !self.gen.opts.no_sig_flattening);
+        let h_sig = self.print_sig(
+            interface_name,
+            func,
+            !self.gen.opts.no_sig_flattening,
+            async_,
+        );
 
         // Generate, in the C source file, the raw wasm signature that has the
         // canonical ABI.
@@ -1851,13 +2382,17 @@ impl InterfaceGenerator<'_> {
             LiftLower::LiftArgsLowerResults,
             func,
             &mut f,
-            false,
+            async_,
         );
-        let FunctionBindgen { src, .. } = f;
+        let FunctionBindgen {
+            src, task_return, ..
+        } = f;
         self.src.c_adapters(&src);
         self.src.c_adapters("}\n");
 
-        if abi::guest_export_needs_post_return(self.resolve, func) {
+        if async_ {
+            self.export_async_helpers(func, &export_name, &import_name, task_return.unwrap());
+        } else if abi::guest_export_needs_post_return(self.resolve, func) {
             uwriteln!(
                 self.src.c_fns,
                 "__attribute__((__weak__, __export_name__(\"cabi_post_{export_name}\")))"
@@ -1889,11 +2424,583 @@ impl InterfaceGenerator<'_> {
         }
     }
 
+    /// Generates an async-lowered import, which starts a subtask calling
+    /// `func` and returns its status.
+    ///
+    /// Rather than lowering the arguments and lifting the results with
+    /// `abi::call`, which would have to wait for the subtask to return, the
+    /// import takes pointers to the caller's buffers. Values whose C
+    /// representation matches their canonical ABI layout are handed to the
+    /// callee as-is, and the rest are lowered into a buffer of their own whose
+    /// results are lifted once the subtask is seen to return.
+    fn import_async(&mut self, interface_name: Option<&WorldKey>, func: &Function) {
+        self.gen.needs_async = true;
+        self.docs(&func.docs, SourceType::HFns);
+        self.src.c_fns("\n");
+
+        let name = self.c_func_name(interface_name, func);
+        self.gen.names.insert(&name).expect("duplicate symbols");
+        let import_name = self.gen.names.tmp(&format!("__wasm_import_{name}"));
+        uwriteln!(
+            self.src.c_fns,
+            "__attribute__((__import_module__(\"{}\"), __import_name__(\"[async]{}\")))",
+            match interface_name {
+                Some(name) => self.resolve.name_world_key(name),
+                None => "$root".to_string(),
+            },
+            func.name
+        );
+        uwriteln!(
+            self.src.c_fns,
+            "extern int32_t {import_name}(uint8_t *, uint8_t *);"
+        );
+
+        let arg_values = param_names(func)
+            .into_iter()
+            .zip(func.params.iter().map(|(_, ty)| *ty))
+            .collect::<Vec<_>>();
+        let result_values = task_return_params(func);
+        let args = self.async_import_buffer(&name, "args", &arg_values);
+        let results = self.async_import_buffer(&name, "results", &result_values);
+        let params = match (&args, &results) {
+            (Some((args, _)), Some((results, _))) => format!("{args}, {results}"),
+            (Some((param, _)), None) | (None, Some((param, _))) => param.clone(),
+            (None, None) => "void".to_string(),
+        };
+        uwriteln!(self.src.h_fns, "extern uint32_t {name}({params});");
+
+        if arg_values
+            .iter()
+            .chain(&result_values)
+            .all(|(_, ty)| is_direct(ty))
+        {
+            let [args, results] =
+                [args, results].map(|buffer| buffer.map_or("NULL".to_string(), |(_, arg)| arg));
+            uwrite!(
+                self.src.c_adapters,
+                "
+                    uint32_t {name}({params}) {{
+                        return (uint32_t) {import_name}({args}, {results});
+                    }}
+                "
+            );
+            return;
+        }
+
+        // Arguments and results are laid out as records, one after the
+        // other, in the buffer they're lowered into.
+        let snake = self.gen.world.to_snake_case();
+        let shouty = self.gen.world.to_shouty_snake_case();
+        let sizes = &self.gen.sizes;
+        let arg_tys = arg_values.iter().map(|(_, ty)| *ty).collect::<Vec<_>>();
+        let result_tys = result_values.iter().map(|(_, ty)| *ty).collect::<Vec<_>>();
+        let args_info = sizes.record(arg_tys.iter());
+        let results_info = sizes.record(result_tys.iter());
+        let results_at = align_to_arch(args_info.size, results_info.align);
+        let size = (results_at + results_info.size).size_wasm32();
+        let align = args_info.align.max(results_info.align).align_wasm32();
+        let results_at = results_at.size_wasm32();
+        let arg_offsets = sizes
+            .field_offsets(&arg_tys)
+            .into_iter()
+            .map(|(offset, _)| offset.size_wasm32())
+            .collect::<Vec<_>>();
+        let result_offsets = sizes
+            .field_offsets(&result_tys)
+            .into_iter()
+            .map(|(offset, _)| offset.size_wasm32() + results_at)
+            .collect::<Vec<_>>();
+        let value = |kind: &str, values: &[(String, Type)], name: &str| {
+            if values.len() == 1 {
+                format!("(*{name})")
+            } else {
+                format!("{kind}->{name}")
+            }
+        };
+
+        // The buffers' parameters, along with the results when lifting them,
+        // are in scope of the generated code.
+        let mut reserved = ["args", "results"]
+            .into_iter()
+            .map(String::from)
+            .chain(
+                arg_values
+                    .iter()
+                    .chain(&result_values)
+                    .map(|(n, _)| n.clone()),
+            )
+            .collect::<Vec<_>>();
+        let mut locals = Ns::default();
+        for name in reserved.iter() {
+            let _ = locals.insert(name);
+        }
+        let buf = locals.tmp("buf");
+        let status = locals.tmp("status");
+        let dst = locals.tmp("dst");
+        reserved.extend([buf.clone(), status.clone(), dst.clone()]);
+
+        let lowers = arg_values
+            .iter()
+            .zip(arg_offsets)
+            .map(|((param, ty), offset)| {
+                let address = format!("({buf} + {offset})");
+                (address, value("args", &arg_values, param), *ty)
+            })
+            .collect::<Vec<_>>();
+        let lower = self.lower_to_memory(&lowers, &reserved);
+
+        let (state, lift, results_ptr) = match &result_values[..] {
+            [] => ("STARTED", "NULL".to_string(), "NULL".to_string()),
+            values => {
+                let lift = self.gen.names.tmp(&format!("__lift_{name}"));
+                let (ptr_ty, results_ptr) = match values {
+                    [(param, ty)] => (format!("{} *", self.gen.type_name(ty)), param.clone()),
+                    _ => (format!("{name}_results_t *"), "results".to_string()),
+                };
+                let lifts = values
+                    .iter()
+                    .zip(result_offsets)
+                    .map(|((param, ty), offset)| {
+                        let address = format!("({buf} + {offset})");
+                        (address, value("results", values, param), *ty)
+                    })
+                    .collect::<Vec<_>>();
+                let src = self.lift_from_memory(&lifts, &reserved);
+                uwrite!(
+                    self.src.c_adapters,
+                    "
+                        static void {lift}(uint8_t *{buf}, void *{dst}, size_t count) {{
+                            (void) count;
+                            {ptr_ty}{results_ptr} = ({ptr_ty}) {dst};
+                            {src}}}
+                    "
+                );
+                ("RETURNED", lift, results_ptr)
+            }
+        };
+        let [args, results] = [
+            (!arg_values.is_empty(), buf.clone()),
+            (!result_values.is_empty(), format!("{buf} + {results_at}")),
+        ]
+        .map(|(used, arg)| if used { arg } else { "NULL".to_string() });
+        let complete = if result_values.is_empty() {
+            String::new()
+        } else {
+            format!("{lift}({buf}, {results_ptr}, 0);\n")
+        };
+
+        uwrite!(
+            self.src.c_adapters,
+            "
+                uint32_t {name}({params}) {{
+                    uint8_t *{buf} = {snake}_async_alloc({size}, {align});
+                    {lower}uint32_t {status} = (uint32_t) {import_name}({args}, {results});
+                    if ({shouty}_SUBTASK_STATUS({status}) >= {shouty}_SUBTASK_{state}) {{
+                        {complete}{snake}_async_free({buf}, {size});
+                    }} else {{
+                        {snake}_async_defer({shouty}_EVENT_CALL_{state}, {shouty}_SUBTASK_HANDLE({status}), {buf}, {size}, {results_ptr}, {lift});
+                    }}
+                    return {status};
+                }}
+            "
+        );
+    }
+
+    /// Returns code lowering each `(address, value, ty)` of `values` into its
+    /// canonical ABI representation at `address`, without declaring any of
+    /// the `reserved` names.
+    fn lower_to_memory(
+        &mut self,
+        values: &[(String, String, Type)],
+        reserved: &[String],
+    ) -> String {
+        let mut f = FunctionBindgen::new(self, CSig::default(), "");
+        for name in reserved {
+            let _ = f.locals.insert(name);
+        }
+        for (address, value, ty) in values {
+            abi::lower_to_memory(f.gen.resolve, &mut f, address.clone(), value.clone(), ty);
+        }
+        lines(f.src)
+    }
+
+    /// Returns code lifting each `(address, dst, ty)` of `values` from its
+    /// canonical ABI representation at `address` and assigning it to `dst`,
+    /// without declaring any of the `reserved` names.
+    fn lift_from_memory(
+        &mut self,
+        values: &[(String, String, Type)],
+        reserved: &[String],
+    ) -> String {
+        let mut f = FunctionBindgen::new(self, CSig::default(), "");
+        for name in reserved {
+            let _ = f.locals.insert(name);
+        }
+        for (address, dst, ty) in values {
+            let value = abi::lift_from_memory(f.gen.resolve, &mut f, address.clone(), ty);
+            uwriteln!(f.src, "{dst} = {value};");
+        }
+        lines(f.src)
+    }
+
+    /// Returns the parameter of an async import pointing to the buffer of
+    /// `values`, defining a struct for them if there's more than one, along
+    /// with the argument passing it to the import.
+    fn async_import_buffer(
+        &mut self,
+        func_name: &str,
+        kind: &str,
+        values: &[(String, Type)],
+    ) -> Option<(String, String)> {
+        match values {
+            [] => None,
+            [(name, ty)] => {
+                let ty = self.gen.type_name(ty);
+                Some((format!("{ty} *{name}"), format!("(uint8_t *) {name}")))
+            }
+            _ => {
+                let ty = format!("{func_name}_{kind}_t");
+                uwriteln!(self.src.h_defs, "\ntypedef struct {ty} {{");
+                for (name, field) in values {
+                    self.print_ty(SourceType::HDefs, field);
+                    uwriteln!(self.src.h_defs, " {name};");
+                }
+                uwriteln!(self.src.h_defs, "}} {ty};");
+                Some((format!("{ty} *{kind}"), format!("(uint8_t *) {kind}")))
+            }
+        }
+    }
+
+    /// Generates the functions accompanying an async-lifted export: the
+    /// `*_return` function delivering its results and the callback export
+    /// forwarding events to the `*_callback` function implementing it.
+    fn export_async_helpers(
+        &mut self,
+        func: &Function,
+        export_name: &str,
+        wasm_export_name: &str,
+        task_return: wit_bindgen_core::Source,
+    ) {
+        self.gen.needs_async = true;
+        let name = self.c_func_name(self.interface.map(|(_, key)| key), func);
+        let snake = self.gen.world.to_snake_case();
+        let shouty = self.gen.world.to_shouty_snake_case();
+
+        let mut params = Vec::new();
+        let mut free = String::new();
+        for (param, ty) in task_return_params(func) {
+            let ty_name = self.gen.type_name(&ty);
+            let ptr = if is_arg_by_pointer(self.resolve, &ty) {
+                params.push(format!("{ty_name} *{param}"));
+                param
+            } else {
+                params.push(format!("{ty_name} {param}"));
+                format!("&{param}")
+            };
+            match ty {
+                Type::String => uwriteln!(free, "{snake}_string_free({ptr});"),
+                Type::Id(id) => {
+                    if let Some(dtor) = self.gen.dtor_funcs.get(&id) {
+                        uwriteln!(free, "{dtor}({ptr});");
+                    }
+                }
+                _ => {}
+            }
+        }
+        let params = if params.is_empty() {
+            "void".to_string()
+        } else {
+            params.join(", ")
+        };
+
+        let return_name = format!("{name}_return");
+        let callback_name = format!("{name}_callback");
+        self.gen
+            .names
+            .insert(&return_name)
+            .expect("duplicate symbols");
+        self.gen
+            .names
+            .insert(&callback_name)
+            .expect("duplicate symbols");
+        uwriteln!(
+            self.src.h_fns,
+            "// Delivers the results of the task, taking ownership of them."
+        );
+        uwriteln!(self.src.h_fns, "extern void {return_name}({params});");
+        uwriteln!(
+            self.src.h_fns,
+            "// Handles a `{shouty}_EVENT_*` for the task, returning whether it's done."
+        );
+        uwriteln!(
+            self.src.h_fns,
+            "bool {callback_name}(void *ctx, int32_t event, uint32_t waitable, uint32_t payload);"
+        );
+        uwrite!(
+            self.src.c_adapters,
+            "
+                void {return_name}({params}) {{
+            "
+        );
+        self.src.c_adapters(&task_return);
+        self.src.c_adapters(&free);
+        self.src.c_adapters("}\n");
+        uwrite!(
+            self.src.c_adapters,
+            r#"
+                __attribute__((__export_name__("[callback]{export_name}")))
+                int32_t {wasm_export_name}_callback(uint8_t *ctx, int32_t event0, int32_t event1, int32_t event2) {{
+                    {snake}_async_event(event0, (uint32_t) event1, (uint32_t) event2);
+                    return {callback_name}(ctx, event0, (uint32_t) event1, (uint32_t) event2);
+                }}
+            "#
+        );
+    }
+
+    /// Defines the helper functions of the futures and streams used by
+    /// `func`.
+    ///
+    /// The intrinsics for these are specific to a function using them, so
+    /// the helpers of each future or stream type come from the first function
+    /// seen using it.
+    fn define_payload_helpers(&mut self, interface_name: Option<&WorldKey>, func: &Function) {
+        let module = format!(
+            "{}{}",
+            if self.in_import {
+                "[import-payload]"
+            } else {
+                "[export-payload]"
+            },
+            match interface_name {
+                Some(name) => self.resolve.name_world_key(name),
+                None => "$root".to_string(),
+            }
+        );
+        let func_name = &func.name;
+        let snake = self.gen.world.to_snake_case();
+        let shouty = self.gen.world.to_shouty_snake_case();
+
+        for (index, id) in func
+            .find_futures_and_streams(self.resolve)
+            .into_iter()
+            .enumerate()
+        {
+            let name = self.gen.type_names[&id].clone();
+            if !self.gen.payload_helpers.insert(name.clone()) {
+                continue;
+            }
+            self.gen.needs_async = true;
+
+            let prefix = name.strip_suffix("_t").unwrap();
+            let (kind, payload) = match &self.resolve.types[id].kind {
+                TypeDefKind::Future(payload) => ("future", payload),
+                TypeDefKind::Stream(payload) => ("stream", payload),
+                _ => unreachable!(),
+            };
+            let payload_ty = *payload;
+            let payload = payload.as_ref().map(|ty| self.gen.type_name(ty));
+            let (read_params, write_params, buffer_args, import_params) = match (kind, &payload) {
+                ("future", Some(ty)) => (
+                    format!(", {ty} *buf"),
+                    format!(", const {ty} *buf"),
+                    ", (uint8_t *) buf",
+                    "int32_t, uint8_t *",
+                ),
+                ("future", None) => (String::new(), String::new(), ", NULL", "int32_t, uint8_t *"),
+                (_, Some(ty)) => (
+                    format!(", {ty} *buf, size_t len"),
+                    format!(", const {ty} *buf, size_t len"),
+                    ", (uint8_t *) buf, len",
+                    "int32_t, uint8_t *, size_t",
+                ),
+                (_, None) => (
+                    ", size_t len".to_string(),
+                    ", size_t len".to_string(),
+                    ", NULL, len",
+                    "int32_t, uint8_t *, size_t",
+                ),
+            };
+            let (read, write) =
+                self.payload_read_write(prefix, kind, payload_ty.as_ref(), buffer_args);
+            let event = kind.to_shouty_snake_case();
+
+            uwrite!(
+                self.src.h_helpers,
+                "
+                    // Creates a {kind} whose handle is its writable end and, once
+                    // passed to another component, its readable end.
+                    extern {name} {prefix}_new(void);
+                    // Starts a read into, or a write from, `buf`, returning
+                    // `{shouty}_ASYNC_BLOCKED` if it's still in progress.
+                    extern uint32_t {prefix}_read({name} {kind}{read_params});
+                    extern uint32_t {prefix}_write({name} {kind}{write_params});
+                    // Cancels a blocked read or write, returning its result or
+                    // `{shouty}_ASYNC_BLOCKED` if it'll be delivered as an event.
+                    extern uint32_t {prefix}_cancel_read({name} {kind});
+                    extern uint32_t {prefix}_cancel_write({name} {kind});
+                    extern void {prefix}_close_readable({name} {kind});
+                    extern void {prefix}_close_writable({name} {kind});
+                "
+            );
+            uwrite!(
+                self.src.c_helpers,
+                r#"
+                    __attribute__((__import_module__("{module}"), __import_name__("[{kind}-new-{index}]{func_name}")))
+                    extern int32_t __wasm_import_{prefix}_new(void);
+
+                    __attribute__((__import_module__("{module}"), __import_name__("[async][{kind}-read-{index}]{func_name}")))
+                    extern uint32_t __wasm_import_{prefix}_read({import_params});
+
+                    __attribute__((__import_module__("{module}"), __import_name__("[async][{kind}-write-{index}]{func_name}")))
+                    extern uint32_t __wasm_import_{prefix}_write({import_params});
+
+                    __attribute__((__import_module__("{module}"), __import_name__("[{kind}-cancel-read-{index}]{func_name}")))
+                    extern uint32_t __wasm_import_{prefix}_cancel_read(int32_t);
+
+                    __attribute__((__import_module__("{module}"), __import_name__("[{kind}-cancel-write-{index}]{func_name}")))
+                    extern uint32_t __wasm_import_{prefix}_cancel_write(int32_t);
+
+                    __attribute__((__import_module__("{module}"), __import_name__("[{kind}-close-readable-{index}]{func_name}")))
+                    extern void __wasm_import_{prefix}_close_readable(int32_t);
+
+                    __attribute__((__import_module__("{module}"), __import_name__("[{kind}-close-writable-{index}]{func_name}")))
+                    extern void __wasm_import_{prefix}_close_writable(int32_t, int32_t);
+
+                    {name} {prefix}_new(void) {{
+                        return ({name}) {{ __wasm_import_{prefix}_new() }};
+                    }}
+
+                    uint32_t {prefix}_read({name} {kind}{read_params}) {{
+                        {read}
+                    }}
+
+                    uint32_t {prefix}_write({name} {kind}{write_params}) {{
+                        {write}
+                    }}
+
+                    uint32_t {prefix}_cancel_read({name} {kind}) {{
+                        uint32_t ret = __wasm_import_{prefix}_cancel_read({kind}.__handle);
+                        if (ret != {shouty}_ASYNC_BLOCKED) {{
+                            {snake}_async_event({shouty}_EVENT_{event}_READ, {kind}.__handle, ret);
+                        }}
+                        return ret;
+                    }}
+
+                    uint32_t {prefix}_cancel_write({name} {kind}) {{
+                        uint32_t ret = __wasm_import_{prefix}_cancel_write({kind}.__handle);
+                        if (ret != {shouty}_ASYNC_BLOCKED) {{
+                            {snake}_async_event({shouty}_EVENT_{event}_WRITE, {kind}.__handle, ret);
+                        }}
+                        return ret;
+                    }}
+
+                    void {prefix}_close_readable({name} {kind}) {{
+                        __wasm_import_{prefix}_close_readable({kind}.__handle);
+                    }}
+
+                    void {prefix}_close_writable({name} {kind}) {{
+                        __wasm_import_{prefix}_close_writable({kind}.__handle, 0);
+                    }}
+                "#
+            );
+        }
+    }
+
+    /// Returns the bodies of the functions reading into and writing from a
+    /// buffer of `payload` values of the future or stream `prefix`.
+    ///
+    /// Payloads whose C representation matches their canonical ABI layout are
+    /// transferred in place. Others are lowered into a buffer of their own
+    /// before writing, and lifted from it once a read completes, which may
+    /// only be when its event is delivered.
+    fn payload_read_write(
+        &mut self,
+        prefix: &str,
+        kind: &str,
+        payload: Option<&Type>,
+        buffer_args: &str,
+    ) -> (String, String) {
+        let Some(ty) = payload.filter(|ty| !is_direct(ty)) else {
+            return (
+                format!("return __wasm_import_{prefix}_read({kind}.__handle{buffer_args});"),
+                format!("return __wasm_import_{prefix}_write({kind}.__handle{buffer_args});"),
+            );
+        };
+        let snake = self.gen.world.to_snake_case();
+        let shouty = self.gen.world.to_shouty_snake_case();
+        let event = kind.to_shouty_snake_case();
+        let size = self.gen.sizes.size(ty).size_wasm32();
+        let align = self.gen.sizes.align(ty).align_wasm32();
+        let ty_name = self.gen.type_name(ty);
+        let (len, args) = if kind == "future" {
+            ("1", "lowered")
+        } else {
+            ("len", "lowered, len")
+        };
+
+        let reserved = [
+            kind, "buf", "len", "lowered", "dst", "count", "values", "i", "elem", "ret",
+        ]
+        .map(String::from);
+        let elem = |value: &str| [("elem".to_string(), value.to_string(), *ty)];
+        let lift = self.lift_from_memory(&elem("values[i]"), &reserved);
+        let lower = self.lower_to_memory(&elem("buf[i]"), &reserved);
+        uwrite!(
+            self.src.c_helpers,
+            "
+                static void __lift_{prefix}(uint8_t *lowered, void *dst, size_t count) {{
+                    {ty_name} *values = ({ty_name} *) dst;
+                    for (size_t i = 0; i < count; i++) {{
+                        uint8_t *elem = lowered + i * {size};
+                        {lift}}}
+                }}
+            "
+        );
+
+        let read = format!(
+            "
+                uint8_t *lowered = {snake}_async_alloc({len} * {size}, {align});
+                uint32_t ret = __wasm_import_{prefix}_read({kind}.__handle, {args});
+                if (ret == {shouty}_ASYNC_BLOCKED) {{
+                    {snake}_async_defer({shouty}_EVENT_{event}_READ, {kind}.__handle, lowered, {len} * {size}, buf, __lift_{prefix});
+                }} else {{
+                    __lift_{prefix}(lowered, buf, ret & ~{shouty}_ASYNC_CLOSED);
+                    {snake}_async_free(lowered, {len} * {size});
+                }}
+                return ret;
+            "
+        )
+        .trim()
+        .to_string();
+        let write = format!(
+            "
+                uint8_t *lowered = {snake}_async_alloc({len} * {size}, {align});
+                for (size_t i = 0; i < {len}; i++) {{
+                    uint8_t *elem = lowered + i * {size};
+                    {lower}}}
+                uint32_t ret = __wasm_import_{prefix}_write({kind}.__handle, {args});
+                if (ret == {shouty}_ASYNC_BLOCKED) {{
+                    {snake}_async_defer({shouty}_EVENT_{event}_WRITE, {kind}.__handle, lowered, {len} * {size}, NULL, NULL);
+                }} else {{
+                    {snake}_async_free(lowered, {len} * {size});
+                }}
+                return ret;
+            "
+        )
+        .trim()
+        .to_string();
+        (read, write)
+    }
+
+    /// Prints the signature of `func` as seen by C code, which for an
+    /// async-lifted export returns a context pointer for its callback instead
+    /// of its results.
     fn print_sig(
         &mut self,
         interface_name: Option<&WorldKey>,
         func: &Function,
         sig_flattening: bool,
+        async_: bool,
     ) -> CSig {
         let name = self.c_func_name(interface_name, func);
         self.gen.names.insert(&name).expect("duplicate symbols");
@@ -1902,22 +3009,32 @@ impl InterfaceGenerator<'_> {
         let mut result_rets = false;
         let mut result_rets_has_ok_type = false;
 
-        let ret = self.classify_ret(func, sig_flattening);
-        match &ret.scalar {
-            None | Some(Scalar::Void) => self.src.h_fns("void"),
-            Some(Scalar::OptionBool(_id)) => self.src.h_fns("bool"),
-            Some(Scalar::ResultBool(ok, _err)) => {
-                result_rets = true;
-                result_rets_has_ok_type = ok.is_some();
-                self.src.h_fns("bool");
+        let ret = if async_ {
+            self.src.h_fns("void*");
+            Return {
+                scalar: Some(Scalar::Void),
+                retptrs: Vec::new(),
             }
-            Some(Scalar::Type(ty)) => self.print_ty(SourceType::HFns, ty),
-        }
+        } else {
+            let ret = self.classify_ret(func, sig_flattening);
+            match &ret.scalar {
+                None | Some(Scalar::Void) => self.src.h_fns("void"),
+                Some(Scalar::OptionBool(_id)) => self.src.h_fns("bool"),
+                Some(Scalar::ResultBool(ok, _err)) => {
+                    result_rets = true;
+                    result_rets_has_ok_type = ok.is_some();
+                    self.src.h_fns("bool");
+                }
+                Some(Scalar::Type(ty)) => self.print_ty(SourceType::HFns, ty),
+            }
+            ret
+        };
         self.src.h_fns(" ");
         self.src.h_fns(&name);
         self.src.h_fns("(");
         let mut params = Vec::new();
-        for (i, (name, ty)) in func.params.iter().enumerate() {
+        let names = param_names(func);
+        for (i, (name, (_, ty))) in names.into_iter().zip(&func.params).enumerate() {
             if i > 0 {
                 self.src.h_fns(", ");
             }
@@ -1938,12 +3055,12 @@ impl InterfaceGenerator<'_> {
             };
             let (print_ty, print_name) = if sig_flattening {
                 if let Some(option_ty) = optional_type {
-                    (option_ty, format!("maybe_{}", to_c_ident(name)))
+                    (option_ty, format!("maybe_{name}"))
                 } else {
-                    (ty, to_c_ident(name))
+                    (ty, name.clone())
                 }
             } else {
-                (ty, to_c_ident(name))
+                (ty, name.clone())
             };
             self.print_ty(SourceType::HFns, print_ty);
             self.src.h_fns(" ");
@@ -1951,7 +3068,7 @@ impl InterfaceGenerator<'_> {
                 self.src.h_fns("*");
             }
             self.src.h_fns(&print_name);
-            params.push((optional_type.is_none() && pointer, to_c_ident(name)));
+            params.push((optional_type.is_none() && pointer, name));
         }
         let mut retptrs = Vec::new();
         let single_ret = ret.retptrs.len() == 1;
@@ -2026,6 +3143,13 @@ impl InterfaceGenerator<'_> {
         self.print_typedef_target(id);
     }
 
+    /// Futures and streams are represented by their handle, like resources.
+    fn define_waitable(&mut self, id: TypeId) {
+        self.start_typedef_struct(id);
+        self.src.h_defs("int32_t __handle;\n");
+        self.finish_typedef_struct(id);
+    }
+
     fn owner_namespace(&self, id: TypeId) -> String {
         owner_namespace(
             self.interface,
@@ -2033,7 +3157,7 @@ impl InterfaceGenerator<'_> {
             self.gen.world.clone(),
             self.resolve,
             id,
-            &self.gen.renamed_interfaces,
+            &self.gen.renames,
         )
     }
 
@@ -2145,6 +3269,13 @@ struct FunctionBindgen<'a, 'b> {
 
     /// Forward declarations for temporary storage of borrow copies.
     borrow_decls: wit_bindgen_core::Source,
+
+    /// The body of an async export, set aside while the body of the function
+    /// delivering its results is generated.
+    start_src: Option<wit_bindgen_core::Source>,
+
+    /// The body of the function delivering the results of an async export.
+    task_return: Option<wit_bindgen_core::Source>,
 }
 
 impl<'a, 'b> FunctionBindgen<'a, 'b> {
@@ -2169,6 +3300,8 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
             import_return_pointer_area_align: 0,
             borrow_decls: Default::default(),
             borrows: Vec::new(),
+            start_src: None,
+            task_return: None,
         }
     }
 
@@ -2183,6 +3316,24 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
         results.push(format!("*(({}*) ({} + {}))", ty, operands[0], offset));
     }
 
+    /// Reports `what` through `WIT_LIFT_ERROR` if lifts are checked and the
+    /// `invalid` condition holds.
+    fn check_lift(&mut self, invalid: &str, what: &str) {
+        if self.gen.gen.opts.checked_lifts == Enabled::Yes {
+            uwriteln!(self.src, "if ({invalid}) WIT_LIFT_ERROR(\"{what}\");");
+        }
+    }
+
+    /// The `default` case of a switch over a lifted discriminant, if lifts are
+    /// checked.
+    fn lift_default(&self, what: &str) -> String {
+        if self.gen.gen.opts.checked_lifts == Enabled::Yes {
+            format!("default: WIT_LIFT_ERROR(\"{what}\");\n")
+        } else {
+            String::new()
+        }
+    }
+
     fn load_ext(&mut self, ty: &str, offset: i32, operands: &[String], results: &mut Vec<String>) {
         self.load(ty, offset, operands, results);
         let result = results.pop().unwrap();
@@ -2286,11 +3437,20 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 }
             }
 
-            // TODO: checked?
-            Instruction::U8FromI32 => results.push(format!("(uint8_t) ({})", operands[0])),
-            Instruction::S8FromI32 => results.push(format!("(int8_t) ({})", operands[0])),
-            Instruction::U16FromI32 => results.push(format!("(uint16_t) ({})", operands[0])),
-            Instruction::S16FromI32 => results.push(format!("(int16_t) ({})", operands[0])),
+            Instruction::U8FromI32
+            | Instruction::S8FromI32
+            | Instruction::U16FromI32
+            | Instruction::S16FromI32 => {
+                let (ty, what) = match inst {
+                    Instruction::U8FromI32 => ("uint8_t", "invalid u8"),
+                    Instruction::S8FromI32 => ("int8_t", "invalid s8"),
+                    Instruction::U16FromI32 => ("uint16_t", "invalid u16"),
+                    _ => ("int16_t", "invalid s16"),
+                };
+                let op = &operands[0];
+                self.check_lift(&format!("({ty}) ({op}) != ({op})"), what);
+                results.push(format!("({ty}) ({op})"));
+            }
             Instruction::U32FromI32 => results.push(format!("(uint32_t) ({})", operands[0])),
             Instruction::S32FromI32 | Instruction::S64FromI64 => results.push(operands[0].clone()),
             Instruction::U64FromI64 => results.push(format!("(uint64_t) ({})", operands[0])),
@@ -2316,9 +3476,15 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 results.push(operands[0].clone());
             }
 
-            // TODO: checked
             Instruction::CharFromI32 => {
-                results.push(format!("(uint32_t) ({})", operands[0]));
+                let op = &operands[0];
+                self.check_lift(
+                    &format!(
+                        "(uint32_t) ({op}) > 0x10FFFF || ((uint32_t) ({op}) >= 0xD800 && (uint32_t) ({op}) <= 0xDFFF)"
+                    ),
+                    "invalid char",
+                );
+                results.push(format!("(uint32_t) ({op})"));
             }
             Instruction::I32FromChar => {
                 results.push(format!("(int32_t) ({})", operands[0]));
@@ -2331,14 +3497,18 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 }
             }
 
-            Instruction::BoolFromI32 | Instruction::I32FromBool => {
+            Instruction::BoolFromI32 => {
+                self.check_lift(&format!("(uint32_t) ({}) > 1", operands[0]), "invalid bool");
+                results.push(operands[0].clone());
+            }
+            Instruction::I32FromBool => {
                 results.push(operands[0].clone());
             }
 
             Instruction::RecordLower { record, .. } => {
                 let op = &operands[0];
-                for f in record.fields.iter() {
-                    results.push(format!("({}).{}", op, to_c_ident(&f.name)));
+                for name in field_names(record) {
+                    results.push(format!("({}).{}", op, name));
                 }
             }
             Instruction::RecordLift { ty, record, .. } => {
@@ -2411,6 +3581,21 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 }
             },
 
+            Instruction::FutureLower { .. }
+            | Instruction::StreamLower { .. }
+            | Instruction::ErrorContextLower { .. } => {
+                let op = &operands[0];
+                results.push(format!("({op}).__handle"))
+            }
+
+            Instruction::FutureLift { ty, .. }
+            | Instruction::StreamLift { ty, .. }
+            | Instruction::ErrorContextLift { ty } => {
+                let op = &operands[0];
+                let name = self.gen.gen.type_name(&Type::Id(*ty));
+                results.push(format!("({name}) {{ {op} }}"));
+            }
+
             // TODO: checked
             Instruction::FlagsLower { flags, ty, .. } => match flags_repr(flags) {
                 Int::U8 | Int::U16 | Int::U32 => {
@@ -2433,7 +3618,9 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                     let name = self.gen.gen.type_name(&Type::Id(*ty));
                     let op0 = &operands[0];
                     let op1 = &operands[1];
-                    results.push(format!("(({name}) ({op0})) | ((({name}) ({op1})) << 32)"));
+                    results.push(format!(
+                        "(({name}) (uint32_t) ({op0})) | ((({name}) (uint32_t) ({op1})) << 32)"
+                    ));
                 }
             },
 
@@ -2471,8 +3658,14 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 let expr_to_match = format!("({}).tag", operands[0]);
 
                 uwriteln!(self.src, "switch ((int32_t) {}) {{", expr_to_match);
-                for (i, ((case, (block, block_results)), payload)) in
-                    variant.cases.iter().zip(blocks).zip(payloads).enumerate()
+                let names = case_names(variant);
+                for (i, (((case, name), (block, block_results)), payload)) in variant
+                    .cases
+                    .iter()
+                    .zip(names)
+                    .zip(blocks)
+                    .zip(payloads)
+                    .enumerate()
                 {
                     uwriteln!(self.src, "case {}: {{", i);
                     if let Some(ty) = case.ty.as_ref() {
@@ -2485,7 +3678,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                             operands[0],
                         );
                         self.src.push_str(".");
-                        self.src.push_str(&to_c_ident(&case.name));
+                        self.src.push_str(&name);
                         self.src.push_str(";\n");
                     }
                     self.src.push_str(&block);
@@ -2509,8 +3702,9 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 uwriteln!(self.src, "{} {};", ty, result);
                 uwriteln!(self.src, "{}.tag = {};", result, operands[0]);
                 uwriteln!(self.src, "switch ((int32_t) {}.tag) {{", result);
-                for (i, (case, (block, block_results))) in
-                    variant.cases.iter().zip(blocks).enumerate()
+                let names = case_names(variant);
+                for (i, ((case, name), (block, block_results))) in
+                    variant.cases.iter().zip(names).zip(blocks).enumerate()
                 {
                     uwriteln!(self.src, "case {}: {{", i);
                     self.src.push_str(&block);
@@ -2519,11 +3713,13 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                     if let Some(_) = case.ty.as_ref() {
                         let mut dst = format!("{}.val", result);
                         dst.push_str(".");
-                        dst.push_str(&to_c_ident(&case.name));
+                        dst.push_str(&name);
                         self.store_op(&block_results[0], &dst);
                     }
                     self.src.push_str("break;\n}\n");
                 }
+                let default = self.lift_default("invalid variant discriminant");
+                self.src.push_str(&default);
                 self.src.push_str("}\n");
                 results.push(result);
             }
@@ -2578,6 +3774,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 uwriteln!(self.src, "{ty} {result};");
                 let op0 = &operands[0];
                 let set_some = format!("{result}.val = {some_result};\n");
+                let default = self.lift_default("invalid option discriminant");
                 if none.len() > 0 {
                     none.push('\n');
                 }
@@ -2598,6 +3795,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                             {set_some}\
                             break;
                         }}
+                        {default}\
                     }}\n"
                 );
                 results.push(result);
@@ -2681,6 +3879,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 };
 
                 let ty = self.gen.gen.type_name(&Type::Id(*ty));
+                let default = self.lift_default("invalid result discriminant");
                 uwriteln!(self.src, "{ty} {result_tmp};");
                 let op0 = &operands[0];
                 uwriteln!(
@@ -2698,13 +3897,21 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                             {set_err}\
                             break;
                         }}
+                        {default}\
                     }}"
                 );
                 results.push(result_tmp);
             }
 
             Instruction::EnumLower { .. } => results.push(format!("(int32_t) {}", operands[0])),
-            Instruction::EnumLift { .. } => results.push(operands.pop().unwrap()),
+            Instruction::EnumLift { enum_, .. } => {
+                let op = operands.pop().unwrap();
+                self.check_lift(
+                    &format!("(uint32_t) ({op}) >= {}", enum_.cases.len()),
+                    "invalid enum discriminant",
+                );
+                results.push(op);
+            }
 
             Instruction::ListCanonLower { .. } | Instruction::StringLower { .. } => {
                 results.push(format!("(uint8_t *) ({}).ptr", operands[0]));
@@ -2774,7 +3981,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 self.src.push_str(");\n");
             }
 
-            Instruction::CallInterface { func, .. } => {
+            Instruction::CallInterface { func, async_ } => {
                 let mut args = String::new();
                 for (i, (op, (byref, _))) in operands.iter().zip(&self.sig.params).enumerate() {
                     if i > 0 {
@@ -2799,6 +4006,12 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                         args.push_str(op);
                     }
                 }
+                if *async_ {
+                    let ret = self.locals.tmp("ret");
+                    uwriteln!(self.src, "void *{ret} = {}({args});", self.sig.name);
+                    results.push(ret);
+                    return;
+                }
                 match &self.sig.ret.scalar {
                     None => {
                         let mut retptrs = Vec::new();
@@ -2913,6 +4126,51 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                     }
                 }
             }
+            Instruction::AsyncPostCallInterface { func } => {
+                // An async export returns the context of its callback, and its
+                // results are lowered in the body of the function it calls to
+                // deliver them once it's done, up to `AsyncCallReturn`.
+                results.push(format!("(uint8_t *) {}", operands[0]));
+                for (name, ty) in task_return_params(func) {
+                    if is_arg_by_pointer(resolve, &ty) {
+                        results.push(format!("*{name}"));
+                    } else {
+                        results.push(name);
+                    }
+                }
+                self.start_src = Some(mem::take(&mut self.src));
+            }
+
+            Instruction::AsyncCallReturn { name, params } => {
+                let module = match self.gen.interface {
+                    Some((_, key)) => self.gen.resolve.name_world_key(key),
+                    None => "$root".to_string(),
+                };
+                let import_name = self
+                    .gen
+                    .gen
+                    .names
+                    .tmp(&format!("__wasm_import_{}_task_return", self.sig.name));
+                let params = if params.is_empty() {
+                    "void".to_string()
+                } else {
+                    params
+                        .iter()
+                        .map(|ty| wasm_type(*ty))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                uwriteln!(
+                    self.gen.src.c_fns,
+                    "__attribute__((__import_module__(\"[export]{module}\"), __import_name__(\"{name}\")))
+                    extern void {import_name}({params});"
+                );
+                uwriteln!(self.src, "{import_name}({});", operands.join(", "));
+
+                let start_src = self.start_src.take().unwrap();
+                self.task_return = Some(mem::replace(&mut self.src, start_src));
+            }
+
             Instruction::Return { .. } if self.gen.in_import => match self.sig.ret.scalar {
                 None => {
                     for op in operands.iter() {
@@ -3017,11 +4275,13 @@ impl Bindgen for FunctionBindgen<'_, '_> {
             }
 
             Instruction::GuestDeallocate { .. } => {
-                uwriteln!(self.src, "free({});", operands[0]);
+                let free = self.gen.gen.free_fn();
+                uwriteln!(self.src, "{free}({});", operands[0]);
             }
             Instruction::GuestDeallocateString => {
+                let free = self.gen.gen.free_fn();
                 uwriteln!(self.src, "if (({}) > 0) {{", operands[1]);
-                uwriteln!(self.src, "free({});", operands[0]);
+                uwriteln!(self.src, "{free}({});", operands[0]);
                 uwriteln!(self.src, "}}");
             }
             Instruction::GuestDeallocateVariant { blocks } => {
@@ -3054,7 +4314,8 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 uwriteln!(self.src, "(void) base;");
                 uwrite!(self.src, "{body}");
                 uwriteln!(self.src, "}}");
-                uwriteln!(self.src, "free({ptr});");
+                let free = self.gen.gen.free_fn();
+                uwriteln!(self.src, "{free}({ptr});");
                 uwriteln!(self.src, "}}");
             }
 
@@ -3167,10 +4428,8 @@ pub fn is_arg_by_pointer(resolve: &Resolve, ty: &Type) -> bool {
             TypeDefKind::Enum(_) => false,
             TypeDefKind::Flags(_) => false,
             TypeDefKind::Handle(_) => false,
+            TypeDefKind::Future(_) | TypeDefKind::Stream(_) | TypeDefKind::ErrorContext => false,
             TypeDefKind::Tuple(_) | TypeDefKind::Record(_) | TypeDefKind::List(_) => true,
-            TypeDefKind::Future(_) => todo!("is_arg_by_pointer for future"),
-            TypeDefKind::Stream(_) => todo!("is_arg_by_pointer for stream"),
-            TypeDefKind::ErrorContext => todo!("is_arg_by_pointer for error-context"),
             TypeDefKind::Resource => todo!("is_arg_by_pointer for resource"),
             TypeDefKind::Unknown => unreachable!(),
         },
@@ -3179,112 +4438,89 @@ pub fn is_arg_by_pointer(resolve: &Resolve, ty: &Type) -> bool {
     }
 }
 
-pub fn to_c_ident(name: &str) -> String {
-    match name {
-        // Escape C and C++ keywords.
-        // Source: https://en.cppreference.com/w/cpp/keyword
-        "alignas" => "alignas_".into(),
-        "alignof" => "alignof_".into(),
-        "and" => "and_".into(),
-        "and_eq" => "and_eq_".into(),
-        "asm" => "asm_".into(),
-        "atomic_cancel" => "atomic_cancel_".into(),
-        "atomic_commit" => "atomic_commit_".into(),
-        "atomic_noexcept" => "atomic_noexcept_".into(),
-        "auto" => "auto_".into(),
-        "bitand" => "bitand_".into(),
-        "bitor" => "bitor_".into(),
-        "bool" => "bool_".into(),
-        "break" => "break_".into(),
-        "case" => "case_".into(),
-        "catch" => "catch_".into(),
-        "char" => "char_".into(),
-        "char8_t" => "char8_t_".into(),
-        "char16_t" => "char16_t_".into(),
-        "char32_t" => "char32_t_".into(),
-        "class" => "class_".into(),
-        "compl" => "compl_".into(),
-        "concept" => "concept_".into(),
-        "const" => "const_".into(),
-        "consteval" => "consteval_".into(),
-        "constexpr" => "constexpr_".into(),
-        "constinit" => "constinit_".into(),
-        "const_cast" => "const_cast_".into(),
-        "continue" => "continue_".into(),
-        "co_await" => "co_await_".into(),
-        "co_return" => "co_return_".into(),
-        "co_yield" => "co_yield_".into(),
-        "decltype" => "decltype_".into(),
-        "default" => "default_".into(),
-        "delete" => "delete_".into(),
-        "do" => "do_".into(),
-        "double" => "double_".into(),
-        "dynamic_cast" => "dynamic_cast_".into(),
-        "else" => "else_".into(),
-        "enum" => "enum_".into(),
-        "explicit" => "explicit_".into(),
-        "export" => "export_".into(),
-        "extern" => "extern_".into(),
-        "false" => "false_".into(),
-        "float" => "float_".into(),
-        "for" => "for_".into(),
-        "friend" => "friend_".into(),
-        "goto" => "goto_".into(),
-        "if" => "if_".into(),
-        "inline" => "inline_".into(),
-        "int" => "int_".into(),
-        "long" => "long_".into(),
-        "mutable" => "mutable_".into(),
-        "namespace" => "namespace_".into(),
-        "new" => "new_".into(),
-        "noexcept" => "noexcept_".into(),
-        "not" => "not_".into(),
-        "not_eq" => "not_eq_".into(),
-        "nullptr" => "nullptr_".into(),
-        "operator" => "operator_".into(),
-        "or" => "or_".into(),
-        "or_eq" => "or_eq_".into(),
-        "private" => "private_".into(),
-        "protected" => "protected_".into(),
-        "public" => "public_".into(),
-        "reflexpr" => "reflexpr_".into(),
-        "register" => "register_".into(),
-        "reinterpret_cast" => "reinterpret_cast_".into(),
-        "requires" => "requires_".into(),
-        "return" => "return_".into(),
-        "short" => "short_".into(),
-        "signed" => "signed_".into(),
-        "sizeof" => "sizeof_".into(),
-        "static" => "static_".into(),
-        "static_assert" => "static_assert_".into(),
-        "static_cast" => "static_cast_".into(),
-        "struct" => "struct_".into(),
-        "switch" => "switch_".into(),
-        "synchronized" => "synchronized_".into(),
-        "template" => "template_".into(),
-        "this" => "this_".into(),
-        "thread_local" => "thread_local_".into(),
-        "throw" => "throw_".into(),
-        "true" => "true_".into(),
-        "try" => "try_".into(),
-        "typedef" => "typedef_".into(),
-        "typeid" => "typeid_".into(),
-        "typename" => "typename_".into(),
-        "union" => "union_".into(),
-        "unsigned" => "unsigned_".into(),
-        "using" => "using_".into(),
-        "virtual" => "virtual_".into(),
-        "void" => "void_".into(),
-        "volatile" => "volatile_".into(),
-        "wchar_t" => "wchar_t_".into(),
-        "while" => "while_".into(),
-        "xor" => "xor_".into(),
-        "xor_eq" => "xor_eq_".into(),
-        "_Packed" => "_Packed_".into(),
-        // ret and err needs to be escaped because they are used as
-        //  variable names for option and result flattening.
-        "ret" => "ret_".into(),
-        "err" => "err_".into(),
-        s => s.to_snake_case(),
+/// The results of `func` as named in the C functions delivering them for
+/// async functions.
+fn task_return_params(func: &Function) -> Vec<(String, Type)> {
+    match &func.results {
+        Results::Anon(ty) => vec![("ret".to_string(), *ty)],
+        Results::Named(results) => results
+            .iter()
+            .map(|(name, ty)| (to_c_ident(name), *ty))
+            .collect(),
+    }
+}
+
+/// Returns the lines of generated code in `src`, without any blank lines
+/// around them, so it can be spliced into a template.
+fn lines(src: wit_bindgen_core::Source) -> String {
+    let src = String::from(src);
+    match src.trim() {
+        "" => String::new(),
+        src => format!("{src}\n"),
     }
 }
+
+/// Whether values of `ty` are laid out the same in C and in the canonical ABI,
+/// so they can be handed to async imports, futures and streams as-is rather
+/// than lowered into buffers of their own.
+fn is_direct(ty: &Type) -> bool {
+    matches!(
+        ty,
+        Type::U8
+            | Type::S8
+            | Type::U16
+            | Type::S16
+            | Type::U32
+            | Type::S32
+            | Type::U64
+            | Type::S64
+            | Type::F32
+            | Type::F64
+    )
+}
+
+pub fn to_c_ident(name: &str) -> String {
+    Keywords::C.ident(name, Case::Snake)
+}
+
+/// Returns the names of the fields of `record`, which are kept distinct even
+/// when several WIT names map to the same C identifier.
+pub fn field_names(record: &Record) -> Vec<String> {
+    let mut scope = Keywords::C.scope();
+    record
+        .fields
+        .iter()
+        .map(|field| scope.ident(&field.name, Case::Snake))
+        .collect()
+}
+
+/// Returns the names of the parameters of `func`, kept distinct like
+/// [`field_names`].
+pub fn param_names(func: &Function) -> Vec<String> {
+    let mut scope = Keywords::C.scope();
+    func.params
+        .iter()
+        .map(|(name, _)| scope.ident(name, Case::Snake))
+        .collect()
+}
+
+/// Returns the names of the union members of a variant's cases, kept
+/// distinct like [`field_names`].
+fn case_names(variant: &Variant) -> Vec<String> {
+    let mut scope = Keywords::C.scope();
+    variant
+        .cases
+        .iter()
+        .map(|case| scope.ident(&case.name, Case::Snake))
+        .collect()
+}
+
+/// Returns the `#define` suffixes of the cases of a variant, enum or flags,
+/// kept distinct like [`field_names`].
+fn case_constants<'a>(cases: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut scope = Keywords::C.scope();
+    cases
+        .into_iter()
+        .map(|case| scope.unique(&case.to_shouty_snake_case()))
+        .collect()
+}
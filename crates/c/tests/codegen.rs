@@ -6,14 +6,6 @@ use std::process::Command;
 use wit_parser::{Resolve, UnresolvedPackageGroup};
 
 macro_rules! codegen_test {
-    // TODO: implement support for stream, future, and error-context, and then
-    // remove these lines:
-    (streams $name:tt $test:tt) => {};
-    (futures $name:tt $test:tt) => {};
-    (resources_with_streams $name:tt $test:tt) => {};
-    (resources_with_futures $name:tt $test:tt) => {};
-    (error_context $name:tt $test:tt) => {};
-
     ($id:ident $name:tt $test:tt) => {
         #[test]
         fn $id() {
@@ -48,6 +40,46 @@ macro_rules! codegen_test {
                 },
                 verify,
             );
+            test_helpers::run_world_codegen_test(
+                "guest-c-async",
+                $test.as_ref(),
+                |resolve, world, files| {
+                    let mut opts = wit_bindgen_c::Opts::default();
+                    opts.async_ = wit_bindgen_c::AsyncConfig::All;
+                    opts.build().generate(resolve, world, files).unwrap()
+                },
+                verify,
+            );
+            test_helpers::run_world_codegen_test(
+                "guest-c-allocator-custom",
+                $test.as_ref(),
+                |resolve, world, files| {
+                    let mut opts = wit_bindgen_c::Opts::default();
+                    opts.allocator = wit_bindgen_c::Allocator::Custom;
+                    opts.build().generate(resolve, world, files).unwrap()
+                },
+                verify,
+            );
+            test_helpers::run_world_codegen_test(
+                "guest-c-allocator-arena",
+                $test.as_ref(),
+                |resolve, world, files| {
+                    let mut opts = wit_bindgen_c::Opts::default();
+                    opts.allocator = wit_bindgen_c::Allocator::Arena;
+                    opts.build().generate(resolve, world, files).unwrap()
+                },
+                verify,
+            );
+            test_helpers::run_world_codegen_test(
+                "guest-c-checked-lifts",
+                $test.as_ref(),
+                |resolve, world, files| {
+                    let mut opts = wit_bindgen_c::Opts::default();
+                    opts.checked_lifts = wit_bindgen_c::Enabled::Yes;
+                    opts.build().generate(resolve, world, files).unwrap()
+                },
+                verify,
+            );
         }
     };
 }
@@ -154,3 +186,46 @@ void exports_rename2_f() {}
     verify(&dir, "rename-option");
     Ok(())
 }
+
+#[test]
+fn large_flags_in_async_aggregates() -> Result<()> {
+    let flags = (0..33)
+        .map(|i| format!("b{i}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut resolve = Resolve::default();
+    // Nested within an aggregate the flags don't match their canonical ABI
+    // layout, so they're lowered into and lifted from buffers of their own.
+    let pkg = resolve.push_group(UnresolvedPackageGroup::parse(
+        "input.wit",
+        &format!(
+            r#"
+                package foo:bar;
+
+                interface types {{
+                    flags big {{ {flags} }}
+                    record holder {{ big: big, tag: u8 }}
+                }}
+
+                world large-flags {{
+                    use types.{{big, holder}};
+                    import send: func(h: holder, b: big) -> holder;
+                    import stream-in: func() -> stream<holder>;
+                    export take: func(s: stream<holder>, f: future<holder>);
+                }}
+            "#
+        ),
+    )?)?;
+    let world = resolve.select_world(pkg, None)?;
+    let mut opts = wit_bindgen_c::Opts::default();
+    opts.async_ = wit_bindgen_c::AsyncConfig::All;
+
+    let dir = test_helpers::test_directory("codegen", "guest-c", "large-flags");
+    let mut files = Default::default();
+    opts.build().generate(&resolve, world, &mut files)?;
+    for (file, contents) in files.iter() {
+        std::fs::write(dir.join(file), contents)?;
+    }
+    verify(&dir, "large-flags");
+    Ok(())
+}
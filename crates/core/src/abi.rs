@@ -569,10 +569,13 @@ def_instruction! {
         /// allocated using `AsyncMalloc` after the callee task returns a value.
         AsyncCallWasm { name: &'a str, size: usize, align: usize } : [2] => [0],
 
-        /// Generate code to run after `CallInterface` for an async-lifted export.
+        /// Generate code to run after `CallInterface` for an async-lifted export
+        /// or, in host-code generation, an async-lowered import.
         ///
         /// For example, this might include task management for the
         /// future/promise/task returned by the call made for `CallInterface`.
+        /// The first value pushed is the core wasm result of the call and the
+        /// remaining values are the results of the interface function.
         AsyncPostCallInterface { func: &'a Function } : [1] => [func.results.len() + 1],
 
         /// Call `task.return` for an async-lifted export once the task returned
@@ -580,6 +583,24 @@ def_instruction! {
         /// yields a value.
         AsyncCallReturn { name: &'a str, params: &'a [WasmType] } : [params.len()] => [0],
 
+        /// Used exclusively for host-code generation this indicates that an
+        /// async-lifted export was started with `CallWasm` and the host must
+        /// now drive the export's task to completion.
+        ///
+        /// This pops the value returned by `CallWasm` and pushes the core wasm
+        /// values which the callee passed to `task.return`. These are either
+        /// the flattened results or a single pointer to them when there are
+        /// too many to pass directly.
+        AsyncTaskReturn { name: &'a str, params: &'a [WasmType] } : [1] => [params.len()],
+
+        /// Used exclusively for host-code generation this indicates that the
+        /// results of the export `func` have been lifted and its post-return
+        /// function must be called with the pointer to them.
+        ///
+        /// Backends derive the exported name of the post-return function
+        /// from `func` in the same way as they name the export itself.
+        CallPostReturn { func: &'a Function } : [1] => [0],
+
         /// Force the evaluation of the specified number of expressions and push
         /// the results to the stack.
         ///
@@ -896,14 +917,22 @@ impl<'a, B: Bindgen> Generator<'a, B> {
     fn call(&mut self, func: &Function) {
         const MAX_FLAT_PARAMS: usize = 16;
 
-        let sig = self.resolve.wasm_signature(self.variant, func);
+        let sig = match (self.variant, self.lift_lower, self.async_) {
+            // A host calling an async-lifted export passes parameters the same
+            // way as a sync export but only receives a single result back.
+            (AbiVariant::GuestExport, LiftLower::LowerArgsLiftResults, true) => self
+                .resolve
+                .wasm_signature(AbiVariant::GuestExportAsync, func),
+            // A host implementing an async-lowered import always receives a
+            // pointer to the parameters and a pointer to write results into.
+            (AbiVariant::GuestImport, LiftLower::LiftArgsLowerResults, true) => self
+                .resolve
+                .wasm_signature(AbiVariant::GuestImportAsync, func),
+            _ => self.resolve.wasm_signature(self.variant, func),
+        };
 
         match self.lift_lower {
             LiftLower::LowerArgsLiftResults => {
-                if let (AbiVariant::GuestExport, true) = (self.variant, self.async_) {
-                    unimplemented!("host-side code generation for async lift/lower not supported");
-                }
-
                 let lower_to_memory = |self_: &mut Self, ptr: B::Operand| {
                     let mut offset = 0usize;
                     for (nth, (_, ty)) in func.params.iter().enumerate() {
@@ -916,7 +945,9 @@ impl<'a, B: Bindgen> Generator<'a, B> {
                     self_.stack.push(ptr);
                 };
 
-                let params_size_align = if self.async_ {
+                let params_size_align = if let (AbiVariant::GuestImport, true) =
+                    (self.variant, self.async_)
+                {
                     let ElementInfo { size, align } = self
                         .bindgen
                         .sizes()
@@ -1005,13 +1036,55 @@ impl<'a, B: Bindgen> Generator<'a, B> {
 
                         assert_eq!(self.stack.len(), sig.params.len());
                         self.emit(&Instruction::CallWasm {
-                            name: &func.name,
+                            name: &if self.async_ {
+                                format!("[async]{}", func.name)
+                            } else {
+                                func.name.clone()
+                            },
                             sig: &sig,
                         });
                         None
                     };
 
-                if !(sig.retptr || self.async_) {
+                if let (AbiVariant::GuestExport, true) = (self.variant, self.async_) {
+                    // The results of an async-lifted export are delivered via
+                    // `task.return`, either as flat values or, if there are too
+                    // many of them, as a pointer into the callee's memory.
+                    let mut results = Vec::new();
+                    for ty in func.results.iter_types() {
+                        self.resolve.push_flat(ty, &mut results);
+                    }
+                    let indirect = results.len() > MAX_FLAT_PARAMS;
+                    let params = if indirect {
+                        vec![WasmType::Pointer]
+                    } else {
+                        results
+                    };
+                    self.emit(&Instruction::AsyncTaskReturn {
+                        name: &format!("[task-return]{}", func.name),
+                        params: &params,
+                    });
+
+                    if indirect {
+                        let ptr = self.stack.pop().unwrap();
+                        self.read_results_from_memory(&func.results, ptr, 0);
+                        self.emit(&Instruction::Flush {
+                            amt: func.results.len(),
+                        });
+                    } else {
+                        let mut args = self
+                            .stack
+                            .drain(self.stack.len() - params.len()..)
+                            .collect::<Vec<_>>();
+                        let mut temp = Vec::new();
+                        for ty in func.results.iter_types() {
+                            temp.truncate(0);
+                            self.resolve.push_flat(ty, &mut temp);
+                            self.stack.extend(args.drain(..temp.len()));
+                            self.lift(ty);
+                        }
+                    }
+                } else if !(sig.retptr || self.async_) {
                     // With no return pointer in use we can simply lift the
                     // result(s) of the function from the result of the core
                     // wasm function.
@@ -1053,6 +1126,14 @@ impl<'a, B: Bindgen> Generator<'a, B> {
                             size: size.size_wasm32(),
                             align: align.align_wasm32(),
                         });
+                    } else if self.variant == AbiVariant::GuestExport
+                        && guest_export_needs_post_return(self.resolve, func)
+                    {
+                        // Once a host has lifted the results of an export it
+                        // invokes the export's `post-return` function to let
+                        // the callee clean up its allocations.
+                        self.stack.push(ptr);
+                        self.emit(&Instruction::CallPostReturn { func });
                     }
                 }
 
@@ -1062,10 +1143,6 @@ impl<'a, B: Bindgen> Generator<'a, B> {
                 });
             }
             LiftLower::LiftArgsLowerResults => {
-                if let (AbiVariant::GuestImport, true) = (self.variant, self.async_) {
-                    todo!("implement host-side support for async lift/lower");
-                }
-
                 let read_from_memory = |self_: &mut Self| {
                     let mut offset = 0usize;
                     let ptr = self_.stack.pop().unwrap();
@@ -1105,17 +1182,24 @@ impl<'a, B: Bindgen> Generator<'a, B> {
                     async_: self.async_,
                 });
 
-                let (lower_to_memory, async_results) = if self.async_ {
-                    self.emit(&Instruction::AsyncPostCallInterface { func });
-
-                    let mut results = Vec::new();
-                    for ty in func.results.iter_types() {
-                        self.resolve.push_flat(ty, &mut results);
-                    }
-                    (results.len() > MAX_FLAT_PARAMS, Some(results))
-                } else {
-                    (sig.retptr, None)
-                };
+                let (lower_to_memory, async_results) =
+                    if let (AbiVariant::GuestImport, true) = (self.variant, self.async_) {
+                        // A host implementing an async-lowered import waits for
+                        // the results and then writes them to the caller-provided
+                        // return area, returning the status of the call.
+                        self.emit(&Instruction::AsyncPostCallInterface { func });
+                        (true, None)
+                    } else if self.async_ {
+                        self.emit(&Instruction::AsyncPostCallInterface { func });
+
+                        let mut results = Vec::new();
+                        for ty in func.results.iter_types() {
+                            self.resolve.push_flat(ty, &mut results);
+                        }
+                        (results.len() > MAX_FLAT_PARAMS, Some(results))
+                    } else {
+                        (sig.retptr, None)
+                    };
 
                 // This was dynamically allocated by the caller (or async start
                 // function) so after it's been read by the guest we need to
@@ -2135,9 +2219,11 @@ impl<'a, B: Bindgen> Generator<'a, B> {
 
                 TypeDefKind::Enum(_) => {}
 
-                TypeDefKind::Future(_) => todo!("read future from memory"),
-                TypeDefKind::Stream(_) => todo!("read stream from memory"),
-                TypeDefKind::ErrorContext => todo!("read error-context from memory"),
+                // Ownership of these handles was transferred to the caller
+                // when they were lifted, so there's nothing in linear memory
+                // to clean up here.
+                TypeDefKind::Future(_) | TypeDefKind::Stream(_) | TypeDefKind::ErrorContext => {}
+
                 TypeDefKind::Unknown => unreachable!(),
             },
         }
@@ -2234,3 +2320,211 @@ fn cast(from: WasmType, to: WasmType) -> Bitcast {
 fn align_to(val: usize, align: usize) -> usize {
     (val + align - 1) & !(align - 1)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wit_parser::{InterfaceId, UnresolvedPackageGroup, WorldItem};
+
+    /// A `Bindgen` which records the name of each instruction it's given.
+    struct Recorder {
+        sizes: SizeAlign,
+        insts: Vec<String>,
+        tmp: usize,
+    }
+
+    impl Bindgen for Recorder {
+        type Operand = String;
+
+        fn emit(
+            &mut self,
+            _resolve: &Resolve,
+            inst: &Instruction<'_>,
+            _operands: &mut Vec<String>,
+            results: &mut Vec<String>,
+        ) {
+            let name = format!("{inst:?}");
+            let name = name.split([' ', '{']).next().unwrap();
+            self.insts.push(name.to_string());
+            for _ in 0..inst.results_len() {
+                self.tmp += 1;
+                results.push(format!("t{}", self.tmp));
+            }
+        }
+
+        fn return_pointer(&mut self, _size: usize, _align: usize) -> String {
+            "ret".to_string()
+        }
+
+        fn push_block(&mut self) {}
+
+        fn finish_block(&mut self, _operand: &mut Vec<String>) {}
+
+        fn sizes(&self) -> &SizeAlign {
+            &self.sizes
+        }
+
+        fn is_list_canonical(&self, _resolve: &Resolve, _element: &Type) -> bool {
+            false
+        }
+    }
+
+    const WIT: &str = r#"
+        package test:host;
+
+        interface i {
+            record r { a: string, b: list<u32>, c: option<u64> }
+
+            small: func(x: u32) -> u32;
+            strings: func(a: u64, b: string) -> list<string>;
+            records: func(x: r) -> result<r, string>;
+            many: func(a: r, b: r, c: r, d: r, e: r, f: r) -> tuple<r, r, r, r, r, r>;
+        }
+
+        interface handles {
+            record body { contents: stream<u8>, trailers: future<list<string>> }
+            variant payload { none, streamed(stream<u8>), failed(error-context) }
+            type futures = list<future<u32>>;
+            type payloads = list<payload>;
+        }
+
+        world w {
+            import i;
+            export i;
+            import handles;
+        }
+    "#;
+
+    fn recorder(resolve: &Resolve) -> Recorder {
+        let mut sizes = SizeAlign::default();
+        sizes.fill(resolve);
+        Recorder {
+            sizes,
+            insts: Vec::new(),
+            tmp: 0,
+        }
+    }
+
+    fn interface(resolve: &mut Resolve, import: bool, nth: usize) -> InterfaceId {
+        let pkg = resolve
+            .push_group(UnresolvedPackageGroup::parse("test.wit", WIT).unwrap())
+            .unwrap();
+        let world = resolve.select_world(pkg, None).unwrap();
+        let items = if import {
+            &resolve.worlds[world].imports
+        } else {
+            &resolve.worlds[world].exports
+        };
+        match items[nth] {
+            WorldItem::Interface { id, .. } => id,
+            _ => unreachable!(),
+        }
+    }
+
+    fn host_calls(variant: AbiVariant, lift_lower: LiftLower, async_: bool) -> Vec<Vec<String>> {
+        let mut resolve = Resolve::default();
+        let id = interface(&mut resolve, variant == AbiVariant::GuestImport, 0);
+        let mut recorder = recorder(&resolve);
+        resolve.interfaces[id]
+            .functions
+            .values()
+            .map(|func| {
+                recorder.insts.clear();
+                call(&resolve, variant, lift_lower, func, &mut recorder, async_);
+                recorder.insts.clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn host_import() {
+        for insts in host_calls(
+            AbiVariant::GuestImport,
+            LiftLower::LiftArgsLowerResults,
+            false,
+        ) {
+            assert!(insts.contains(&"CallInterface".to_string()));
+            assert_eq!(insts.last().unwrap(), "Return");
+        }
+    }
+
+    #[test]
+    fn host_import_async() {
+        for insts in host_calls(
+            AbiVariant::GuestImport,
+            LiftLower::LiftArgsLowerResults,
+            true,
+        ) {
+            assert_eq!(insts[0], "GetArg");
+            assert!(insts.contains(&"AsyncPostCallInterface".to_string()));
+            assert!(!insts.contains(&"AsyncCallReturn".to_string()));
+            assert_eq!(insts.last().unwrap(), "Return");
+        }
+    }
+
+    #[test]
+    fn host_export() {
+        let calls = host_calls(
+            AbiVariant::GuestExport,
+            LiftLower::LowerArgsLiftResults,
+            false,
+        );
+        // `small` returns a scalar and needs no post-return while the rest
+        // return allocations which the callee must free.
+        assert!(!calls[0].contains(&"CallPostReturn".to_string()));
+        for insts in &calls {
+            assert_eq!(insts.iter().filter(|i| *i == "CallWasm").count(), 1);
+        }
+        for insts in &calls[1..] {
+            assert_eq!(insts.iter().filter(|i| *i == "CallPostReturn").count(), 1);
+        }
+    }
+
+    #[test]
+    fn host_export_async() {
+        for insts in host_calls(
+            AbiVariant::GuestExport,
+            LiftLower::LowerArgsLiftResults,
+            true,
+        ) {
+            assert_eq!(insts.iter().filter(|i| *i == "CallWasm").count(), 1);
+            assert!(insts.contains(&"AsyncTaskReturn".to_string()));
+            assert_eq!(insts.last().unwrap(), "Return");
+        }
+    }
+
+    #[test]
+    fn async_handles_in_memory() {
+        let mut resolve = Resolve::default();
+        let id = interface(&mut resolve, true, 1);
+        let mut recorder = recorder(&resolve);
+        let expected = [
+            ("body", &["StreamLift", "FutureLift"][..]),
+            ("payload", &["StreamLift", "ErrorContextLift"]),
+            ("futures", &["FutureLift"]),
+            ("payloads", &["StreamLift", "ErrorContextLift"]),
+        ];
+        for (name, insts) in expected {
+            let ty = Type::Id(resolve.interfaces[id].types[name]);
+
+            recorder.insts.clear();
+            lift_from_memory(&resolve, &mut recorder, "ptr".to_string(), &ty);
+            for inst in insts {
+                assert!(recorder.insts.iter().any(|i| i == inst), "{name}: {inst}");
+            }
+
+            recorder.insts.clear();
+            lower_to_memory(
+                &resolve,
+                &mut recorder,
+                "ptr".to_string(),
+                "val".to_string(),
+                &ty,
+            );
+            for inst in insts {
+                let inst = inst.replace("Lift", "Lower");
+                assert!(recorder.insts.contains(&inst), "{name}: {inst}");
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Selecting which functions use the async ABI, shared by generators
+//! supporting async.
+
+use wit_parser::*;
+
+/// Which imported and exported functions are generated with the async ABI.
+#[derive(Default, Debug, Clone)]
+pub enum AsyncConfig {
+    #[default]
+    None,
+    Some {
+        imports: Vec<String>,
+        exports: Vec<String>,
+    },
+    All,
+}
+
+impl AsyncConfig {
+    /// Returns whether `func`, imported or exported either directly by the
+    /// world or via `interface`, should use the async ABI.
+    ///
+    /// Each selector is matched against:
+    ///
+    /// * the function's name, e.g. `f` or `[method]res.get`,
+    /// * the function's name within its resource, e.g. `res.get` or
+    ///   `res.constructor`,
+    /// * the resource's name, e.g. `res`, selecting all of its functions,
+    ///
+    /// each prefixed by the interface name and `#` (as in
+    /// `wasi:http/handler@0.3.0-draft#handle`) for functions in interfaces,
+    /// as well as the interface name on its own, selecting all of its
+    /// functions. Selectors may contain `*` wildcards, so
+    /// `wasi:http/handler@0.3.0-draft#*` and `wasi:http/*` select every
+    /// function in the `handler` interface and the `wasi:http` package,
+    /// respectively.
+    pub fn is_async(
+        &self,
+        resolve: &Resolve,
+        interface: Option<&WorldKey>,
+        func: &Function,
+        is_import: bool,
+    ) -> bool {
+        let selectors = match self {
+            AsyncConfig::None => return false,
+            AsyncConfig::All => return true,
+            AsyncConfig::Some { imports, exports } => {
+                if is_import {
+                    imports
+                } else {
+                    exports
+                }
+            }
+        };
+
+        let mut names = vec![func.name.clone()];
+        match func.kind {
+            FunctionKind::Freestanding => {}
+            FunctionKind::Method(id) | FunctionKind::Static(id) | FunctionKind::Constructor(id) => {
+                let resource = resolve.types[id].name.as_ref().unwrap();
+                names.push(format!("{resource}.{}", func.item_name()));
+                names.push(resource.clone());
+            }
+        }
+        if let Some(key) = interface {
+            let interface = resolve.name_world_key(key);
+            for name in names.iter_mut() {
+                *name = format!("{interface}#{name}");
+            }
+            names.push(interface);
+        }
+
+        selectors
+            .iter()
+            .any(|selector| names.iter().any(|name| glob_matches(selector, name)))
+    }
+
+    /// Parses an `--async` option as passed on the command line: `none`,
+    /// `all`, or `some=` followed by comma-separated `import:<selector>` and
+    /// `export:<selector>` values.
+    pub fn parse(s: &str) -> Result<AsyncConfig, String> {
+        Ok(match s {
+            "none" => AsyncConfig::None,
+            "all" => AsyncConfig::All,
+            _ => {
+                if let Some(values) = s.strip_prefix("some=") {
+                    let mut imports = Vec::new();
+                    let mut exports = Vec::new();
+                    for value in values.split(',') {
+                        let error = || {
+                            Err(format!(
+                                "expected string of form `import:<name>` or `export:<name>`; got `{value}`"
+                            ))
+                        };
+                        if let Some((k, v)) = value.split_once(":") {
+                            match k {
+                                "import" => imports.push(v.into()),
+                                "export" => exports.push(v.into()),
+                                _ => return error(),
+                            }
+                        } else {
+                            return error();
+                        }
+                    }
+                    AsyncConfig::Some { imports, exports }
+                } else {
+                    return Err(format!(
+                        "expected string of form `none`, `all`, or `some=<value>[,<value>...]`; got `{s}`"
+                    ));
+                }
+            }
+        })
+    }
+}
+
+/// Returns whether `name` matches `pattern`, in which `*` matches any
+/// sequence of characters.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => match name.strip_prefix(prefix) {
+            Some(name) => name
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain([name.len()])
+                .any(|i| glob_matches(rest, &name[i..])),
+            None => false,
+        },
+    }
+}
@@ -1,9 +1,13 @@
 use std::fmt::Write;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 pub use wit_parser;
 use wit_parser::*;
 pub mod abi;
+mod async_config;
+pub use async_config::AsyncConfig;
+pub mod naming;
+pub use naming::{Case, Keywords, Naming, Renames, Scope};
 mod ns;
 pub use ns::Ns;
 pub mod source;
@@ -60,14 +64,21 @@ pub trait WorldGenerator {
         // Interfaces are then generated afterwards so if the same interface is
         // both imported and exported the right types are all used everywhere.
         let mut interfaces = Vec::new();
+        types.clear();
         for (name, export) in world.exports.iter() {
             match export {
                 WorldItem::Function(f) => funcs.push((unwrap_name(name), f)),
                 WorldItem::Interface { id, .. } => interfaces.push((name, id)),
-                WorldItem::Type(_) => unreachable!(),
+                WorldItem::Type(id) => types.push((unwrap_name(name), *id)),
             }
         }
-        if !funcs.is_empty() {
+        if !types.is_empty() {
+            self.export_types(resolve, id, &types, files)?;
+        }
+        // Exported resources are implemented alongside the world's exported
+        // functions, so those are generated even if there aren't any
+        // functions.
+        if !funcs.is_empty() || !types.is_empty() {
             self.export_funcs(resolve, id, &funcs, files)?;
         }
 
@@ -129,6 +140,28 @@ pub trait WorldGenerator {
         types: &[(&str, TypeId)],
         files: &mut Files,
     );
+
+    /// Called with the types, including resources, which a world exports
+    /// directly rather than through an interface.
+    ///
+    /// This is called before `export_funcs`, which is then called even if the
+    /// world exports no functions. Generators which don't support exporting
+    /// types from worlds return an error.
+    fn export_types(
+        &mut self,
+        resolve: &Resolve,
+        world: WorldId,
+        types: &[(&str, TypeId)],
+        files: &mut Files,
+    ) -> Result<()> {
+        let _ = (resolve, files);
+        let names = types.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        bail!(
+            "exporting types from world `{}` is not supported by this generator: {}",
+            resolve.worlds[world].name,
+            names.join(", ")
+        )
+    }
     fn finish(&mut self, resolve: &Resolve, world: WorldId, files: &mut Files) -> Result<()>;
 }
 
@@ -235,3 +268,123 @@ pub fn dealias(resolve: &Resolve, mut id: TypeId) -> TypeId {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        calls: Vec<String>,
+    }
+
+    impl Recorder {
+        fn record(&mut self, method: &str, names: impl Iterator<Item = String>) {
+            let names = names.collect::<Vec<_>>();
+            self.calls.push(format!("{method} {names:?}"));
+        }
+    }
+
+    impl WorldGenerator for Recorder {
+        fn import_interface(
+            &mut self,
+            _: &Resolve,
+            _: &WorldKey,
+            _: InterfaceId,
+            _: &mut Files,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn export_interface(
+            &mut self,
+            _: &Resolve,
+            _: &WorldKey,
+            _: InterfaceId,
+            _: &mut Files,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn import_funcs(
+            &mut self,
+            _: &Resolve,
+            _: WorldId,
+            funcs: &[(&str, &Function)],
+            _: &mut Files,
+        ) {
+            self.record("import_funcs", funcs.iter().map(|(n, _)| n.to_string()));
+        }
+
+        fn export_funcs(
+            &mut self,
+            _: &Resolve,
+            _: WorldId,
+            funcs: &[(&str, &Function)],
+            _: &mut Files,
+        ) -> Result<()> {
+            self.record("export_funcs", funcs.iter().map(|(n, _)| n.to_string()));
+            Ok(())
+        }
+
+        fn import_types(
+            &mut self,
+            _: &Resolve,
+            _: WorldId,
+            types: &[(&str, TypeId)],
+            _: &mut Files,
+        ) {
+            self.record("import_types", types.iter().map(|(n, _)| n.to_string()));
+        }
+
+        fn export_types(
+            &mut self,
+            _: &Resolve,
+            _: WorldId,
+            types: &[(&str, TypeId)],
+            _: &mut Files,
+        ) -> Result<()> {
+            self.record("export_types", types.iter().map(|(n, _)| n.to_string()));
+            Ok(())
+        }
+
+        fn finish(&mut self, _: &Resolve, _: WorldId, _: &mut Files) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn world_exported_types() {
+        let mut resolve = Resolve::default();
+        let pkg = resolve
+            .push_str(
+                "test.wit",
+                "package a:b; world w { resource r; type t = u32; import f: func(); }",
+            )
+            .unwrap();
+        let world = resolve.select_world(pkg, None).unwrap();
+
+        // WIT syntax can't export types from a world, but such worlds can
+        // still be constructed, so move the world's types to its exports.
+        let imports = std::mem::take(&mut resolve.worlds[world].imports);
+        for (key, item) in imports {
+            let dst = match item {
+                WorldItem::Type(_) => &mut resolve.worlds[world].exports,
+                _ => &mut resolve.worlds[world].imports,
+            };
+            dst.insert(key, item);
+        }
+
+        let mut gen = Recorder::default();
+        gen.generate(&resolve, world, &mut Files::default())
+            .unwrap();
+        assert_eq!(
+            gen.calls,
+            [
+                "import_funcs [\"f\"]",
+                "export_types [\"r\", \"t\"]",
+                "export_funcs []",
+            ]
+        );
+    }
+}
@@ -0,0 +1,743 @@
+//! Shared support for turning WIT names into identifiers of a target
+//! language.
+//!
+//! Generators describe their language with a [`Keywords`] table, which knows
+//! how to escape identifiers that would otherwise be reserved. A [`Naming`]
+//! combines such a table with user-supplied [`Renames`] and hands out
+//! collision-free [`Scope`]s for names that share a namespace.
+
+use crate::Ns;
+use anyhow::{bail, Result};
+use heck::{ToKebabCase, ToLowerCamelCase, ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
+use std::collections::{BTreeMap, BTreeSet};
+use wit_parser::{Resolve, TypeId, TypeOwner, WorldId, WorldItem, WorldKey};
+
+/// Case conventions that WIT's kebab-case names are converted to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Case {
+    /// `foo_bar`
+    Snake,
+    /// `FOO_BAR`
+    ShoutySnake,
+    /// `FooBar`
+    UpperCamel,
+    /// `fooBar`
+    LowerCamel,
+    /// `foo-bar`
+    Kebab,
+}
+
+impl Case {
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            Case::Snake => name.to_snake_case(),
+            Case::ShoutySnake => name.to_shouty_snake_case(),
+            Case::UpperCamel => name.to_upper_camel_case(),
+            Case::LowerCamel => name.to_lower_camel_case(),
+            Case::Kebab => name.to_kebab_case(),
+        }
+    }
+}
+
+/// How an identifier which collides with a keyword is made usable.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Escape {
+    /// Prepend the string to the identifier, as in `@class` or `_type`.
+    Prefix(&'static str),
+    /// Append the string to the identifier, as in `type_`.
+    Suffix(&'static str),
+}
+
+/// The reserved words of a target language along with how to escape them.
+#[derive(Debug, Copy, Clone)]
+pub struct Keywords {
+    pub words: &'static [&'static str],
+    pub escape: Escape,
+}
+
+impl Keywords {
+    /// C and C++ keywords.
+    ///
+    /// Source: <https://en.cppreference.com/w/cpp/keyword>
+    pub const C: Keywords = Keywords {
+        words: &[
+            "alignas",
+            "alignof",
+            "and",
+            "and_eq",
+            "asm",
+            "atomic_cancel",
+            "atomic_commit",
+            "atomic_noexcept",
+            "auto",
+            "bitand",
+            "bitor",
+            "bool",
+            "break",
+            "case",
+            "catch",
+            "char",
+            "char8_t",
+            "char16_t",
+            "char32_t",
+            "class",
+            "compl",
+            "concept",
+            "const",
+            "consteval",
+            "constexpr",
+            "constinit",
+            "const_cast",
+            "continue",
+            "co_await",
+            "co_return",
+            "co_yield",
+            "decltype",
+            "default",
+            "delete",
+            "do",
+            "double",
+            "dynamic_cast",
+            "else",
+            "enum",
+            "explicit",
+            "export",
+            "extern",
+            "false",
+            "float",
+            "for",
+            "friend",
+            "goto",
+            "if",
+            "inline",
+            "int",
+            "long",
+            "mutable",
+            "namespace",
+            "new",
+            "noexcept",
+            "not",
+            "not_eq",
+            "nullptr",
+            "operator",
+            "or",
+            "or_eq",
+            "private",
+            "protected",
+            "public",
+            "reflexpr",
+            "register",
+            "reinterpret_cast",
+            "requires",
+            "return",
+            "short",
+            "signed",
+            "sizeof",
+            "static",
+            "static_assert",
+            "static_cast",
+            "struct",
+            "switch",
+            "synchronized",
+            "template",
+            "this",
+            "thread_local",
+            "throw",
+            "true",
+            "try",
+            "typedef",
+            "typeid",
+            "typename",
+            "union",
+            "unsigned",
+            "using",
+            "virtual",
+            "void",
+            "volatile",
+            "wchar_t",
+            "while",
+            "xor",
+            "xor_eq",
+            "_Packed",
+            // ret and err needs to be escaped because they are used as
+            // variable names for option and result flattening.
+            "ret",
+            "err",
+        ],
+        escape: Escape::Suffix("_"),
+    };
+
+    /// C# keywords.
+    ///
+    /// Source: <https://learn.microsoft.com/en-us/dotnet/csharp/language-reference/keywords/>
+    pub const CSHARP: Keywords = Keywords {
+        words: &[
+            "abstract",
+            "as",
+            "base",
+            "bool",
+            "break",
+            "byte",
+            "case",
+            "catch",
+            "char",
+            "checked",
+            "class",
+            "const",
+            "continue",
+            "decimal",
+            "default",
+            "delegate",
+            "do",
+            "double",
+            "else",
+            "enum",
+            "event",
+            "explicit",
+            "extern",
+            "false",
+            "finally",
+            "fixed",
+            "float",
+            "for",
+            "foreach",
+            "goto",
+            "if",
+            "implicit",
+            "in",
+            "int",
+            "interface",
+            "internal",
+            "is",
+            "lock",
+            "long",
+            "namespace",
+            "new",
+            "null",
+            "object",
+            "operator",
+            "out",
+            "override",
+            "params",
+            "private",
+            "protected",
+            "public",
+            "readonly",
+            "ref",
+            "return",
+            "sbyte",
+            "sealed",
+            "short",
+            "sizeof",
+            "stackalloc",
+            "static",
+            "string",
+            "struct",
+            "switch",
+            "this",
+            "throw",
+            "true",
+            "try",
+            "typeof",
+            "uint",
+            "ulong",
+            "unchecked",
+            "unsafe",
+            "ushort",
+            "using",
+            "virtual",
+            "void",
+            "volatile",
+            "while",
+        ],
+        escape: Escape::Prefix("@"),
+    };
+
+    /// Go keywords.
+    ///
+    /// Source: <https://go.dev/ref/spec#Keywords>
+    pub const GO: Keywords = Keywords {
+        words: &[
+            "break",
+            "default",
+            "func",
+            "interface",
+            "select",
+            "case",
+            "defer",
+            "go",
+            "map",
+            "struct",
+            "chan",
+            "else",
+            "goto",
+            "package",
+            "switch",
+            "const",
+            "fallthrough",
+            "if",
+            "range",
+            "type",
+            "continue",
+            "for",
+            "import",
+            "return",
+            "var",
+            // not a Go keyword but needs to escape due to
+            // it's used as a variable name that passes to C
+            "ret",
+        ],
+        escape: Escape::Prefix("_"),
+    };
+
+    /// Java keywords.
+    ///
+    /// Source: <https://docs.oracle.com/javase/tutorial/java/nutsandbolts/_keywords.html>
+    pub const JAVA: Keywords = Keywords {
+        words: &[
+            "abstract",
+            "continue",
+            "for",
+            "new",
+            "switch",
+            "assert",
+            "default",
+            "goto",
+            "package",
+            "synchronized",
+            "boolean",
+            "do",
+            "if",
+            "private",
+            "this",
+            "break",
+            "double",
+            "implements",
+            "protected",
+            "throw",
+            "byte",
+            "else",
+            "import",
+            "public",
+            "throws",
+            "case",
+            "enum",
+            "instanceof",
+            "return",
+            "transient",
+            "catch",
+            "extends",
+            "int",
+            "short",
+            "try",
+            "char",
+            "final",
+            "interface",
+            "static",
+            "void",
+            "class",
+            "finally",
+            "long",
+            "strictfp",
+            "volatile",
+            "const",
+            "float",
+            "native",
+            "super",
+            "while",
+        ],
+        escape: Escape::Suffix("_"),
+    };
+
+    /// MoonBit keywords.
+    pub const MOONBIT: Keywords = Keywords {
+        words: &[
+            "continue",
+            "for",
+            "match",
+            "if",
+            "pub",
+            "priv",
+            "readonly",
+            "break",
+            "raise",
+            "try",
+            "except",
+            "catch",
+            "else",
+            "enum",
+            "struct",
+            "type",
+            "trait",
+            "return",
+            "let",
+            "mut",
+            "while",
+            "loop",
+            "extern",
+            "with",
+            "throw",
+            "init",
+            "main",
+            "test",
+            "in",
+            "guard",
+            "typealias",
+        ],
+        escape: Escape::Suffix("_"),
+    };
+
+    /// Rust keywords, including those reserved for future use.
+    ///
+    /// Source: <https://doc.rust-lang.org/reference/keywords.html>
+    pub const RUST: Keywords = Keywords {
+        words: &[
+            "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn",
+            "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+            "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe",
+            "use", "where", "while", "async", "await", "dyn", "abstract", "become", "box", "do",
+            "final", "macro", "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+        ],
+        escape: Escape::Suffix("_"),
+    };
+
+    pub fn contains(&self, ident: &str) -> bool {
+        self.words.contains(&ident)
+    }
+
+    /// Returns `ident` escaped if it's a keyword, or unmodified otherwise.
+    pub fn escape(&self, ident: &str) -> String {
+        if !self.contains(ident) {
+            return ident.to_string();
+        }
+        match self.escape {
+            Escape::Prefix(prefix) => format!("{prefix}{ident}"),
+            Escape::Suffix(suffix) => format!("{ident}{suffix}"),
+        }
+    }
+
+    /// Converts the WIT `name` to `case`, or escapes it as-is if it's a
+    /// keyword.
+    pub fn ident(&self, name: &str, case: Case) -> String {
+        if self.contains(name) {
+            self.escape(name)
+        } else {
+            case.apply(name)
+        }
+    }
+
+    /// Creates a new, empty, scope of names.
+    pub fn scope(&self) -> Scope {
+        Scope {
+            keywords: *self,
+            ns: Ns::default(),
+        }
+    }
+}
+
+/// User-supplied renames of WIT items, as passed with `--rename FROM=TO`.
+///
+/// `FROM` names an interface as `foo:bar/baz`, an item within an interface
+/// as `foo:bar/baz.qux`, or an item defined directly in the world as `qux`.
+/// `TO` is used verbatim as the identifier of the item. Items within an
+/// interface are looked up by the [`WorldKey`] the interface is imported or
+/// exported with, so that interfaces imported by plain name can be renamed
+/// too.
+///
+/// Functions are renamed by their full WIT name, so a method is renamed as
+/// `foo:bar/baz.[method]res.qux`.
+#[derive(Debug, Default, Clone)]
+pub struct Renames {
+    map: BTreeMap<String, String>,
+}
+
+impl Renames {
+    /// Parses a `FROM=TO` pair as passed on the command line.
+    pub fn parse(rename: &str) -> Result<(String, String)> {
+        match rename.split_once('=') {
+            Some((from, to)) if !from.is_empty() && !to.is_empty() => {
+                Ok((from.to_string(), to.to_string()))
+            }
+            _ => bail!("`--rename` option must be of the form `FROM=TO` (e.g. `--rename a=b`)"),
+        }
+    }
+
+    pub fn insert(&mut self, from: &str, to: &str) {
+        self.map.insert(from.to_string(), to.to_string());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the new name of the interface or world-level item `key`.
+    pub fn interface(&self, resolve: &Resolve, key: &WorldKey) -> Option<&str> {
+        self.get(&resolve.name_world_key(key))
+    }
+
+    /// Returns the new name of `name` defined within `owner`, an interface, or
+    /// directly within the world if `None`.
+    pub fn item(&self, resolve: &Resolve, owner: Option<&WorldKey>, name: &str) -> Option<&str> {
+        match owner {
+            Some(key) => self.get(&format!("{}.{name}", resolve.name_world_key(key))),
+            None => self.get(name),
+        }
+    }
+
+    /// Returns the new name of the named type `id` as used in `world`.
+    ///
+    /// Types defined in an interface are looked up by the key the interface
+    /// is imported or exported with in `world`.
+    pub fn ty(&self, resolve: &Resolve, world: WorldId, id: TypeId) -> Option<&str> {
+        let ty = &resolve.types[id];
+        let name = ty.name.as_deref()?;
+        match ty.owner {
+            TypeOwner::Interface(iface) => {
+                let world = &resolve.worlds[world];
+                let key =
+                    world
+                        .imports
+                        .iter()
+                        .chain(world.exports.iter())
+                        .find_map(|(key, item)| match item {
+                            WorldItem::Interface { id, .. } if *id == iface => Some(key),
+                            _ => None,
+                        })?;
+                self.item(resolve, Some(key), name)
+            }
+            TypeOwner::World(_) => self.get(name),
+            TypeOwner::None => None,
+        }
+    }
+
+    fn get(&self, from: &str) -> Option<&str> {
+        self.map.get(from).map(|s| s.as_str())
+    }
+
+    /// Returns the renames which don't refer to anything in `world`, which
+    /// are most likely typos.
+    pub fn unmatched(&self, resolve: &Resolve, world: WorldId) -> Vec<&str> {
+        let mut names = BTreeSet::new();
+        let world = &resolve.worlds[world];
+        for (key, item) in world.imports.iter().chain(world.exports.iter()) {
+            let prefix = resolve.name_world_key(key);
+            if let (WorldKey::Name(_), WorldItem::Function(_) | WorldItem::Type(_)) = (key, item) {
+                names.insert(prefix);
+                continue;
+            }
+            if let WorldItem::Interface { id, .. } = item {
+                let iface = &resolve.interfaces[*id];
+                for name in iface.types.keys().chain(iface.functions.keys()) {
+                    names.insert(format!("{prefix}.{name}"));
+                }
+            }
+            names.insert(prefix);
+        }
+        self.map
+            .keys()
+            .filter(|from| !names.contains(*from))
+            .map(|s| s.as_str())
+            .collect()
+    }
+
+    /// Prints a warning for each of the [`unmatched`](Renames::unmatched)
+    /// renames.
+    pub fn warn_unmatched(&self, resolve: &Resolve, world: WorldId) {
+        for from in self.unmatched(resolve, world) {
+            eprintln!("warning: rename of `{from}` did not match any interfaces or items");
+        }
+    }
+
+    /// Returns the renames which refer to something in `world`, for passing
+    /// on to another generator without warning about the others twice.
+    pub fn matched(&self, resolve: &Resolve, world: WorldId) -> Vec<(String, String)> {
+        let unmatched = self.unmatched(resolve, world);
+        self.map
+            .iter()
+            .filter(|(from, _)| !unmatched.contains(&from.as_str()))
+            .map(|(from, to)| (from.clone(), to.clone()))
+            .collect()
+    }
+}
+
+/// Identifier naming for a language: its keywords and any user renames.
+#[derive(Debug, Clone)]
+pub struct Naming {
+    pub keywords: Keywords,
+    pub renames: Renames,
+}
+
+impl Naming {
+    pub fn new(keywords: Keywords) -> Naming {
+        Naming {
+            keywords,
+            renames: Renames::default(),
+        }
+    }
+
+    /// See [`Keywords::ident`].
+    pub fn ident(&self, name: &str, case: Case) -> String {
+        self.keywords.ident(name, case)
+    }
+
+    /// Returns the identifier for the named type `id` as used in `world`,
+    /// honoring renames.
+    pub fn type_ident(&self, resolve: &Resolve, world: WorldId, id: TypeId, case: Case) -> String {
+        match self.renames.ty(resolve, world, id) {
+            Some(name) => name.to_string(),
+            None => self.ident(resolve.types[id].name.as_deref().unwrap(), case),
+        }
+    }
+
+    /// Returns the identifier for `name` within `owner`, honoring renames.
+    ///
+    /// See [`Renames::item`].
+    pub fn item_ident(
+        &self,
+        resolve: &Resolve,
+        owner: Option<&WorldKey>,
+        name: &str,
+        case: Case,
+    ) -> String {
+        match self.renames.item(resolve, owner, name) {
+            Some(name) => name.to_string(),
+            None => self.ident(name, case),
+        }
+    }
+
+    /// Creates a new, empty, scope of names.
+    pub fn scope(&self) -> Scope {
+        self.keywords.scope()
+    }
+}
+
+/// A namespace in generated code, such as the fields of a struct or the
+/// parameters of a function, within which identifiers must be unique.
+pub struct Scope {
+    keywords: Keywords,
+    ns: Ns,
+}
+
+impl Scope {
+    /// Reserves `ident` verbatim, such as for a name the generator itself
+    /// uses in this scope.
+    pub fn reserve(&mut self, ident: &str) -> Result<(), String> {
+        self.ns.insert(ident)
+    }
+
+    /// Returns an identifier for `name` which is unique within this scope.
+    ///
+    /// If two distinct WIT names map to the same identifier, as `foo-bar` and
+    /// `FOO-BAR` would in snake case, the later one is given a numeric suffix.
+    pub fn ident(&mut self, name: &str, case: Case) -> String {
+        let ident = self.keywords.ident(name, case);
+        self.ns.tmp(&ident)
+    }
+
+    /// Returns `ident`, made unique within this scope with a numeric suffix
+    /// if necessary.
+    pub fn unique(&mut self, ident: &str) -> String {
+        self.ns.tmp(ident)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keywords_are_escaped() {
+        assert_eq!(Keywords::RUST.ident("type", Case::Snake), "type_");
+        assert_eq!(Keywords::RUST.ident("my-type", Case::Snake), "my_type");
+        assert_eq!(Keywords::GO.ident("type", Case::Snake), "_type");
+        assert_eq!(Keywords::CSHARP.ident("class", Case::LowerCamel), "@class");
+        assert_eq!(Keywords::CSHARP.ident("class", Case::UpperCamel), "@class");
+        assert_eq!(
+            Keywords::JAVA.ident("new-thing", Case::LowerCamel),
+            "newThing"
+        );
+        assert_eq!(Keywords::C.ident("ret", Case::Snake), "ret_");
+    }
+
+    #[test]
+    fn scopes_resolve_collisions() {
+        let naming = Naming::new(Keywords::RUST);
+        let mut scope = naming.scope();
+        scope.reserve("ret").unwrap();
+        assert_eq!(scope.ident("foo-bar", Case::Snake), "foo_bar");
+        assert_eq!(scope.ident("FOO-BAR", Case::Snake), "foo_bar0");
+        assert_eq!(scope.ident("ret", Case::Snake), "ret1");
+        assert_eq!(scope.ident("type", Case::Snake), "type_");
+        assert_eq!(scope.unique("foo_bar"), "foo_bar2");
+    }
+
+    #[test]
+    fn renames() {
+        let mut resolve = Resolve::default();
+        let pkg = resolve
+            .push_str(
+                "test.wit",
+                "
+                    package foo:bar;
+                    interface baz {
+                        record qux {}
+                        f: func();
+                    }
+                    world w {
+                        import baz;
+                        import named: interface {
+                            record inner {}
+                        }
+                        record top {}
+                    }
+                ",
+            )
+            .unwrap();
+        let world = resolve.select_world(pkg, None).unwrap();
+        let iface = resolve.packages[pkg].interfaces["baz"];
+        let key = WorldKey::Interface(iface);
+        let named = WorldKey::Name("named".to_string());
+        let qux = resolve.interfaces[iface].types["qux"];
+        let top = resolve.worlds[world]
+            .imports
+            .values()
+            .find_map(|item| match item {
+                WorldItem::Type(id) => Some(*id),
+                _ => None,
+            })
+            .unwrap();
+
+        let mut renames = Renames::default();
+        for rename in [
+            "foo:bar/baz=b",
+            "foo:bar/baz.qux=Quux",
+            "named.inner=Inner",
+            "top=Top",
+            "nope=x",
+        ] {
+            let (from, to) = Renames::parse(rename).unwrap();
+            renames.insert(&from, &to);
+        }
+        assert!(Renames::parse("nope").is_err());
+
+        assert_eq!(renames.interface(&resolve, &key), Some("b"));
+        assert_eq!(renames.item(&resolve, Some(&key), "qux"), Some("Quux"));
+        assert_eq!(renames.item(&resolve, Some(&named), "inner"), Some("Inner"));
+        assert_eq!(renames.item(&resolve, None, "top"), Some("Top"));
+        assert_eq!(renames.item(&resolve, Some(&key), "f"), None);
+        assert_eq!(renames.unmatched(&resolve, world), ["nope"]);
+        let matched = renames.matched(&resolve, world);
+        assert_eq!(matched.len(), 4);
+        assert!(matched.iter().all(|(from, _)| from != "nope"));
+
+        assert_eq!(renames.ty(&resolve, world, qux), Some("Quux"));
+        assert_eq!(renames.ty(&resolve, world, top), Some("Top"));
+        let mut naming = Naming::new(Keywords::C);
+        naming.renames = renames;
+        assert_eq!(naming.type_ident(&resolve, world, qux, Case::Snake), "Quux");
+        assert_eq!(
+            naming.item_ident(&resolve, Some(&key), "f", Case::Snake),
+            "f"
+        );
+    }
+}
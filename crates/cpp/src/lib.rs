@@ -0,0 +1,1729 @@
+use anyhow::Result;
+use heck::*;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use wit_bindgen_c::{
+    c_func_name, flags_repr, gen_type_name, imported_types_used_by_exported_interfaces, int_repr,
+    is_arg_by_pointer, owner_namespace as c_owner_namespace, to_c_ident, Allocator, CTypeNameInfo,
+};
+use wit_bindgen_core::{
+    dealias, uwriteln, wit_parser::*, Case, Direction, Files, InterfaceGenerator as _, Keywords,
+    Naming, Source, WorldGenerator,
+};
+
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "clap", derive(clap::Args))]
+pub struct Opts {
+    /// Rename the interface or item `K` to `V` in the generated source code.
+    ///
+    /// `K` is either an interface such as `foo:bar/baz`, an item within an
+    /// interface such as `foo:bar/baz.qux`, or an item defined in the world.
+    /// Renamed interfaces name the innermost C++ namespace, and the C names
+    /// follow the conventions of `wit-bindgen c --rename`.
+    #[cfg_attr(feature = "clap", arg(long, name = "K=V", value_parser = wit_bindgen_core::Renames::parse))]
+    pub rename: Vec<(String, String)>,
+
+    /// Rename the world in the generated source code and file names.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub rename_world: Option<String>,
+
+    /// Skip generating an object file which contains type information for the
+    /// world that is being generated.
+    #[cfg_attr(feature = "clap", arg(long, default_value_t = false))]
+    pub no_object_file: bool,
+
+    /// Add the specified suffix to the name of the custom section containing
+    /// the component type.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub type_section_suffix: Option<String>,
+
+    /// Configure how the C bindings and the C++ conversions allocate and
+    /// release memory.
+    ///
+    /// With `custom`, C++ code calls `wit_malloc` and `wit_free` directly, so
+    /// those must be defined even if `WIT_MALLOC` and `WIT_FREE` are.
+    #[cfg_attr(feature = "clap", arg(long, default_value_t = Allocator::default()))]
+    pub allocator: Allocator,
+}
+
+impl Opts {
+    pub fn build(&self) -> Box<dyn WorldGenerator> {
+        Box::new(Cpp {
+            opts: self.clone(),
+            world: String::new(),
+            world_id: None,
+            naming: Naming::new(Keywords::C),
+            h_src: Source::default(),
+            cpp_src: Source::default(),
+            needs_string: false,
+            c_type_names: HashMap::new(),
+            type_names: HashMap::new(),
+            resources: HashMap::new(),
+            conversions: HashSet::new(),
+        })
+    }
+}
+
+/// Generates C++ bindings which wrap the bindings of `wit-bindgen-c`.
+///
+/// The C bindings, generated without signature flattening, take care of the
+/// canonical ABI. The C++ bindings convert between their C types and
+/// idiomatic C++ ones, and manage resource handles with RAII classes.
+struct Cpp {
+    opts: Opts,
+    world: String,
+    world_id: Option<WorldId>,
+    naming: Naming,
+
+    /// The header's type definitions, conversions and imported functions.
+    h_src: Source,
+    /// The C functions which the C bindings expect exports to define,
+    /// forwarding to their C++ implementations.
+    cpp_src: Source,
+    needs_string: bool,
+
+    /// Names of the C types of all types defined so far, as computed by the C
+    /// generator.
+    c_type_names: HashMap<TypeId, String>,
+    /// Fully qualified names of the C++ structs, classes and enums.
+    type_names: HashMap<TypeId, String>,
+    resources: HashMap<TypeId, ResourceInfo>,
+    /// Names of the C types which conversion functions were defined for.
+    ///
+    /// Anonymous types made up of primitives share a C type across
+    /// interfaces, and so share their conversions as well.
+    conversions: HashSet<String>,
+}
+
+struct ResourceInfo {
+    direction: Direction,
+    /// Fully qualified name of the C++ class.
+    class: String,
+    /// The prefix of the C functions managing handles to this resource.
+    c_prefix: String,
+    own: String,
+    borrow: String,
+    /// The C type standing in for the representation of exported resources.
+    rep: String,
+}
+
+impl WorldGenerator for Cpp {
+    fn preprocess(&mut self, resolve: &Resolve, world: WorldId) {
+        self.world = self
+            .opts
+            .rename_world
+            .clone()
+            .unwrap_or_else(|| resolve.worlds[world].name.clone());
+        self.world_id = Some(world);
+
+        for (from, to) in self.opts.rename.iter() {
+            self.naming.renames.insert(from, to);
+        }
+        self.naming.renames.warn_unmatched(resolve, world);
+    }
+
+    fn import_interface(
+        &mut self,
+        resolve: &Resolve,
+        name: &WorldKey,
+        id: InterfaceId,
+        _files: &mut Files,
+    ) -> Result<()> {
+        let mut gen = self.interface(resolve, Some((id, name)), Direction::Import);
+        gen.define_interface_types(id);
+        for (_name, func) in resolve.interfaces[id].functions.iter() {
+            gen.import(Some(name), func);
+        }
+        gen.finish();
+        Ok(())
+    }
+
+    fn import_funcs(
+        &mut self,
+        resolve: &Resolve,
+        _world: WorldId,
+        funcs: &[(&str, &Function)],
+        _files: &mut Files,
+    ) {
+        let mut gen = self.interface(resolve, None, Direction::Import);
+        gen.define_function_types(funcs);
+        for (_name, func) in funcs.iter() {
+            gen.import(None, func);
+        }
+        gen.finish();
+    }
+
+    fn import_types(
+        &mut self,
+        resolve: &Resolve,
+        _world: WorldId,
+        types: &[(&str, TypeId)],
+        _files: &mut Files,
+    ) {
+        let mut gen = self.interface(resolve, None, Direction::Import);
+        let mut live = LiveTypes::default();
+        for (_, id) in types {
+            live.add_type_id(resolve, *id);
+        }
+        gen.define_live_types(&live);
+        gen.finish();
+    }
+
+    fn export_types(
+        &mut self,
+        resolve: &Resolve,
+        _world: WorldId,
+        types: &[(&str, TypeId)],
+        _files: &mut Files,
+    ) -> Result<()> {
+        let mut gen = self.interface(resolve, None, Direction::Export);
+        let mut live = LiveTypes::default();
+        for (_, id) in types {
+            live.add_type_id(resolve, *id);
+        }
+        gen.define_live_types(&live);
+        gen.finish();
+        Ok(())
+    }
+
+    fn export_funcs(
+        &mut self,
+        resolve: &Resolve,
+        _world: WorldId,
+        funcs: &[(&str, &Function)],
+        _files: &mut Files,
+    ) -> Result<()> {
+        let mut gen = self.interface(resolve, None, Direction::Export);
+        gen.define_function_types(funcs);
+        for (_name, func) in funcs.iter() {
+            gen.export(None, func);
+        }
+        gen.finish();
+        Ok(())
+    }
+
+    fn pre_export_interface(&mut self, resolve: &Resolve, _files: &mut Files) -> Result<()> {
+        // Exported interfaces get types of their own, except for the imported
+        // types that they use, mirroring the C bindings.
+        let world = self.world_id.unwrap();
+        let live_import_types = imported_types_used_by_exported_interfaces(resolve, world);
+        self.c_type_names
+            .retain(|k, _| live_import_types.contains(k));
+        self.type_names.retain(|k, _| live_import_types.contains(k));
+        self.resources.retain(|k, _| live_import_types.contains(k));
+        Ok(())
+    }
+
+    fn export_interface(
+        &mut self,
+        resolve: &Resolve,
+        name: &WorldKey,
+        id: InterfaceId,
+        _files: &mut Files,
+    ) -> Result<()> {
+        let mut gen = self.interface(resolve, Some((id, name)), Direction::Export);
+        gen.define_interface_types(id);
+        for (_name, func) in resolve.interfaces[id].functions.iter() {
+            gen.export(Some(name), func);
+        }
+        gen.finish();
+        Ok(())
+    }
+
+    fn finish(&mut self, resolve: &Resolve, id: WorldId, files: &mut Files) -> Result<()> {
+        let snake = self.world.to_snake_case();
+        let shouty = self.world.to_shouty_snake_case();
+        let version = env!("CARGO_PKG_VERSION");
+
+        let mut h = Source::default();
+        uwriteln!(h, "// Generated by `wit-bindgen` {version}. DO NOT EDIT!");
+        uwriteln!(h, "#ifndef __BINDINGS_{shouty}_HPP");
+        uwriteln!(h, "#define __BINDINGS_{shouty}_HPP");
+        uwriteln!(h, "\n#include \"{snake}.h\"\n");
+        for header in [
+            "cstdint",
+            "cstddef",
+            "cstdlib",
+            "cstring",
+            "expected",
+            "functional",
+            "memory",
+            "optional",
+            "span",
+            "string",
+            "string_view",
+            "tuple",
+            "utility",
+            "variant",
+            "vector",
+        ] {
+            uwriteln!(h, "#include <{header}>");
+        }
+        h.push_str(RUNTIME);
+        let (allocate, deallocate) = match self.opts.allocator {
+            Allocator::Libc => ("std::malloc(size)".to_string(), "std::free(ptr);"),
+            Allocator::Custom => (
+                "wit_malloc(size, alignof(std::max_align_t))".to_string(),
+                "wit_free(ptr);",
+            ),
+            Allocator::Arena => (
+                format!("{snake}_arena_alloc(size, alignof(std::max_align_t))"),
+                "(void) ptr;",
+            ),
+        };
+        let hooks = format!(
+            "
+            namespace {snake}_abi {{
+
+            /// Allocates memory the same way as the C bindings do.
+            inline void *allocate(size_t size) {{
+                void *ptr = {allocate};
+                if (ptr == nullptr) {{
+                    std::abort();
+                }}
+                return ptr;
+            }}
+
+            /// Releases memory the same way as the C bindings do.
+            inline void deallocate(void *ptr) {{
+                {deallocate}
+            }}
+
+            /// Allocates `size` bytes from `scratch`, or with `allocate` when the
+            /// memory is handed over to the caller of an export.
+            inline void *alloc(size_t size, wit::Scratch *scratch) {{
+                if (size == 0) {{
+                    return nullptr;
+                }}
+                if (scratch != nullptr) {{
+                    return scratch->alloc(size);
+                }}
+                return allocate(size);
+            }}
+
+            }}  // namespace {snake}_abi
+            "
+        );
+        h.push_str(&hooks);
+        if self.needs_string {
+            let helpers = format!(
+                "
+                namespace {snake}_abi {{
+
+                inline std::string lift_string({snake}_string_t *src) {{
+                    std::string ret(reinterpret_cast<const char *>(src->ptr), src->len);
+                    if (src->len > 0) {{
+                        deallocate(src->ptr);
+                    }}
+                    return ret;
+                }}
+
+                inline void lower_string(std::string_view src, {snake}_string_t *dst, wit::Scratch *scratch) {{
+                    dst->len = src.size();
+                    if (scratch != nullptr) {{
+                        dst->ptr = reinterpret_cast<uint8_t *>(const_cast<char *>(src.data()));
+                        return;
+                    }}
+                    dst->ptr = static_cast<uint8_t *>(alloc(src.size(), nullptr));
+                    if (!src.empty()) {{
+                        std::memcpy(dst->ptr, src.data(), src.size());
+                    }}
+                }}
+
+                }}  // namespace {snake}_abi
+                "
+            );
+            h.push_str(&helpers);
+        }
+        h.push_str(&self.h_src);
+        uwriteln!(h, "\n#endif");
+        files.push(&format!("{snake}.hpp"), h.as_bytes());
+
+        let mut cpp = Source::default();
+        uwriteln!(cpp, "// Generated by `wit-bindgen` {version}. DO NOT EDIT!");
+        uwriteln!(cpp, "#include \"{snake}.hpp\"");
+        cpp.push_str(&self.cpp_src);
+        files.push(&format!("{snake}.cpp"), cpp.as_bytes());
+
+        let mut opts = wit_bindgen_c::Opts::default();
+        opts.no_sig_flattening = true;
+        opts.autodrop_borrows = wit_bindgen_c::Enabled::Yes;
+        opts.rename = self.naming.renames.matched(resolve, id);
+        opts.rename_world = self.opts.rename_world.clone();
+        opts.no_object_file = self.opts.no_object_file;
+        opts.type_section_suffix = self.opts.type_section_suffix.clone();
+        opts.allocator = self.opts.allocator;
+        opts.build().generate(resolve, id, files)
+    }
+}
+
+/// Support code shared by the bindings of all worlds.
+const RUNTIME: &str = "
+#ifndef __WIT_BINDGEN_CPP_RUNTIME
+#define __WIT_BINDGEN_CPP_RUNTIME
+
+namespace wit {
+
+/// Memory allocated while lowering the arguments of an imported function,
+/// which is freed once the call has returned.
+///
+/// The memory is managed with the allocation hooks of the bindings which
+/// created the scratch space.
+class Scratch {
+public:
+    Scratch(void *(*allocate)(size_t), void (*deallocate)(void *))
+        : allocate_(allocate), deallocate_(deallocate) {}
+    Scratch(const Scratch &) = delete;
+    Scratch &operator=(const Scratch &) = delete;
+    ~Scratch() {
+        for (void *ptr : allocs_) {
+            deallocate_(ptr);
+        }
+    }
+
+    void *alloc(size_t size) {
+        void *ptr = allocate_(size);
+        allocs_.push_back(ptr);
+        return ptr;
+    }
+
+private:
+    void *(*allocate_)(size_t);
+    void (*deallocate_)(void *);
+    std::vector<void *> allocs_;
+};
+
+}  // namespace wit
+
+#endif
+";
+
+impl Cpp {
+    fn interface<'a>(
+        &'a mut self,
+        resolve: &'a Resolve,
+        interface: Option<(InterfaceId, &'a WorldKey)>,
+        direction: Direction,
+    ) -> InterfaceGenerator<'a> {
+        let namespace = self.namespace(resolve, interface.map(|(_, key)| key), direction);
+        InterfaceGenerator {
+            gen: self,
+            resolve,
+            interface,
+            direction,
+            namespace,
+            decls: Source::default(),
+            types: Source::default(),
+            conversions: Source::default(),
+            funcs: Source::default(),
+        }
+    }
+
+    /// Returns the C++ namespace of the interface `key`, or of the world
+    /// itself if `key` is `None`.
+    fn namespace(&self, resolve: &Resolve, key: Option<&WorldKey>, direction: Direction) -> String {
+        let ident = |name: &str| Keywords::C.ident(name, Case::Snake);
+        let mut ns = String::new();
+        if let Direction::Export = direction {
+            ns.push_str("exports::");
+        }
+        let key = match key {
+            Some(key) => key,
+            None => {
+                ns.push_str(&ident(&self.world));
+                return ns;
+            }
+        };
+        let name = match key {
+            WorldKey::Name(name) => name,
+            WorldKey::Interface(id) => {
+                let iface = &resolve.interfaces[*id];
+                let pkg = &resolve.packages[iface.package.unwrap()];
+                ns.push_str(&ident(&pkg.name.namespace));
+                ns.push_str("::");
+                ns.push_str(&ident(&pkg.name.name));
+                ns.push_str("::");
+                let pkg_has_multiple_versions = resolve.packages.iter().any(|(_, p)| {
+                    p.name.namespace == pkg.name.namespace
+                        && p.name.name == pkg.name.name
+                        && p.name.version != pkg.name.version
+                });
+                if pkg_has_multiple_versions {
+                    if let Some(version) = &pkg.name.version {
+                        let version = version.to_string().replace(['.', '-', '+'], "_");
+                        ns.push_str(&format!("v{version}::"));
+                    }
+                }
+                iface.name.as_ref().unwrap()
+            }
+        };
+        match self.naming.renames.interface(resolve, key) {
+            Some(rename) => ns.push_str(rename),
+            None => ns.push_str(&ident(name)),
+        }
+        ns
+    }
+}
+
+/// Names used within the bindings of a function.
+struct FuncNames {
+    /// The parameters of the C++ function, excluding the `self` of methods.
+    params: Vec<(String, Type)>,
+    /// Locals holding the lowered form of each parameter.
+    locals: Vec<String>,
+    scratch: String,
+    /// Locals holding the C results of imports.
+    rets: Vec<String>,
+}
+
+struct InterfaceGenerator<'a> {
+    gen: &'a mut Cpp,
+    resolve: &'a Resolve,
+    interface: Option<(InterfaceId, &'a WorldKey)>,
+    direction: Direction,
+    /// The C++ namespace, without a leading `::`, of this interface.
+    namespace: String,
+    /// Forward declarations of the types defined in `types`, so that
+    /// resources may refer to types defined after them.
+    decls: Source,
+    types: Source,
+    /// Functions converting between C and C++ types.
+    conversions: Source,
+    funcs: Source,
+}
+
+impl<'a> InterfaceGenerator<'a> {
+    fn define_interface_types(&mut self, id: InterfaceId) {
+        let mut live = LiveTypes::default();
+        live.add_interface(self.resolve, id);
+        self.define_live_types(&live);
+    }
+
+    fn define_function_types(&mut self, funcs: &[(&str, &Function)]) {
+        let mut live = LiveTypes::default();
+        for (_, func) in funcs {
+            live.add_func(self.resolve, func);
+        }
+        self.define_live_types(&live);
+    }
+
+    fn define_live_types(&mut self, live: &LiveTypes) {
+        let resolve = self.resolve;
+        let mut defined = Vec::new();
+        for id in live.iter() {
+            if self.gen.c_type_names.contains_key(&id) {
+                continue;
+            }
+
+            // Name the C type the same way as the C generator does.
+            let (info, mut encoded) = gen_type_name(resolve, id);
+            let namespace = match info {
+                CTypeNameInfo::Anonymous { is_prim: true } => self.gen.world.to_snake_case(),
+                CTypeNameInfo::Named { name } => {
+                    let owner = match resolve.types[id].owner {
+                        TypeOwner::Interface(_) => self.interface.map(|(_, key)| key),
+                        _ => None,
+                    };
+                    if let Some(rename) = self.gen.naming.renames.item(resolve, owner, name) {
+                        encoded = rename.to_string();
+                    }
+                    self.c_owner_namespace(id)
+                }
+                _ => self.c_owner_namespace(id),
+            };
+            let prev = self
+                .gen
+                .c_type_names
+                .insert(id, format!("{namespace}_{encoded}_t"));
+            assert!(prev.is_none());
+
+            if let Some(name) = &resolve.types[id].name {
+                self.declare_type(id, name);
+            }
+            defined.push(id);
+        }
+
+        for id in defined {
+            if let Some(name) = &resolve.types[id].name {
+                self.define_type(name, id);
+            }
+            self.define_conversions(id);
+        }
+    }
+
+    fn c_owner_namespace(&self, id: TypeId) -> String {
+        c_owner_namespace(
+            self.interface,
+            matches!(self.direction, Direction::Import),
+            self.gen.world.clone(),
+            self.resolve,
+            id,
+            &self.gen.naming.renames,
+        )
+    }
+
+    /// Records the C++ name of the named type `id` and forward declares it.
+    fn declare_type(&mut self, id: TypeId, name: &str) {
+        let ident = type_ident(name);
+        let class = format!("::{}::{ident}", self.namespace);
+        match &self.resolve.types[id].kind {
+            TypeDefKind::Record(_) | TypeDefKind::Variant(_) => {
+                uwriteln!(self.decls, "struct {ident};");
+            }
+            TypeDefKind::Enum(e) => {
+                uwriteln!(self.decls, "enum class {ident} : {};", int_repr(e.tag()));
+            }
+            TypeDefKind::Flags(f) => {
+                uwriteln!(
+                    self.decls,
+                    "enum class {ident} : {};",
+                    int_repr(flags_repr(f))
+                );
+            }
+            TypeDefKind::Resource => {
+                uwriteln!(self.decls, "class {ident};");
+                let ns = self.c_owner_namespace(id);
+                let snake = name.to_snake_case();
+                let info = ResourceInfo {
+                    direction: self.direction,
+                    class: class.clone(),
+                    c_prefix: format!("{ns}_{snake}"),
+                    own: format!("{ns}_own_{snake}_t"),
+                    borrow: format!("{ns}_borrow_{snake}_t"),
+                    rep: self.gen.c_type_names[&id].clone(),
+                };
+                self.gen.resources.insert(id, info);
+            }
+            _ => return,
+        }
+        self.gen.type_names.insert(id, class);
+    }
+
+    /// Defines the functions lifting values of the anonymous or aggregate
+    /// type `id` out of their C representation, and lowering them into it.
+    fn define_conversions(&mut self, id: TypeId) {
+        let resolve = self.resolve;
+        let kind = &resolve.types[id].kind;
+        if !matches!(
+            kind,
+            TypeDefKind::Record(_)
+                | TypeDefKind::Variant(_)
+                | TypeDefKind::Tuple(_)
+                | TypeDefKind::Option(_)
+                | TypeDefKind::Result(_)
+                | TypeDefKind::List(_)
+        ) {
+            return;
+        }
+        let c_ty = self.gen.c_type_names[&id].clone();
+        if !self.gen.conversions.insert(c_ty.clone()) {
+            return;
+        }
+        let prefix = c_ty.strip_suffix("_t").unwrap();
+        let ty = Type::Id(id);
+        let cpp_ty = self.cpp_type(&ty);
+
+        let mut lift = String::new();
+        let mut lower = String::new();
+        let mut empty = false;
+        match kind {
+            TypeDefKind::Record(record) => {
+                empty = record.fields.is_empty();
+                uwriteln!(lift, "return {cpp_ty}{{");
+                for field in record.fields.iter() {
+                    let c_field = to_c_ident(&field.name);
+                    let value = self.lift(&field.ty, &format!("src->{c_field}"));
+                    uwriteln!(lift, "{value},");
+
+                    let src = self.moved(&field.ty, &format!("src.{}", field_ident(&field.name)));
+                    let dst = format!("dst->{c_field}");
+                    lower.push_str(&self.lower(&field.ty, &src, &dst, "scratch"));
+                }
+                uwriteln!(lift, "}};");
+            }
+
+            TypeDefKind::Tuple(tuple) => {
+                empty = tuple.types.is_empty();
+                let mut values = Vec::new();
+                for (i, ty) in tuple.types.iter().enumerate() {
+                    values.push(self.lift(ty, &format!("src->f{i}")));
+
+                    let src = self.moved(ty, &format!("std::get<{i}>(src)"));
+                    lower.push_str(&self.lower(ty, &src, &format!("dst->f{i}"), "scratch"));
+                }
+                uwriteln!(lift, "return {cpp_ty}{{{}}};", values.join(", "));
+            }
+
+            TypeDefKind::Variant(variant) => {
+                uwriteln!(lift, "switch (src->tag) {{");
+                for (i, case) in variant.cases.iter().enumerate() {
+                    let case_ty = format!("{cpp_ty}::{}", case_ident(&cpp_ty, &case.name));
+                    let value = match &case.ty {
+                        Some(ty) => self.lift(ty, &format!("src->val.{}", to_c_ident(&case.name))),
+                        None => String::new(),
+                    };
+                    uwriteln!(lift, "case {i}:");
+                    uwriteln!(lift, "return {cpp_ty}{{{case_ty}{{{value}}}}};");
+                }
+                uwriteln!(lift, "default:");
+                uwriteln!(lift, "std::abort();");
+                uwriteln!(lift, "}}");
+
+                let tag = int_repr(variant.tag());
+                uwriteln!(
+                    lower,
+                    "dst->tag = static_cast<{tag}>(src.variants.index());"
+                );
+                if variant.cases.iter().any(|c| c.ty.is_some()) {
+                    uwriteln!(lower, "switch (src.variants.index()) {{");
+                    for (i, case) in variant.cases.iter().enumerate() {
+                        let Some(ty) = &case.ty else { continue };
+                        let src =
+                            self.moved(ty, &format!("std::get_if<{i}>(&src.variants)->value"));
+                        let dst = format!("dst->val.{}", to_c_ident(&case.name));
+                        uwriteln!(lower, "case {i}:");
+                        lower.push_str(&self.lower(ty, &src, &dst, "scratch"));
+                        uwriteln!(lower, "break;");
+                    }
+                    uwriteln!(lower, "default:");
+                    uwriteln!(lower, "break;");
+                    uwriteln!(lower, "}}");
+                }
+            }
+
+            TypeDefKind::Option(payload) => {
+                let value = self.lift(payload, "src->val");
+                uwriteln!(lift, "if (!src->is_some) {{");
+                uwriteln!(lift, "return std::nullopt;");
+                uwriteln!(lift, "}}");
+                uwriteln!(lift, "return {cpp_ty}({value});");
+
+                let src = self.moved(payload, "*src");
+                uwriteln!(lower, "dst->is_some = src.has_value();");
+                uwriteln!(lower, "if (src.has_value()) {{");
+                lower.push_str(&self.lower(payload, &src, "dst->val", "scratch"));
+                uwriteln!(lower, "}}");
+            }
+
+            TypeDefKind::Result(result) => {
+                let (err_ty, err) = match &result.err {
+                    Some(ty) => (self.cpp_type(ty), self.lift(ty, "src->val.err")),
+                    None => ("std::monostate".to_string(), "std::monostate{}".to_string()),
+                };
+                let ok = match &result.ok {
+                    Some(ty) => self.lift(ty, "src->val.ok"),
+                    None => String::new(),
+                };
+                uwriteln!(lift, "if (src->is_err) {{");
+                uwriteln!(lift, "return std::unexpected<{err_ty}>({err});");
+                uwriteln!(lift, "}}");
+                uwriteln!(lift, "return {cpp_ty}({ok});");
+
+                uwriteln!(lower, "dst->is_err = !src.has_value();");
+                match (&result.ok, &result.err) {
+                    (Some(ok), err) => {
+                        let src = self.moved(ok, "*src");
+                        uwriteln!(lower, "if (src.has_value()) {{");
+                        lower.push_str(&self.lower(ok, &src, "dst->val.ok", "scratch"));
+                        if let Some(err) = err {
+                            let src = self.moved(err, "src.error()");
+                            uwriteln!(lower, "}} else {{");
+                            lower.push_str(&self.lower(err, &src, "dst->val.err", "scratch"));
+                        }
+                        uwriteln!(lower, "}}");
+                    }
+                    (None, Some(err)) => {
+                        let src = self.moved(err, "src.error()");
+                        uwriteln!(lower, "if (!src.has_value()) {{");
+                        lower.push_str(&self.lower(err, &src, "dst->val.err", "scratch"));
+                        uwriteln!(lower, "}}");
+                    }
+                    (None, None) => {}
+                }
+            }
+
+            TypeDefKind::List(elem) => {
+                let c_elem = self.c_type(elem);
+                if self.is_prim(elem) {
+                    uwriteln!(lift, "{cpp_ty} ret(src->ptr, src->ptr + src->len);");
+                } else {
+                    let value = self.lift(elem, "src->ptr[i]");
+                    uwriteln!(lift, "{cpp_ty} ret;");
+                    uwriteln!(lift, "ret.reserve(src->len);");
+                    uwriteln!(lift, "for (size_t i = 0; i < src->len; i++) {{");
+                    uwriteln!(lift, "ret.push_back({value});");
+                    uwriteln!(lift, "}}");
+                }
+                uwriteln!(lift, "if (src->len > 0) {{");
+                uwriteln!(lift, "deallocate(src->ptr);");
+                uwriteln!(lift, "}}");
+                uwriteln!(lift, "return ret;");
+
+                uwriteln!(lower, "dst->len = src.size();");
+                if self.is_prim(elem) && !matches!(self.dealias_ty(elem), Type::Bool) {
+                    // The arguments of imports are passed as-is, without a
+                    // copy, when C and C++ agree on their representation.
+                    uwriteln!(lower, "if (scratch != nullptr) {{");
+                    uwriteln!(
+                        lower,
+                        "dst->ptr = const_cast<{c_elem} *>(reinterpret_cast<const {c_elem} *>(src.data()));"
+                    );
+                    uwriteln!(lower, "return;");
+                    uwriteln!(lower, "}}");
+                }
+                uwriteln!(
+                    lower,
+                    "dst->ptr = static_cast<{c_elem} *>(alloc(src.size() * sizeof({c_elem}), scratch));"
+                );
+                let src = self.moved(elem, "src[i]");
+                uwriteln!(lower, "for (size_t i = 0; i < src.size(); i++) {{");
+                lower.push_str(&self.lower(elem, &src, "dst->ptr[i]", "scratch"));
+                uwriteln!(lower, "}}");
+            }
+
+            _ => unreachable!(),
+        }
+
+        let param = declare(&self.lower_param(&ty), "src");
+        let unused = if empty { "[[maybe_unused]] " } else { "" };
+        let conversions = format!(
+            "
+inline {cpp_ty} lift_{prefix}({unused}{c_ty} *src) {{
+{lift}}}
+
+inline void lower_{prefix}({unused}{param}, {unused}{c_ty} *dst, [[maybe_unused]] wit::Scratch *scratch) {{
+{lower}}}
+"
+        );
+        self.conversions.push_str(&conversions);
+    }
+
+    /// Returns the functions, including constructors, of the resource `id`.
+    fn resource_functions(&self, id: TypeId) -> Vec<&'a Function> {
+        let resolve = self.resolve;
+        let funcs: Vec<&'a Function> = match self.interface {
+            Some((iface, _)) => resolve.interfaces[iface].functions.values().collect(),
+            None => {
+                let world = &resolve.worlds[self.gen.world_id.unwrap()];
+                let items = match self.direction {
+                    Direction::Import => &world.imports,
+                    Direction::Export => &world.exports,
+                };
+                items
+                    .values()
+                    .filter_map(|item| match item {
+                        WorldItem::Function(func) => Some(func),
+                        _ => None,
+                    })
+                    .collect()
+            }
+        };
+        funcs
+            .into_iter()
+            .filter(|func| func.kind.resource() == Some(id))
+            .collect()
+    }
+
+    fn import(&mut self, key: Option<&WorldKey>, func: &Function) {
+        let resolve = self.resolve;
+        let c_name = c_func_name(
+            true,
+            resolve,
+            &self.gen.world,
+            key,
+            func,
+            &self.gen.naming.renames,
+        );
+        let names = self.func_names(func);
+        let params = self.params(&names.params);
+        let ret_ty = self.result_type(func);
+
+        let mut body = String::new();
+        let mut args = Vec::new();
+        if let FunctionKind::Method(_) = func.kind {
+            args.push("borrow_handle()".to_string());
+        }
+        let scratch = &names.scratch;
+        if names.params.iter().any(|(_, ty)| self.uses_scratch(ty)) {
+            let abi = format!("::{}_abi", self.gen.world.to_snake_case());
+            uwriteln!(
+                body,
+                "wit::Scratch {scratch}({abi}::allocate, {abi}::deallocate);"
+            );
+        }
+        for ((param, ty), local) in names.params.iter().zip(&names.locals) {
+            let src = self.moved(ty, param);
+            match self.lower_expr(ty, &src) {
+                Some(arg) => args.push(arg),
+                None => {
+                    uwriteln!(body, "{} {local}{{}};", self.c_type(ty));
+                    body.push_str(&self.lower(ty, &src, local, &format!("&{scratch}")));
+                    args.push(format!("&{local}"));
+                }
+            }
+        }
+
+        let results = func.results.iter_types().collect::<Vec<_>>();
+        match &results[..] {
+            [] => uwriteln!(body, "{c_name}({});", args.join(", ")),
+            [ty] if matches!(func.kind, FunctionKind::Constructor(_)) => {
+                debug_assert!(!is_arg_by_pointer(resolve, ty));
+                uwriteln!(body, "handle_ = {c_name}({}).__handle;", args.join(", "));
+            }
+            [ty] if !is_arg_by_pointer(resolve, ty) => {
+                let ret = &names.rets[0];
+                uwriteln!(
+                    body,
+                    "{} {ret} = {c_name}({});",
+                    self.c_type(ty),
+                    args.join(", ")
+                );
+                uwriteln!(body, "return {};", self.lift(ty, ret));
+            }
+            results => {
+                let mut values = Vec::new();
+                for (ty, ret) in results.iter().zip(&names.rets) {
+                    uwriteln!(body, "{} {ret}{{}};", self.c_type(ty));
+                    args.push(format!("&{ret}"));
+                    values.push(self.lift(ty, ret));
+                }
+                uwriteln!(body, "{c_name}({});", args.join(", "));
+                if let [value] = &values[..] {
+                    uwriteln!(body, "return {value};");
+                } else {
+                    uwriteln!(body, "return {ret_ty}({});", values.join(", "));
+                }
+            }
+        }
+
+        let name = func_ident(func);
+        let sig = match &func.kind {
+            FunctionKind::Freestanding => format!("inline {ret_ty} {name}({params})"),
+            FunctionKind::Method(id) => {
+                let class = self.resource_ident(*id);
+                format!("inline {ret_ty} {class}::{name}({params}) const")
+            }
+            FunctionKind::Static(id) => {
+                let class = self.resource_ident(*id);
+                format!("inline {ret_ty} {class}::{name}({params})")
+            }
+            FunctionKind::Constructor(id) => {
+                let class = self.resource_ident(*id);
+                format!("inline {class}::{class}({params}) : handle_(0), owned_(true)")
+            }
+        };
+        self.funcs.push_str("\n");
+        if let FunctionKind::Freestanding = func.kind {
+            docs_to(&mut self.funcs, &func.docs);
+        }
+        let def = format!("{sig} {{\n{body}}}\n");
+        self.funcs.push_str(&def);
+    }
+
+    fn export(&mut self, key: Option<&WorldKey>, func: &Function) {
+        let resolve = self.resolve;
+        let c_name = c_func_name(
+            false,
+            resolve,
+            &self.gen.world,
+            key,
+            func,
+            &self.gen.naming.renames,
+        );
+        let ret_ty = self.result_type(func);
+        let name = func_ident(func);
+
+        // Resources declare their functions as members of their class, and
+        // freestanding functions are declared here for the user to define.
+        if let FunctionKind::Freestanding = func.kind {
+            let names = self.func_names(func);
+            let params = self.params(&names.params);
+            self.funcs.push_str("\n");
+            docs_to(&mut self.funcs, &func.docs);
+            uwriteln!(self.funcs, "{ret_ty} {name}({params});");
+        }
+
+        let mut c_params = Vec::new();
+        let mut args = Vec::new();
+        for (i, (_, ty)) in func.params.iter().enumerate() {
+            let c_ty = self.c_type(ty);
+            let arg = if is_arg_by_pointer(resolve, ty) {
+                c_params.push(format!("{c_ty} *arg{i}"));
+                format!("*arg{i}")
+            } else {
+                c_params.push(format!("{c_ty} arg{i}"));
+                format!("arg{i}")
+            };
+            if i == 0 && matches!(func.kind, FunctionKind::Method(_)) {
+                continue;
+            }
+            args.push(self.lift(ty, &arg));
+        }
+        let callee = match &func.kind {
+            FunctionKind::Freestanding => format!("::{}::{name}", self.namespace),
+            FunctionKind::Method(id) => {
+                let class = &self.gen.resources[id].class;
+                format!("reinterpret_cast<{class} *>(arg0)->{name}")
+            }
+            FunctionKind::Static(id) => format!("{}::{name}", self.gen.resources[id].class),
+            FunctionKind::Constructor(id) => format!("{}::create", self.gen.resources[id].class),
+        };
+        let call = format!("{callee}({})", args.join(", "));
+
+        let mut body = String::new();
+        let results = func.results.iter_types().collect::<Vec<_>>();
+        let c_ret = match &results[..] {
+            [] => {
+                uwriteln!(body, "{call};");
+                "void".to_string()
+            }
+            [ty] if !is_arg_by_pointer(resolve, ty) => {
+                let result = self.moved(ty, "result");
+                let value = self.lower_expr(ty, &result).unwrap();
+                uwriteln!(body, "auto result = {call};");
+                uwriteln!(body, "return {value};");
+                self.c_type(ty)
+            }
+            [ty] => {
+                c_params.push(format!("{} *ret", self.c_type(ty)));
+                let result = self.moved(ty, "result");
+                uwriteln!(body, "auto result = {call};");
+                body.push_str(&self.lower(ty, &result, "*ret", "nullptr"));
+                "void".to_string()
+            }
+            results => {
+                uwriteln!(body, "auto result = {call};");
+                for (i, ty) in results.iter().enumerate() {
+                    c_params.push(format!("{} *ret{i}", self.c_type(ty)));
+                    let result = self.moved(ty, &format!("std::get<{i}>(result)"));
+                    body.push_str(&self.lower(ty, &result, &format!("*ret{i}"), "nullptr"));
+                }
+                "void".to_string()
+            }
+        };
+        if c_params.is_empty() {
+            c_params.push("void".to_string());
+        }
+        let glue = format!("\n{c_ret} {c_name}({}) {{\n{body}}}\n", c_params.join(", "));
+        self.gen.cpp_src.push_str(&glue);
+    }
+
+    fn finish(self) {
+        let ns = &self.namespace;
+        let h = &mut self.gen.h_src;
+        if !self.decls.is_empty() || !self.types.is_empty() {
+            uwriteln!(h, "\nnamespace {ns} {{");
+            h.push_str("\n");
+            h.push_str(&self.decls);
+            h.push_str(&self.types);
+            uwriteln!(h, "\n}}  // namespace {ns}");
+        }
+        if !self.conversions.is_empty() {
+            let abi = format!("{}_abi", self.gen.world.to_snake_case());
+            uwriteln!(h, "\nnamespace {abi} {{");
+            h.push_str(&self.conversions);
+            uwriteln!(h, "\n}}  // namespace {abi}");
+        }
+        if !self.funcs.is_empty() {
+            uwriteln!(h, "\nnamespace {ns} {{");
+            h.push_str(&self.funcs);
+            uwriteln!(h, "\n}}  // namespace {ns}");
+        }
+    }
+
+    /// Names the parameters and locals of the bindings of `func`, avoiding
+    /// collisions between the two.
+    fn func_names(&self, func: &Function) -> FuncNames {
+        let mut scope = self.gen.naming.scope();
+        let skip = usize::from(matches!(func.kind, FunctionKind::Method(_)));
+        let params = func.params[skip..]
+            .iter()
+            .map(|(name, ty)| (scope.ident(name, Case::Snake), *ty))
+            .collect::<Vec<_>>();
+        let scratch = scope.unique("scratch");
+        let locals = params
+            .iter()
+            .map(|(name, _)| scope.unique(&format!("c_{name}")))
+            .collect();
+        let rets = match func.results.len() {
+            1 => vec![scope.unique("ret")],
+            n => (0..n).map(|i| scope.unique(&format!("ret{i}"))).collect(),
+        };
+        FuncNames {
+            params,
+            locals,
+            scratch,
+            rets,
+        }
+    }
+
+    fn params(&self, params: &[(String, Type)]) -> String {
+        params
+            .iter()
+            .map(|(name, ty)| declare(&self.param_type(ty), name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn result_type(&self, func: &Function) -> String {
+        let results = func.results.iter_types().collect::<Vec<_>>();
+        match &results[..] {
+            [] => "void".to_string(),
+            [ty] => self.cpp_type(ty),
+            results => format!("std::tuple<{}>", self.cpp_types(results.iter().copied())),
+        }
+    }
+
+    fn resource(&self, id: TypeId) -> &ResourceInfo {
+        &self.gen.resources[&dealias(self.resolve, id)]
+    }
+
+    /// The unqualified name of the class of resource `id`.
+    fn resource_ident(&self, id: TypeId) -> String {
+        type_ident(self.resolve.types[id].name.as_ref().unwrap())
+    }
+
+    /// The type of C++ values of `ty`, as returned from functions and stored
+    /// in aggregates.
+    fn cpp_type(&self, ty: &Type) -> String {
+        let id = match ty {
+            Type::Char => return "char32_t".to_string(),
+            Type::String => return "std::string".to_string(),
+            Type::Id(id) => *id,
+            _ => return self.c_type(ty),
+        };
+        match &self.resolve.types[id].kind {
+            TypeDefKind::Record(_)
+            | TypeDefKind::Variant(_)
+            | TypeDefKind::Enum(_)
+            | TypeDefKind::Flags(_)
+            | TypeDefKind::Resource => self.gen.type_names[&id].clone(),
+            TypeDefKind::Type(t) => self.cpp_type(t),
+            TypeDefKind::Tuple(t) => format!("std::tuple<{}>", self.cpp_types(t.types.iter())),
+            TypeDefKind::Option(t) => format!("std::optional<{}>", self.cpp_type(t)),
+            TypeDefKind::Result(r) => {
+                let ok = match &r.ok {
+                    Some(ty) => self.cpp_type(ty),
+                    None => "void".to_string(),
+                };
+                let err = match &r.err {
+                    Some(ty) => self.cpp_type(ty),
+                    None => "std::monostate".to_string(),
+                };
+                format!("std::expected<{ok}, {err}>")
+            }
+            TypeDefKind::List(t) => format!("std::vector<{}>", self.cpp_type(t)),
+            TypeDefKind::Handle(Handle::Own(r)) => {
+                let info = self.resource(*r);
+                match info.direction {
+                    Direction::Import => info.class.clone(),
+                    Direction::Export => format!("{}::Own", info.class),
+                }
+            }
+            TypeDefKind::Handle(Handle::Borrow(r)) => {
+                let info = self.resource(*r);
+                match info.direction {
+                    Direction::Import => info.class.clone(),
+                    Direction::Export => format!("std::reference_wrapper<{}>", info.class),
+                }
+            }
+            TypeDefKind::Future(_) | TypeDefKind::Stream(_) | TypeDefKind::ErrorContext => {
+                self.c_type(ty)
+            }
+            TypeDefKind::Unknown => unreachable!(),
+        }
+    }
+
+    fn cpp_types<'b>(&self, types: impl Iterator<Item = &'b Type>) -> String {
+        types
+            .map(|ty| self.cpp_type(ty))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The type of parameters of `ty`, which borrow strings and lists rather
+    /// than requiring them to be owned.
+    fn param_type(&self, ty: &Type) -> String {
+        let id = match ty {
+            Type::String => return "std::string_view".to_string(),
+            Type::Id(id) => *id,
+            _ => return self.cpp_type(ty),
+        };
+        match &self.resolve.types[id].kind {
+            TypeDefKind::Type(t) => self.param_type(t),
+            TypeDefKind::List(elem) => self.list_view(elem, true),
+            TypeDefKind::Handle(Handle::Borrow(r)) => {
+                let info = self.resource(*r);
+                match info.direction {
+                    Direction::Import => format!("const {} &", info.class),
+                    Direction::Export => format!("{} &", info.class),
+                }
+            }
+            TypeDefKind::Enum(_)
+            | TypeDefKind::Flags(_)
+            | TypeDefKind::Handle(Handle::Own(_))
+            | TypeDefKind::Future(_)
+            | TypeDefKind::Stream(_)
+            | TypeDefKind::ErrorContext => self.cpp_type(ty),
+            _ if self.has_own(ty) => self.cpp_type(ty),
+            _ => format!("const {} &", self.cpp_type(ty)),
+        }
+    }
+
+    /// The type of the value lowered by the conversion function of `ty`.
+    fn lower_param(&self, ty: &Type) -> String {
+        if let Type::Id(id) = ty {
+            if let TypeDefKind::List(elem) = &self.resolve.types[*id].kind {
+                return self.list_view(elem, false);
+            }
+        }
+        if self.has_own(ty) {
+            format!("{} &&", self.cpp_type(ty))
+        } else {
+            format!("const {} &", self.cpp_type(ty))
+        }
+    }
+
+    /// The type through which lists of `elem` are lowered.
+    ///
+    /// Lists of handles must be moved into the callee as their handles are,
+    /// and `std::vector<bool>` can't be viewed as a span.
+    fn list_view(&self, elem: &Type, by_value: bool) -> String {
+        let elem_ty = self.cpp_type(elem);
+        if self.has_own(elem) {
+            if by_value {
+                format!("std::vector<{elem_ty}>")
+            } else {
+                format!("std::vector<{elem_ty}> &&")
+            }
+        } else if let Type::Bool = self.dealias_ty(elem) {
+            "const std::vector<bool> &".to_string()
+        } else {
+            format!("std::span<const {elem_ty}>")
+        }
+    }
+
+    /// The C type of `ty`, as named by the C bindings.
+    fn c_type(&self, ty: &Type) -> String {
+        match ty {
+            Type::Bool => "bool".to_string(),
+            Type::Char => "uint32_t".to_string(),
+            Type::U8 => "uint8_t".to_string(),
+            Type::S8 => "int8_t".to_string(),
+            Type::U16 => "uint16_t".to_string(),
+            Type::S16 => "int16_t".to_string(),
+            Type::U32 => "uint32_t".to_string(),
+            Type::S32 => "int32_t".to_string(),
+            Type::U64 => "uint64_t".to_string(),
+            Type::S64 => "int64_t".to_string(),
+            Type::F32 => "float".to_string(),
+            Type::F64 => "double".to_string(),
+            Type::String => format!("{}_string_t", self.gen.world.to_snake_case()),
+            Type::Id(id) => self.gen.c_type_names[id].clone(),
+        }
+    }
+
+    fn dealias_ty(&self, ty: &Type) -> Type {
+        match ty {
+            Type::Id(id) => match &self.resolve.types[*id].kind {
+                TypeDefKind::Type(t) => self.dealias_ty(t),
+                _ => *ty,
+            },
+            _ => *ty,
+        }
+    }
+
+    fn is_prim(&self, ty: &Type) -> bool {
+        !matches!(self.dealias_ty(ty), Type::String | Type::Id(_))
+    }
+
+    /// Whether `ty` contains owned handles, making its values move-only.
+    fn has_own(&self, ty: &Type) -> bool {
+        let Type::Id(id) = ty else {
+            return false;
+        };
+        match &self.resolve.types[*id].kind {
+            TypeDefKind::Handle(Handle::Own(_)) => true,
+            TypeDefKind::Record(r) => r.fields.iter().any(|f| self.has_own(&f.ty)),
+            TypeDefKind::Variant(v) => v
+                .cases
+                .iter()
+                .any(|c| c.ty.as_ref().is_some_and(|ty| self.has_own(ty))),
+            TypeDefKind::Tuple(t) => t.types.iter().any(|ty| self.has_own(ty)),
+            TypeDefKind::Result(r) => r.ok.iter().chain(r.err.iter()).any(|ty| self.has_own(ty)),
+            TypeDefKind::Option(ty) | TypeDefKind::List(ty) | TypeDefKind::Type(ty) => {
+                self.has_own(ty)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether lowering `ty` for an import may need scratch memory.
+    fn uses_scratch(&self, ty: &Type) -> bool {
+        match self.dealias_ty(ty) {
+            Type::String => true,
+            Type::Id(id) => matches!(
+                self.resolve.types[id].kind,
+                TypeDefKind::Record(_)
+                    | TypeDefKind::Variant(_)
+                    | TypeDefKind::Tuple(_)
+                    | TypeDefKind::Option(_)
+                    | TypeDefKind::Result(_)
+                    | TypeDefKind::List(_)
+            ),
+            _ => false,
+        }
+    }
+
+    /// Wraps `expr` in `std::move` if values of `ty` can't be copied.
+    fn moved(&self, ty: &Type, expr: &str) -> String {
+        if self.has_own(ty) {
+            format!("std::move({expr})")
+        } else {
+            expr.to_string()
+        }
+    }
+
+    /// The name of the conversion function `kind`, either `lift` or `lower`,
+    /// of `ty`.
+    fn conversion(&mut self, kind: &str, ty: &Type) -> String {
+        let abi = format!("::{}_abi", self.gen.world.to_snake_case());
+        match ty {
+            Type::String => {
+                self.gen.needs_string = true;
+                format!("{abi}::{kind}_string")
+            }
+            Type::Id(id) => match &self.resolve.types[*id].kind {
+                TypeDefKind::Type(t) => self.conversion(kind, t),
+                _ => {
+                    let c_ty = &self.gen.c_type_names[id];
+                    format!("{abi}::{kind}_{}", c_ty.strip_suffix("_t").unwrap())
+                }
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns an expression lifting the C value `src` of `ty`, taking
+    /// ownership of any memory it points to.
+    fn lift(&mut self, ty: &Type, src: &str) -> String {
+        let id = match ty {
+            Type::Char => return format!("static_cast<char32_t>({src})"),
+            Type::String => return format!("{}({})", self.conversion("lift", ty), address(src)),
+            Type::Id(id) => *id,
+            _ => return src.to_string(),
+        };
+        match &self.resolve.types[id].kind {
+            TypeDefKind::Type(t) => self.lift(t, src),
+            TypeDefKind::Enum(_) | TypeDefKind::Flags(_) => {
+                format!("static_cast<{}>({src})", self.cpp_type(ty))
+            }
+            TypeDefKind::Handle(Handle::Own(r)) => {
+                let info = self.resource(*r);
+                match info.direction {
+                    Direction::Import => format!("{}({src})", info.class),
+                    Direction::Export => format!("{}::Own({src})", info.class),
+                }
+            }
+            TypeDefKind::Handle(Handle::Borrow(r)) => {
+                let info = self.resource(*r);
+                match info.direction {
+                    Direction::Import => format!("{}({src})", info.class),
+                    Direction::Export => {
+                        format!("std::ref(*reinterpret_cast<{} *>({src}))", info.class)
+                    }
+                }
+            }
+            TypeDefKind::Future(_) | TypeDefKind::Stream(_) | TypeDefKind::ErrorContext => {
+                src.to_string()
+            }
+            TypeDefKind::Resource | TypeDefKind::Unknown => unreachable!(),
+            _ => format!("{}({})", self.conversion("lift", ty), address(src)),
+        }
+    }
+
+    /// Returns an expression lowering `src` of `ty`, or `None` if `ty` needs
+    /// a conversion function which lowers into a C value in memory.
+    fn lower_expr(&self, ty: &Type, src: &str) -> Option<String> {
+        let id = match ty {
+            Type::String => return None,
+            Type::Id(id) => *id,
+            _ => return Some(src.to_string()),
+        };
+        match &self.resolve.types[id].kind {
+            TypeDefKind::Type(t) => self.lower_expr(t, src),
+            TypeDefKind::Enum(_) | TypeDefKind::Flags(_) => {
+                Some(format!("static_cast<{}>({src})", self.c_type(ty)))
+            }
+            TypeDefKind::Handle(Handle::Own(_)) => Some(format!("{}.into_handle()", operand(src))),
+            TypeDefKind::Handle(Handle::Borrow(r)) => {
+                let info = self.resource(*r);
+                Some(match info.direction {
+                    Direction::Import => format!("{}.borrow_handle()", operand(src)),
+                    Direction::Export => format!(
+                        "reinterpret_cast<{} *>(&static_cast<{} &>({src}))",
+                        info.rep, info.class
+                    ),
+                })
+            }
+            TypeDefKind::Future(_) | TypeDefKind::Stream(_) | TypeDefKind::ErrorContext => {
+                Some(src.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns a statement lowering `src` of `ty` into the C value `dst`,
+    /// allocating any memory needed from `scratch`.
+    fn lower(&mut self, ty: &Type, src: &str, dst: &str, scratch: &str) -> String {
+        match self.lower_expr(ty, src) {
+            Some(value) => format!("{dst} = {value};\n"),
+            None => {
+                let func = self.conversion("lower", ty);
+                format!("{func}({src}, {}, {scratch});\n", address(dst))
+            }
+        }
+    }
+
+    fn type_typedef(&mut self, id: TypeId, name: &str, docs: &Docs) {
+        let ty = match &self.resolve.types[id].kind {
+            TypeDefKind::Type(t) => self.cpp_type(t),
+            _ => self.cpp_type(&Type::Id(id)),
+        };
+        self.types.push_str("\n");
+        docs_to(&mut self.types, docs);
+        uwriteln!(self.types, "using {} = {ty};", type_ident(name));
+    }
+
+    fn member_decls(&mut self, id: TypeId) -> String {
+        let mut members = Source::default();
+        for func in self.resource_functions(id) {
+            let names = self.func_names(func);
+            let params = self.params(&names.params);
+            let ret_ty = self.result_type(func);
+            let name = func_ident(func);
+            members.push_str("\n");
+            docs_to(&mut members, &func.docs);
+            match (&func.kind, self.direction) {
+                (FunctionKind::Constructor(_), Direction::Import) => {
+                    let class = self.resource_ident(id);
+                    uwriteln!(members, "explicit {class}({params});");
+                }
+                (FunctionKind::Constructor(_), Direction::Export) => {
+                    uwriteln!(members, "static Own create({params});");
+                }
+                (FunctionKind::Method(_), Direction::Import) => {
+                    uwriteln!(members, "{ret_ty} {name}({params}) const;");
+                }
+                (FunctionKind::Method(_), Direction::Export) => {
+                    uwriteln!(members, "virtual {ret_ty} {name}({params}) = 0;");
+                }
+                (FunctionKind::Static(_), _) => {
+                    uwriteln!(members, "static {ret_ty} {name}({params});");
+                }
+                (FunctionKind::Freestanding, _) => unreachable!(),
+            }
+        }
+        members.as_str().to_string()
+    }
+}
+
+impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
+    fn resolve(&self) -> &'a Resolve {
+        self.resolve
+    }
+
+    fn type_record(&mut self, _id: TypeId, name: &str, record: &Record, docs: &Docs) {
+        self.types.push_str("\n");
+        docs_to(&mut self.types, docs);
+        uwriteln!(self.types, "struct {} {{", type_ident(name));
+        for field in record.fields.iter() {
+            docs_to(&mut self.types, &field.docs);
+            let ty = self.cpp_type(&field.ty);
+            uwriteln!(self.types, "{ty} {};", field_ident(&field.name));
+        }
+        uwriteln!(self.types, "}};");
+    }
+
+    fn type_resource(&mut self, id: TypeId, name: &str, docs: &Docs) {
+        let ident = type_ident(name);
+        let info = &self.gen.resources[&id];
+        let (prefix, own, borrow, rep) = (
+            info.c_prefix.clone(),
+            info.own.clone(),
+            info.borrow.clone(),
+            info.rep.clone(),
+        );
+        let members = self.member_decls(id);
+
+        let class = match self.direction {
+            Direction::Import => format!(
+                "
+                class {ident} {{
+                public:
+                    /// Takes ownership of `handle`, dropping it when destroyed.
+                    explicit {ident}({own} handle) : handle_(handle.__handle), owned_(true) {{}}
+                    /// Refers to the resource behind `handle` without owning it.
+                    explicit {ident}({borrow} handle) : handle_(handle.__handle), owned_(false) {{}}
+                    {ident}({ident} &&other) noexcept : handle_(std::exchange(other.handle_, 0)), owned_(other.owned_) {{}}
+                    {ident} &operator=({ident} &&other) noexcept {{
+                        std::swap(handle_, other.handle_);
+                        std::swap(owned_, other.owned_);
+                        return *this;
+                    }}
+                    {ident}(const {ident} &) = delete;
+                    {ident} &operator=(const {ident} &) = delete;
+                    ~{ident}() {{
+                        if (owned_ && handle_ != 0) {{
+                            {prefix}_drop_own({own}{{handle_}});
+                        }}
+                    }}
+
+                    /// Returns a view of this resource which doesn't own its handle.
+                    {ident} borrowed() const {{
+                        return {ident}({borrow}{{handle_}});
+                    }}
+
+                    {borrow} borrow_handle() const {{
+                        return {borrow}{{handle_}};
+                    }}
+
+                    /// Releases the handle, which the caller is then responsible for.
+                    {own} into_handle() && {{
+                        return {own}{{std::exchange(handle_, 0)}};
+                    }}
+                "
+            ),
+            Direction::Export => {
+                let class = &self.gen.resources[&id].class;
+                let glue = format!(
+                    "
+                    void {prefix}_destructor({rep} *rep) {{
+                        delete reinterpret_cast<{class} *>(rep);
+                    }}
+                    "
+                );
+                self.gen.cpp_src.push_str(&glue);
+                format!(
+                    "
+                    class {ident} {{
+                    public:
+                        /// An owned handle to a `{ident}`, which is dropped when destroyed.
+                        class Own {{
+                        public:
+                            /// Creates a new resource, handing ownership of `rep` to it.
+                            explicit Own(std::unique_ptr<{ident}> rep) : handle_({prefix}_new(reinterpret_cast<{rep} *>(rep.release())).__handle) {{}}
+                            explicit Own({own} handle) : handle_(handle.__handle) {{}}
+                            Own(Own &&other) noexcept : handle_(std::exchange(other.handle_, 0)) {{}}
+                            Own &operator=(Own &&other) noexcept {{
+                                std::swap(handle_, other.handle_);
+                                return *this;
+                            }}
+                            Own(const Own &) = delete;
+                            Own &operator=(const Own &) = delete;
+                            ~Own() {{
+                                if (handle_ != 0) {{
+                                    {prefix}_drop_own({own}{{handle_}});
+                                }}
+                            }}
+
+                            {ident} *get() const {{
+                                return reinterpret_cast<{ident} *>({prefix}_rep({own}{{handle_}}));
+                            }}
+                            {ident} *operator->() const {{
+                                return get();
+                            }}
+                            {ident} &operator*() const {{
+                                return *get();
+                            }}
+
+                            /// Releases the handle, which the caller is then responsible for.
+                            {own} into_handle() && {{
+                                return {own}{{std::exchange(handle_, 0)}};
+                            }}
+
+                        private:
+                            int32_t handle_;
+                        }};
+
+                        virtual ~{ident}() = default;
+                    "
+                )
+            }
+        };
+        let tail = match self.direction {
+            Direction::Import => "\nprivate:\nint32_t handle_;\nbool owned_;\n};\n",
+            Direction::Export => "};\n",
+        };
+        docs_to(&mut self.types, docs);
+        self.types.push_str(&class);
+        self.types.push_str(&members);
+        self.types.push_str(tail);
+    }
+
+    fn type_flags(&mut self, _id: TypeId, name: &str, flags: &Flags, docs: &Docs) {
+        let ident = type_ident(name);
+        let repr = int_repr(flags_repr(flags));
+        self.types.push_str("\n");
+        docs_to(&mut self.types, docs);
+        uwriteln!(self.types, "enum class {ident} : {repr} {{");
+        for (i, flag) in flags.flags.iter().enumerate() {
+            docs_to(&mut self.types, &flag.docs);
+            uwriteln!(
+                self.types,
+                "{} = 1ULL << {i},",
+                Keywords::C.ident(&flag.name, Case::UpperCamel)
+            );
+        }
+        uwriteln!(self.types, "}};");
+        for op in ["|", "&", "^"] {
+            uwriteln!(
+                self.types,
+                "constexpr {ident} operator{op}({ident} a, {ident} b) {{ return static_cast<{ident}>(static_cast<{repr}>(a) {op} static_cast<{repr}>(b)); }}"
+            );
+        }
+        uwriteln!(
+            self.types,
+            "constexpr {ident} operator~({ident} a) {{ return static_cast<{ident}>(~static_cast<{repr}>(a)); }}"
+        );
+    }
+
+    fn type_tuple(&mut self, id: TypeId, name: &str, _tuple: &Tuple, docs: &Docs) {
+        self.type_typedef(id, name, docs);
+    }
+
+    fn type_variant(&mut self, _id: TypeId, name: &str, variant: &Variant, docs: &Docs) {
+        let ident = type_ident(name);
+        self.types.push_str("\n");
+        docs_to(&mut self.types, docs);
+        uwriteln!(self.types, "struct {ident} {{");
+        let mut cases = Vec::new();
+        for case in variant.cases.iter() {
+            let case_name = case_ident(&ident, &case.name);
+            docs_to(&mut self.types, &case.docs);
+            match &case.ty {
+                Some(ty) => {
+                    let ty = self.cpp_type(ty);
+                    uwriteln!(self.types, "struct {case_name} {{");
+                    uwriteln!(self.types, "{ty} value;");
+                    uwriteln!(self.types, "}};");
+                }
+                None => uwriteln!(self.types, "struct {case_name} {{}};"),
+            }
+            cases.push(case_name);
+        }
+        uwriteln!(self.types, "std::variant<{}> variants;", cases.join(", "));
+        uwriteln!(self.types, "}};");
+    }
+
+    fn type_option(&mut self, id: TypeId, name: &str, _payload: &Type, docs: &Docs) {
+        self.type_typedef(id, name, docs);
+    }
+
+    fn type_result(&mut self, id: TypeId, name: &str, _result: &Result_, docs: &Docs) {
+        self.type_typedef(id, name, docs);
+    }
+
+    fn type_enum(&mut self, _id: TypeId, name: &str, enum_: &Enum, docs: &Docs) {
+        let ident = type_ident(name);
+        self.types.push_str("\n");
+        docs_to(&mut self.types, docs);
+        uwriteln!(
+            self.types,
+            "enum class {ident} : {} {{",
+            int_repr(enum_.tag())
+        );
+        for case in enum_.cases.iter() {
+            docs_to(&mut self.types, &case.docs);
+            uwriteln!(
+                self.types,
+                "{},",
+                Keywords::C.ident(&case.name, Case::UpperCamel)
+            );
+        }
+        uwriteln!(self.types, "}};");
+    }
+
+    fn type_alias(&mut self, id: TypeId, name: &str, _ty: &Type, docs: &Docs) {
+        self.type_typedef(id, name, docs);
+    }
+
+    fn type_list(&mut self, id: TypeId, name: &str, _ty: &Type, docs: &Docs) {
+        self.type_typedef(id, name, docs);
+    }
+
+    fn type_builtin(&mut self, id: TypeId, name: &str, _ty: &Type, docs: &Docs) {
+        self.type_typedef(id, name, docs);
+    }
+
+    fn type_future(&mut self, id: TypeId, name: &str, _ty: &Option<Type>, docs: &Docs) {
+        self.type_typedef(id, name, docs);
+    }
+
+    fn type_stream(&mut self, id: TypeId, name: &str, _ty: &Option<Type>, docs: &Docs) {
+        self.type_typedef(id, name, docs);
+    }
+
+    fn type_error_context(&mut self, id: TypeId, name: &str, docs: &Docs) {
+        self.type_typedef(id, name, docs);
+    }
+}
+
+fn docs_to(src: &mut Source, docs: &Docs) {
+    let Some(docs) = &docs.contents else {
+        return;
+    };
+    for line in docs.trim().lines() {
+        uwriteln!(src, "/// {line}");
+    }
+}
+
+/// Declares `name` as a parameter of type `ty`.
+fn declare(ty: &str, name: &str) -> String {
+    if ty.ends_with('&') {
+        format!("{ty}{name}")
+    } else {
+        format!("{ty} {name}")
+    }
+}
+
+/// Returns the address of the lvalue `expr`.
+fn address(expr: &str) -> String {
+    match expr.strip_prefix('*') {
+        Some(ptr) => ptr.to_string(),
+        None => format!("&{expr}"),
+    }
+}
+
+/// Parenthesizes dereferences so that members of the result can be accessed.
+fn operand(expr: &str) -> String {
+    if expr.starts_with('*') {
+        format!("({expr})")
+    } else {
+        expr.to_string()
+    }
+}
+
+fn type_ident(name: &str) -> String {
+    Keywords::C.ident(name, Case::UpperCamel)
+}
+
+fn field_ident(name: &str) -> String {
+    Keywords::C.ident(name, Case::Snake)
+}
+
+fn func_ident(func: &Function) -> String {
+    Keywords::C.ident(func.item_name(), Case::Snake)
+}
+
+/// The name of the struct of a variant's case, which can't share the name of
+/// the variant itself.
+fn case_ident(variant: &str, case: &str) -> String {
+    let ident = Keywords::C.ident(case, Case::UpperCamel);
+    let variant = variant.rsplit("::").next().unwrap();
+    if ident == variant {
+        format!("{ident}_")
+    } else {
+        ident
+    }
+}
@@ -0,0 +1,135 @@
+use heck::*;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use wit_parser::{Resolve, UnresolvedPackageGroup};
+
+macro_rules! codegen_test {
+    ($id:ident $name:tt $test:tt) => {
+        #[test]
+        fn $id() {
+            test_helpers::run_world_codegen_test(
+                "guest-cpp",
+                $test.as_ref(),
+                |resolve, world, files| {
+                    wit_bindgen_cpp::Opts::default()
+                        .build()
+                        .generate(resolve, world, files)
+                        .unwrap()
+                },
+                verify,
+            );
+            test_helpers::run_world_codegen_test(
+                "guest-cpp-allocator-custom",
+                $test.as_ref(),
+                |resolve, world, files| {
+                    let mut opts = wit_bindgen_cpp::Opts::default();
+                    opts.allocator = wit_bindgen_c::Allocator::Custom;
+                    opts.build().generate(resolve, world, files).unwrap()
+                },
+                verify,
+            );
+            test_helpers::run_world_codegen_test(
+                "guest-cpp-allocator-arena",
+                $test.as_ref(),
+                |resolve, world, files| {
+                    let mut opts = wit_bindgen_cpp::Opts::default();
+                    opts.allocator = wit_bindgen_c::Allocator::Arena;
+                    opts.build().generate(resolve, world, files).unwrap()
+                },
+                verify,
+            );
+        }
+    };
+}
+
+test_helpers::codegen_tests!();
+
+fn verify(dir: &Path, name: &str) {
+    let name = name.to_snake_case();
+    let sdk_path = PathBuf::from(
+        env::var_os("WASI_SDK_PATH").expect("environment variable WASI_SDK_PATH should be set"),
+    );
+    let sysroot = sdk_path.join("share/wasi-sysroot");
+
+    let shared_args = vec![
+        "--sysroot",
+        sysroot.to_str().unwrap(),
+        "-I",
+        dir.to_str().unwrap(),
+        "-Wall",
+        "-Wextra",
+        "-Werror",
+        "-Wno-unused-parameter",
+        "-c",
+        "-o",
+    ];
+
+    let mut cmd = Command::new(sdk_path.join("bin/clang"));
+    cmd.args(&shared_args);
+    cmd.arg(dir.join("obj.o"));
+    cmd.arg(dir.join(format!("{name}.c")));
+    test_helpers::run_command(&mut cmd);
+
+    let mut cmd = Command::new(sdk_path.join("bin/clang++"));
+    cmd.args(&shared_args);
+    cmd.arg(dir.join("obj.o"));
+    cmd.arg("-std=c++23");
+    cmd.arg("-fno-exceptions");
+    cmd.arg(dir.join(format!("{name}.cpp")));
+    test_helpers::run_command(&mut cmd);
+}
+
+#[test]
+fn rename_option() -> anyhow::Result<()> {
+    let dir = test_helpers::test_directory("codegen", "guest-cpp", "rename-option");
+
+    let mut opts = wit_bindgen_cpp::Opts::default();
+    opts.rename.push(("a".to_string(), "rename1".to_string()));
+    opts.rename
+        .push(("foo:bar/b".to_string(), "rename2".to_string()));
+    opts.rename
+        .push(("foo:bar/b.point".to_string(), "pos".to_string()));
+
+    let mut resolve = Resolve::default();
+    let pkg = resolve.push_group(UnresolvedPackageGroup::parse(
+        "input.wit",
+        r#"
+            package foo:bar;
+
+            interface b {
+                record point { x: u32, label: string }
+                f: func(p: point) -> point;
+            }
+
+            world rename-option {
+                import a: interface {
+                    f: func(s: string) -> string;
+                }
+                import b;
+                export b;
+            }
+        "#,
+    )?)?;
+    let world = resolve.select_world(pkg, None)?;
+    let mut files = wit_bindgen_core::Files::default();
+    opts.build().generate(&resolve, world, &mut files)?;
+    for (file, contents) in files.iter() {
+        let dst = dir.join(file);
+        std::fs::create_dir_all(dst.parent().unwrap()).unwrap();
+        std::fs::write(&dst, contents).unwrap();
+    }
+
+    // The C bindings use the new names while the C++ ones keep the WIT names.
+    let (_, h) = files
+        .iter()
+        .find(|(name, _)| *name == "rename_option.h")
+        .unwrap();
+    let h = std::str::from_utf8(h)?;
+    assert!(h.contains("rename1_f("));
+    assert!(h.contains("typedef struct rename2_pos_t"));
+    assert!(h.contains("exports_rename2_f("));
+
+    verify(&dir, "rename-option");
+    Ok(())
+}
@@ -1,111 +1,66 @@
-use heck::{ToLowerCamelCase, ToUpperCamelCase};
+use heck::{ToShoutySnakeCase, ToUpperCamelCase};
+use wit_bindgen_core::{Case, Keywords};
+use wit_parser::{Function, Record, Variant};
 
 pub(crate) trait ToCSharpIdent: ToOwned {
-    fn csharp_keywords() -> &'static [&'static str];
     fn to_csharp_ident(&self) -> Self::Owned;
-    fn to_csharp_ident_upper(&self) -> Self::Owned;
 }
 
 impl ToCSharpIdent for str {
-    // Source: https://learn.microsoft.com/en-us/dotnet/csharp/language-reference/keywords/
-    fn csharp_keywords() -> &'static [&'static str] {
-        static CSHARP_KEY_WORDS: &[&str] = &[
-            "abstract",
-            "as",
-            "base",
-            "bool",
-            "break",
-            "byte",
-            "case",
-            "catch",
-            "char",
-            "checked",
-            "class",
-            "const",
-            "continue",
-            "decimal",
-            "default",
-            "delegate",
-            "do",
-            "double",
-            "else",
-            "enum",
-            "event",
-            "explicit",
-            "extern",
-            "false",
-            "finally",
-            "fixed",
-            "float",
-            "for",
-            "foreach",
-            "goto",
-            "if",
-            "implicit",
-            "in",
-            "int",
-            "interface",
-            "internal",
-            "is",
-            "lock",
-            "long",
-            "namespace",
-            "new",
-            "null",
-            "object",
-            "operator",
-            "out",
-            "override",
-            "params",
-            "private",
-            "protected",
-            "public",
-            "readonly",
-            "ref",
-            "return",
-            "sbyte",
-            "sealed",
-            "short",
-            "sizeof",
-            "stackalloc",
-            "static",
-            "string",
-            "struct",
-            "switch",
-            "this",
-            "throw",
-            "true",
-            "try",
-            "typeof",
-            "uint",
-            "ulong",
-            "unchecked",
-            "unsafe",
-            "ushort",
-            "using",
-            "virtual",
-            "void",
-            "volatile",
-            "while",
-        ];
-        CSHARP_KEY_WORDS
-    }
-
     fn to_csharp_ident(&self) -> String {
-        // Escape C# keywords
-        if Self::csharp_keywords().contains(&self) {
-            format!("@{}", self)
-        } else {
-            self.to_lower_camel_case()
-        }
+        Keywords::CSHARP.ident(self, Case::LowerCamel)
     }
+}
 
-    fn to_csharp_ident_upper(&self) -> String {
-        // Escape C# keywords
-        if Self::csharp_keywords().contains(&self) {
-            format!("@{}", self)
-        } else {
-            self.to_upper_camel_case()
-        }
-    }
+/// Returns the names of the fields of `record`, which are kept distinct even
+/// when several WIT names map to the same C# identifier.
+pub(crate) fn field_names(record: &Record) -> Vec<String> {
+    let mut scope = Keywords::CSHARP.scope();
+    record
+        .fields
+        .iter()
+        .map(|field| scope.ident(&field.name, Case::LowerCamel))
+        .collect()
+}
+
+/// Returns the names of the parameters of `func`, kept distinct like
+/// [`field_names`].
+pub(crate) fn param_names(func: &Function) -> Vec<String> {
+    let mut scope = Keywords::CSHARP.scope();
+    func.params
+        .iter()
+        .map(|(name, _)| scope.ident(name, Case::LowerCamel))
+        .collect()
+}
+
+/// Returns the tags of the cases of `variant`, which also name their
+/// constructors, kept distinct like [`field_names`].
+pub(crate) fn case_tags(variant: &Variant) -> Vec<String> {
+    let mut scope = Keywords::CSHARP.scope();
+    variant
+        .cases
+        .iter()
+        .map(|case| scope.ident(&case.name, Case::UpperCamel))
+        .collect()
+}
+
+/// Returns the names of the `As*` accessors of the cases of `variant`, kept
+/// distinct like [`field_names`].
+pub(crate) fn case_accessors(variant: &Variant) -> Vec<String> {
+    let mut scope = Keywords::CSHARP.scope();
+    variant
+        .cases
+        .iter()
+        .map(|case| format!("As{}", scope.unique(&case.name.to_upper_camel_case())))
+        .collect()
+}
+
+/// Returns the members of an enum generated for a WIT enum or flags, kept
+/// distinct like [`field_names`].
+pub(crate) fn enum_members<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut scope = Keywords::CSHARP.scope();
+    names
+        .into_iter()
+        .map(|name| scope.unique(&name.to_shouty_snake_case()))
+        .collect()
 }
@@ -1,4 +1,4 @@
-use crate::csharp_ident::ToCSharpIdent;
+use crate::csharp_ident::{case_accessors, case_tags, field_names};
 use crate::interface::InterfaceGenerator;
 use crate::world_generator::CSharp;
 use heck::ToUpperCamelCase;
@@ -97,12 +97,10 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
             .zip(payloads)
             .enumerate()
             .map(
-                |(i, (((name, ty), Block { body, results, .. }), payload))| {
+                |(i, (((accessor, ty), Block { body, results, .. }), payload))| {
                     let payload = if let Some(ty) = self.interface_gen.non_empty_type(ty.as_ref()) {
                         let ty = self.interface_gen.type_name_with_qualifier(ty, true);
-                        let name = name.to_upper_camel_case();
-
-                        format!("{ty} {payload} = {op}.As{name};")
+                        format!("{ty} {payload} = {op}.{accessor};")
                     } else {
                         String::new()
                     };
@@ -184,7 +182,7 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
                     String::new()
                 };
 
-                let method = case_name.to_csharp_ident_upper();
+                let method = case_name;
 
                 let call = if let Some(position) = generics_position {
                     let (ty, generics) = ty.split_at(position);
@@ -459,11 +457,11 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 }
             }
 
-            Instruction::FlagsLift { flags, name, ty } => {
+            Instruction::FlagsLift { flags, ty, .. } => {
                 let qualified_type_name = format!(
                     "{}{}",
                     self.interface_gen.qualifier(true, ty),
-                    name.to_string().to_upper_camel_case()
+                    self.interface_gen.type_ident(*ty)
                 );
                 if flags.flags.len() > 32 {
                     results.push(format!(
@@ -479,15 +477,15 @@ impl Bindgen for FunctionBindgen<'_, '_> {
 
             Instruction::RecordLower { record, .. } => {
                 let op = &operands[0];
-                for f in record.fields.iter() {
-                    results.push(format!("{}.{}", op, f.name.to_csharp_ident()));
+                for name in field_names(record) {
+                    results.push(format!("{}.{}", op, name));
                 }
             }
-            Instruction::RecordLift { ty, name, .. } => {
+            Instruction::RecordLift { ty, .. } => {
                 let qualified_type_name = format!(
                     "{}{}",
                     self.interface_gen.qualifier(true, ty),
-                    name.to_string().to_upper_camel_case()
+                    self.interface_gen.type_ident(*ty)
                 );
                 let mut result = format!("new {} (\n", qualified_type_name);
 
@@ -527,27 +525,33 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 variant,
                 results: lowered_types,
                 ..
-            } => self.lower_variant(
-                &variant
-                    .cases
-                    .iter()
-                    .map(|case| (case.name.deref(), case.ty))
-                    .collect::<Vec<_>>(),
-                lowered_types,
-                &operands[0],
-                results,
-            ),
+            } => {
+                let accessors = case_accessors(variant);
+                self.lower_variant(
+                    &accessors
+                        .iter()
+                        .map(|accessor| accessor.deref())
+                        .zip(variant.cases.iter().map(|case| case.ty))
+                        .collect::<Vec<_>>(),
+                    lowered_types,
+                    &operands[0],
+                    results,
+                )
+            }
 
-            Instruction::VariantLift { variant, ty, .. } => self.lift_variant(
-                &Type::Id(*ty),
-                &variant
-                    .cases
-                    .iter()
-                    .map(|case| (case.name.deref(), case.ty))
-                    .collect::<Vec<_>>(),
-                &operands[0],
-                results,
-            ),
+            Instruction::VariantLift { variant, ty, .. } => {
+                let tags = case_tags(variant);
+                self.lift_variant(
+                    &Type::Id(*ty),
+                    &tags
+                        .iter()
+                        .map(|tag| tag.deref())
+                        .zip(variant.cases.iter().map(|case| case.ty))
+                        .collect::<Vec<_>>(),
+                    &operands[0],
+                    results,
+                )
+            }
 
             Instruction::OptionLower {
                 results: lowered_types,
@@ -688,7 +692,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 result,
                 ..
             } => self.lower_variant(
-                &[("Ok", result.ok), ("Err", result.err)],
+                &[("AsOk", result.ok), ("AsErr", result.err)],
                 lowered_types,
                 &operands[0],
                 results,
@@ -955,13 +959,10 @@ impl Bindgen for FunctionBindgen<'_, '_> {
 
             Instruction::CallInterface { func, .. } => {
                 let module = self.interface_gen.name;
-                let func_name = self.func_name.to_upper_camel_case();
+                let func_name = self.interface_gen.func_ident(func);
                 let interface_name = CSharp::get_class_name_from_qualified_name(module).1;
 
-                let class_name_root = interface_name
-                    .strip_prefix("I")
-                    .unwrap()
-                    .to_upper_camel_case();
+                let class_name_root = interface_name.strip_prefix("I").unwrap();
 
                 let mut oper = String::new();
 
@@ -1168,6 +1169,8 @@ impl Bindgen for FunctionBindgen<'_, '_> {
             Instruction::AsyncMalloc { .. }
             | Instruction::AsyncPostCallInterface { .. }
             | Instruction::AsyncCallReturn { .. }
+            | Instruction::AsyncTaskReturn { .. }
+            | Instruction::CallPostReturn { .. }
             | Instruction::FutureLower { .. }
             | Instruction::FutureLift { .. }
             | Instruction::StreamLower { .. }
@@ -1329,6 +1332,7 @@ struct BlockStorage {
 pub struct ResourceInfo {
     pub(crate) module: String,
     pub(crate) name: String,
+    pub(crate) class_name: String,
     pub(crate) docs: Docs,
     pub(crate) direction: Direction,
 }
@@ -1343,9 +1347,8 @@ impl ResourceInfo {
             CSharp::get_class_name_from_qualified_name(&self.module)
                 .1
                 .strip_prefix("I")
-                .unwrap()
-                .to_upper_camel_case(),
-            self.name.to_upper_camel_case()
+                .unwrap(),
+            self.class_name
         )
     }
 }
@@ -1,8 +1,10 @@
-use crate::csharp_ident::ToCSharpIdent;
+use crate::csharp_ident::{
+    case_accessors, case_tags, enum_members, field_names, param_names, ToCSharpIdent,
+};
 use crate::function::FunctionBindgen;
 use crate::function::ResourceInfo;
 use crate::world_generator::CSharp;
-use heck::{ToShoutySnakeCase, ToUpperCamelCase};
+use heck::ToUpperCamelCase;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
 use std::ops::Deref;
@@ -48,12 +50,40 @@ pub(crate) struct InterfaceGenerator<'a> {
     pub(crate) csharp_gen: &'a mut CSharp,
     pub(crate) resolve: &'a Resolve,
     pub(crate) name: &'a str,
+    pub(crate) key: Option<&'a WorldKey>,
     pub(crate) direction: Direction,
     pub(crate) usings: HashSet<String>,
     pub(crate) interop_usings: HashSet<String>,
 }
 
 impl InterfaceGenerator<'_> {
+    /// Returns the name of the C# method generated for `func`, which isn't a
+    /// constructor, honoring renames.
+    pub(crate) fn func_ident(&self, func: &Function) -> String {
+        match self
+            .csharp_gen
+            .renames
+            .item(self.resolve, self.key, &func.name)
+        {
+            Some(rename) => rename.to_string(),
+            None => func.item_name().to_upper_camel_case(),
+        }
+    }
+
+    /// Returns the name of the C# type generated for the named type `id`,
+    /// honoring renames.
+    pub(crate) fn type_ident(&self, id: TypeId) -> String {
+        let world = self.csharp_gen.world.unwrap();
+        match self.csharp_gen.renames.ty(self.resolve, world, id) {
+            Some(rename) => rename.to_string(),
+            None => self.resolve.types[id]
+                .name
+                .as_ref()
+                .unwrap()
+                .to_upper_camel_case(),
+        }
+    }
+
     pub(crate) fn define_interface_types(&mut self, id: InterfaceId) {
         let mut live = LiveTypes::default();
         live.add_interface(self.resolve, id);
@@ -180,13 +210,10 @@ impl InterfaceGenerator<'_> {
     pub(crate) fn import(&mut self, import_module_name: &str, func: &Function) {
         let (camel_name, modifiers) = match &func.kind {
             FunctionKind::Freestanding | FunctionKind::Static(_) => {
-                (func.item_name().to_upper_camel_case(), "static")
+                (self.func_ident(func), "static")
             }
-            FunctionKind::Method(_) => (func.item_name().to_upper_camel_case(), ""),
-            FunctionKind::Constructor(id) => (
-                self.csharp_gen.all_resources[id].name.to_upper_camel_case(),
-                "",
-            ),
+            FunctionKind::Method(_) => (self.func_ident(func), ""),
+            FunctionKind::Constructor(id) => (self.type_ident(*id), ""),
         };
 
         let access = self.csharp_gen.access_modifier();
@@ -251,14 +278,14 @@ impl InterfaceGenerator<'_> {
             self,
             &func.item_name(),
             &func.kind,
-            func.params
-                .iter()
+            param_names(func)
+                .into_iter()
                 .enumerate()
-                .map(|(i, (name, _))| {
+                .map(|(i, name)| {
                     if i == 0 && matches!(&func.kind, FunctionKind::Method(_)) {
                         "this".to_owned()
                     } else {
-                        name.to_csharp_ident()
+                        name
                     }
                 })
                 .collect(),
@@ -279,15 +306,14 @@ impl InterfaceGenerator<'_> {
         let params = func
             .params
             .iter()
+            .zip(param_names(func))
             .skip(if let FunctionKind::Method(_) = &func.kind {
                 1
             } else {
                 0
             })
-            .map(|param| {
-                let ty = self.type_name_with_qualifier(&param.1, true);
-                let param_name = &param.0;
-                let param_name = param_name.to_csharp_ident();
+            .map(|((_, ty), param_name)| {
+                let ty = self.type_name_with_qualifier(ty, true);
                 format!("{ty} {param_name}")
             })
             .collect::<Vec<_>>()
@@ -338,13 +364,10 @@ impl InterfaceGenerator<'_> {
     pub(crate) fn export(&mut self, func: &Function, interface_name: Option<&WorldKey>) {
         let (camel_name, modifiers) = match &func.kind {
             FunctionKind::Freestanding | FunctionKind::Static(_) => {
-                (func.item_name().to_upper_camel_case(), "static abstract")
+                (self.func_ident(func), "static abstract")
             }
-            FunctionKind::Method(_) => (func.item_name().to_upper_camel_case(), ""),
-            FunctionKind::Constructor(id) => (
-                self.csharp_gen.all_resources[id].name.to_upper_camel_case(),
-                "",
-            ),
+            FunctionKind::Method(_) => (self.func_ident(func), ""),
+            FunctionKind::Constructor(id) => (self.type_ident(*id), ""),
         };
 
         let extra_modifiers = extra_modifiers(func, &camel_name);
@@ -430,14 +453,14 @@ impl InterfaceGenerator<'_> {
         let params = func
             .params
             .iter()
+            .zip(param_names(func))
             .skip(if let FunctionKind::Method(_) = &func.kind {
                 1
             } else {
                 0
             })
-            .map(|(name, ty)| {
+            .map(|((_, ty), name)| {
                 let ty = self.type_name(ty);
-                let name = name.to_csharp_ident();
                 format!("{ty} {name}")
             })
             .collect::<Vec<String>>()
@@ -595,12 +618,8 @@ impl InterfaceGenerator<'_> {
                         self.type_name_with_qualifier(&Type::Id(*id), qualifier)
                     }
                     _ => {
-                        if let Some(name) = &ty.name {
-                            format!(
-                                "{}{}",
-                                self.qualifier(qualifier, id),
-                                name.to_upper_camel_case()
-                            )
+                        if ty.name.is_some() {
+                            format!("{}{}", self.qualifier(qualifier, id), self.type_ident(*id))
                         } else {
                             unreachable!("todo: {ty:?}")
                         }
@@ -668,7 +687,7 @@ impl InterfaceGenerator<'_> {
         let qualified = self.type_name_with_qualifier(&Type::Id(id), true);
         let info = &self.csharp_gen.all_resources[&id];
         let name = info.name.clone();
-        let upper_camel = name.to_upper_camel_case();
+        let upper_camel = self.type_ident(id);
         let docs = info.docs.clone();
         self.print_docs(&docs);
 
@@ -870,14 +889,14 @@ impl InterfaceGenerator<'_> {
         let params = func
             .params
             .iter()
+            .zip(param_names(func))
             .skip(if let FunctionKind::Method(_) = &func.kind {
                 1
             } else {
                 0
             })
-            .map(|(name, ty)| {
+            .map(|((_, ty), name)| {
                 let ty = self.type_name_with_qualifier(ty, qualifier);
-                let name = name.to_csharp_ident();
                 format!("{ty} {name}")
             })
             .collect::<Vec<_>>()
@@ -885,13 +904,10 @@ impl InterfaceGenerator<'_> {
 
         let (camel_name, modifiers) = match &func.kind {
             FunctionKind::Freestanding | FunctionKind::Static(_) => {
-                (func.item_name().to_upper_camel_case(), "static")
+                (self.func_ident(func), "static")
             }
-            FunctionKind::Method(_) => (func.item_name().to_upper_camel_case(), ""),
-            FunctionKind::Constructor(id) => (
-                self.csharp_gen.all_resources[id].name.to_upper_camel_case(),
-                "",
-            ),
+            FunctionKind::Method(_) => (self.func_ident(func), ""),
+            FunctionKind::Constructor(id) => (self.type_ident(*id), ""),
         };
 
         let access = self.csharp_gen.access_modifier();
@@ -905,33 +921,25 @@ impl<'a> CoreInterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.resolve
     }
 
-    fn type_record(&mut self, _id: TypeId, name: &str, record: &Record, docs: &Docs) {
+    fn type_record(&mut self, id: TypeId, _name: &str, record: &Record, docs: &Docs) {
         let access = self.csharp_gen.access_modifier();
 
         self.print_docs(docs);
 
-        let name = name.to_upper_camel_case();
+        let name = self.type_ident(id);
+        let field_names = field_names(record);
 
         let parameters = record
             .fields
             .iter()
-            .map(|field| {
-                format!(
-                    "{} {}",
-                    self.type_name(&field.ty),
-                    field.name.to_csharp_ident()
-                )
-            })
+            .zip(&field_names)
+            .map(|(field, name)| format!("{} {name}", self.type_name(&field.ty)))
             .collect::<Vec<_>>()
             .join(", ");
 
-        let assignments = record
-            .fields
+        let assignments = field_names
             .iter()
-            .map(|field| {
-                let name = field.name.to_csharp_ident();
-                format!("this.{name} = {name};")
-            })
+            .map(|name| format!("this.{name} = {name};"))
             .collect::<Vec<_>>()
             .join("\n");
 
@@ -941,12 +949,9 @@ impl<'a> CoreInterfaceGenerator<'a> for InterfaceGenerator<'a> {
             record
                 .fields
                 .iter()
-                .map(|field| {
-                    format!(
-                        "{access} readonly {} {};",
-                        self.type_name(&field.ty),
-                        field.name.to_csharp_ident()
-                    )
+                .zip(&field_names)
+                .map(|(field, name)| {
+                    format!("{access} readonly {} {name};", self.type_name(&field.ty))
                 })
                 .collect::<Vec<_>>()
                 .join("\n")
@@ -966,17 +971,15 @@ impl<'a> CoreInterfaceGenerator<'a> for InterfaceGenerator<'a> {
         );
     }
 
-    fn type_flags(&mut self, _id: TypeId, name: &str, flags: &Flags, docs: &Docs) {
+    fn type_flags(&mut self, id: TypeId, _name: &str, flags: &Flags, docs: &Docs) {
         self.print_docs(docs);
 
-        let name = name.to_upper_camel_case();
+        let name = self.type_ident(id);
 
-        let enum_elements = flags
-            .flags
-            .iter()
+        let enum_elements = enum_members(flags.flags.iter().map(|flag| flag.name.as_str()))
+            .into_iter()
             .enumerate()
-            .map(|(i, flag)| {
-                let flag_name = flag.name.to_shouty_snake_case();
+            .map(|(i, flag_name)| {
                 let suffix = if matches!(flags.repr(), FlagsRepr::U32(2)) {
                     "UL"
                 } else {
@@ -1010,19 +1013,21 @@ impl<'a> CoreInterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.type_name(&Type::Id(id));
     }
 
-    fn type_variant(&mut self, _id: TypeId, name: &str, variant: &Variant, docs: &Docs) {
+    fn type_variant(&mut self, id: TypeId, _name: &str, variant: &Variant, docs: &Docs) {
         self.print_docs(docs);
 
-        let name = name.to_upper_camel_case();
+        let name = self.type_ident(id);
         let tag_type = int_type(variant.tag());
         let access = self.csharp_gen.access_modifier();
+        let tags = case_tags(variant);
+        let accessors = case_accessors(variant);
 
         let constructors = variant
             .cases
             .iter()
-            .map(|case| {
+            .zip(&tags)
+            .map(|(case, tag)| {
                 let case_name = case.name.to_csharp_ident();
-                let tag = case.name.to_csharp_ident_upper();
                 let (parameter, argument) = if let Some(ty) = self.non_empty_type(case.ty.as_ref())
                 {
                     (
@@ -1046,13 +1051,12 @@ impl<'a> CoreInterfaceGenerator<'a> for InterfaceGenerator<'a> {
         let accessors = variant
             .cases
             .iter()
-            .filter_map(|case| {
+            .zip(tags.iter().zip(&accessors))
+            .filter_map(|(case, (tag, accessor))| {
                 self.non_empty_type(case.ty.as_ref()).map(|ty| {
-                    let case_name = case.name.to_upper_camel_case();
-                    let tag = case.name.to_csharp_ident_upper();
                     let ty = self.type_name(ty);
                     format!(
-                        r#"{access} {ty} As{case_name}
+                        r#"{access} {ty} {accessor}
                         {{
                             get
                             {{
@@ -1069,14 +1073,10 @@ impl<'a> CoreInterfaceGenerator<'a> for InterfaceGenerator<'a> {
             .collect::<Vec<_>>()
             .join("\n");
 
-        let tags = variant
-            .cases
+        let tags = tags
             .iter()
             .enumerate()
-            .map(|(i, case)| {
-                let tag = case.name.to_csharp_ident_upper();
-                format!("{access} const {tag_type} {tag} = {i};")
-            })
+            .map(|(i, tag)| format!("{access} const {tag_type} {tag} = {i};"))
             .collect::<Vec<_>>()
             .join("\n");
 
@@ -1111,17 +1111,12 @@ impl<'a> CoreInterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.type_name(&Type::Id(id));
     }
 
-    fn type_enum(&mut self, _id: TypeId, name: &str, enum_: &Enum, docs: &Docs) {
+    fn type_enum(&mut self, id: TypeId, _name: &str, enum_: &Enum, docs: &Docs) {
         self.print_docs(docs);
 
-        let name = name.to_upper_camel_case();
+        let name = self.type_ident(id);
 
-        let cases = enum_
-            .cases
-            .iter()
-            .map(|case| case.name.to_shouty_snake_case())
-            .collect::<Vec<_>>()
-            .join(", ");
+        let cases = enum_members(enum_.cases.iter().map(|case| case.name.as_str())).join(", ");
 
         let access = self.csharp_gen.access_modifier();
 
@@ -1150,12 +1145,14 @@ impl<'a> CoreInterfaceGenerator<'a> for InterfaceGenerator<'a> {
     fn type_resource(&mut self, id: TypeId, name: &str, docs: &Docs) {
         // Here we just record information about the resource; we don't actually emit any code until we're ready to
         // visit any functions associated with the resource (e.g. in CSharp::import_interface, etc.).
+        let class_name = self.type_ident(id);
         self.csharp_gen
             .all_resources
             .entry(id)
             .or_insert_with(|| ResourceInfo {
                 module: self.name.to_owned(),
                 name: name.to_owned(),
+                class_name,
                 docs: docs.clone(),
                 direction: Direction::Import,
             })
@@ -34,6 +34,15 @@ pub struct Opts {
     /// Generate code for WIT `Result` types instead of exceptions
     #[cfg_attr(feature = "clap", arg(long))]
     pub with_wit_results: bool,
+
+    /// Rename the interface or item `K` to `V` in the generated source code.
+    ///
+    /// `K` is either an interface such as `foo:bar/baz`, an item within an
+    /// interface such as `foo:bar/baz.qux`, or an item defined in the world.
+    /// A renamed interface only changes the name of its own C# interface,
+    /// not of the namespaces it's nested in.
+    #[cfg_attr(feature = "clap", arg(long, name = "K=V", value_parser = wit_bindgen_core::Renames::parse))]
+    pub rename: Vec<(String, String)>,
 }
 
 impl Opts {
@@ -8,7 +8,9 @@ use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
 use std::ops::Deref;
 use std::{iter, mem};
-use wit_bindgen_core::{uwrite, Direction, Files, InterfaceGenerator as _, WorldGenerator};
+use wit_bindgen_core::{
+    uwrite, Direction, Files, InterfaceGenerator as _, Renames, WorldGenerator,
+};
 use wit_component::WitPrinter;
 use wit_parser::abi::WasmType;
 use wit_parser::{
@@ -25,6 +27,8 @@ use wit_parser::{
 pub struct CSharp {
     pub(crate) opts: Opts,
     pub(crate) name: String,
+    pub(crate) world: Option<WorldId>,
+    pub(crate) renames: Renames,
     pub(crate) usings: HashSet<String>,
     #[allow(unused)]
     pub(crate) interop_usings: HashSet<String>,
@@ -43,6 +47,7 @@ pub struct CSharp {
     pub(crate) anonymous_type_owners: HashMap<TypeId, TypeOwner>,
     pub(crate) all_resources: HashMap<TypeId, ResourceInfo>,
     pub(crate) world_resources: HashMap<TypeId, ResourceInfo>,
+    pub(crate) world_export_resources: HashMap<TypeId, ResourceInfo>,
     pub(crate) import_funcs_called: bool,
 }
 
@@ -64,6 +69,7 @@ impl CSharp {
         &'a mut self,
         resolve: &'a Resolve,
         name: &'a str,
+        key: Option<&'a WorldKey>,
         direction: Direction,
     ) -> InterfaceGenerator<'a> {
         InterfaceGenerator {
@@ -73,6 +79,7 @@ impl CSharp {
             csharp_gen: self,
             resolve,
             name,
+            key,
             direction,
             usings: HashSet::<String>::new(),
             interop_usings: HashSet::<String>::new(),
@@ -113,7 +120,13 @@ impl WorldGenerator for CSharp {
     fn preprocess(&mut self, resolve: &Resolve, world: WorldId) {
         let name = &resolve.worlds[world].name;
         self.name = name.to_string();
+        self.world = Some(world);
         self.sizes.fill(resolve);
+
+        for (from, to) in self.opts.rename.iter() {
+            self.renames.insert(from, to);
+        }
+        self.renames.warn_unmatched(resolve, world);
     }
 
     fn import_interface(
@@ -125,7 +138,7 @@ impl WorldGenerator for CSharp {
     ) -> anyhow::Result<()> {
         let name = interface_name(self, resolve, key, Direction::Import);
         self.interface_names.insert(id, name.clone());
-        let mut gen = self.interface(resolve, &name, Direction::Import);
+        let mut gen = self.interface(resolve, &name, Some(key), Direction::Import);
 
         let mut old_resources = mem::take(&mut gen.csharp_gen.all_resources);
         gen.types(id);
@@ -173,7 +186,7 @@ impl WorldGenerator for CSharp {
 
         let name = &format!("{}-world", resolve.worlds[world].name).to_upper_camel_case();
         let name = &format!("{name}.I{name}");
-        let mut gen = self.interface(resolve, name, Direction::Import);
+        let mut gen = self.interface(resolve, name, None, Direction::Import);
 
         for (resource, funcs) in by_resource(
             funcs.iter().copied(),
@@ -204,7 +217,7 @@ impl WorldGenerator for CSharp {
     ) -> anyhow::Result<()> {
         let name = interface_name(self, resolve, key, Direction::Export);
         self.interface_names.insert(id, name.clone());
-        let mut gen = self.interface(resolve, &name, Direction::Export);
+        let mut gen = self.interface(resolve, &name, Some(key), Direction::Export);
 
         let mut old_resources = mem::take(&mut gen.csharp_gen.all_resources);
         gen.types(id);
@@ -248,9 +261,12 @@ impl WorldGenerator for CSharp {
     ) -> anyhow::Result<()> {
         let name = &format!("{}-world", resolve.worlds[world].name).to_upper_camel_case();
         let name = &format!("{name}.I{name}");
-        let mut gen = self.interface(resolve, name, Direction::Export);
+        let mut gen = self.interface(resolve, name, None, Direction::Export);
 
-        for (resource, funcs) in by_resource(funcs.iter().copied(), iter::empty()) {
+        for (resource, funcs) in by_resource(
+            funcs.iter().copied(),
+            gen.csharp_gen.world_export_resources.keys().copied(),
+        ) {
             if let Some(resource) = resource {
                 gen.start_resource(resource, None);
             }
@@ -277,7 +293,7 @@ impl WorldGenerator for CSharp {
     ) {
         let name = &format!("{}-world", resolve.worlds[world].name).to_upper_camel_case();
         let name = &format!("{name}.I{name}");
-        let mut gen = self.interface(resolve, name, Direction::Import);
+        let mut gen = self.interface(resolve, name, None, Direction::Import);
 
         let mut old_resources = mem::take(&mut gen.csharp_gen.all_resources);
         for (ty_name, ty) in types {
@@ -291,6 +307,30 @@ impl WorldGenerator for CSharp {
         gen.add_world_fragment();
     }
 
+    fn export_types(
+        &mut self,
+        resolve: &Resolve,
+        world: WorldId,
+        types: &[(&str, TypeId)],
+        _files: &mut Files,
+    ) -> anyhow::Result<()> {
+        let name = &format!("{}-world", resolve.worlds[world].name).to_upper_camel_case();
+        let name = &format!("{name}.I{name}");
+        let mut gen = self.interface(resolve, name, None, Direction::Export);
+
+        let mut old_resources = mem::take(&mut gen.csharp_gen.all_resources);
+        for (ty_name, ty) in types {
+            gen.define_type(ty_name, *ty);
+        }
+        let new_resources = mem::take(&mut gen.csharp_gen.all_resources);
+        old_resources.extend(new_resources.clone());
+        gen.csharp_gen.all_resources = old_resources;
+        gen.csharp_gen.world_export_resources = new_resources;
+
+        gen.add_world_fragment();
+        Ok(())
+    }
+
     fn finish(&mut self, resolve: &Resolve, id: WorldId, files: &mut Files) -> anyhow::Result<()> {
         if !self.import_funcs_called {
             // Ensure that we emit type declarations for any top-level imported resource types:
@@ -868,9 +908,10 @@ fn interface_name(
         }
     };
 
-    let name = match name {
-        WorldKey::Name(name) => name.to_upper_camel_case(),
-        WorldKey::Interface(id) => resolve.interfaces[*id]
+    let name = match (csharp.renames.interface(resolve, name), name) {
+        (Some(rename), _) => rename.to_string(),
+        (None, WorldKey::Name(name)) => name.to_upper_camel_case(),
+        (None, WorldKey::Interface(id)) => resolve.interfaces[*id]
             .name
             .as_ref()
             .unwrap()
@@ -15,6 +15,7 @@ macro_rules! codegen_test {
     (resources_with_streams $name:tt $test:tt) => {};
     (resources_with_futures $name:tt $test:tt) => {};
     (error_context $name:tt $test:tt) => {};
+    (async_handles_in_aggregates $name:tt $test:tt) => {};
 
     ($id:ident $name:tt $test:tt) => {
         #[test]
@@ -34,6 +35,7 @@ macro_rules! codegen_test {
                         internal: false,
                         skip_support_files: false,
                         with_wit_results: false,
+                        rename: Vec::new(),
                     }
                     .build()
                     .generate(resolve, world, files)
@@ -172,3 +174,32 @@ fn mono_verify(dir: &Path, name: &str) {
         _ => {}
     }
 }
+
+#[test]
+fn world_exported_types() {
+    let (resolve, world) = test_helpers::world_exporting_types();
+    let mut files = Default::default();
+    wit_bindgen_csharp::Opts {
+        generate_stub: false,
+        string_encoding: StringEncoding::UTF8,
+        runtime: Default::default(),
+        internal: false,
+        skip_support_files: true,
+        with_wit_results: false,
+        rename: Vec::new(),
+    }
+    .build()
+    .generate(&resolve, world, &mut files)
+    .unwrap();
+
+    let (_, src) = files
+        .iter()
+        .find(|(name, _)| *name == "ExportedTypes.cs")
+        .unwrap();
+    let src = String::from_utf8_lossy(src);
+    assert!(src.contains("public class Point"));
+    assert!(src.contains("public abstract class Counter"));
+    assert!(src.contains("Point Add(uint n);"));
+    assert!(src.contains("EntryPoint = \"[dtor]counter\""));
+    assert!(src.contains("EntryPoint = \"[method]counter.add\""));
+}
@@ -1,12 +1,12 @@
 use std::fmt::Write as _;
 
-use heck::{ToSnakeCase, ToUpperCamelCase};
+use heck::ToSnakeCase;
 use wit_bindgen_c::{flags_repr, int_repr};
 use wit_bindgen_core::wit_parser::Handle::{Borrow, Own};
-use wit_bindgen_core::wit_parser::{Field, Function, Type, TypeDefKind};
+use wit_bindgen_core::wit_parser::{Function, Type, TypeDefKind};
 use wit_bindgen_core::{dealias, uwriteln, Direction, Source};
 
-use super::avoid_keyword;
+use super::{avoid_keyword, case_names, field_names, param_names};
 use crate::interface;
 
 pub(crate) struct FunctionBindgen<'a, 'b> {
@@ -34,13 +34,12 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
     }
 
     pub(crate) fn process_args(&mut self) {
-        self.func
-            .params
-            .iter()
-            .for_each(|(name, ty)| match self.interface.direction {
-                Direction::Import => self.lower(&avoid_keyword(&name.to_snake_case()), ty),
-                Direction::Export => self.lift(&avoid_keyword(&name.to_snake_case()), ty),
-            });
+        for (name, (_, ty)) in param_names(self.func).iter().zip(&self.func.params) {
+            match self.interface.direction {
+                Direction::Import => self.lower(name, ty),
+                Direction::Export => self.lift(name, ty),
+            }
+        }
     }
 
     pub(crate) fn process_returns(&mut self) {
@@ -196,9 +195,10 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
                     TypeDefKind::Record(r) => {
                         let c_typedef_target = self.interface.gen.get_c_ty(&Type::Id(*id)); // okay to unwrap because a record must have a name
                         uwriteln!(self.lower_src, "var {lower_name} {c_typedef_target}");
-                        for field in r.fields.iter() {
-                            let c_field_name = &self.get_c_field_name(field);
-                            let field_name = &self.get_go_field_name(field);
+                        let c_field_names = wit_bindgen_c::field_names(r);
+                        let names = field_names(r).into_iter().zip(c_field_names);
+                        for (field, (field_name, c_field_name)) in r.fields.iter().zip(names) {
+                            let c_field_name = &avoid_keyword(&c_field_name);
 
                             self.lower_value(
                                 &format!("{param}.{field_name}"),
@@ -277,8 +277,8 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
                         let c_typedef_target = self.interface.gen.get_c_ty(&Type::Id(*id));
                         let ty = self.interface.get_ty(&Type::Id(*id));
                         uwriteln!(self.lower_src, "var {lower_name} {c_typedef_target}");
-                        for (i, case) in v.cases.iter().enumerate() {
-                            let case_name = case.name.to_upper_camel_case();
+                        let case_names = case_names(v.cases.iter().map(|c| c.name.as_str()));
+                        for (i, (case, case_name)) in v.cases.iter().zip(case_names).enumerate() {
                             uwriteln!(
                                 self.lower_src,
                                 "if {param}.Kind() == {ty}Kind{case_name} {{"
@@ -307,8 +307,8 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
                         let c_typedef_target = self.interface.gen.get_c_ty(&Type::Id(*id));
                         let ty = self.interface.get_ty(&Type::Id(*id));
                         uwriteln!(self.lower_src, "var {lower_name} {c_typedef_target}");
-                        for (i, case) in e.cases.iter().enumerate() {
-                            let case_name = case.name.to_upper_camel_case();
+                        let case_names = case_names(e.cases.iter().map(|c| c.name.as_str()));
+                        for (i, case_name) in case_names.iter().enumerate() {
                             uwriteln!(
                                 self.lower_src,
                                 "if {param}.Kind() == {ty}Kind{case_name} {{"
@@ -439,9 +439,10 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
                             name = lift_name,
                             ty_name = self.interface.get_ty(&Type::Id(*id)),
                         );
-                        for field in r.fields.iter() {
-                            let field_name = &self.get_go_field_name(field);
-                            let c_field_name = &self.get_c_field_name(field);
+                        let c_field_names = wit_bindgen_c::field_names(r);
+                        let names = field_names(r).into_iter().zip(c_field_names);
+                        for (field, (field_name, c_field_name)) in r.fields.iter().zip(names) {
+                            let c_field_name = &avoid_keyword(&c_field_name);
                             self.lift_value(
                                 &format!("{param}.{c_field_name}"),
                                 &field.ty,
@@ -573,8 +574,8 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
                         self.interface.gen.with_import_unsafe(true);
                         let ty_name: String = self.interface.get_ty(&Type::Id(*id));
                         uwriteln!(self.lift_src, "var {lift_name} {ty_name}");
-                        for (i, case) in v.cases.iter().enumerate() {
-                            let case_name = case.name.to_upper_camel_case();
+                        let case_names = case_names(v.cases.iter().map(|c| c.name.as_str()));
+                        for (i, (case, case_name)) in v.cases.iter().zip(case_names).enumerate() {
                             self.lift_src
                                 .push_str(&format!("if {param}.tag == {i} {{\n"));
                             if let Some(ty) = case.ty.as_ref() {
@@ -601,8 +602,8 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
                     TypeDefKind::Enum(e) => {
                         let ty_name = self.interface.get_ty(&Type::Id(*id));
                         uwriteln!(self.lift_src, "var {lift_name} {ty_name}");
-                        for (i, case) in e.cases.iter().enumerate() {
-                            let case_name = case.name.to_upper_camel_case();
+                        let case_names = case_names(e.cases.iter().map(|c| c.name.as_str()));
+                        for (i, case_name) in case_names.iter().enumerate() {
                             uwriteln!(self.lift_src, "if {param} == {i} {{");
                             uwriteln!(self.lift_src, "{lift_name} = {ty_name}{case_name}()");
                             self.lift_src.push_str("}\n");
@@ -690,14 +691,4 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
             }
         }
     }
-
-    pub(crate) fn get_c_field_name(&mut self, field: &Field) -> String {
-        let name = wit_bindgen_c::to_c_ident(field.name.to_snake_case().as_str());
-        avoid_keyword(&name)
-    }
-
-    pub(crate) fn get_go_field_name(&mut self, field: &Field) -> String {
-        let name = &self.interface.field_name(field);
-        avoid_keyword(name)
-    }
 }
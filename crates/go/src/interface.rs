@@ -7,12 +7,12 @@ use wit_bindgen_c::{
     CTypeNameInfo,
 };
 use wit_bindgen_core::wit_parser::{
-    Docs, Enum, Field, Flags, Function, FunctionKind, Handle, InterfaceId, LiveTypes, Record,
-    Resolve, Result_, Tuple, Type, TypeDefKind, TypeId, TypeOwner, Variant, WorldKey,
+    Docs, Enum, Flags, Function, FunctionKind, Handle, InterfaceId, LiveTypes, Record, Resolve,
+    Result_, Tuple, Type, TypeDefKind, TypeId, TypeOwner, Variant, WorldKey,
 };
 use wit_bindgen_core::{uwriteln, Direction, InterfaceGenerator as _, Source};
 
-use super::{avoid_keyword, bindgen, TinyGo};
+use super::{bindgen, case_names, field_names, param_names, TinyGo};
 
 pub(crate) struct InterfaceGenerator<'a> {
     pub(crate) src: Source,
@@ -62,8 +62,16 @@ impl InterfaceGenerator<'_> {
             let prev = self.gen.c_type_namespaces.insert(ty, name.clone());
             assert!(prev.is_none());
 
+            let owner = match self.resolve.types[ty].owner {
+                TypeOwner::Interface(_) => self.interface.map(|(_, key)| key),
+                _ => None,
+            };
+            let rename = match info {
+                CTypeNameInfo::Named { name } => self.gen.renames.item(self.resolve, owner, name),
+                CTypeNameInfo::Anonymous { .. } => None,
+            };
             name.push('_');
-            name.push_str(&encoded);
+            name.push_str(rename.unwrap_or(&encoded));
             name.push_str("_t");
             let prev = self.gen.c_type_names.insert(ty, name.clone());
             assert!(prev.is_none());
@@ -112,7 +120,7 @@ impl InterfaceGenerator<'_> {
             self.gen.world.clone(),
             self.resolve,
             id,
-            &Default::default(),
+            &self.gen.renames,
         )
     }
 
@@ -132,6 +140,16 @@ impl InterfaceGenerator<'_> {
 
     /// Returns the identifier of the given interface.
     pub(crate) fn interface_identifier(&self, key: &WorldKey) -> String {
+        if let Some(rename) = self.gen.renames.interface(self.resolve, key) {
+            let mut name = String::new();
+            if matches!(self.direction, Direction::Export) && matches!(key, WorldKey::Interface(_))
+            {
+                name.push_str("Exports");
+            }
+            name.push_str(rename);
+            return name;
+        }
+
         match key {
             WorldKey::Name(k) => k.to_upper_camel_case(),
             WorldKey::Interface(id) => {
@@ -156,6 +174,11 @@ impl InterfaceGenerator<'_> {
 
     /// Returns the function name of the given function.
     pub(crate) fn func_name(&self, func: &Function) -> String {
+        let owner = self.interface.map(|(_, key)| key);
+        if let Some(rename) = self.gen.renames.item(self.resolve, owner, &func.name) {
+            return rename.to_string();
+        }
+
         match func.kind {
             FunctionKind::Freestanding => func.name.to_upper_camel_case(),
             FunctionKind::Static(_) => func.name.replace('.', " ").to_upper_camel_case(),
@@ -267,7 +290,11 @@ impl InterfaceGenerator<'_> {
                 let ty = &self.resolve.types[*id];
                 // if a type has name, return the name
                 if let Some(name) = &ty.name {
-                    return name.to_upper_camel_case();
+                    let world = self.gen.world_id.unwrap();
+                    return match self.gen.renames.ty(self.resolve, world, *id) {
+                        Some(rename) => rename.to_string(),
+                        None => name.to_upper_camel_case(),
+                    };
                 }
                 // otherwise, return the anonymous type name
                 match &ty.kind {
@@ -327,24 +354,12 @@ impl InterfaceGenerator<'_> {
                         src
                     }
                     TypeDefKind::ErrorContext => "ErrorContext".to_owned(),
-                    TypeDefKind::Handle(Handle::Own(ty)) => {
+                    TypeDefKind::Handle(Handle::Own(ty))
+                    | TypeDefKind::Handle(Handle::Borrow(ty)) => {
                         // Currently there is no different between Own and Borrow
                         // in the Go code. They are just represented as
                         // the name of the resource type.
-                        let mut src = String::new();
-                        let ty = &self.resolve.types[*ty];
-                        if let Some(name) = &ty.name {
-                            src.push_str(&name.to_upper_camel_case());
-                        }
-                        src
-                    }
-                    TypeDefKind::Handle(Handle::Borrow(ty)) => {
-                        let mut src = String::new();
-                        let ty = &self.resolve.types[*ty];
-                        if let Some(name) = &ty.name {
-                            src.push_str(&name.to_upper_camel_case());
-                        }
-                        src
+                        self.ty_name(&Type::Id(*ty))
                     }
                     TypeDefKind::Unknown => unreachable!(),
                 }
@@ -362,17 +377,13 @@ impl InterfaceGenerator<'_> {
 
     pub(crate) fn func_params(&mut self, func: &Function) -> String {
         let mut params = String::new();
-        match func.kind {
-            FunctionKind::Method(_) => {
-                for (i, (name, param)) in func.params.iter().skip(1).enumerate() {
-                    self.get_func_params_common(i, &mut params, name, param);
-                }
-            }
-            _ => {
-                for (i, (name, param)) in func.params.iter().enumerate() {
-                    self.get_func_params_common(i, &mut params, name, param);
-                }
-            }
+        let skip = match func.kind {
+            FunctionKind::Method(_) => 1,
+            _ => 0,
+        };
+        let names = param_names(func);
+        for (i, (name, (_, param))) in names.iter().zip(&func.params).skip(skip).enumerate() {
+            self.get_func_params_common(i, &mut params, name, param);
         }
 
         params
@@ -382,13 +393,13 @@ impl InterfaceGenerator<'_> {
         &mut self,
         i: usize,
         params: &mut String,
-        name: &String,
+        name: &str,
         param: &Type,
     ) {
         if i > 0 {
             params.push_str(", ");
         }
-        params.push_str(&avoid_keyword(&name.to_snake_case()));
+        params.push_str(name);
         params.push(' ');
         params.push_str(&self.get_ty(param));
     }
@@ -466,16 +477,12 @@ impl InterfaceGenerator<'_> {
         func: &Function,
         direction: Direction,
     ) {
-        for (i, (name, param)) in func.params.iter().enumerate() {
+        let names = param_names(func);
+        for (i, (name, (_, param))) in names.iter().zip(&func.params).enumerate() {
             if i > 0 {
                 params.push_str(", ");
             }
-            self.c_param(
-                params,
-                &avoid_keyword(&name.to_snake_case()),
-                param,
-                direction,
-            );
+            self.c_param(params, name, param, direction);
         }
     }
 
@@ -542,7 +549,7 @@ impl InterfaceGenerator<'_> {
                 &self.gen.world,
                 self.interface.map(|(_, key)| key),
                 func,
-                &Default::default(),
+                &self.gen.renames,
             )
         } else {
             // do not want to generate public functions
@@ -601,10 +608,6 @@ impl InterfaceGenerator<'_> {
         self.src.push_str("{\n");
     }
 
-    pub(crate) fn field_name(&mut self, field: &Field) -> String {
-        field.name.to_upper_camel_case()
-    }
-
     pub(crate) fn extract_result_ty(&self, ty: &Type) -> (Option<Type>, Option<Type>) {
         //TODO: don't copy from the C code
         // optimization on the C size.
@@ -834,7 +837,7 @@ impl InterfaceGenerator<'_> {
                 &self.gen.world,
                 self.interface.map(|(_, key)| key),
                 func,
-                &Default::default(),
+                &self.gen.renames,
             );
             src.push_str(&name);
             src.push('\n');
@@ -844,10 +847,10 @@ impl InterfaceGenerator<'_> {
             src.push_str(" {\n");
 
             // free all the parameters
-            for (name, ty) in func.params.iter() {
+            for (name, (_, ty)) in param_names(func).iter().zip(&func.params) {
                 // TODO: should test if owns anything
                 if false {
-                    let free = self.free_c_arg(ty, &avoid_keyword(&name.to_snake_case()));
+                    let free = self.free_c_arg(ty, name);
                     src.push_str(&free);
                 }
             }
@@ -1009,19 +1012,18 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.resolve
     }
 
-    fn type_record(&mut self, _id: TypeId, name: &str, record: &Record, _docs: &Docs) {
-        let name = self.type_name(name, true);
+    fn type_record(&mut self, id: TypeId, _name: &str, record: &Record, _docs: &Docs) {
+        let name = self.gen.type_names[&id].clone();
         self.src.push_str(&format!("type {name} struct {{\n",));
-        for field in record.fields.iter() {
+        for (field, name) in record.fields.iter().zip(field_names(record)) {
             let ty = self.get_ty(&field.ty);
-            let name = self.field_name(field);
             self.src.push_str(&format!("   {name} {ty}\n",));
         }
         self.src.push_str("}\n\n");
     }
 
     fn type_resource(&mut self, id: TypeId, name: &str, _docs: &Docs) {
-        let type_name = self.type_name(name, true);
+        let type_name = self.gen.type_names[&id].clone();
         let private_type_name = type_name.to_snake_case();
         // for imports, generate a `int32` type for resource handle representation.
         // for exports, generate a map to store unique IDs of resources to their
@@ -1156,15 +1158,14 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         };
     }
 
-    fn type_flags(&mut self, _id: TypeId, name: &str, flags: &Flags, _docs: &Docs) {
-        let name = self.type_name(name, true);
+    fn type_flags(&mut self, id: TypeId, _name: &str, flags: &Flags, _docs: &Docs) {
+        let name = self.gen.type_names[&id].clone();
 
         // TODO: use flags repr to determine how many flags are needed
         self.src.push_str(&format!("type {name} uint64\n"));
         self.src.push_str("const (\n");
-        for (i, flag) in flags.flags.iter().enumerate() {
-            let case_flag = flag.name.to_upper_camel_case();
-
+        let case_flags = case_names(flags.flags.iter().map(|f| f.name.as_str()));
+        for (i, case_flag) in case_flags.iter().enumerate() {
             if i == 0 {
                 self.src.push_str(&format!(
                     "   {name}_{flag} {name} = 1 << iota\n",
@@ -1182,8 +1183,8 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.src.push_str(")\n\n");
     }
 
-    fn type_tuple(&mut self, _id: TypeId, name: &str, tuple: &Tuple, _docs: &Docs) {
-        let name = self.type_name(name, true);
+    fn type_tuple(&mut self, id: TypeId, _name: &str, tuple: &Tuple, _docs: &Docs) {
+        let name = self.gen.type_names[&id].clone();
         self.src.push_str(&format!("type {name} struct {{\n",));
         for (i, case) in tuple.types.iter().enumerate() {
             let ty = self.get_ty(case);
@@ -1192,16 +1193,16 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.src.push_str("}\n\n");
     }
 
-    fn type_variant(&mut self, _id: TypeId, name: &str, variant: &Variant, _docs: &Docs) {
-        let name = self.type_name(name, true);
+    fn type_variant(&mut self, id: TypeId, _name: &str, variant: &Variant, _docs: &Docs) {
+        let name = self.gen.type_names[&id].clone();
         // TODO: use variant's tag to determine how many cases are needed
         // this will help to optmize the Kind type.
         self.src.push_str(&format!("type {name}Kind int\n\n"));
         self.src.push_str("const (\n");
 
-        for (i, case) in variant.cases.iter().enumerate() {
-            let case_name = case.name.to_upper_camel_case();
-            self.print_variant_field(&name, &case_name, i);
+        let case_names = case_names(variant.cases.iter().map(|c| c.name.as_str()));
+        for (i, case_name) in case_names.iter().enumerate() {
+            self.print_variant_field(&name, case_name, i);
         }
         self.src.push_str(")\n\n");
 
@@ -1212,27 +1213,26 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
 
         self.print_kind_method(&name);
 
-        for case in variant.cases.iter() {
-            let case_name = case.name.to_upper_camel_case();
+        for (case, case_name) in variant.cases.iter().zip(&case_names) {
             if let Some(ty) = case.ty.as_ref() {
                 self.gen.with_fmt_import(true);
-                self.print_accessor_methods(&name, &case_name, ty);
+                self.print_accessor_methods(&name, case_name, ty);
             } else {
-                self.print_constructor_method_without_value(&name, &case_name);
+                self.print_constructor_method_without_value(&name, case_name);
             }
         }
     }
 
-    fn type_enum(&mut self, _id: TypeId, name: &str, enum_: &Enum, _docs: &Docs) {
-        let name = self.type_name(name, true);
+    fn type_enum(&mut self, id: TypeId, _name: &str, enum_: &Enum, _docs: &Docs) {
+        let name = self.gen.type_names[&id].clone();
         // TODO: use variant's tag to determine how many cases are needed
         // this will help to optmize the Kind type.
         self.src.push_str(&format!("type {name}Kind int\n\n"));
         self.src.push_str("const (\n");
 
-        for (i, case) in enum_.cases.iter().enumerate() {
-            let case_name = case.name.to_upper_camel_case();
-            self.print_variant_field(&name, &case_name, i);
+        let case_names = case_names(enum_.cases.iter().map(|c| c.name.as_str()));
+        for (i, case_name) in case_names.iter().enumerate() {
+            self.print_variant_field(&name, case_name, i);
         }
         self.src.push_str(")\n\n");
 
@@ -1242,14 +1242,13 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
 
         self.print_kind_method(&name);
 
-        for case in enum_.cases.iter() {
-            let case_name = case.name.to_upper_camel_case();
-            self.print_constructor_method_without_value(&name, &case_name);
+        for case_name in case_names.iter() {
+            self.print_constructor_method_without_value(&name, case_name);
         }
     }
 
-    fn type_alias(&mut self, _id: TypeId, name: &str, ty: &Type, _docs: &Docs) {
-        let name = self.type_name(name, true);
+    fn type_alias(&mut self, id: TypeId, _name: &str, ty: &Type, _docs: &Docs) {
+        let name = self.gen.type_names[&id].clone();
         let ty = self.get_ty(ty);
         self.src.push_str(&format!("type {name} = {ty}\n"));
     }
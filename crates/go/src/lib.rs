@@ -4,12 +4,13 @@ use std::mem;
 use std::process::Stdio;
 
 use anyhow::Result;
-use heck::ToSnakeCase;
+use heck::{ToSnakeCase, ToUpperCamelCase};
 use wit_bindgen_c::imported_types_used_by_exported_interfaces;
 use wit_bindgen_core::wit_parser::{
-    Function, InterfaceId, LiveTypes, Resolve, SizeAlign, Type, TypeId, WorldId, WorldKey,
+    Function, InterfaceId, LiveTypes, Record, Resolve, SizeAlign, Type, TypeId, TypeOwner, WorldId,
+    WorldKey,
 };
-use wit_bindgen_core::{Direction, Files, Source, WorldGenerator};
+use wit_bindgen_core::{Case, Direction, Files, Keywords, Renames, Source, WorldGenerator};
 
 mod bindgen;
 mod imports;
@@ -25,6 +26,15 @@ pub struct Opts {
     /// Rename the Go package in the generated source code.
     #[cfg_attr(feature = "clap", arg(long))]
     pub rename_package: Option<String>,
+
+    /// Rename the interface or item `K` to `V` in the generated source code.
+    ///
+    /// `K` is either an interface such as `foo:bar/baz`, an item within an
+    /// interface such as `foo:bar/baz.qux`, or an item defined in the world.
+    /// `V` is used verbatim, both in the Go source and in the C bindings it
+    /// calls into.
+    #[cfg_attr(feature = "clap", arg(long, name = "K=V", value_parser = Renames::parse))]
+    pub rename: Vec<(String, String)>,
 }
 
 impl Default for Opts {
@@ -32,6 +42,7 @@ impl Default for Opts {
         Self {
             gofmt: true,
             rename_package: None,
+            rename: Vec::new(),
         } // Set the default value of gofmt to true
     }
 }
@@ -55,6 +66,9 @@ pub struct TinyGo {
 
     world: String,
 
+    // user-supplied renames of interfaces and items
+    renames: Renames,
+
     // import requirements for the generated code
     import_requirements: imports::ImportRequirements,
 
@@ -161,6 +175,11 @@ impl WorldGenerator for TinyGo {
             .unwrap_or_else(|| resolve.worlds[world].name.clone());
         self.sizes.fill(resolve);
         self.world_id = Some(world);
+
+        for (from, to) in self.opts.rename.iter() {
+            self.renames.insert(from, to);
+        }
+        self.renames.warn_unmatched(resolve, world);
     }
 
     fn import_interface(
@@ -265,7 +284,16 @@ impl WorldGenerator for TinyGo {
         self.src
             .push_str(&format!("// Export functions from {name}\n"));
 
+        // Resources exported from the world itself were defined by
+        // `export_types`, and are implemented alongside these functions.
+        let resources = self
+            .exported_resources
+            .iter()
+            .copied()
+            .filter(|id| resolve.types[*id].owner == TypeOwner::World(world))
+            .collect();
         let mut gen = self.interface(resolve, Direction::Export, None);
+        gen.exported_resources = resources;
         gen.define_function_types(funcs);
 
         for (_name, func) in funcs.iter() {
@@ -298,6 +326,26 @@ impl WorldGenerator for TinyGo {
         self.src.push_str(&src);
     }
 
+    fn export_types(
+        &mut self,
+        resolve: &Resolve,
+        _world: WorldId,
+        types: &[(&str, TypeId)],
+        _files: &mut Files,
+    ) -> Result<()> {
+        let mut gen = self.interface(resolve, Direction::Export, None);
+        let mut live = LiveTypes::default();
+        for (_, id) in types {
+            live.add_type_id(resolve, *id);
+        }
+        gen.define_live_types(&live);
+        let src = mem::take(&mut gen.src);
+        let preamble = mem::take(&mut gen.preamble);
+        self.src.push_str(&src);
+        self.preamble.append_src(&preamble);
+        Ok(())
+    }
+
     fn finish(&mut self, resolve: &Resolve, id: WorldId, files: &mut Files) -> Result<()> {
         // make sure all types are defined on top of the file
         let src = mem::take(&mut self.src);
@@ -357,6 +405,7 @@ impl WorldGenerator for TinyGo {
         opts.no_sig_flattening = true;
         opts.no_object_file = true;
         opts.rename_world = self.opts.rename_package.clone();
+        opts.rename = self.renames.matched(resolve, id);
         opts.build()
             .generate(resolve, id, files)
             .expect("C generator should be infallible");
@@ -366,41 +415,36 @@ impl WorldGenerator for TinyGo {
 }
 
 fn avoid_keyword(s: &str) -> String {
-    if GOKEYWORDS.contains(&s) {
-        format!("_{s}")
-    } else {
-        s.into()
-    }
+    Keywords::GO.escape(s)
+}
+
+/// Returns the names of the fields of `record`, which are kept distinct even
+/// when several WIT names map to the same Go identifier.
+fn field_names(record: &Record) -> Vec<String> {
+    let mut scope = Keywords::GO.scope();
+    record
+        .fields
+        .iter()
+        .map(|field| scope.unique(&field.name.to_upper_camel_case()))
+        .collect()
 }
 
-// a list of Go keywords
-const GOKEYWORDS: [&str; 26] = [
-    "break",
-    "default",
-    "func",
-    "interface",
-    "select",
-    "case",
-    "defer",
-    "go",
-    "map",
-    "struct",
-    "chan",
-    "else",
-    "goto",
-    "package",
-    "switch",
-    "const",
-    "fallthrough",
-    "if",
-    "range",
-    "type",
-    "continue",
-    "for",
-    "import",
-    "return",
-    "var",
-    // not a Go keyword but needs to escape due to
-    // it's used as a variable name that passes to C
-    "ret",
-];
+/// Returns the names of the parameters of `func`, kept distinct like
+/// [`field_names`].
+fn param_names(func: &Function) -> Vec<String> {
+    let mut scope = Keywords::GO.scope();
+    func.params
+        .iter()
+        .map(|(name, _)| scope.ident(name, Case::Snake))
+        .collect()
+}
+
+/// Returns the names of the cases of a variant, enum or flags, kept distinct
+/// like [`field_names`].
+fn case_names<'a>(cases: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut scope = Keywords::GO.scope();
+    cases
+        .into_iter()
+        .map(|case| scope.unique(&case.to_upper_camel_case()))
+        .collect()
+}
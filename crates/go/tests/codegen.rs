@@ -16,6 +16,7 @@ macro_rules! codegen_test {
     (resources_with_streams $name:tt $test:tt) => {};
     (resources_with_futures $name:tt $test:tt) => {};
     (error_context $name:tt $test:tt) => {};
+    (async_handles_in_aggregates $name:tt $test:tt) => {};
 
     ($id:ident $name:tt $test:tt) => {
         #[test]
@@ -96,3 +97,22 @@ fn verify(dir: &Path, name: &str) {
     cmd.current_dir(dir);
     test_helpers::run_command(&mut cmd);
 }
+
+#[test]
+fn world_exported_types() {
+    let (resolve, world) = test_helpers::world_exporting_types();
+    let mut opts = wit_bindgen_go::Opts::default();
+    opts.gofmt = false;
+    let mut files = Default::default();
+    opts.build().generate(&resolve, world, &mut files).unwrap();
+
+    let (_, src) = files
+        .iter()
+        .find(|(name, _)| *name == "exported_types.go")
+        .unwrap();
+    let src = String::from_utf8_lossy(src);
+    assert!(src.contains("type ExportedTypesPoint struct"));
+    assert!(src.contains("type ExportedTypesCounter interface"));
+    assert!(src.contains("MethodCounterAdd(n ExportedTypesAmount) ExportedTypesPoint"));
+    assert!(src.contains("//export exported_types_counter_destructor"));
+}
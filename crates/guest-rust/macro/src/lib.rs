@@ -115,7 +115,9 @@ impl Parse for Config {
                             .map(|p| p.into_token_stream().to_string())
                             .collect()
                     }
+                    Opt::Serde(enable) => opts.serde = enable.value(),
                     Opt::With(with) => opts.with.extend(with),
+                    Opt::Rename(rename) => opts.rename.extend(rename),
                     Opt::GenerateAll => {
                         opts.generate_all = true;
                     }
@@ -137,6 +139,18 @@ impl Parse for Config {
                     Opt::GenerateUnusedTypes(enable) => {
                         opts.generate_unused_types = enable.value();
                     }
+                    Opt::GenerateAllPayloads(enable) => {
+                        opts.generate_all_payloads = enable.value();
+                    }
+                    Opt::DynExports(enable) => {
+                        opts.dyn_exports = enable.value();
+                    }
+                    Opt::Mocks(enable) => {
+                        opts.mocks = enable.value();
+                    }
+                    Opt::Host(enable) => {
+                        opts.host = enable.value();
+                    }
                     Opt::Features(f) => {
                         features.extend(f.into_iter().map(|f| f.value()));
                     }
@@ -323,7 +337,9 @@ mod kw {
     syn::custom_keyword!(stubs);
     syn::custom_keyword!(export_prefix);
     syn::custom_keyword!(additional_derives);
+    syn::custom_keyword!(serde);
     syn::custom_keyword!(with);
+    syn::custom_keyword!(rename);
     syn::custom_keyword!(generate_all);
     syn::custom_keyword!(type_section_suffix);
     syn::custom_keyword!(disable_run_ctors_once_workaround);
@@ -331,6 +347,10 @@ mod kw {
     syn::custom_keyword!(export_macro_name);
     syn::custom_keyword!(pub_export_macro);
     syn::custom_keyword!(generate_unused_types);
+    syn::custom_keyword!(generate_all_payloads);
+    syn::custom_keyword!(dyn_exports);
+    syn::custom_keyword!(mocks);
+    syn::custom_keyword!(host);
     syn::custom_keyword!(features);
     syn::custom_keyword!(disable_custom_section_link_helpers);
     syn::custom_keyword!(imports);
@@ -383,7 +403,9 @@ enum Opt {
     ExportPrefix(syn::LitStr),
     // Parse as paths so we can take the concrete types/macro names rather than raw strings
     AdditionalDerives(Vec<syn::Path>),
+    Serde(syn::LitBool),
     With(HashMap<String, WithOption>),
+    Rename(Vec<(String, String)>),
     GenerateAll,
     TypeSectionSuffix(syn::LitStr),
     DisableRunCtorsOnceWorkaround(syn::LitBool),
@@ -391,6 +413,10 @@ enum Opt {
     ExportMacroName(syn::LitStr),
     PubExportMacro(syn::LitBool),
     GenerateUnusedTypes(syn::LitBool),
+    GenerateAllPayloads(syn::LitBool),
+    DynExports(syn::LitBool),
+    Mocks(syn::LitBool),
+    Host(syn::LitBool),
     Features(Vec<syn::LitStr>),
     DisableCustomSectionLinkHelpers(syn::LitBool),
     Async(AsyncConfig, Span),
@@ -496,6 +522,10 @@ impl Parse for Opt {
             syn::bracketed!(contents in input);
             let list = Punctuated::<_, Token![,]>::parse_terminated(&contents)?;
             Ok(Opt::AdditionalDerives(list.iter().cloned().collect()))
+        } else if l.peek(kw::serde) {
+            input.parse::<kw::serde>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::Serde(input.parse()?))
         } else if l.peek(kw::with) {
             input.parse::<kw::with>()?;
             input.parse::<Token![:]>()?;
@@ -504,6 +534,14 @@ impl Parse for Opt {
             let fields: Punctuated<_, Token![,]> =
                 contents.parse_terminated(with_field_parse, Token![,])?;
             Ok(Opt::With(HashMap::from_iter(fields.into_iter())))
+        } else if l.peek(kw::rename) {
+            input.parse::<kw::rename>()?;
+            input.parse::<Token![:]>()?;
+            let contents;
+            let _lbrace = braced!(contents in input);
+            let fields: Punctuated<_, Token![,]> =
+                contents.parse_terminated(rename_field_parse, Token![,])?;
+            Ok(Opt::Rename(fields.into_iter().collect()))
         } else if l.peek(kw::generate_all) {
             input.parse::<kw::generate_all>()?;
             Ok(Opt::GenerateAll)
@@ -531,6 +569,22 @@ impl Parse for Opt {
             input.parse::<kw::generate_unused_types>()?;
             input.parse::<Token![:]>()?;
             Ok(Opt::GenerateUnusedTypes(input.parse()?))
+        } else if l.peek(kw::generate_all_payloads) {
+            input.parse::<kw::generate_all_payloads>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::GenerateAllPayloads(input.parse()?))
+        } else if l.peek(kw::dyn_exports) {
+            input.parse::<kw::dyn_exports>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::DynExports(input.parse()?))
+        } else if l.peek(kw::mocks) {
+            input.parse::<kw::mocks>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::Mocks(input.parse()?))
+        } else if l.peek(kw::host) {
+            input.parse::<kw::host>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::Host(input.parse()?))
         } else if l.peek(kw::features) {
             input.parse::<kw::features>()?;
             input.parse::<Token![:]>()?;
@@ -623,6 +677,13 @@ fn with_field_parse(input: ParseStream<'_>) -> Result<(String, WithOption)> {
     Ok((interface, WithOption::Path(buf)))
 }
 
+fn rename_field_parse(input: ParseStream<'_>) -> Result<(String, String)> {
+    let from = input.parse::<syn::LitStr>()?.value();
+    input.parse::<Token![:]>()?;
+    let to = input.parse::<syn::LitStr>()?.value();
+    Ok((from, to))
+}
+
 /// Format a valid Rust string
 fn fmt(input: &str) -> Result<String> {
     let syntax_tree = syn::parse_file(&input)?;
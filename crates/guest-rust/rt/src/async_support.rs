@@ -5,43 +5,80 @@ extern crate std;
 use std::alloc::{self, Layout};
 use std::any::Any;
 use std::boxed::Box;
+use std::cell::RefCell;
+#[cfg(any(not(target_arch = "wasm32"), feature = "wasi-clocks"))]
+use std::collections::BTreeMap;
 use std::collections::{hash_map, HashMap};
 use std::fmt::{self, Debug, Display};
 use std::future::Future;
+use std::mem;
 use std::pin::Pin;
 use std::ptr;
 use std::string::String;
-use std::sync::Arc;
-use std::task::{Context, Poll, Wake, Waker};
+use std::task::{Context, Poll, Waker};
 use std::vec::Vec;
 
 use futures::channel::oneshot;
-use futures::future::FutureExt;
+use futures::future::{Either, FutureExt};
 use futures::stream::{FuturesUnordered, StreamExt};
-use once_cell::sync::Lazy;
+#[cfg(target_arch = "wasm32")]
+use {
+    once_cell::sync::Lazy,
+    std::{sync::Arc, task::Wake},
+};
 
 mod future_support;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod native;
 mod stream_support;
+#[cfg(any(not(target_arch = "wasm32"), feature = "wasi-clocks"))]
+mod time;
 
 pub use {
     future_support::{FutureReader, FutureVtable, FutureWriter},
     stream_support::{StreamReader, StreamVtable, StreamWriter},
 };
 
+#[cfg(any(not(target_arch = "wasm32"), feature = "wasi-clocks"))]
+pub use time::{sleep, timeout, Elapsed, Sleep};
+
 pub use futures;
 
-type BoxFuture = Pin<Box<dyn Future<Output = ()> + 'static>>;
+type BoxFuture<'a> = Pin<Box<dyn Future<Output = ()> + 'a>>;
 
 /// Represents a task created by either a call to an async-lifted export or a
 /// future run using `block_on` or `poll_future`.
-struct FutureState {
+///
+/// Tasks created by `block_on` may borrow from the caller's stack, hence the
+/// lifetime; tasks created by `first_poll` outlive the export call and are
+/// always `'static`.
+struct FutureState<'a> {
     /// Number of in-progress async-lowered import calls and/or stream/future reads/writes.
     todo: usize,
     /// Remaining work to do (if any) before this task can be considered "done".
     ///
     /// Note that we won't tell the host the task is done until this is drained
     /// and `todo` is zero.
-    tasks: Option<FuturesUnordered<BoxFuture>>,
+    tasks: Option<FuturesUnordered<BoxFuture<'a>>>,
+}
+
+/// An operation the host will notify us about via `callback`.
+struct Pending {
+    /// Receives the event's result.
+    tx: oneshot::Sender<u32>,
+    /// The task whose `todo` count includes this operation.
+    task: *mut FutureState<'static>,
+}
+
+/// Register an in-progress operation with the current task, returning a
+/// receiver for its result.
+unsafe fn register(id: i32) -> oneshot::Receiver<u32> {
+    let task = globals().current;
+    assert!(!task.is_null());
+    (*task).todo += 1;
+    let (tx, rx) = oneshot::channel();
+    globals().calls.insert(id, Pending { tx, task });
+    rx
 }
 
 /// Represents the state of a stream or future.
@@ -55,26 +92,100 @@ pub enum Handle {
     Write,
 }
 
-/// The current task being polled (or null if none).
-static mut CURRENT: *mut FutureState = ptr::null_mut();
+/// State shared by all tasks.
+struct Globals {
+    /// The current task being polled (or null if none).
+    ///
+    /// The lifetime is erased here; only the task's `todo` count is ever
+    /// accessed through this pointer.
+    current: *mut FutureState<'static>,
+
+    /// Map of any in-progress calls to async-lowered imports and
+    /// stream/future reads/writes, keyed by the identifiers issued by the host.
+    calls: HashMap<i32, Pending>,
+
+    /// Parameter buffers of import calls whose futures were dropped before the
+    /// host finished reading them, keyed by call identifier.
+    abandoned: HashMap<i32, (*mut u8, Layout)>,
+
+    /// Pending timers, keyed by deadline in nanoseconds and a unique
+    /// identifier.
+    #[cfg(any(not(target_arch = "wasm32"), feature = "wasi-clocks"))]
+    timers: BTreeMap<(u64, u64), Waker>,
+
+    /// Any newly-deferred work queued by calls to the `spawn` function while
+    /// polling the current task.
+    spawned: Vec<BoxFuture<'static>>,
+
+    /// The states of all currently-open streams and futures.
+    handles: HashMap<u32, Handle>,
+}
+
+impl Globals {
+    fn new() -> Self {
+        Self {
+            current: ptr::null_mut(),
+            calls: HashMap::new(),
+            abandoned: HashMap::new(),
+            #[cfg(any(not(target_arch = "wasm32"), feature = "wasi-clocks"))]
+            timers: BTreeMap::new(),
+            spawned: Vec::new(),
+            handles: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+unsafe fn globals() -> &'static mut Globals {
+    static mut GLOBALS: Lazy<Globals> = Lazy::new(Globals::new);
+    &mut GLOBALS
+}
 
-/// Map of any in-progress calls to async-lowered imports, keyed by the
-/// identifiers issued by the host.
-static mut CALLS: Lazy<HashMap<i32, oneshot::Sender<u32>>> = Lazy::new(HashMap::new);
+/// Off `wasm32` each thread gets its own runtime so that tests using the
+/// native backend can run in parallel.
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn globals() -> &'static mut Globals {
+    std::thread_local! {
+        static GLOBALS: std::cell::UnsafeCell<Globals> = std::cell::UnsafeCell::new(Globals::new());
+    }
+    &mut *GLOBALS.with(|globals| globals.get())
+}
 
-/// Any newly-deferred work queued by calls to the `spawn` function while
-/// polling the current task.
-static mut SPAWNED: Vec<BoxFuture> = Vec::new();
+/// Allocate a handle for a stream or future which never leaves this component.
+///
+/// On `wasm32` these count down from the top of the `u32` range (`u32::MAX`
+/// itself means "no handle") so they don't collide with handles issued by the
+/// host.
+#[doc(hidden)]
+pub fn new_local_handle() -> u32 {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        native::new_handle()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        use std::sync::atomic::{AtomicU32, Ordering::Relaxed};
 
-/// The states of all currently-open streams and futures.
-static mut HANDLES: Lazy<HashMap<u32, Handle>> = Lazy::new(HashMap::new);
+        static NEXT: AtomicU32 = AtomicU32::new(u32::MAX - 1);
+        NEXT.fetch_sub(1, Relaxed)
+    }
+}
+
+fn local_only() -> ! {
+    unreachable!(
+        "streams and futures whose payload type appears in no function \
+         signature can't be passed to another component"
+    )
+}
 
 #[doc(hidden)]
 pub fn with_entry<T>(handle: u32, fun: impl FnOnce(hash_map::Entry<'_, u32, Handle>) -> T) -> T {
-    fun(unsafe { HANDLES.entry(handle) })
+    fun(unsafe { globals().handles.entry(handle) })
 }
 
-fn dummy_waker() -> Waker {
+#[cfg(target_arch = "wasm32")]
+fn waker() -> Waker {
     struct DummyWaker;
 
     impl Wake for DummyWaker {
@@ -86,16 +197,53 @@ fn dummy_waker() -> Waker {
     WAKER.clone().into()
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn waker() -> Waker {
+    native::waker()
+}
+
+/// Restores the task which was current before a (possibly nested) call to
+/// `poll`, even if polling panics.
+struct RestoreCurrent {
+    current: *mut FutureState<'static>,
+    spawned: Vec<BoxFuture<'static>>,
+}
+
+impl Drop for RestoreCurrent {
+    fn drop(&mut self) {
+        unsafe {
+            globals().current = self.current;
+            globals().spawned = mem::take(&mut self.spawned);
+        }
+    }
+}
+
 /// Poll the specified task until it either completes or can't make immediate
 /// progress.
-unsafe fn poll(state: *mut FutureState) -> Poll<()> {
+///
+/// This may be called while another task is being polled (e.g. by a
+/// `block_on` nested inside an async function), in which case the outer
+/// task's state is set aside until this returns. Otherwise any futures passed
+/// to `spawn` outside of a task become part of this one.
+unsafe fn poll(state: *mut FutureState<'_>) -> Poll<()> {
+    let current = globals().current;
+    let _restore = RestoreCurrent {
+        current,
+        spawned: if current.is_null() {
+            Vec::new()
+        } else {
+            mem::take(&mut globals().spawned)
+        },
+    };
+    #[cfg(any(not(target_arch = "wasm32"), feature = "wasi-clocks"))]
+    time::wake_expired();
     loop {
         if let Some(futures) = (*state).tasks.as_mut() {
-            CURRENT = state;
-            let poll = futures.poll_next_unpin(&mut Context::from_waker(&dummy_waker()));
-            CURRENT = ptr::null_mut();
+            globals().current = state.cast();
+            let poll = futures.poll_next_unpin(&mut Context::from_waker(&waker()));
+            globals().current = ptr::null_mut();
 
-            if SPAWNED.is_empty() {
+            if globals().spawned.is_empty() {
                 match poll {
                     Poll::Ready(Some(())) => (),
                     Poll::Ready(None) => {
@@ -105,7 +253,7 @@ unsafe fn poll(state: *mut FutureState) -> Poll<()> {
                     Poll::Pending => break Poll::Pending,
                 }
             } else {
-                futures.extend(SPAWNED.drain(..));
+                futures.extend(globals().spawned.drain(..).map(|f| f as BoxFuture<'_>));
             }
         } else {
             break Poll::Ready(());
@@ -127,14 +275,18 @@ pub fn first_poll<T: 'static>(
     let state = Box::into_raw(Box::new(FutureState {
         todo: 0,
         tasks: Some(
-            [Box::pin(future.map(|v| fun(&v))) as BoxFuture]
+            [Box::pin(future.map(|v| fun(&v))) as BoxFuture<'static>]
                 .into_iter()
                 .collect(),
         ),
     }));
-    match unsafe { poll(state) } {
-        Poll::Ready(()) => ptr::null_mut(),
-        Poll::Pending => state as _,
+    unsafe {
+        if poll(state).is_ready() || run_timers(state) {
+            drop(Box::from_raw(state));
+            ptr::null_mut()
+        } else {
+            state as _
+        }
     }
 }
 
@@ -151,29 +303,57 @@ pub async unsafe fn await_result(
     const STATUS_RETURNED: u32 = 2;
     const STATUS_DONE: u32 = 3;
 
+    /// Forgets about a call whose future is dropped before the host has
+    /// returned, so the task can still finish once the host is done with it.
+    ///
+    /// Parameters are freed once the host is done reading them; the result
+    /// buffer is leaked since the host may still write to it.
+    struct AbandonOnDrop {
+        call: i32,
+        params: Option<(*mut u8, Layout)>,
+    }
+
+    impl Drop for AbandonOnDrop {
+        fn drop(&mut self) {
+            unsafe {
+                if globals().calls.remove(&self.call).is_some() {
+                    if let Some(params) = self.params {
+                        globals().abandoned.insert(self.call, params);
+                    }
+                }
+            }
+        }
+    }
+
     let result = import(params, results) as u32;
     let status = result >> 30;
     let call = (result & !(0b11 << 30)) as i32;
 
-    if status != STATUS_DONE {
-        assert!(!CURRENT.is_null());
-        (*CURRENT).todo += 1;
-    }
-
     match status {
         STATUS_STARTING => {
-            let (tx, rx) = oneshot::channel();
-            CALLS.insert(call, tx);
+            let rx = register(call);
+            let guard = AbandonOnDrop {
+                call,
+                params: Some((params, params_layout)),
+            };
             rx.await.unwrap();
+            mem::forget(guard);
             alloc::dealloc(params, params_layout);
         }
         STATUS_STARTED => {
             alloc::dealloc(params, params_layout);
-            let (tx, rx) = oneshot::channel();
-            CALLS.insert(call, tx);
+            let rx = register(call);
+            let guard = AbandonOnDrop { call, params: None };
             rx.await.unwrap();
+            mem::forget(guard);
+        }
+        STATUS_RETURNED => {
+            alloc::dealloc(params, params_layout);
+            // The host will still tell us when the subtask is done.
+            assert!(!globals().current.is_null());
+            (*globals().current).todo += 1;
         }
-        STATUS_RETURNED | STATUS_DONE => {
+        STATUS_DONE => {
             alloc::dealloc(params, params_layout);
         }
         _ => unreachable!(),
@@ -197,11 +377,7 @@ pub async unsafe fn await_future_result(
     let result = import(future, address);
     match result {
         results::BLOCKED => {
-            assert!(!CURRENT.is_null());
-            (*CURRENT).todo += 1;
-            let (tx, rx) = oneshot::channel();
-            CALLS.insert(future as _, tx);
-            let v = rx.await.unwrap();
+            let v = register(future as _).await.unwrap();
             v == 1
         }
         results::CLOSED | results::CANCELED => false,
@@ -221,11 +397,7 @@ pub async unsafe fn await_stream_result(
     let result = import(stream, address, count);
     match result {
         results::BLOCKED => {
-            assert!(!CURRENT.is_null());
-            (*CURRENT).todo += 1;
-            let (tx, rx) = oneshot::channel();
-            CALLS.insert(stream as _, tx);
-            let v = rx.await.unwrap();
+            let v = register(stream as _).await.unwrap();
             if let results::CLOSED | results::CANCELED = v {
                 None
             } else {
@@ -237,6 +409,21 @@ pub async unsafe fn await_stream_result(
     }
 }
 
+/// Account for the result of a `{stream,future}.cancel-{read,write}` call on
+/// `handle`.
+///
+/// If the cancellation completed immediately the host won't send an event for
+/// the canceled operation, so it's resolved here instead.
+#[doc(hidden)]
+pub unsafe fn finish_cancel(handle: u32, result: u32) {
+    if result != results::BLOCKED {
+        if let Some(pending) = globals().calls.remove(&(handle as i32)) {
+            _ = pending.tx.send(result);
+            (*pending.task).todo -= 1;
+        }
+    }
+}
+
 /// Call the `subtask.drop` canonical built-in function.
 fn subtask_drop(subtask: u32) {
     #[cfg(not(target_arch = "wasm32"))]
@@ -262,6 +449,17 @@ fn subtask_drop(subtask: u32) {
 /// async-lowered import or a stream/future read/write operation.
 #[doc(hidden)]
 pub unsafe fn callback(ctx: *mut u8, event0: i32, event1: i32, event2: i32) -> i32 {
+    let state = ctx as *mut FutureState<'static>;
+    if handle_event(state, event0, event1, event2) || run_timers(state) {
+        drop(Box::from_raw(state));
+        1
+    } else {
+        0
+    }
+}
+
+/// Deliver an event to `state`, returning whether the task is now done.
+unsafe fn handle_event(state: *mut FutureState<'_>, event0: i32, event1: i32, event2: i32) -> bool {
     const _EVENT_CALL_STARTING: i32 = 0;
     const EVENT_CALL_STARTED: i32 = 1;
     const EVENT_CALL_RETURNED: i32 = 2;
@@ -273,14 +471,18 @@ pub unsafe fn callback(ctx: *mut u8, event0: i32, event1: i32, event2: i32) -> i
     const EVENT_FUTURE_WRITE: i32 = 8;
 
     match event0 {
-        EVENT_CALL_STARTED => 0,
+        EVENT_CALL_STARTED => false,
         EVENT_CALL_RETURNED | EVENT_CALL_DONE | EVENT_STREAM_READ | EVENT_STREAM_WRITE
         | EVENT_FUTURE_READ | EVENT_FUTURE_WRITE => {
-            if let Some(call) = CALLS.remove(&event1) {
-                _ = call.send(event2 as _);
+            if let Some(pending) = globals().calls.remove(&event1) {
+                _ = pending.tx.send(event2 as _);
+            }
+            if matches!(event0, EVENT_CALL_RETURNED | EVENT_CALL_DONE) {
+                if let Some((params, layout)) = globals().abandoned.remove(&event1) {
+                    alloc::dealloc(params, layout);
+                }
             }
 
-            let state = ctx as *mut FutureState;
             let done = poll(state).is_ready();
 
             if event0 == EVENT_CALL_DONE {
@@ -298,12 +500,7 @@ pub unsafe fn callback(ctx: *mut u8, event0: i32, event1: i32, event2: i32) -> i
                 (*state).todo -= 1;
             }
 
-            if done && (*state).todo == 0 {
-                drop(Box::from_raw(state));
-                1
-            } else {
-                0
-            }
+            done && (*state).todo == 0
         }
         _ => unreachable!(),
     }
@@ -329,8 +526,7 @@ impl ErrorContext {
     pub fn debug_message(&self) -> String {
         #[cfg(not(target_arch = "wasm32"))]
         {
-            _ = self;
-            unreachable!();
+            native::error_context_debug_message(self.handle)
         }
 
         #[cfg(target_arch = "wasm32")]
@@ -369,7 +565,7 @@ impl Drop for ErrorContext {
     fn drop(&mut self) {
         #[cfg(not(target_arch = "wasm32"))]
         {
-            unreachable!();
+            native::error_context_drop(self.handle);
         }
 
         #[cfg(target_arch = "wasm32")]
@@ -390,20 +586,191 @@ impl Drop for ErrorContext {
 /// task has returned a value.
 ///
 /// The task will remain in a running state until all spawned futures have
-/// completed.
+/// completed. Since the future may outlive the caller it must be `'static`;
+/// use a [`LocalScope`] to run futures which borrow from the caller
+/// concurrently.
 pub fn spawn(future: impl Future<Output = ()> + 'static) {
-    unsafe { SPAWNED.push(Box::pin(future)) }
+    unsafe { globals().spawned.push(Box::pin(future)) }
+}
+
+/// A local combinator running futures which may borrow from their
+/// surroundings concurrently with each other.
+///
+/// Unlike [`spawn`], this isn't registered with the current task: futures are
+/// added with [`LocalScope::spawn`] and make progress only while
+/// [`LocalScope::run`] is being awaited, which resolves once its own future
+/// and every spawned future have completed. The task doesn't wait for them
+/// otherwise, and any futures still pending when the `LocalScope` is dropped
+/// are dropped along with it.
+///
+/// ```ignore
+/// let mut buf = Vec::new();
+/// let scope = LocalScope::new();
+/// scope.spawn(async { buf.extend(read_chunk().await) });
+/// scope.run(async { write_log("reading").await }).await;
+/// ```
+pub struct LocalScope<'a> {
+    running: RefCell<FuturesUnordered<BoxFuture<'a>>>,
+    spawned: RefCell<Vec<BoxFuture<'a>>>,
+}
+
+impl<'a> LocalScope<'a> {
+    /// Create an empty scope.
+    pub fn new() -> Self {
+        Self {
+            running: RefCell::new(FuturesUnordered::new()),
+            spawned: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Add a future to this scope.
+    ///
+    /// This may be called from within futures already running in the scope.
+    pub fn spawn(&self, future: impl Future<Output = ()> + 'a) {
+        self.spawned.borrow_mut().push(Box::pin(future));
+    }
+
+    /// Run `future` alongside every future spawned into this scope, returning
+    /// its result once all of them have completed.
+    pub async fn run<T>(&self, future: impl Future<Output = T>) -> T {
+        let mut future = std::pin::pin!(future);
+        let mut result = None;
+        futures::future::poll_fn(|cx| {
+            if result.is_none() {
+                if let Poll::Ready(v) = future.as_mut().poll(cx) {
+                    result = Some(v);
+                }
+            }
+            loop {
+                let mut running = self.running.borrow_mut();
+                running.extend(self.spawned.borrow_mut().drain(..));
+                let poll = running.poll_next_unpin(cx);
+                drop(running);
+                if !self.spawned.borrow().is_empty() {
+                    continue;
+                }
+                match poll {
+                    Poll::Ready(Some(())) => {}
+                    Poll::Ready(None) if result.is_some() => break Poll::Ready(()),
+                    Poll::Ready(None) | Poll::Pending => break Poll::Pending,
+                }
+            }
+        })
+        .await;
+        result.unwrap()
+    }
+}
+
+impl Default for LocalScope<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-fn task_wait(state: &mut FutureState) {
+impl Debug for LocalScope<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalScope")
+            .field("running", &self.running.borrow().len())
+            .finish()
+    }
+}
+
+/// Run two futures concurrently, returning the output of whichever completes
+/// first.
+///
+/// The other future is dropped, which cancels it: pending stream and future
+/// reads and writes are canceled, and pending calls to async-lowered imports
+/// are abandoned. The canonical ABI targeted here has no way to cancel a
+/// subtask, so the host still runs abandoned calls to completion, their
+/// results are discarded, and the current task isn't considered done until
+/// they have finished.
+pub async fn select<A: Future, B: Future>(a: A, b: B) -> Either<A::Output, B::Output> {
+    match futures::future::select(std::pin::pin!(a), std::pin::pin!(b)).await {
+        Either::Left((a, _)) => Either::Left(a),
+        Either::Right((b, _)) => Either::Right(b),
+    }
+}
+
+/// Run two futures concurrently, returning both outputs once both complete.
+pub async fn join<A: Future, B: Future>(a: A, b: B) -> (A::Output, B::Output) {
+    futures::future::join(a, b).await
+}
+
+#[cfg(any(not(target_arch = "wasm32"), feature = "wasi-clocks"))]
+/// Returns the earliest deadline of any pending timer.
+fn next_timer() -> Option<u64> {
+    unsafe {
+        globals()
+            .timers
+            .keys()
+            .next()
+            .map(|(deadline, _)| *deadline)
+    }
+}
+
+/// How long to block for a timer at a time while import calls or stream/future
+/// operations are in flight, in nanoseconds, so that their events are still
+/// noticed promptly.
+#[cfg(all(target_arch = "wasm32", feature = "wasi-clocks"))]
+const TIMER_SLICE: u64 = 1_000_000;
+
+/// Makes progress on the timers of `state`, returning `None` if it has none
+/// and otherwise whether the task is now done.
+///
+/// The host knows nothing about timers, so they're serviced here rather than
+/// by waiting for an event: an event which is already available is delivered,
+/// otherwise this blocks until the next deadline, or for at most
+/// [`TIMER_SLICE`] while other operations are in flight.
+#[cfg(all(target_arch = "wasm32", feature = "wasi-clocks"))]
+unsafe fn service_timers(state: *mut FutureState<'_>) -> Option<bool> {
+    let deadline = next_timer()?;
+    if (*state).todo > 0 {
+        #[link(wasm_import_module = "$root")]
+        extern "C" {
+            #[link_name = "[task-poll]"]
+            fn task_poll(_: *mut i32) -> i32;
+        }
+        let mut event = [0i32; 3];
+        if task_poll(event.as_mut_ptr()) != 0 {
+            return Some(handle_event(state, event[0], event[1], event[2]));
+        }
+        time::block_until(deadline.min(time::now().saturating_add(TIMER_SLICE)));
+    } else {
+        time::block_until(deadline);
+    }
+    Some(poll(state).is_ready() && (*state).todo == 0)
+}
+
+/// Services the timers of an async-lifted export's task until it's done or
+/// none are left, returning whether it's done.
+///
+/// Without this a task waiting only on timers would never hear from the host
+/// again.
+unsafe fn run_timers(state: *mut FutureState<'_>) -> bool {
+    #[cfg(all(target_arch = "wasm32", feature = "wasi-clocks"))]
+    while let Some(done) = service_timers(state) {
+        if done {
+            return true;
+        }
+    }
+    _ = state;
+    false
+}
+
+fn task_wait(state: &mut FutureState<'_>) {
     #[cfg(not(target_arch = "wasm32"))]
     {
         _ = state;
-        unreachable!();
+        native::wait(next_timer());
     }
 
     #[cfg(target_arch = "wasm32")]
     {
+        #[cfg(feature = "wasi-clocks")]
+        if unsafe { service_timers(state) }.is_some() {
+            return;
+        }
+
         #[link(wasm_import_module = "$root")]
         extern "C" {
             #[link_name = "[task-wait]"]
@@ -412,7 +779,7 @@ fn task_wait(state: &mut FutureState) {
         let mut payload = [0i32; 2];
         unsafe {
             let event0 = wait(payload.as_mut_ptr());
-            callback(state as *mut _ as _, event0, payload[0], payload[1]);
+            handle_event(state, event0, payload[0], payload[1]);
         }
     }
 }
@@ -420,22 +787,26 @@ fn task_wait(state: &mut FutureState) {
 /// Run the specified future to completion, returning the result.
 ///
 /// This uses `task.wait` to poll for progress on any in-progress calls to
-/// async-lowered imports as necessary.
-// TODO: refactor so `'static` bounds aren't necessary
-pub fn block_on<T: 'static>(future: impl Future<Output = T> + 'static) -> T {
+/// async-lowered imports as necessary. Off `wasm32` the [`native`] event loop
+/// is used instead.
+///
+/// The future may borrow from the caller: it, and anything passed to [`spawn`]
+/// while it runs, is driven to completion before this returns. This also waits
+/// for the host to finish any import calls abandoned along the way.
+pub fn block_on<T>(future: impl Future<Output = T>) -> T {
     let (tx, mut rx) = oneshot::channel();
     let state = &mut FutureState {
         todo: 0,
         tasks: Some(
-            [Box::pin(future.map(move |v| drop(tx.send(v)))) as BoxFuture]
+            [Box::pin(future.map(move |v| drop(tx.send(v)))) as BoxFuture<'_>]
                 .into_iter()
                 .collect(),
         ),
     };
     loop {
         match unsafe { poll(state) } {
-            Poll::Ready(()) => break rx.try_recv().unwrap().unwrap(),
-            Poll::Pending => task_wait(state),
+            Poll::Ready(()) if state.todo == 0 => break rx.try_recv().unwrap().unwrap(),
+            _ => task_wait(state),
         }
     }
 }
@@ -446,10 +817,9 @@ pub fn block_on<T: 'static>(future: impl Future<Output = T> + 'static) -> T {
 /// progress.  It's a good idea to call this inside a busy loop which does not
 /// otherwise ever yield control the the host.
 pub fn task_yield() {
+    // There's no host to yield to natively, so this is a no-op.
     #[cfg(not(target_arch = "wasm32"))]
-    {
-        unreachable!();
-    }
+    {}
 
     #[cfg(target_arch = "wasm32")]
     {
@@ -472,8 +842,7 @@ pub fn task_yield() {
 pub fn task_backpressure(enabled: bool) {
     #[cfg(not(target_arch = "wasm32"))]
     {
-        _ = enabled;
-        unreachable!();
+        native::set_backpressure(enabled);
     }
 
     #[cfg(target_arch = "wasm32")]
@@ -493,8 +862,7 @@ pub fn task_backpressure(enabled: bool) {
 pub fn error_context_new(debug_message: &str) -> ErrorContext {
     #[cfg(not(target_arch = "wasm32"))]
     {
-        _ = debug_message;
-        unreachable!();
+        ErrorContext::from_handle(native::error_context_new(debug_message))
     }
 
     #[cfg(target_arch = "wasm32")]
@@ -27,6 +27,22 @@ pub struct FutureVtable<T> {
     pub close_readable: fn(future: u32),
 }
 
+impl<T> FutureVtable<T> {
+    /// Vtable for futures whose payload type appears in no function signature.
+    ///
+    /// Such futures can't be passed to another component, so only their
+    /// local, in-memory representation is ever used.
+    #[doc(hidden)]
+    pub const LOCAL: Self = Self {
+        write: |_, _| super::local_only(),
+        read: |_| super::local_only(),
+        cancel_write: |_| super::local_only(),
+        cancel_read: |_| super::local_only(),
+        close_writable: |_| {},
+        close_readable: |_| {},
+    };
+}
+
 /// Represents the writable end of a Component Model `future`.
 pub struct FutureWriter<T: 'static> {
     handle: u32,
@@ -116,7 +132,9 @@ impl<T> FutureWriter<T> {
                         let mut v = Some(v);
                         Box::pin(future::poll_fn(move |cx| {
                             super::with_entry(handle, |entry| match entry {
-                                Entry::Vacant(_) => unreachable!(),
+                                // The reader took the value and has already
+                                // been dropped.
+                                Entry::Vacant(_) => Poll::Ready(()),
                                 Entry::Occupied(mut entry) => match entry.get() {
                                     Handle::LocalOpen => {
                                         entry.insert(Handle::LocalReady(
@@ -127,9 +145,18 @@ impl<T> FutureWriter<T> {
                                     }
                                     Handle::LocalReady(..) => Poll::Pending,
                                     Handle::LocalClosed => Poll::Ready(()),
-                                    Handle::LocalWaiting(_) | Handle::Read | Handle::Write => {
-                                        unreachable!()
+                                    Handle::LocalWaiting(_) => {
+                                        // The reader started waiting before
+                                        // this write was first polled.
+                                        let Handle::LocalWaiting(tx) =
+                                            entry.insert(Handle::LocalClosed)
+                                        else {
+                                            unreachable!()
+                                        };
+                                        _ = tx.send(Box::new(v.take().unwrap()));
+                                        Poll::Ready(())
                                     }
+                                    Handle::Read | Handle::Write => unreachable!(),
                                 },
                             })
                         })) as Pin<Box<dyn Future<Output = _>>>
@@ -153,7 +180,8 @@ impl<T> FutureWriter<T> {
 impl<T> Drop for FutureWriter<T> {
     fn drop(&mut self) {
         super::with_entry(self.handle, |entry| match entry {
-            Entry::Vacant(_) => unreachable!(),
+            // Both ends are done with a value passed locally.
+            Entry::Vacant(_) => {}
             Entry::Occupied(mut entry) => match entry.get_mut() {
                 Handle::LocalOpen | Handle::LocalWaiting(_) | Handle::LocalReady(..) => {
                     entry.insert(Handle::LocalClosed);
@@ -337,7 +365,8 @@ impl<T> Drop for FutureReader<T> {
             u32::MAX => {}
             handle => {
                 super::with_entry(handle, |entry| match entry {
-                    Entry::Vacant(_) => unreachable!(),
+                    // Both ends are done with a value passed locally.
+                    Entry::Vacant(_) => {}
                     Entry::Occupied(mut entry) => match entry.get_mut() {
                         Handle::LocalReady(..) => {
                             let Handle::LocalReady(_, waker) = entry.insert(Handle::LocalClosed)
@@ -0,0 +1,115 @@
+//! A simulated canonical ABI used when compiling for targets other than
+//! `wasm32`.
+//!
+//! This makes it possible to run async component code with `cargo test` on
+//! the host: `block_on` and `spawn` are driven by an in-process event loop,
+//! streams and futures are passed around in memory, and `error-context`
+//! values are kept in a local table. Calls to async imports are routed to the
+//! mock traits generated by the `mocks` option.
+//!
+//! Time is simulated as well: the clock used by [`sleep`](super::sleep) only
+//! moves forward when every task is blocked, at which point it jumps straight
+//! to the next timer's deadline. Tests using timers therefore run instantly
+//! and deterministically.
+//!
+//! All state is per-thread, so tests using this backend may run in parallel.
+
+extern crate std;
+
+use std::borrow::ToOwned;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::string::String;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Wake, Waker};
+
+struct State {
+    next_handle: Cell<u32>,
+    backpressure: Cell<bool>,
+    now: Cell<u64>,
+    error_contexts: RefCell<HashMap<u32, String>>,
+    woken: Arc<Woken>,
+}
+
+std::thread_local! {
+    static STATE: State = State {
+        next_handle: Cell::new(1),
+        backpressure: Cell::new(false),
+        now: Cell::new(0),
+        error_contexts: RefCell::new(HashMap::new()),
+        woken: Arc::new(Woken(AtomicBool::new(false))),
+    };
+}
+
+/// Records whether any task has been woken since the event loop last checked.
+struct Woken(AtomicBool);
+
+impl Wake for Woken {
+    fn wake(self: Arc<Self>) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+pub(super) fn waker() -> Waker {
+    STATE.with(|s| s.woken.clone().into())
+}
+
+/// Stands in for `task.wait`: returns once some task has been woken, advancing
+/// the clock to `next_timer` if nothing else could make progress, or panics if
+/// there's no timer either.
+pub(super) fn wait(next_timer: Option<u64>) {
+    if STATE.with(|s| s.woken.0.swap(false, Ordering::SeqCst)) {
+        return;
+    }
+    match next_timer {
+        Some(deadline) => STATE.with(|s| s.now.set(s.now.get().max(deadline))),
+        None => panic!("deadlock: every task is blocked and nothing is left to wake one up"),
+    }
+}
+
+/// Returns the simulated time, in nanoseconds since the thread's runtime
+/// started.
+pub(super) fn now() -> u64 {
+    STATE.with(|s| s.now.get())
+}
+
+pub(super) fn set_backpressure(enabled: bool) {
+    STATE.with(|s| s.backpressure.set(enabled));
+}
+
+/// Returns whether backpressure was most recently enabled via
+/// [`task_backpressure`](super::task_backpressure).
+pub fn backpressure() -> bool {
+    STATE.with(|s| s.backpressure.get())
+}
+
+/// Allocates a new handle for a stream, future, or `error-context`.
+#[doc(hidden)]
+pub fn new_handle() -> u32 {
+    STATE.with(|s| {
+        let handle = s.next_handle.get();
+        s.next_handle.set(handle + 1);
+        handle
+    })
+}
+
+pub(super) fn error_context_new(debug_message: &str) -> u32 {
+    let handle = new_handle();
+    STATE.with(|s| {
+        s.error_contexts
+            .borrow_mut()
+            .insert(handle, debug_message.to_owned())
+    });
+    handle
+}
+
+pub(super) fn error_context_debug_message(handle: u32) -> String {
+    STATE
+        .with(|s| s.error_contexts.borrow().get(&handle).cloned())
+        .unwrap_or_else(|| panic!("unknown error-context handle {handle}"))
+}
+
+pub(super) fn error_context_drop(handle: u32) {
+    STATE.with(|s| s.error_contexts.borrow_mut().remove(&handle));
+}
@@ -5,6 +5,7 @@ use {
     futures::{
         channel::oneshot,
         future::{self, FutureExt},
+        io::{self, AsyncBufRead, AsyncRead, AsyncWrite},
         sink::Sink,
         stream::Stream,
     },
@@ -18,8 +19,8 @@ use {
         mem::{self, MaybeUninit},
         pin::Pin,
         sync::atomic::{AtomicU32, Ordering::Relaxed},
-        task::{Context, Poll},
-        vec::Vec,
+        task::{ready, Context, Poll},
+        vec::{self, Vec},
     },
 };
 
@@ -27,6 +28,30 @@ fn ceiling(x: usize, y: usize) -> usize {
     (x / y) + if x % y == 0 { 0 } else { 1 }
 }
 
+/// Drives `op`, a read or write lowering values straight from or into a
+/// buffer which is only borrowed for the current poll, to completion before
+/// returning, cancelling it whenever it would block.
+///
+/// Returns the result of `op` along with whether it had to be cancelled.
+fn poll_now<R>(
+    cx: &mut Context<'_>,
+    mut op: Pin<Box<dyn Future<Output = R> + '_>>,
+    cancel: impl Fn(),
+) -> (R, bool) {
+    let mut canceled = false;
+    loop {
+        match op.as_mut().poll(cx) {
+            Poll::Ready(result) => return (result, canceled),
+            // Cancellation completes synchronously, reporting how many values
+            // were transferred so far to `op`.
+            Poll::Pending => {
+                cancel();
+                canceled = true;
+            }
+        }
+    }
+}
+
 #[doc(hidden)]
 pub struct StreamVtable<T> {
     pub write: fn(future: u32, values: &[T]) -> Pin<Box<dyn Future<Output = usize> + '_>>,
@@ -40,6 +65,22 @@ pub struct StreamVtable<T> {
     pub close_readable: fn(future: u32),
 }
 
+impl<T> StreamVtable<T> {
+    /// Vtable for streams whose payload type appears in no function signature.
+    ///
+    /// Such streams can't be passed to another component, so only their
+    /// local, in-memory representation is ever used.
+    #[doc(hidden)]
+    pub const LOCAL: Self = Self {
+        write: |_, _| super::local_only(),
+        read: |_, _| super::local_only(),
+        cancel_write: |_| super::local_only(),
+        cancel_read: |_| super::local_only(),
+        close_writable: |_| {},
+        close_readable: |_| {},
+    };
+}
+
 struct CancelWriteOnDrop<T: 'static> {
     handle: Option<u32>,
     vtable: &'static StreamVtable<T>,
@@ -68,8 +109,11 @@ impl<T> Drop for CancelWriteOnDrop<T> {
 /// Represents the writable end of a Component Model `stream`.
 pub struct StreamWriter<T: 'static> {
     handle: u32,
-    future: Option<Pin<Box<dyn Future<Output = ()> + 'static>>>,
+    /// The pending write, resolving to whether the readable end is still open.
+    future: Option<Pin<Box<dyn Future<Output = bool> + 'static>>>,
     vtable: &'static StreamVtable<T>,
+    /// Whether the readable end was found to be closed.
+    closed: bool,
 }
 
 impl<T> StreamWriter<T> {
@@ -79,6 +123,7 @@ impl<T> StreamWriter<T> {
             handle,
             future: None,
             vtable,
+            closed: false,
         }
     }
 
@@ -89,6 +134,140 @@ impl<T> StreamWriter<T> {
         assert!(self.future.is_some());
         self.future = None;
     }
+
+    /// Write `values` to this `stream`, returning how many of them were
+    /// written before the readable end was closed.
+    ///
+    /// Unlike sending a `Vec` through the [`Sink`] implementation, values are
+    /// lowered straight from the slice when the readable end belongs to
+    /// another component. Otherwise they're cloned and handed over in memory.
+    pub async fn write(&mut self, values: &[T]) -> usize
+    where
+        T: Clone,
+    {
+        future::poll_fn(|cx| Pin::new(&mut *self).poll_ready(cx))
+            .await
+            .unwrap();
+        if self.is_remote() {
+            self.write_remote(values).await
+        } else {
+            self.write_local(values.to_vec()).await
+        }
+    }
+
+    fn is_remote(&self) -> bool {
+        super::with_entry(self.handle, |entry| match entry {
+            Entry::Vacant(_) => unreachable!(),
+            Entry::Occupied(entry) => matches!(entry.get(), Handle::Write),
+        })
+    }
+
+    /// Whether the readable end is known to be closed, in which case nothing
+    /// more can be written.
+    fn is_closed(&self) -> bool {
+        self.closed
+            || super::with_entry(self.handle, |entry| match entry {
+                Entry::Vacant(_) => unreachable!(),
+                Entry::Occupied(entry) => matches!(entry.get(), Handle::LocalClosed),
+            })
+    }
+
+    async fn write_remote(&mut self, values: &[T]) -> usize {
+        if self.closed {
+            return 0;
+        }
+        let mut cancel_on_drop = CancelWriteOnDrop::<T> {
+            handle: Some(self.handle),
+            vtable: self.vtable,
+        };
+        let count = (self.vtable.write)(self.handle, values).await;
+        cancel_on_drop.handle = None;
+        self.closed = count < values.len();
+        count
+    }
+
+    async fn write_local(&mut self, values: Vec<T>) -> usize {
+        let count = values.len();
+        if self.is_closed() {
+            return 0;
+        }
+        Pin::new(&mut *self).start_send(values).unwrap();
+        future::poll_fn(|cx| Pin::new(&mut *self).poll_ready(cx))
+            .await
+            .unwrap();
+        if self.closed {
+            0
+        } else {
+            count
+        }
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl StreamWriter<u8> {
+    /// Write `bytes` to this `stream`, returning how many of them were written
+    /// before the readable end was closed.
+    ///
+    /// This doesn't copy `bytes` if it's uniquely owned and the readable end
+    /// is in this component.
+    pub async fn write_bytes(&mut self, bytes: bytes::Bytes) -> usize {
+        future::poll_fn(|cx| Pin::new(&mut *self).poll_ready(cx))
+            .await
+            .unwrap();
+        if self.is_remote() {
+            self.write_remote(&bytes).await
+        } else {
+            self.write_local(bytes.into()).await
+        }
+    }
+}
+
+/// When the readable end belongs to another component and is ready to receive
+/// bytes, they're lowered straight from the buffer passed to each write.
+/// Otherwise the buffer is copied into a chunk which is sent in the
+/// background, and completion is reported by the next write, flush, or close.
+///
+/// Writing after the readable end was closed fails with
+/// [`io::ErrorKind::BrokenPipe`].
+impl AsyncWrite for StreamWriter<u8> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.as_mut().poll_ready(cx)).unwrap();
+        if self.is_closed() {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        if self.is_remote() {
+            let (handle, vtable) = (self.handle, self.vtable);
+            let (count, canceled) = poll_now(cx, (vtable.write)(handle, buf), || {
+                (vtable.cancel_write)(handle)
+            });
+            if count > 0 {
+                return Poll::Ready(Ok(count));
+            }
+            if !canceled {
+                self.closed = true;
+                return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+            }
+            // The reader isn't ready for more bytes yet, so fall back to
+            // sending a copy which stays alive until it is.
+        }
+        self.start_send(buf.to_vec()).unwrap();
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_ready(cx).map(|_| Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_ready(cx).map(|_| Ok(()))
+    }
 }
 
 impl<T> fmt::Debug for StreamWriter<T> {
@@ -107,8 +286,9 @@ impl<T> Sink<Vec<T>> for StreamWriter<T> {
 
         if let Some(future) = &mut me.future {
             match future.as_mut().poll(cx) {
-                Poll::Ready(_) => {
+                Poll::Ready(open) => {
                     me.future = None;
+                    me.closed |= !open;
                     Poll::Ready(Ok(()))
                 }
                 Poll::Pending => Poll::Pending,
@@ -143,17 +323,31 @@ impl<T> Sink<Vec<T>> for StreamWriter<T> {
                                         Poll::Pending
                                     } else {
                                         cancel_on_drop.take().unwrap().handle = None;
-                                        Poll::Ready(())
+                                        Poll::Ready(true)
                                     }
                                 }
                                 Handle::LocalReady(..) => Poll::Pending,
                                 Handle::LocalClosed => {
                                     cancel_on_drop.take().unwrap().handle = None;
-                                    Poll::Ready(())
+                                    Poll::Ready(false)
                                 }
-                                Handle::LocalWaiting(_) | Handle::Read | Handle::Write => {
-                                    unreachable!()
+                                Handle::LocalWaiting(_) => {
+                                    // The reader started waiting before this
+                                    // write was first polled, so hand the item
+                                    // over directly. If the item was already
+                                    // delivered this is a new read to leave be.
+                                    if let Some(item) = item.take() {
+                                        let Handle::LocalWaiting(tx) =
+                                            entry.insert(Handle::LocalOpen)
+                                        else {
+                                            unreachable!()
+                                        };
+                                        _ = tx.send(Box::new(item));
+                                    }
+                                    cancel_on_drop.take().unwrap().handle = None;
+                                    Poll::Ready(true)
                                 }
+                                Handle::Read | Handle::Write => unreachable!(),
                             },
                         })
                     })));
@@ -164,7 +358,7 @@ impl<T> Sink<Vec<T>> for StreamWriter<T> {
                     };
                     _ = tx.send(Box::new(item));
                 }
-                Handle::LocalClosed => (),
+                Handle::LocalClosed => self.get_mut().closed = true,
                 Handle::Read | Handle::LocalReady(..) => unreachable!(),
                 Handle::Write => {
                     let handle = self.handle;
@@ -174,9 +368,10 @@ impl<T> Sink<Vec<T>> for StreamWriter<T> {
                         vtable,
                     };
                     self.get_mut().future = Some(Box::pin(async move {
-                        (vtable.write)(handle, &item).await;
+                        let count = (vtable.write)(handle, &item).await;
                         cancel_on_drop.handle = None;
                         drop(cancel_on_drop);
+                        count == item.len()
                     }));
                 }
             },
@@ -243,6 +438,8 @@ pub struct StreamReader<T: 'static> {
     handle: AtomicU32,
     future: Option<Pin<Box<dyn Future<Output = Option<Vec<T>>> + 'static>>>,
     vtable: &'static StreamVtable<T>,
+    /// Values read but not yet consumed by `read` or the `AsyncRead` impl.
+    buffered: Option<Box<vec::IntoIter<T>>>,
 }
 
 impl<T> StreamReader<T> {
@@ -270,6 +467,7 @@ impl<T> StreamReader<T> {
             handle: AtomicU32::new(handle),
             future: None,
             vtable,
+            buffered: None,
         }
     }
 
@@ -297,6 +495,7 @@ impl<T> StreamReader<T> {
             handle: AtomicU32::new(handle),
             future: None,
             vtable,
+            buffered: None,
         }
     }
 
@@ -320,12 +519,132 @@ impl<T> StreamReader<T> {
     }
 }
 
+impl<T> StreamReader<T> {
+    /// Read values from this `stream` into `buf`, returning how many were read,
+    /// or `None` if the writable end was closed.
+    ///
+    /// When the writable end belongs to another component, values are lifted
+    /// straight into `buf` rather than into an intermediate `Vec`.
+    pub async fn read(&mut self, buf: &mut [MaybeUninit<T>]) -> Option<usize> {
+        if buf.is_empty() {
+            return Some(0);
+        }
+        loop {
+            if let Some(buffered) = self.buffered.as_mut().filter(|b| b.len() > 0) {
+                let count = buf.len().min(buffered.len());
+                for (dst, value) in buf.iter_mut().zip(buffered.by_ref()) {
+                    dst.write(value);
+                }
+                return Some(count);
+            }
+
+            let handle = self.handle.load(Relaxed);
+            let remote = self.future.is_none()
+                && super::with_entry(handle, |entry| match entry {
+                    Entry::Vacant(_) => unreachable!(),
+                    Entry::Occupied(entry) => matches!(entry.get(), Handle::Read),
+                });
+            if remote {
+                let mut cancel_on_drop = CancelReadOnDrop::<T> {
+                    handle: Some(handle),
+                    vtable: self.vtable,
+                };
+                let count = (self.vtable.read)(handle, buf).await;
+                cancel_on_drop.handle = None;
+                return count;
+            }
+
+            let chunk = future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await?;
+            self.buffered = Some(Box::new(chunk.into_iter()));
+        }
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl StreamReader<u8> {
+    /// Read the next chunk of bytes from this `stream`, or `None` if the
+    /// writable end was closed.
+    pub async fn read_bytes(&mut self) -> Option<bytes::Bytes> {
+        future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx))
+            .await
+            .map(bytes::Bytes::from)
+    }
+}
+
+/// When the writable end belongs to another component and has bytes ready,
+/// they're lifted straight into the buffer passed to each read. Otherwise a
+/// chunk is read in the background and copied out once it arrives.
+impl AsyncRead for StreamReader<u8> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        let idle = self.future.is_none() && !matches!(&self.buffered, Some(b) if b.len() > 0);
+        let handle = self.handle.load(Relaxed);
+        if idle
+            && super::with_entry(handle, |entry| match entry {
+                Entry::Vacant(_) => unreachable!(),
+                Entry::Occupied(entry) => matches!(entry.get(), Handle::Read),
+            })
+        {
+            let vtable = self.vtable;
+            // SAFETY: `MaybeUninit<u8>` has the same layout as `u8` and only
+            // initialized bytes are written through it.
+            let uninit = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
+            let (count, canceled) = poll_now(cx, (vtable.read)(handle, uninit), || {
+                (vtable.cancel_read)(handle)
+            });
+            match count {
+                Some(count) if count > 0 => return Poll::Ready(Ok(count)),
+                None if !canceled => return Poll::Ready(Ok(0)),
+                // Nothing is available yet, so wait for it in the background.
+                _ => {}
+            }
+        }
+
+        let available = ready!(self.as_mut().poll_fill_buf(cx))?;
+        let count = buf.len().min(available.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.consume(count);
+        Poll::Ready(Ok(count))
+    }
+}
+
+impl AsyncBufRead for StreamReader<u8> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let me = self.get_mut();
+        while !matches!(&me.buffered, Some(b) if b.len() > 0) {
+            match ready!(Pin::new(&mut *me).poll_next(cx)) {
+                Some(chunk) => me.buffered = Some(Box::new(chunk.into_iter())),
+                None => return Poll::Ready(Ok(&[])),
+            }
+        }
+        Poll::Ready(Ok(me.buffered.as_ref().unwrap().as_slice()))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        if let Some(buffered) = &mut self.get_mut().buffered {
+            buffered.by_ref().take(amt).for_each(drop);
+        }
+    }
+}
+
 impl<T> Stream for StreamReader<T> {
     type Item = Vec<T>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
         let me = self.get_mut();
 
+        if let Some(buffered) = me.buffered.take() {
+            if buffered.len() > 0 {
+                return Poll::Ready(Some(buffered.collect()));
+            }
+        }
+
         if me.future.is_none() {
             me.future = Some(super::with_entry(
                 me.handle.load(Relaxed),
@@ -0,0 +1,163 @@
+//! Timers, built on `wasi:clocks/monotonic-clock` when targeting `wasm32`
+//! (which requires the `wasi-clocks` feature) and on the simulated clock of the
+//! [`native`](super::native) backend otherwise.
+//!
+//! On `wasm32` this imports `now` and `subscribe-instant` from
+//! `wasi:clocks/monotonic-clock@0.2.0` along with `[method]pollable.block` and
+//! `[resource-drop]pollable` from `wasi:io/poll@0.2.0`, so the component must
+//! be instantiated with those interfaces available.
+//!
+//! Expired timers are woken whenever a task is polled. While a task has
+//! pending timers the runtime services them itself, both in [`block_on`] and
+//! in async-lifted exports, instead of returning to the host: events which
+//! are already available are delivered, and otherwise it sleeps until the
+//! next deadline, waking up periodically to check for events while other
+//! operations are in flight. An export whose task has pending timers
+//! therefore doesn't return control to the host until they've fired or been
+//! dropped.
+//!
+//! [`block_on`]: super::block_on
+
+extern crate std;
+
+use {
+    super::globals,
+    futures::future::Either,
+    std::{
+        fmt::{self, Display},
+        future::Future,
+        pin::Pin,
+        sync::atomic::{AtomicU64, Ordering::Relaxed},
+        task::{Context, Poll},
+        time::Duration,
+    },
+};
+
+/// Returns the current time in nanoseconds.
+pub(super) fn now() -> u64 {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        super::native::now()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        #[link(wasm_import_module = "wasi:clocks/monotonic-clock@0.2.0")]
+        extern "C" {
+            #[link_name = "now"]
+            fn now() -> i64;
+        }
+        unsafe { now() as u64 }
+    }
+}
+
+/// Block until the monotonic clock reaches `deadline`.
+#[cfg(target_arch = "wasm32")]
+pub(super) fn block_until(deadline: u64) {
+    #[link(wasm_import_module = "wasi:clocks/monotonic-clock@0.2.0")]
+    extern "C" {
+        #[link_name = "subscribe-instant"]
+        fn subscribe_instant(when: i64) -> i32;
+    }
+    #[link(wasm_import_module = "wasi:io/poll@0.2.0")]
+    extern "C" {
+        #[link_name = "[method]pollable.block"]
+        fn block(pollable: i32);
+        #[link_name = "[resource-drop]pollable"]
+        fn drop_pollable(pollable: i32);
+    }
+    unsafe {
+        let pollable = subscribe_instant(deadline as i64);
+        block(pollable);
+        drop_pollable(pollable);
+    }
+}
+
+/// Wake every timer whose deadline has passed.
+pub(super) fn wake_expired() {
+    let now = now();
+    for (_, waker) in unsafe { globals().timers.range(..=(now, u64::MAX)) } {
+        waker.wake_by_ref();
+    }
+}
+
+/// A future which completes once a given amount of time has passed.
+///
+/// Created by [`sleep`].
+#[derive(Debug)]
+pub struct Sleep {
+    deadline: u64,
+    id: u64,
+    registered: bool,
+}
+
+/// Returns a future which completes once `duration` has passed.
+pub fn sleep(duration: Duration) -> Sleep {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+    let duration = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+    Sleep {
+        deadline: now().saturating_add(duration),
+        id: NEXT_ID.fetch_add(1, Relaxed),
+        registered: false,
+    }
+}
+
+impl Sleep {
+    fn unregister(&mut self) {
+        if self.registered {
+            self.registered = false;
+            unsafe { globals().timers.remove(&(self.deadline, self.id)) };
+        }
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let me = self.get_mut();
+        if now() >= me.deadline {
+            me.unregister();
+            Poll::Ready(())
+        } else {
+            me.registered = true;
+            unsafe {
+                globals()
+                    .timers
+                    .insert((me.deadline, me.id), cx.waker().clone())
+            };
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        self.unregister();
+    }
+}
+
+/// Run `future`, giving up if it doesn't complete within `duration`.
+///
+/// If the time runs out the future is dropped, canceling any work it has in
+/// flight as described for [`select`](super::select). In particular calls to
+/// async-lowered imports are abandoned rather than canceled.
+pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Elapsed> {
+    match super::select(future, sleep(duration)).await {
+        Either::Left(v) => Ok(v),
+        Either::Right(()) => Err(Elapsed),
+    }
+}
+
+/// The error returned by [`timeout`] when the time runs out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "deadline has elapsed")
+    }
+}
+
+impl std::error::Error for Elapsed {}
@@ -674,6 +674,15 @@
 ///     // By default this set is empty.
 ///     additional_derives: [PartialEq, Eq, Hash, Clone],
 ///
+///     // Implement `serde::Serialize` and `serde::Deserialize` for generated
+///     // types, except those containing resources, streams, futures or
+///     // `error-context`s. Records, variants and enums use their WIT names,
+///     // and flags are serialized as a list of the names of set flags. Using
+///     // this requires a dependency on `serde` with the `derive` feature.
+///     //
+///     // By default this is `false`.
+///     serde: false,
+///
 ///     // When generating bindings for interfaces that are not defined in the
 ///     // same package as `world`, this option can be used to either generate
 ///     // those bindings or point to already generated bindings.
@@ -704,6 +713,16 @@
 ///     // to be marked with `generate`.
 ///     generate_all,
 ///
+///     // Renames interfaces, or items within them, in the generated bindings.
+///     // Keys are an interface such as `foo:bar/baz`, an item within an
+///     // interface such as `foo:bar/baz.qux`, or an item defined in the
+///     // world, and values are used verbatim as the new Rust name. A renamed
+///     // interface only changes the name of its own module.
+///     rename: {
+///         "some:package/my-interface": "mine",
+///         "some:package/my-interface.my-type": "Other",
+///     },
+///
 ///     // An optional list of function names to skip generating bindings for.
 ///     // This is only applicable to imports and the name specified is the name
 ///     // of the function.
@@ -806,6 +825,35 @@
 ///     // or return value of a function.
 ///     generate_unused_types: false,
 ///
+///     // Whether to generate `wit_future` and `wit_stream` payload impls for
+///     // every type which can be a payload. By default impls are only
+///     // generated for payload types used by some `future<T>` or `stream<T>`
+///     // in a function signature.
+///     generate_all_payloads: false,
+///
+///     // Dispatch exports through a `Box<dyn Guest>` registered at runtime
+///     // with the generated `set_guest` functions rather than through a type
+///     // passed to `export!`. This is useful when the implementation lives in
+///     // a different crate than the bindings. Async exports aren't supported
+///     // in this mode.
+///     dyn_exports: false,
+///
+///     // When not compiling to wasm32, route calls to imports through
+///     // generated `Mock` traits whose implementation is set per thread with
+///     // `set_mock`. Imported resources are created from a `MockX`
+///     // implementation with `X::from_mock`. This makes it possible to unit
+///     // test code using imports with `cargo test`.
+///     mocks: false,
+///
+///     // Generate bindings for a host embedding components instead of for a
+///     // guest. Imported interfaces get a `Host` trait and a `dispatch`
+///     // function calling it with core wasm values, and exports become
+///     // functions calling into a guest, all working against the traits of
+///     // the generated `host` module. Resources, futures, streams and
+///     // `error-context`s are passed as handles, and async imports run to
+///     // completion before `dispatch` returns.
+///     host: false,
+///
 ///     // A list of "features" which correspond to WIT features to activate
 ///     // when parsing WIT files. This enables `@unstable` annotations showing
 ///     // up and having bindings generated for them.
@@ -834,14 +882,19 @@
 ///     // [async ABI](https://github.com/WebAssembly/component-model/blob/main/design/mvp/Async.md).
 ///     // This may be specified either as a boolean (e.g. `async: true`, meaning
 ///     // all imports and exports should use the async ABI) or as lists of
-///     // specific imports and/or exports as shown here:
+///     // specific imports and/or exports as shown here.
+///     //
+///     // Each entry selects a function (`iface#f`), all of a resource's
+///     // functions (`iface#res`) or just one of them (`iface#res.method`,
+///     // `iface#res.constructor`), or an entire interface (`iface`). Entries
+///     // may also contain `*` wildcards.
 ///     async: {
 ///         imports: [
-///             "wasi:http/types@0.3.0-draft#[static]body.finish",
+///             "wasi:http/types@0.3.0-draft#body.finish",
 ///             "wasi:http/handler@0.3.0-draft#handle",
 ///         ],
 ///         exports: [
-///             "wasi:http/handler@0.3.0-draft#handle",
+///             "wasi:http/handler@0.3.0-draft#*",
 ///         ]
 ///     }
 /// });
@@ -10,7 +10,7 @@ use wit_bindgen_core::{
         Resolve, Result_, SizeAlign, Tuple, Type, TypeDef, TypeDefKind, TypeId, TypeOwner, Variant,
         WorldId, WorldKey,
     },
-    Direction, Files, InterfaceGenerator as _, Ns, Source, WorldGenerator,
+    Case, Direction, Files, InterfaceGenerator as _, Keywords, Ns, Renames, Source, WorldGenerator,
 };
 
 // Assumptions:
@@ -203,6 +203,14 @@ pub struct Opts {
     /// The package/dir to generate the program entrance
     #[cfg_attr(feature = "clap", arg(long, default_value = "gen"))]
     pub gen_dir: String,
+
+    /// Rename the interface or item `K` to `V` in the generated source code.
+    ///
+    /// `K` is either an interface such as `foo:bar/baz`, an item within an
+    /// interface such as `foo:bar/baz.qux`, or an item defined in the world.
+    /// A renamed interface only changes the last segment of its package.
+    #[cfg_attr(feature = "clap", arg(long, name = "K=V", value_parser = Renames::parse))]
+    pub rename: Vec<(String, String)>,
 }
 
 impl Opts {
@@ -230,6 +238,8 @@ struct Imports {
 pub struct MoonBit {
     opts: Opts,
     name: String,
+    world: Option<WorldId>,
+    renames: Renames,
     needs_cleanup: bool,
     import_interface_fragments: HashMap<String, Vec<InterfaceFragment>>,
     export_interface_fragments: HashMap<String, Vec<InterfaceFragment>>,
@@ -254,6 +264,7 @@ impl MoonBit {
         resolve: &'a Resolve,
         name: &'a str,
         module: &'a str,
+        key: Option<&'a WorldKey>,
         direction: Direction,
     ) -> InterfaceGenerator<'a> {
         InterfaceGenerator {
@@ -264,6 +275,7 @@ impl MoonBit {
             resolve,
             name,
             module,
+            key,
             direction,
         }
     }
@@ -272,7 +284,13 @@ impl MoonBit {
 impl WorldGenerator for MoonBit {
     fn preprocess(&mut self, resolve: &Resolve, world: WorldId) {
         self.name = world_name(resolve, world);
+        self.world = Some(world);
         self.sizes.fill(resolve);
+
+        for (from, to) in self.opts.rename.iter() {
+            self.renames.insert(from, to);
+        }
+        self.renames.warn_unmatched(resolve, world);
     }
 
     fn import_interface(
@@ -282,7 +300,7 @@ impl WorldGenerator for MoonBit {
         id: InterfaceId,
         files: &mut Files,
     ) -> Result<()> {
-        let name = interface_name(resolve, key);
+        let name = interface_name(&self.renames, resolve, key);
         let name = self.interface_ns.tmp(&name);
         self.import_interface_names.insert(id, name.clone());
 
@@ -296,7 +314,7 @@ impl WorldGenerator for MoonBit {
         }
 
         let module = &resolve.name_world_key(key);
-        let mut gen = self.interface(resolve, &name, module, Direction::Import);
+        let mut gen = self.interface(resolve, &name, module, Some(key), Direction::Import);
         gen.types(id);
 
         for (_, func) in resolve.interfaces[id].functions.iter() {
@@ -316,7 +334,7 @@ impl WorldGenerator for MoonBit {
         _files: &mut Files,
     ) {
         let name = world_name(resolve, world);
-        let mut gen = self.interface(resolve, &name, "$root", Direction::Import);
+        let mut gen = self.interface(resolve, &name, "$root", None, Direction::Import);
 
         for (_, func) in funcs {
             gen.import("$root", func);
@@ -332,7 +350,11 @@ impl WorldGenerator for MoonBit {
         id: InterfaceId,
         files: &mut Files,
     ) -> Result<()> {
-        let name = format!("{}.{}", self.opts.gen_dir, interface_name(resolve, key));
+        let name = format!(
+            "{}.{}",
+            self.opts.gen_dir,
+            interface_name(&self.renames, resolve, key)
+        );
         let name = self.interface_ns.tmp(&name);
         self.export_interface_names.insert(id, name.clone());
 
@@ -346,7 +368,7 @@ impl WorldGenerator for MoonBit {
         }
 
         let module = &resolve.name_world_key(key);
-        let mut gen = self.interface(resolve, &name, module, Direction::Export);
+        let mut gen = self.interface(resolve, &name, module, Some(key), Direction::Export);
         gen.types(id);
 
         for (_, func) in resolve.interfaces[id].functions.iter() {
@@ -365,7 +387,7 @@ impl WorldGenerator for MoonBit {
         _files: &mut Files,
     ) -> Result<()> {
         let name = format!("{}.{}", self.opts.gen_dir, world_name(resolve, world));
-        let mut gen = self.interface(resolve, &name, "$root", Direction::Export);
+        let mut gen = self.interface(resolve, &name, "$root", None, Direction::Export);
 
         for (_, func) in funcs {
             gen.export(None, func);
@@ -383,7 +405,7 @@ impl WorldGenerator for MoonBit {
         _files: &mut Files,
     ) {
         let name = world_name(resolve, world);
-        let mut gen = self.interface(resolve, &name, "$root", Direction::Import);
+        let mut gen = self.interface(resolve, &name, "$root", None, Direction::Import);
 
         for (ty_name, ty) in types {
             gen.define_type(ty_name, *ty);
@@ -392,6 +414,24 @@ impl WorldGenerator for MoonBit {
         gen.add_world_fragment();
     }
 
+    fn export_types(
+        &mut self,
+        resolve: &Resolve,
+        world: WorldId,
+        types: &[(&str, TypeId)],
+        _files: &mut Files,
+    ) -> Result<()> {
+        let name = format!("{}.{}", self.opts.gen_dir, world_name(resolve, world));
+        let mut gen = self.interface(resolve, &name, "$root", None, Direction::Export);
+
+        for (ty_name, ty) in types {
+            gen.define_type(ty_name, *ty);
+        }
+
+        gen.add_world_fragment();
+        Ok(())
+    }
+
     fn finish(&mut self, resolve: &Resolve, id: WorldId, files: &mut Files) -> Result<()> {
         let project_name = resolve.worlds[id]
             .package
@@ -559,7 +599,7 @@ impl WorldGenerator for MoonBit {
         let export_dir = self.opts.gen_dir.clone();
 
         // Export project entry point
-        let mut gen = self.interface(resolve, &export_dir.as_str(), "", Direction::Export);
+        let mut gen = self.interface(resolve, &export_dir.as_str(), "", None, Direction::Export);
         let ffi_qualifier = gen.qualify_package(&FFI_DIR.to_string());
 
         let mut body = Source::default();
@@ -668,10 +708,40 @@ struct InterfaceGenerator<'a> {
     // The current interface getting generated
     name: &'a str,
     module: &'a str,
+    key: Option<&'a WorldKey>,
     direction: Direction,
 }
 
 impl InterfaceGenerator<'_> {
+    /// Returns the name of the MoonBit function generated for `func`, without
+    /// the type it belongs to, honoring renames.
+    fn func_ident(&self, func: &Function) -> String {
+        if let Some(rename) = self.gen.renames.item(self.resolve, self.key, &func.name) {
+            return rename.to_string();
+        }
+        match func.kind {
+            FunctionKind::Freestanding => func.name.to_moonbit_ident(),
+            FunctionKind::Constructor(_) => {
+                func.name.replace("[constructor]", "").to_moonbit_ident()
+            }
+            _ => func.name.split(".").last().unwrap().to_moonbit_ident(),
+        }
+    }
+
+    /// Returns the name of the MoonBit type generated for the named type
+    /// `id`, honoring renames.
+    fn type_ident(&self, id: TypeId) -> String {
+        let world = self.gen.world.unwrap();
+        match self.gen.renames.ty(self.resolve, world, id) {
+            Some(rename) => rename.to_string(),
+            None => self.resolve.types[id]
+                .name
+                .as_ref()
+                .unwrap()
+                .to_moonbit_type_ident(),
+        }
+    }
+
     fn qualify_package(&mut self, name: &String) -> String {
         if name != self.name {
             let imports = self
@@ -763,15 +833,8 @@ impl InterfaceGenerator<'_> {
     }
 
     fn import(&mut self, module: &str, func: &Function) {
-        let mut bindgen = FunctionBindgen::new(
-            self,
-            &func.name,
-            self.name,
-            func.params
-                .iter()
-                .map(|(name, _)| name.to_moonbit_ident())
-                .collect(),
-        );
+        let mut bindgen =
+            FunctionBindgen::new(self, &func.name, self.name, param_names(func).into());
 
         abi::call(
             bindgen.gen.resolve,
@@ -853,6 +916,7 @@ impl InterfaceGenerator<'_> {
             self.resolve,
             export_dir.as_str(),
             self.module,
+            self.key,
             Direction::Export,
         );
 
@@ -1028,20 +1092,22 @@ impl InterfaceGenerator<'_> {
                         }
                     }
                     TypeDefKind::Handle(handle) => {
-                        let ty = match handle {
-                            Handle::Own(ty) => ty,
-                            Handle::Borrow(ty) => ty,
+                        let id = match handle {
+                            Handle::Own(id) => id,
+                            Handle::Borrow(id) => id,
                         };
-                        let ty = &self.resolve.types[dealias(self.resolve, *ty)];
-                        if let Some(name) = &ty.name {
-                            format!("{}{}", self.qualifier(ty), name.to_moonbit_type_ident())
+                        let id = dealias(self.resolve, *id);
+                        let ty = &self.resolve.types[id];
+                        if ty.name.is_some() {
+                            format!("{}{}", self.qualifier(ty), self.type_ident(id))
                         } else {
                             unreachable!()
                         }
                     }
                     _ => {
-                        if let Some(name) = &ty.name {
-                            format!("{}{}", self.qualifier(ty), name.to_moonbit_type_ident())
+                        if ty.name.is_some() {
+                            let id = dealias(self.resolve, *id);
+                            format!("{}{}", self.qualifier(ty), self.type_ident(id))
                         } else {
                             unreachable!()
                         }
@@ -1069,13 +1135,7 @@ impl InterfaceGenerator<'_> {
     }
 
     fn sig_string(&mut self, func: &Function, ignore_param: bool) -> String {
-        let name = match func.kind {
-            FunctionKind::Freestanding => func.name.to_moonbit_ident(),
-            FunctionKind::Constructor(_) => {
-                func.name.replace("[constructor]", "").to_moonbit_ident()
-            }
-            _ => func.name.split(".").last().unwrap().to_moonbit_ident(),
-        };
+        let name = self.func_ident(func);
         let type_name = match func.kind {
             FunctionKind::Freestanding => "".into(),
             FunctionKind::Method(ty) | FunctionKind::Constructor(ty) | FunctionKind::Static(ty) => {
@@ -1101,12 +1161,13 @@ impl InterfaceGenerator<'_> {
         let params = func
             .params
             .iter()
-            .map(|(name, ty)| {
+            .zip(param_names(func))
+            .map(|((_, ty), name)| {
                 let ty = self.type_name(ty, true);
                 let name = if ignore_param {
-                    format!("_{}", name.to_moonbit_ident())
+                    format!("_{name}")
                 } else {
-                    name.to_moonbit_ident()
+                    name
                 };
                 format!("{name} : {ty}")
             })
@@ -1122,20 +1183,17 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.resolve
     }
 
-    fn type_record(&mut self, _id: TypeId, name: &str, record: &Record, docs: &Docs) {
+    fn type_record(&mut self, id: TypeId, _name: &str, record: &Record, docs: &Docs) {
         print_docs(&mut self.src, docs);
 
-        let name = name.to_moonbit_type_ident();
+        let name = self.type_ident(id);
 
         let parameters = record
             .fields
             .iter()
-            .map(|field| {
-                format!(
-                    "{} : {}",
-                    field.name.to_moonbit_ident(),
-                    self.type_name(&field.ty, true),
-                )
+            .zip(field_names(record))
+            .map(|(field, field_name)| {
+                format!("{field_name} : {}", self.type_name(&field.ty, true))
             })
             .collect::<Vec<_>>()
             .join("; ");
@@ -1159,10 +1217,10 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         );
     }
 
-    fn type_resource(&mut self, _id: TypeId, name: &str, docs: &Docs) {
+    fn type_resource(&mut self, id: TypeId, name: &str, docs: &Docs) {
         print_docs(&mut self.src, docs);
         let type_name = name;
-        let name = name.to_moonbit_type_ident();
+        let name = self.type_ident(id);
 
         let mut deriviation: Vec<_> = Vec::new();
         if self.gen.opts.derive_show {
@@ -1231,11 +1289,14 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
                 "#,
             );
 
+            // Resources exported from a world are defined outside of the
+            // package holding the stubs.
+            let qualifier = self.qualifier(&self.resolve.types[id]);
             uwrite!(
                 &mut self.stub,
                 r#"
                 /// Destructor of the resource.
-                pub fn {name}::dtor(_self : {name}) -> Unit {{
+                pub fn {qualifier}{name}::dtor(_self : {qualifier}{name}) -> Unit {{
                   abort("todo")
                 }}
                 "#
@@ -1245,9 +1306,13 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
 
             let export_dir = self.gen.opts.gen_dir.clone();
 
-            let mut gen =
-                self.gen
-                    .interface(self.resolve, export_dir.as_str(), "", Direction::Export);
+            let mut gen = self.gen.interface(
+                self.resolve,
+                export_dir.as_str(),
+                "",
+                None,
+                Direction::Export,
+            );
 
             uwrite!(
                 self.ffi,
@@ -1259,16 +1324,18 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
                 gen.qualify_package(&self.name.to_string())
             );
 
-            self.gen
-                .export
-                .insert(func_name, format!("{module}#[dtor]{type_name}"));
+            let export_name = match self.resolve.types[id].owner {
+                TypeOwner::World(_) => format!("[dtor]{type_name}"),
+                _ => format!("{module}#[dtor]{type_name}"),
+            };
+            self.gen.export.insert(func_name, export_name);
         }
     }
 
-    fn type_flags(&mut self, _id: TypeId, name: &str, flags: &Flags, docs: &Docs) {
+    fn type_flags(&mut self, id: TypeId, _name: &str, flags: &Flags, docs: &Docs) {
         print_docs(&mut self.src, docs);
 
-        let name = name.to_moonbit_type_ident();
+        let name = self.type_ident(id);
 
         let ty = match flags.repr() {
             FlagsRepr::U8 => "Byte",
@@ -1277,19 +1344,14 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
             _ => unreachable!(), // https://github.com/WebAssembly/component-model/issues/370
         };
 
-        let cases = flags
-            .flags
-            .iter()
-            .map(|flag| flag.name.to_shouty_snake_case())
-            .collect::<Vec<_>>()
-            .join("; ");
+        let flag_names = constant_names(flags.flags.iter().map(|flag| flag.name.as_str()));
 
-        let map_to_int = flags
-            .flags
+        let cases = flag_names.join("; ");
+
+        let map_to_int = flag_names
             .iter()
             .enumerate()
-            .map(|(i, flag)| {
-                let flag_name = flag.name.to_shouty_snake_case();
+            .map(|(i, flag_name)| {
                 let suffix = if matches!(flags.repr(), FlagsRepr::U32(2)) {
                     "UL"
                 } else {
@@ -1360,16 +1422,16 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         // Not needed
     }
 
-    fn type_variant(&mut self, _id: TypeId, name: &str, variant: &Variant, docs: &Docs) {
+    fn type_variant(&mut self, id: TypeId, _name: &str, variant: &Variant, docs: &Docs) {
         print_docs(&mut self.src, docs);
 
-        let name = name.to_moonbit_type_ident();
+        let name = self.type_ident(id);
 
         let cases = variant
             .cases
             .iter()
-            .map(|case| {
-                let name = case.name.to_upper_camel_case();
+            .zip(case_names(variant))
+            .map(|(case, name)| {
                 if let Some(ty) = case.ty {
                     let ty = self.type_name(&ty, true);
                     format!("{name}({ty})")
@@ -1412,18 +1474,14 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         // Not needed
     }
 
-    fn type_enum(&mut self, _id: TypeId, name: &str, enum_: &Enum, docs: &Docs) {
+    fn type_enum(&mut self, id: TypeId, _name: &str, enum_: &Enum, docs: &Docs) {
         print_docs(&mut self.src, docs);
 
-        let name = name.to_moonbit_type_ident();
+        let name = self.type_ident(id);
+        let case_names = constant_names(enum_.cases.iter().map(|case| case.name.as_str()));
 
         // Type definition
-        let cases = enum_
-            .cases
-            .iter()
-            .map(|case| case.name.to_shouty_snake_case())
-            .collect::<Vec<_>>()
-            .join("; ");
+        let cases = case_names.join("; ");
 
         let mut deriviation: Vec<_> = Vec::new();
         if self.gen.opts.derive_show {
@@ -1449,11 +1507,10 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         );
 
         // Case to integer
-        let cases = enum_
-            .cases
+        let cases = case_names
             .iter()
             .enumerate()
-            .map(|(i, case)| format!("{} => {i}", case.name.to_shouty_snake_case()))
+            .map(|(i, case)| format!("{case} => {i}"))
             .collect::<Vec<_>>()
             .join("\n  ");
 
@@ -1469,11 +1526,10 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         );
 
         // Integer to case
-        let cases = enum_
-            .cases
+        let cases = case_names
             .iter()
             .enumerate()
-            .map(|(i, case)| format!("{i} => {}", case.name.to_shouty_snake_case()))
+            .map(|(i, case)| format!("{i} => {case}"))
             .collect::<Vec<_>>()
             .join("\n  ");
 
@@ -1583,7 +1639,7 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
 
     fn lower_variant(
         &mut self,
-        cases: &[(&str, Option<Type>)],
+        cases: &[(String, Option<Type>)],
         lowered_types: &[WasmType],
         op: &str,
         results: &mut Vec<String>,
@@ -1613,7 +1669,6 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
             .zip(blocks)
             .zip(payloads)
             .map(|(((name, ty), Block { body, results, .. }), payload)| {
-                let name = name.to_upper_camel_case();
                 let assignments = results
                     .iter()
                     .map(|result| format!("{result}"))
@@ -1671,7 +1726,7 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
     fn lift_variant(
         &mut self,
         ty: &Type,
-        cases: &[(&str, Option<Type>)],
+        cases: &[(String, Option<Type>)],
         op: &str,
         results: &mut Vec<String>,
         is_result: bool,
@@ -1696,7 +1751,7 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
                     String::new()
                 };
 
-                let constructor = format!("{ty}::{}", case_name.to_upper_camel_case());
+                let constructor = format!("{ty}::{case_name}");
 
                 if payload.is_empty() && !is_result {
                     format!(
@@ -1915,21 +1970,15 @@ impl Bindgen for FunctionBindgen<'_, '_> {
 
             Instruction::RecordLower { record, .. } => {
                 let op = &operands[0];
-                for field in record.fields.iter() {
-                    results.push(format!("({op}).{}", field.name.to_moonbit_ident()));
+                for name in field_names(record) {
+                    results.push(format!("({op}).{name}"));
                 }
             }
             Instruction::RecordLift { ty, record, .. } => {
                 let ops = operands
                     .iter()
-                    .enumerate()
-                    .map(|(i, op)| {
-                        format!(
-                            "{} : {}",
-                            record.fields[i].name.to_moonbit_ident(),
-                            op.to_string()
-                        )
-                    })
+                    .zip(field_names(record))
+                    .map(|(op, name)| format!("{name} : {op}"))
                     .collect::<Vec<_>>()
                     .join(", ");
 
@@ -1973,10 +2022,9 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 results: lowered_types,
                 ..
             } => self.lower_variant(
-                &variant
-                    .cases
-                    .iter()
-                    .map(|case| (case.name.deref(), case.ty))
+                &case_names(variant)
+                    .into_iter()
+                    .zip(variant.cases.iter().map(|case| case.ty))
                     .collect::<Vec<_>>(),
                 lowered_types,
                 &operands[0],
@@ -1986,10 +2034,9 @@ impl Bindgen for FunctionBindgen<'_, '_> {
 
             Instruction::VariantLift { variant, ty, .. } => self.lift_variant(
                 &Type::Id(*ty),
-                &variant
-                    .cases
-                    .iter()
-                    .map(|case| (case.name.deref(), case.ty))
+                &case_names(variant)
+                    .into_iter()
+                    .zip(variant.cases.iter().map(|case| case.ty))
                     .collect::<Vec<_>>(),
                 &operands[0],
                 results,
@@ -2105,7 +2152,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 result,
                 ..
             } => self.lower_variant(
-                &[("Ok", result.ok), ("Err", result.err)],
+                &[("Ok".into(), result.ok), ("Err".into(), result.err)],
                 lowered_types,
                 &operands[0],
                 results,
@@ -2114,7 +2161,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
 
             Instruction::ResultLift { result, ty } => self.lift_variant(
                 &Type::Id(*ty),
-                &[("Ok", result.ok), ("Err", result.err)],
+                &[("Ok".into(), result.ok), ("Err".into(), result.err)],
                 &operands[0],
                 results,
                 true,
@@ -2364,29 +2411,19 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                     }
                 };
 
+                let func_name = self.gen.func_ident(func);
                 let name = match func.kind {
                     FunctionKind::Freestanding => {
                         format!(
-                            "{}{}",
+                            "{}{func_name}",
                             self.r#gen.qualify_package(&self.func_interface.to_string()),
-                            func.name.to_moonbit_ident()
                         )
                     }
-                    FunctionKind::Constructor(ty) => {
+                    FunctionKind::Constructor(ty)
+                    | FunctionKind::Method(ty)
+                    | FunctionKind::Static(ty) => {
                         let name = self.gen.type_name(&Type::Id(ty), false);
-                        format!(
-                            "{}::{}",
-                            name,
-                            func.name.replace("[constructor]", "").to_moonbit_ident()
-                        )
-                    }
-                    FunctionKind::Method(ty) | FunctionKind::Static(ty) => {
-                        let name = self.gen.type_name(&Type::Id(ty), false);
-                        format!(
-                            "{}::{}",
-                            name,
-                            func.name.split(".").last().unwrap().to_moonbit_ident()
-                        )
+                        format!("{name}::{func_name}")
                     }
                 };
 
@@ -2604,6 +2641,8 @@ impl Bindgen for FunctionBindgen<'_, '_> {
             Instruction::AsyncMalloc { .. }
             | Instruction::AsyncPostCallInterface { .. }
             | Instruction::AsyncCallReturn { .. }
+            | Instruction::AsyncTaskReturn { .. }
+            | Instruction::CallPostReturn { .. }
             | Instruction::FutureLower { .. }
             | Instruction::FutureLift { .. }
             | Instruction::StreamLower { .. }
@@ -2776,7 +2815,7 @@ fn world_name(resolve: &Resolve, world: WorldId) -> String {
     format!("world.{}", resolve.worlds[world].name.to_lower_camel_case())
 }
 
-fn interface_name(resolve: &Resolve, name: &WorldKey) -> String {
+fn interface_name(renames: &Renames, resolve: &Resolve, name: &WorldKey) -> String {
     let pkg = match name {
         WorldKey::Name(_) => None,
         WorldKey::Interface(id) => {
@@ -2785,11 +2824,15 @@ fn interface_name(resolve: &Resolve, name: &WorldKey) -> String {
         }
     };
 
-    let name = match name {
-        WorldKey::Name(name) => name,
-        WorldKey::Interface(id) => resolve.interfaces[*id].name.as_ref().unwrap(),
-    }
-    .to_lower_camel_case();
+    let name = match (renames.interface(resolve, name), name) {
+        (Some(rename), _) => rename.to_string(),
+        (None, WorldKey::Name(name)) => name.to_lower_camel_case(),
+        (None, WorldKey::Interface(id)) => resolve.interfaces[*id]
+            .name
+            .as_ref()
+            .unwrap()
+            .to_lower_camel_case(),
+    };
 
     format!(
         "interface.{}{name}",
@@ -2811,16 +2854,7 @@ trait ToMoonBitIdent: ToOwned {
 
 impl ToMoonBitIdent for str {
     fn to_moonbit_ident(&self) -> String {
-        // Escape MoonBit keywords
-        match self {
-            "continue" | "for" | "match" | "if" | "pub" | "priv" | "readonly" | "break"
-            | "raise" | "try" | "except" | "catch" | "else" | "enum" | "struct" | "type"
-            | "trait" | "return" | "let" | "mut" | "while" | "loop" | "extern" | "with"
-            | "throw" | "init" | "main" | "test" | "in" | "guard" | "typealias" => {
-                format!("{self}_")
-            }
-            _ => self.to_snake_case(),
-        }
+        Keywords::MOONBIT.ident(self, Case::Snake)
     }
 }
 
@@ -2842,6 +2876,48 @@ impl ToMoonBitTypeIdent for str {
     }
 }
 
+/// Returns the names of the fields of `record`, which are kept distinct even
+/// when several WIT names map to the same MoonBit identifier.
+fn field_names(record: &Record) -> Vec<String> {
+    let mut scope = Keywords::MOONBIT.scope();
+    record
+        .fields
+        .iter()
+        .map(|field| scope.ident(&field.name, Case::Snake))
+        .collect()
+}
+
+/// Returns the names of the parameters of `func`, kept distinct like
+/// [`field_names`].
+fn param_names(func: &Function) -> Vec<String> {
+    let mut scope = Keywords::MOONBIT.scope();
+    func.params
+        .iter()
+        .map(|(name, _)| scope.ident(name, Case::Snake))
+        .collect()
+}
+
+/// Returns the names of the constructors of the cases of `variant`, kept
+/// distinct like [`field_names`].
+fn case_names(variant: &Variant) -> Vec<String> {
+    let mut scope = Keywords::MOONBIT.scope();
+    variant
+        .cases
+        .iter()
+        .map(|case| scope.unique(&case.name.to_upper_camel_case()))
+        .collect()
+}
+
+/// Returns the names of the constructors generated for the flags of a WIT
+/// flags or the cases of an enum, kept distinct like [`field_names`].
+fn constant_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut scope = Keywords::MOONBIT.scope();
+    names
+        .into_iter()
+        .map(|name| scope.unique(&name.to_shouty_snake_case()))
+        .collect()
+}
+
 fn generated_preamble(src: &mut Source, version: &str) {
     uwriteln!(src, "// Generated by `wit-bindgen` {version}.")
 }
@@ -9,6 +9,7 @@ macro_rules! codegen_test {
     (resources_with_streams $name:tt $test:tt) => {};
     (resources_with_futures $name:tt $test:tt) => {};
     (error_context $name:tt $test:tt) => {};
+    (async_handles_in_aggregates $name:tt $test:tt) => {};
 
     ($id:ident $name:tt $test:tt) => {
         #[test]
@@ -23,6 +24,7 @@ macro_rules! codegen_test {
                         derive_error: true,
                         ignore_stub: false,
                         gen_dir: "gen".to_string(),
+                        rename: Vec::new(),
                     }
                     .build()
                     .generate(resolve, world, files)
@@ -54,3 +56,31 @@ fn verify(dir: &Path, _name: &str) {
 
     test_helpers::run_command(&mut cmd);
 }
+
+#[test]
+fn world_exported_types() {
+    let (resolve, world) = test_helpers::world_exporting_types();
+    let mut files = Default::default();
+    wit_bindgen_moonbit::Opts {
+        derive_show: false,
+        derive_eq: false,
+        derive_error: false,
+        ignore_stub: false,
+        gen_dir: "gen".to_string(),
+        rename: Vec::new(),
+    }
+    .build()
+    .generate(&resolve, world, &mut files)
+    .unwrap();
+
+    let file = |name: &str| {
+        let (_, src) = files.iter().find(|(n, _)| *n == name).unwrap();
+        String::from_utf8_lossy(src).into_owned()
+    };
+    let types = file("world/exportedTypes/top.mbt");
+    assert!(types.contains("pub struct Point"));
+    assert!(types.contains("\"[export]$root\" \"[resource-new]counter\""));
+    let stub = file("gen/world/exportedTypes/stub.mbt");
+    assert!(stub.contains("pub fn @exportedTypes.Counter::dtor"));
+    assert!(file("gen/moon.pkg.json").contains("wasmExportCounterDtor:[dtor]counter"));
+}
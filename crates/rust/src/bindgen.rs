@@ -1,5 +1,6 @@
-use crate::{int_repr, to_rust_ident, wasm_type, Identifier, InterfaceGenerator, RustFlagsRepr};
-use heck::*;
+use crate::{
+    case_names, field_names, int_repr, to_rust_ident, wasm_type, InterfaceGenerator, RustFlagsRepr,
+};
 use std::fmt::Write as _;
 use std::mem;
 use wit_bindgen_core::abi::{Bindgen, Instruction, LiftLower, WasmType};
@@ -145,8 +146,7 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
         let name = self.typename_lower(id);
         self.push_str(&name);
         self.push_str("{ ");
-        for field in record.fields.iter() {
-            let name = to_rust_ident(&field.name);
+        for name in field_names(record) {
             let arg = format!("{}{}", name, tmp);
             self.push_str(&name);
             self.push_str(":");
@@ -168,8 +168,8 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
     ) {
         let mut result = self.typename_lift(id);
         result.push_str("{\n");
-        for (field, val) in ty.fields.iter().zip(operands) {
-            result.push_str(&to_rust_ident(&field.name));
+        for (field, val) in field_names(ty).iter().zip(operands) {
+            result.push_str(field);
             result.push_str(": ");
             result.push_str(val);
             result.push_str(",\n");
@@ -455,11 +455,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                     let name = self.gen.type_path(dealiased_resource, true);
                     format!("{name}::from_handle({op} as u32)")
                 } else if self.gen.is_exported_resource(*resource) {
-                    let name = resolve.types[*resource]
-                        .name
-                        .as_deref()
-                        .unwrap()
-                        .to_upper_camel_case();
+                    let name = self.gen.type_ident(*resource);
                     format!("{name}Borrow::lift({op} as u32 as usize)")
                 } else {
                     let tmp = format!("handle{}", self.tmp());
@@ -483,14 +479,8 @@ impl Bindgen for FunctionBindgen<'_, '_> {
             Instruction::FutureLift { payload, .. } => {
                 let async_support = self.gen.gen.async_support_path();
                 let op = &operands[0];
-                let name = payload
-                    .as_ref()
-                    .map(|ty| {
-                        self.gen
-                            .type_name_owned_with_id(ty, Identifier::StreamOrFuturePayload)
-                    })
-                    .unwrap_or_else(|| "()".into());
-                let ordinal = self.gen.gen.future_payloads.get_index_of(&name).unwrap();
+                let key = self.gen.payload_key(payload.as_ref());
+                let ordinal = self.gen.gen.future_payloads.get_index_of(&key).unwrap();
                 let path = self.gen.path_to_root();
                 results.push(format!(
                     "{async_support}::FutureReader::from_handle_and_vtable\
@@ -506,14 +496,8 @@ impl Bindgen for FunctionBindgen<'_, '_> {
             Instruction::StreamLift { payload, .. } => {
                 let async_support = self.gen.gen.async_support_path();
                 let op = &operands[0];
-                let name = payload
-                    .as_ref()
-                    .map(|ty| {
-                        self.gen
-                            .type_name_owned_with_id(ty, Identifier::StreamOrFuturePayload)
-                    })
-                    .unwrap_or_else(|| "()".into());
-                let ordinal = self.gen.gen.stream_payloads.get_index_of(&name).unwrap();
+                let key = self.gen.payload_key(payload.as_ref());
+                let ordinal = self.gen.gen.stream_payloads.get_index_of(&key).unwrap();
                 let path = self.gen.path_to_root();
                 results.push(format!(
                     "{async_support}::StreamReader::from_handle_and_vtable\
@@ -571,8 +555,8 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 self.let_results(result_types.len(), results);
                 let op0 = &operands[0];
                 self.push_str(&format!("match {op0} {{\n"));
-                for (case, block) in variant.cases.iter().zip(blocks) {
-                    let case_name = case.name.to_upper_camel_case();
+                let case_names = case_names(variant.cases.iter().map(|c| c.name.as_str()));
+                for ((case, case_name), block) in variant.cases.iter().zip(case_names).zip(blocks) {
                     self.push_str(&format!("{name}::{case_name}"));
                     if case.ty.is_some() {
                         self.push_str(&format!("(e) => {block},\n"));
@@ -602,7 +586,9 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                     name
                 };
                 uwriteln!(self.src, "let v{tmp} = match {op0} {{");
-                for (i, (case, block)) in variant.cases.iter().zip(blocks).enumerate() {
+                let case_names = case_names(variant.cases.iter().map(|c| c.name.as_str()));
+                let cases = variant.cases.iter().zip(case_names).zip(blocks);
+                for (i, ((case, case_name), block)) in cases.enumerate() {
                     if i == variant.cases.len() - 1 {
                         uwriteln!(
                             self.src,
@@ -613,7 +599,6 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                     } else {
                         uwriteln!(self.src, "{i} => {{");
                     }
-                    let case_name = case.name.to_upper_camel_case();
                     if case.ty.is_none() {
                         uwriteln!(self.src, "{name}::{case_name}");
                     } else {
@@ -895,20 +880,46 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                     self.let_results(func.results.len(), results);
                 };
                 let constructor_type = match &func.kind {
-                    FunctionKind::Freestanding => {
-                        self.push_str(&format!("T::{}", to_rust_ident(&func.name)));
+                    // With `dyn_exports` the implementation is a trait object
+                    // registered at runtime, and resource constructors and
+                    // static functions are methods of the `Guest` trait.
+                    _ if self.gen.gen.opts.dyn_exports => {
+                        let callee = match &func.kind {
+                            FunctionKind::Freestanding => {
+                                format!("_guest().{}", self.gen.func_ident(func))
+                            }
+                            FunctionKind::Method(ty) => format!(
+                                "Guest{}::{}",
+                                self.gen.type_ident(*ty),
+                                self.gen.func_ident(func)
+                            ),
+                            FunctionKind::Constructor(ty) => format!(
+                                "_guest().{}",
+                                to_rust_ident(&format!(
+                                    "{}-new",
+                                    resolve.types[*ty].name.as_deref().unwrap()
+                                ))
+                            ),
+                            FunctionKind::Static(ty) => format!(
+                                "_guest().{}",
+                                to_rust_ident(&format!(
+                                    "{}-{}",
+                                    resolve.types[*ty].name.as_deref().unwrap(),
+                                    func.item_name()
+                                ))
+                            ),
+                        };
+                        self.push_str(&callee);
                         None
                     }
-                    FunctionKind::Method(_) | FunctionKind::Static(_) => {
-                        self.push_str(&format!("T::{}", to_rust_ident(func.item_name())));
+                    FunctionKind::Freestanding
+                    | FunctionKind::Method(_)
+                    | FunctionKind::Static(_) => {
+                        self.push_str(&format!("T::{}", self.gen.func_ident(func)));
                         None
                     }
                     FunctionKind::Constructor(ty) => {
-                        let ty = resolve.types[*ty]
-                            .name
-                            .as_deref()
-                            .unwrap()
-                            .to_upper_camel_case();
+                        let ty = self.gen.type_ident(*ty);
                         let call = if self.async_ {
                             let async_support = self.gen.gen.async_support_path();
                             format!("{async_support}::futures::FutureExt::map(T::new")
@@ -1187,6 +1198,10 @@ impl Bindgen for FunctionBindgen<'_, '_> {
 
             Instruction::Malloc { .. } => unimplemented!(),
 
+            Instruction::AsyncTaskReturn { .. } | Instruction::CallPostReturn { .. } => {
+                unreachable!("host-only instruction")
+            }
+
             Instruction::GuestDeallocate { size, align } => {
                 let dealloc = self.gen.path_to_cabi_dealloc();
                 self.push_str(&format!(
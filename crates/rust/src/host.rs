@@ -0,0 +1,1224 @@
+//! Bindings for hosts embedding components, generated when `Opts::host` is
+//! set.
+//!
+//! Rather than calling into the canonical ABI directly these bindings work
+//! against the traits of the generated `host` module, which an embedder
+//! implements on top of its runtime of choice. Imports are implemented with
+//! the `Host` trait of each imported interface and dispatched to from core
+//! wasm values, and exports are called through typed functions lowering
+//! their arguments into the guest's memory.
+
+use crate::{case_names, field_names, int_repr, InterfaceGenerator, Opts};
+use anyhow::{bail, Result};
+use std::fmt::Write as _;
+use std::mem;
+use wit_bindgen_core::abi::{Bindgen, Bitcast, Instruction, WasmType};
+use wit_bindgen_core::{dealias, uwrite, uwriteln, wit_parser::*, Source};
+
+/// Returns an error if `opts` asks for something host bindings can't
+/// represent.
+pub(crate) fn check_opts(opts: &Opts) -> Result<()> {
+    if let crate::Ownership::Borrowing { .. } = opts.ownership {
+        bail!("host bindings don't support `ownership: Borrowing`");
+    }
+    Ok(())
+}
+
+pub(crate) struct HostFunctionBindgen<'a, 'b> {
+    pub gen: &'b mut InterfaceGenerator<'a>,
+    params: Vec<String>,
+    host: String,
+    /// Name of the interface whose exports are called by this function, or
+    /// `None` for functions exported by the world itself.
+    interface: Option<String>,
+    /// Whether this is glue dispatching an import to a `Host` trait, as
+    /// opposed to a function calling an export.
+    import: bool,
+    /// Name of the core wasm function called by `CallWasm`, whose task is
+    /// waited on by `AsyncTaskReturn` for async exports.
+    export: String,
+    pub src: Source,
+    blocks: Vec<String>,
+    block_storage: Vec<Source>,
+    tmp: usize,
+}
+
+impl<'a, 'b> HostFunctionBindgen<'a, 'b> {
+    pub(crate) fn new(
+        gen: &'b mut InterfaceGenerator<'a>,
+        params: Vec<String>,
+        interface: Option<String>,
+        import: bool,
+    ) -> HostFunctionBindgen<'a, 'b> {
+        let host = format!("{}host", gen.path_to_root());
+        HostFunctionBindgen {
+            gen,
+            params,
+            host,
+            interface,
+            import,
+            export: String::new(),
+            src: Default::default(),
+            blocks: Vec::new(),
+            block_storage: Vec::new(),
+            tmp: 0,
+        }
+    }
+
+    fn tmp(&mut self) -> usize {
+        let ret = self.tmp;
+        self.tmp += 1;
+        ret
+    }
+
+    fn push_str(&mut self, s: &str) {
+        self.src.push_str(s);
+    }
+
+    fn let_results(&mut self, amt: usize, results: &mut Vec<String>) {
+        match amt {
+            0 => {}
+            1 => {
+                let tmp = self.tmp();
+                let res = format!("result{tmp}");
+                uwrite!(self.src, "let {res} = ");
+                results.push(res);
+            }
+            n => {
+                let tmp = self.tmp();
+                self.push_str("let (");
+                for i in 0..n {
+                    let arg = format!("result{tmp}_{i}");
+                    uwrite!(self.src, "{arg},");
+                    results.push(arg);
+                }
+                self.push_str(") = ");
+            }
+        }
+    }
+
+    /// Returns a name for `path` which can be used in patterns.
+    fn type_alias(&mut self, path: String) -> String {
+        if path.contains("::") {
+            let tmp = self.tmp();
+            uwriteln!(self.src, "use {path} as V{tmp};");
+            format!("V{tmp}")
+        } else {
+            path
+        }
+    }
+
+    fn load(&mut self, bytes: usize, operands: &[String], offset: i32) -> String {
+        let host = &self.host;
+        format!("{host}::load::<_, {bytes}>(cx, {}, {offset})?", operands[0])
+    }
+
+    fn let_load(&mut self, expr: String, results: &mut Vec<String>) {
+        let tmp = self.tmp();
+        uwriteln!(self.src, "let l{tmp} = {expr};");
+        results.push(format!("l{tmp}"));
+    }
+
+    fn store(&mut self, bytes: String, operands: &[String], offset: i32) {
+        let host = &self.host;
+        uwriteln!(
+            self.src,
+            "{host}::store(cx, {}, {offset}, &{bytes})?;",
+            operands[1]
+        );
+    }
+
+    fn invalid_discriminant(&self) -> String {
+        format!("return Err({}::Error::invalid_discriminant())", self.host)
+    }
+
+    /// Returns the name of the exported resource `id`, qualified with the
+    /// name of its interface like the core wasm functions exported for it.
+    fn resource_name(&self, id: TypeId) -> String {
+        let resolve = self.gen.resolve;
+        let ty = &resolve.types[dealias(resolve, id)];
+        let name = ty.name.as_ref().unwrap();
+        let TypeOwner::Interface(owner) = ty.owner else {
+            return name.clone();
+        };
+        let world = &resolve.worlds[self.gen.gen.world.unwrap()];
+        let key = world
+            .exports
+            .iter()
+            .find_map(|(key, item)| match item {
+                WorldItem::Interface { id, .. } if *id == owner => Some(key),
+                _ => None,
+            })
+            .unwrap();
+        format!("{}#{name}", resolve.name_world_key(key))
+    }
+}
+
+impl Bindgen for HostFunctionBindgen<'_, '_> {
+    type Operand = String;
+
+    fn push_block(&mut self) {
+        let prev_src = mem::take(&mut self.src);
+        self.block_storage.push(prev_src);
+    }
+
+    fn finish_block(&mut self, operands: &mut Vec<String>) {
+        let prev_src = self.block_storage.pop().unwrap();
+        let src = mem::replace(&mut self.src, prev_src);
+        let expr = match operands.len() {
+            0 => "()".to_string(),
+            1 => operands[0].clone(),
+            _ => format!("({})", operands.join(", ")),
+        };
+        if src.is_empty() {
+            self.blocks.push(expr);
+        } else if operands.is_empty() {
+            self.blocks.push(format!("{{\n{}}}", &src[..]));
+        } else {
+            self.blocks.push(format!("{{\n{}\n{}\n}}", &src[..], expr));
+        }
+    }
+
+    fn return_pointer(&mut self, _size: usize, _align: usize) -> String {
+        unreachable!("host bindings never provide a return area")
+    }
+
+    fn sizes(&self) -> &SizeAlign {
+        &self.gen.sizes
+    }
+
+    fn is_list_canonical(&self, _resolve: &Resolve, ty: &Type) -> bool {
+        primitive(ty).is_some()
+    }
+
+    fn emit(
+        &mut self,
+        _resolve: &Resolve,
+        inst: &Instruction<'_>,
+        operands: &mut Vec<String>,
+        results: &mut Vec<String>,
+    ) {
+        let mut top_as = |cvt: &str| {
+            let s = operands.pop().unwrap();
+            results.push(format!("({s}) as {cvt}"));
+        };
+
+        match inst {
+            Instruction::GetArg { nth } => results.push(self.params[*nth].clone()),
+            Instruction::I32Const { val } => results.push(format!("{val}i32")),
+            Instruction::ConstZero { tys } => {
+                for ty in tys.iter() {
+                    results.push(format!("0{}", core_type(*ty)));
+                }
+            }
+            Instruction::Bitcasts { casts } => {
+                for (cast, operand) in casts.iter().zip(operands.drain(..)) {
+                    results.push(bitcast(cast, operand));
+                }
+            }
+
+            Instruction::I32Load { offset }
+            | Instruction::PointerLoad { offset }
+            | Instruction::LengthLoad { offset } => {
+                let load = self.load(4, operands, *offset);
+                self.let_load(format!("i32::from_le_bytes({load})"), results);
+            }
+            Instruction::I32Load8U { offset } => {
+                let load = self.load(1, operands, *offset);
+                self.let_load(format!("i32::from({load}[0])"), results);
+            }
+            Instruction::I32Load8S { offset } => {
+                let load = self.load(1, operands, *offset);
+                self.let_load(format!("i32::from({load}[0] as i8)"), results);
+            }
+            Instruction::I32Load16U { offset } => {
+                let load = self.load(2, operands, *offset);
+                self.let_load(format!("i32::from(u16::from_le_bytes({load}))"), results);
+            }
+            Instruction::I32Load16S { offset } => {
+                let load = self.load(2, operands, *offset);
+                self.let_load(format!("i32::from(i16::from_le_bytes({load}))"), results);
+            }
+            Instruction::I64Load { offset } => {
+                let load = self.load(8, operands, *offset);
+                self.let_load(format!("i64::from_le_bytes({load})"), results);
+            }
+            Instruction::F32Load { offset } => {
+                let load = self.load(4, operands, *offset);
+                self.let_load(format!("f32::from_le_bytes({load})"), results);
+            }
+            Instruction::F64Load { offset } => {
+                let load = self.load(8, operands, *offset);
+                self.let_load(format!("f64::from_le_bytes({load})"), results);
+            }
+
+            Instruction::I32Store { offset }
+            | Instruction::I64Store { offset }
+            | Instruction::F32Store { offset }
+            | Instruction::F64Store { offset }
+            | Instruction::PointerStore { offset }
+            | Instruction::LengthStore { offset } => {
+                let bytes = format!("({}).to_le_bytes()", operands[0]);
+                self.store(bytes, operands, *offset);
+            }
+            Instruction::I32Store8 { offset } => {
+                let bytes = format!("[({}) as u8]", operands[0]);
+                self.store(bytes, operands, *offset);
+            }
+            Instruction::I32Store16 { offset } => {
+                let bytes = format!("(({}) as u16).to_le_bytes()", operands[0]);
+                self.store(bytes, operands, *offset);
+            }
+
+            Instruction::I64FromU64 | Instruction::I64FromS64 => {
+                let s = operands.pop().unwrap();
+                results.push(format!("{}({s})", self.gen.path_to_as_i64()));
+            }
+            Instruction::I32FromChar
+            | Instruction::I32FromU8
+            | Instruction::I32FromS8
+            | Instruction::I32FromU16
+            | Instruction::I32FromS16
+            | Instruction::I32FromU32
+            | Instruction::I32FromS32 => {
+                let s = operands.pop().unwrap();
+                results.push(format!("{}({s})", self.gen.path_to_as_i32()));
+            }
+            Instruction::CoreF32FromF32 => {
+                let s = operands.pop().unwrap();
+                results.push(format!("{}({s})", self.gen.path_to_as_f32()));
+            }
+            Instruction::CoreF64FromF64 => {
+                let s = operands.pop().unwrap();
+                results.push(format!("{}({s})", self.gen.path_to_as_f64()));
+            }
+            Instruction::F32FromCoreF32
+            | Instruction::F64FromCoreF64
+            | Instruction::S32FromI32
+            | Instruction::S64FromI64 => {
+                results.push(operands.pop().unwrap());
+            }
+            Instruction::S8FromI32 => top_as("i8"),
+            Instruction::U8FromI32 => top_as("u8"),
+            Instruction::S16FromI32 => top_as("i16"),
+            Instruction::U16FromI32 => top_as("u16"),
+            Instruction::U32FromI32 => top_as("u32"),
+            Instruction::U64FromI64 => top_as("u64"),
+            Instruction::CharFromI32 => {
+                results.push(format!("{}::char_lift({})?", self.host, operands[0]));
+            }
+            Instruction::I32FromBool => {
+                results.push(format!(
+                    "match {} {{ true => 1i32, false => 0i32 }}",
+                    operands[0]
+                ));
+            }
+            Instruction::BoolFromI32 => {
+                results.push(format!("({}) != 0", operands[0]));
+            }
+
+            Instruction::FlagsLower { flags, .. } => {
+                let tmp = self.tmp();
+                uwriteln!(self.src, "let flags{tmp} = {};", operands[0]);
+                for i in 0..flags.repr().count() {
+                    results.push(format!("(flags{tmp}.bits() >> {}) as i32", i * 32));
+                }
+            }
+            Instruction::FlagsLift { flags, ty, .. } => {
+                let repr = crate::RustFlagsRepr::new(flags);
+                let name = self.gen.type_path(*ty, true);
+                let mut result = format!("{name}::empty()");
+                for (i, op) in operands.iter().enumerate() {
+                    uwrite!(
+                        result,
+                        " | {name}::from_bits_truncate(((({op}) as u32 as {repr}) << {}) as _)",
+                        i * 32
+                    );
+                }
+                results.push(result);
+            }
+
+            Instruction::RecordLower { ty, record, .. } => {
+                let tmp = self.tmp();
+                let name = self.gen.type_path(*ty, true);
+                uwrite!(self.src, "let {name} {{ ");
+                for name in field_names(record) {
+                    let arg = format!("{name}{tmp}");
+                    uwrite!(self.src, "{name}: {arg}, ");
+                    results.push(arg);
+                }
+                uwriteln!(self.src, "}} = {};", operands[0]);
+            }
+            Instruction::RecordLift { ty, record, .. } => {
+                let mut result = self.gen.type_path(*ty, true);
+                result.push_str(" {\n");
+                for (field, val) in field_names(record).iter().zip(operands.iter()) {
+                    uwriteln!(result, "{field}: {val},");
+                }
+                result.push('}');
+                results.push(result);
+            }
+
+            Instruction::TupleLower { tuple, .. } => {
+                let tmp = self.tmp();
+                self.push_str("let (");
+                for i in 0..tuple.types.len() {
+                    let arg = format!("t{tmp}_{i}");
+                    uwrite!(self.src, "{arg}, ");
+                    results.push(arg);
+                }
+                uwriteln!(self.src, ") = {};", operands[0]);
+            }
+            Instruction::TupleLift { .. } => {
+                if operands.len() == 1 {
+                    results.push(format!("({},)", operands[0]));
+                } else {
+                    results.push(format!("({})", operands.join(", ")));
+                }
+            }
+
+            Instruction::VariantPayloadName => results.push("e".to_string()),
+
+            Instruction::VariantLower {
+                variant,
+                results: result_types,
+                ty,
+                ..
+            } => {
+                let blocks = self
+                    .blocks
+                    .drain(self.blocks.len() - variant.cases.len()..)
+                    .collect::<Vec<_>>();
+                let path = self.gen.type_path(*ty, true);
+                let name = self.type_alias(path);
+                self.let_results(result_types.len(), results);
+                uwriteln!(self.src, "match {} {{", operands[0]);
+                let case_names = case_names(variant.cases.iter().map(|c| c.name.as_str()));
+                for ((case, case_name), block) in variant.cases.iter().zip(case_names).zip(blocks) {
+                    if case.ty.is_some() {
+                        uwriteln!(self.src, "{name}::{case_name}(e) => {block},");
+                    } else {
+                        uwriteln!(self.src, "{name}::{case_name} => {{\n{block}\n}}");
+                    }
+                }
+                if results.is_empty() {
+                    self.push_str("}\n");
+                } else {
+                    self.push_str("};\n");
+                }
+            }
+            Instruction::VariantLift { variant, ty, .. } => {
+                let blocks = self
+                    .blocks
+                    .drain(self.blocks.len() - variant.cases.len()..)
+                    .collect::<Vec<_>>();
+                let path = self.gen.type_path(*ty, true);
+                let name = self.type_alias(path);
+                let tmp = self.tmp();
+                uwriteln!(self.src, "let v{tmp} = match {} {{", operands[0]);
+                let case_names = case_names(variant.cases.iter().map(|c| c.name.as_str()));
+                let cases = variant.cases.iter().zip(case_names).zip(blocks);
+                for (i, ((case, case_name), block)) in cases.enumerate() {
+                    if case.ty.is_none() {
+                        uwriteln!(self.src, "{i} => {name}::{case_name},");
+                    } else {
+                        uwriteln!(
+                            self.src,
+                            "{i} => {{
+                                let e{tmp} = {block};
+                                {name}::{case_name}(e{tmp})
+                            }}"
+                        );
+                    }
+                }
+                let invalid = self.invalid_discriminant();
+                uwriteln!(self.src, "_ => {invalid},");
+                uwriteln!(self.src, "}};");
+                results.push(format!("v{tmp}"));
+            }
+
+            Instruction::OptionLower {
+                results: result_types,
+                ..
+            } => {
+                let some = self.blocks.pop().unwrap();
+                let none = self.blocks.pop().unwrap();
+                self.let_results(result_types.len(), results);
+                uwriteln!(
+                    self.src,
+                    "match {} {{
+                        Some(e) => {some},
+                        None => {{\n{none}\n}},
+                    }};",
+                    operands[0]
+                );
+            }
+            Instruction::OptionLift { .. } => {
+                let some = self.blocks.pop().unwrap();
+                let none = self.blocks.pop().unwrap();
+                assert_eq!(none, "()");
+                results.push(format!(
+                    "match {} {{
+                        0 => None,
+                        1 => {{
+                            let e = {some};
+                            Some(e)
+                        }}
+                        _ => {},
+                    }}",
+                    operands[0],
+                    self.invalid_discriminant(),
+                ));
+            }
+
+            Instruction::ResultLower {
+                results: result_types,
+                result,
+                ..
+            } => {
+                let err = self.blocks.pop().unwrap();
+                let ok = self.blocks.pop().unwrap();
+                self.let_results(result_types.len(), results);
+                let ok_binding = if result.ok.is_some() { "e" } else { "_" };
+                let err_binding = if result.err.is_some() { "e" } else { "_" };
+                uwriteln!(
+                    self.src,
+                    "match {} {{
+                        Ok({ok_binding}) => {{ {ok} }},
+                        Err({err_binding}) => {{ {err} }},
+                    }};",
+                    operands[0]
+                );
+            }
+            Instruction::ResultLift { .. } => {
+                let err = self.blocks.pop().unwrap();
+                let ok = self.blocks.pop().unwrap();
+                results.push(format!(
+                    "match {} {{
+                        0 => {{
+                            let e = {ok};
+                            Ok(e)
+                        }}
+                        1 => {{
+                            let e = {err};
+                            Err(e)
+                        }}
+                        _ => {},
+                    }}",
+                    operands[0],
+                    self.invalid_discriminant(),
+                ));
+            }
+
+            Instruction::EnumLower { .. } => {
+                results.push(format!("{}.clone() as i32", operands[0]));
+            }
+            Instruction::EnumLift { enum_, ty, .. } => {
+                let path = self.gen.type_path(*ty, true);
+                let name = self.type_alias(path);
+                let repr = int_repr(enum_.tag());
+                let tmp = self.tmp();
+                uwriteln!(
+                    self.src,
+                    "let e{tmp} = match ({}) as {repr} {{",
+                    operands[0]
+                );
+                let case_names = case_names(enum_.cases.iter().map(|c| c.name.as_str()));
+                for (i, case_name) in case_names.iter().enumerate() {
+                    uwriteln!(self.src, "{i} => {name}::{case_name},");
+                }
+                let invalid = self.invalid_discriminant();
+                uwriteln!(self.src, "_ => {invalid},");
+                uwriteln!(self.src, "}};");
+                results.push(format!("e{tmp}"));
+            }
+
+            Instruction::ListCanonLower { element, .. } => {
+                let tmp = self.tmp();
+                let element = primitive(element).unwrap();
+                uwriteln!(
+                    self.src,
+                    "let (ptr{tmp}, len{tmp}) = {}::store_list::<_, {element}>(cx, &{})?;",
+                    self.host,
+                    operands[0]
+                );
+                results.push(format!("ptr{tmp}"));
+                results.push(format!("len{tmp}"));
+            }
+            Instruction::ListCanonLift { element, .. } => {
+                let element = primitive(element).unwrap();
+                results.push(format!(
+                    "{}::load_list::<_, {element}>(cx, {}, {})?",
+                    self.host, operands[0], operands[1]
+                ));
+            }
+            Instruction::StringLower { .. } => {
+                let tmp = self.tmp();
+                uwriteln!(
+                    self.src,
+                    "let (ptr{tmp}, len{tmp}) = {}::store_string(cx, &{})?;",
+                    self.host,
+                    operands[0]
+                );
+                results.push(format!("ptr{tmp}"));
+                results.push(format!("len{tmp}"));
+            }
+            Instruction::StringLift => {
+                let host = &self.host;
+                if self.gen.gen.opts.raw_strings {
+                    results.push(format!(
+                        "{host}::load_list::<_, u8>(cx, {}, {})?",
+                        operands[0], operands[1]
+                    ));
+                } else {
+                    results.push(format!(
+                        "{host}::load_string(cx, {}, {})?",
+                        operands[0], operands[1]
+                    ));
+                }
+            }
+            Instruction::ListLower { element, .. } => {
+                let body = self.blocks.pop().unwrap();
+                let tmp = self.tmp();
+                let size = self.gen.sizes.size(element).size_wasm32();
+                let align = self.gen.sizes.align(element).align_wasm32();
+                let host = &self.host;
+                uwriteln!(
+                    self.src,
+                    "let vec{tmp} = {};
+                     let len{tmp} = {host}::list_len(vec{tmp}.len())?;
+                     let result{tmp} = {host}::alloc_list(cx, len{tmp}, {size}, {align})?;
+                     for (i, e) in vec{tmp}.into_iter().enumerate() {{
+                        let base = result{tmp}.wrapping_add((i * {size}) as i32);
+                        {body}
+                     }}",
+                    operands[0]
+                );
+                results.push(format!("result{tmp}"));
+                results.push(format!("len{tmp}"));
+            }
+            Instruction::ListLift { element, .. } => {
+                let body = self.blocks.pop().unwrap();
+                let tmp = self.tmp();
+                let size = self.gen.sizes.size(element).size_wasm32();
+                let vec = self.gen.path_to_vec();
+                // The length comes from the guest, so memory isn't reserved
+                // for it upfront.
+                // Formatted upfront as `Source` trims the leading whitespace
+                // of each multi-line piece `write!` hands it.
+                let code = format!(
+                    "let base{tmp} = {};
+                     let len{tmp} = {};
+                     let mut result{tmp} = {vec}::new();
+                     for i in 0..len{tmp} as u32 {{
+                        let base = base{tmp}.wrapping_add((i as i32).wrapping_mul({size}));
+                        let e{tmp} = {body};
+                        result{tmp}.push(e{tmp});
+                     }}\n",
+                    operands[0], operands[1]
+                );
+                self.src.push_str(&code);
+                results.push(format!("result{tmp}"));
+            }
+            Instruction::IterElem { .. } => results.push("e".to_string()),
+            Instruction::IterBasePointer => results.push("base".to_string()),
+
+            Instruction::Malloc { size, align, .. } => {
+                let tmp = self.tmp();
+                uwriteln!(
+                    self.src,
+                    "let ptr{tmp} = {}::alloc_list(cx, 1, {size}, {align})?;",
+                    self.host
+                );
+                results.push(format!("ptr{tmp}"));
+            }
+
+            Instruction::CallWasm { name, sig } => {
+                let (prefix, name) = match name.strip_prefix("[async]") {
+                    Some(name) => ("[async]", name),
+                    None => ("", *name),
+                };
+                let name = match &self.interface {
+                    Some(interface) => format!("{prefix}{interface}#{name}"),
+                    None => format!("{prefix}{name}"),
+                };
+                self.export = name.clone();
+                let host = self.host.clone();
+                let tmp = self.tmp();
+                let args = operands
+                    .iter()
+                    .map(|op| format!("{host}::Val::from({op})"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if sig.results.is_empty() {
+                    uwriteln!(
+                        self.src,
+                        "{host}::Instance::call(cx, \"{name}\", &[{args}])?;"
+                    );
+                    return;
+                }
+                uwriteln!(
+                    self.src,
+                    "let ret{tmp} = {host}::Instance::call(cx, \"{name}\", &[{args}])?;"
+                );
+                for (i, ty) in sig.results.iter().enumerate() {
+                    let ty = core_type(*ty);
+                    uwriteln!(
+                        self.src,
+                        "let ret{tmp}_{i} = {host}::result::<{ty}>(&ret{tmp}, {i})?;"
+                    );
+                    results.push(format!("ret{tmp}_{i}"));
+                }
+            }
+
+            Instruction::CallInterface { func, async_ } => {
+                let trait_ = match func.kind.resource() {
+                    Some(id) => format!("Host{}", self.gen.type_ident(id)),
+                    None => "Host".to_string(),
+                };
+                let name = match func.kind {
+                    FunctionKind::Constructor(_) => "new".to_string(),
+                    _ => self.gen.func_ident(func),
+                };
+                if !async_ {
+                    self.let_results(func.results.len(), results);
+                } else if func.results.len() == 0 {
+                    results.push("()".to_string());
+                } else {
+                    self.let_results(1, results);
+                }
+                uwriteln!(self.src, "{trait_}::{name}(cx, {});", operands.join(", "));
+            }
+
+            Instruction::AsyncPostCallInterface { func } => {
+                let result = operands.pop().unwrap();
+                // The host's implementation has already returned, so the
+                // guest is told that the call is done.
+                results.push(format!("{}::STATUS_DONE", self.host));
+                match func.results.len() {
+                    0 => {}
+                    1 => results.push(result),
+                    n => {
+                        let tmp = self.tmp();
+                        self.push_str("let (");
+                        for i in 0..n {
+                            let arg = format!("result{tmp}_{i}");
+                            uwrite!(self.src, "{arg},");
+                            results.push(arg);
+                        }
+                        uwriteln!(self.src, ") = {result};");
+                    }
+                }
+            }
+
+            Instruction::AsyncTaskReturn { params, .. } => {
+                let host = self.host.clone();
+                let tmp = self.tmp();
+                uwriteln!(
+                    self.src,
+                    "let ret{tmp} = {host}::Instance::task_return(cx, \"{}\", {host}::Val::from({}))?;",
+                    self.export,
+                    operands[0]
+                );
+                for (i, ty) in params.iter().enumerate() {
+                    let ty = core_type(*ty);
+                    uwriteln!(
+                        self.src,
+                        "let ret{tmp}_{i} = {host}::result::<{ty}>(&ret{tmp}, {i})?;"
+                    );
+                    results.push(format!("ret{tmp}_{i}"));
+                }
+            }
+
+            Instruction::Return { amt, .. } => {
+                if self.import {
+                    let host = &self.host;
+                    let vals = operands
+                        .iter()
+                        .map(|op| format!("{host}::Val::from({op})"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let vec = self.gen.path_to_vec();
+                    uwriteln!(self.src, "Ok({vec}::from([{vals}]))");
+                } else {
+                    match amt {
+                        0 => self.push_str("Ok(())\n"),
+                        1 => uwriteln!(self.src, "Ok({})", operands[0]),
+                        _ => uwriteln!(self.src, "Ok(({}))", operands.join(", ")),
+                    }
+                }
+            }
+
+            Instruction::CallPostReturn { func } => {
+                let name = func.legacy_core_export_name(self.interface.as_deref());
+                uwriteln!(
+                    self.src,
+                    "{}::Instance::call(cx, \"cabi_post_{name}\", &[{}::Val::from({})])?;",
+                    self.host,
+                    self.host,
+                    operands[0]
+                );
+            }
+
+            Instruction::Flush { amt } => {
+                for operand in operands.iter().take(*amt) {
+                    let tmp = self.tmp();
+                    uwriteln!(self.src, "let result{tmp} = {operand};");
+                    results.push(format!("result{tmp}"));
+                }
+            }
+
+            Instruction::HandleLower {
+                handle: Handle::Borrow(resource),
+                ..
+            } if !self.import && self.gen.is_exported_resource(*resource) => {
+                // Guests are passed borrows of their own resources as the
+                // resources' representations.
+                let name = self.resource_name(*resource);
+                let tmp = self.tmp();
+                uwriteln!(
+                    self.src,
+                    "let rep{tmp} = {}::Instance::resource_rep(cx, \"{name}\", ({}).handle())? as i32;",
+                    self.host,
+                    operands[0]
+                );
+                results.push(format!("rep{tmp}"));
+            }
+            Instruction::HandleLower { .. }
+            | Instruction::FutureLower { .. }
+            | Instruction::StreamLower { .. }
+            | Instruction::ErrorContextLower { .. } => {
+                results.push(format!("({}).handle() as i32", operands[0]));
+            }
+            // Borrows are passed by value like owned handles, since neither
+            // carries a destructor.
+            Instruction::HandleLift {
+                handle: Handle::Own(resource) | Handle::Borrow(resource),
+                ..
+            } => {
+                let name = self
+                    .gen
+                    .type_path(dealias(self.gen.resolve, *resource), true);
+                results.push(format!("{name}::from_handle({} as u32)", operands[0]));
+            }
+            Instruction::FutureLift { .. } => {
+                results.push(format!(
+                    "{}::FutureReader::from_handle({} as u32)",
+                    self.host, operands[0]
+                ));
+            }
+            Instruction::StreamLift { .. } => {
+                results.push(format!(
+                    "{}::StreamReader::from_handle({} as u32)",
+                    self.host, operands[0]
+                ));
+            }
+            Instruction::ErrorContextLift { .. } => {
+                results.push(format!(
+                    "{}::ErrorContext::from_handle({} as u32)",
+                    self.host, operands[0]
+                ));
+            }
+
+            Instruction::GuestDeallocate { .. }
+            | Instruction::GuestDeallocateString
+            | Instruction::GuestDeallocateList { .. }
+            | Instruction::GuestDeallocateVariant { .. }
+            | Instruction::AsyncMalloc { .. }
+            | Instruction::AsyncCallWasm { .. }
+            | Instruction::AsyncCallReturn { .. } => {
+                unreachable!("{inst:?} is only used by guest bindings")
+            }
+        }
+    }
+}
+
+/// Returns the Rust type of the elements of a list of `ty` which is copied
+/// to and from memory in bulk.
+fn primitive(ty: &Type) -> Option<&'static str> {
+    Some(match ty {
+        Type::U8 => "u8",
+        Type::S8 => "i8",
+        Type::U16 => "u16",
+        Type::S16 => "i16",
+        Type::U32 => "u32",
+        Type::S32 => "i32",
+        Type::U64 => "u64",
+        Type::S64 => "i64",
+        Type::F32 => "f32",
+        Type::F64 => "f64",
+        _ => return None,
+    })
+}
+
+/// Returns the Rust type of core wasm values of type `ty`, with pointers into
+/// the guest's 32-bit memory being `i32`s.
+pub(crate) fn core_type(ty: WasmType) -> &'static str {
+    match ty {
+        WasmType::I32 | WasmType::Pointer | WasmType::Length => "i32",
+        WasmType::I64 | WasmType::PointerOrI64 => "i64",
+        WasmType::F32 => "f32",
+        WasmType::F64 => "f64",
+    }
+}
+
+fn bitcast(cast: &Bitcast, operand: String) -> String {
+    match cast {
+        Bitcast::None
+        | Bitcast::P64ToI64
+        | Bitcast::I64ToP64
+        | Bitcast::I32ToP
+        | Bitcast::PToI32
+        | Bitcast::PToL
+        | Bitcast::LToP
+        | Bitcast::I32ToL
+        | Bitcast::LToI32 => operand,
+        Bitcast::F32ToI32 => format!("({operand}).to_bits() as i32"),
+        Bitcast::F64ToI64 => format!("({operand}).to_bits() as i64"),
+        Bitcast::I32ToI64 | Bitcast::PToP64 | Bitcast::LToI64 => format!("i64::from({operand})"),
+        Bitcast::F32ToI64 => format!("i64::from(({operand}).to_bits())"),
+        Bitcast::I32ToF32 => format!("f32::from_bits(({operand}) as u32)"),
+        Bitcast::I64ToF64 => format!("f64::from_bits(({operand}) as u64)"),
+        Bitcast::I64ToI32 | Bitcast::P64ToP | Bitcast::I64ToL => format!("({operand}) as i32"),
+        Bitcast::I64ToF32 => format!("f32::from_bits(({operand}) as u32)"),
+        Bitcast::Sequence(sequence) => {
+            let [first, second] = &**sequence;
+            bitcast(second, bitcast(first, operand))
+        }
+    }
+}
+
+/// Source of the `host` module emitted at the root of host bindings.
+pub(crate) const RUNTIME: &str = r#"
+/// Support for host bindings.
+///
+/// Embedders implement [`Memory`], [`Realloc`] and [`Instance`] for their
+/// runtime's view of a component instance, which the generated bindings then
+/// use to move values in and out of the guest.
+pub mod host {
+    #![allow(dead_code, clippy::all)]
+
+    use core::fmt;
+
+    /// A core wasm value passed to or returned from a core wasm function.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Val {
+        I32(i32),
+        I64(i64),
+        F32(f32),
+        F64(f64),
+    }
+
+    macro_rules! val {
+        ($($ty:ident $case:ident)*) => ($(
+            impl From<$ty> for Val {
+                fn from(val: $ty) -> Val {
+                    Val::$case(val)
+                }
+            }
+
+            impl TryFrom<Val> for $ty {
+                type Error = Error;
+
+                fn try_from(val: Val) -> Result<$ty, Error> {
+                    match val {
+                        Val::$case(val) => Ok(val),
+                        other => Err(Error::new(format!(
+                            "expected a core wasm `{}`, found {other:?}",
+                            stringify!($ty),
+                        ))),
+                    }
+                }
+            }
+        )*)
+    }
+
+    val!(i32 I32 i64 I64 f32 F32 f64 F64);
+
+    /// An error raised while moving values in and out of a guest, such as
+    /// an out-of-bounds memory access or an invalid value.
+    #[derive(Clone, Debug)]
+    pub struct Error {
+        message: String,
+    }
+
+    impl Error {
+        pub fn new(message: impl Into<String>) -> Error {
+            Error {
+                message: message.into(),
+            }
+        }
+
+        #[doc(hidden)]
+        pub fn invalid_discriminant() -> Error {
+            Error::new("invalid discriminant")
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.message)
+        }
+    }
+
+    impl std::error::Error for Error {}
+
+    /// The linear memory of a guest.
+    pub trait Memory {
+        /// Fills `dst` with the bytes of memory starting at `offset`, failing
+        /// if they are out of bounds.
+        fn read(&self, offset: u32, dst: &mut [u8]) -> Result<(), Error>;
+
+        /// Writes `src` into memory starting at `offset`, failing if it is out
+        /// of bounds.
+        fn write(&mut self, offset: u32, src: &[u8]) -> Result<(), Error>;
+    }
+
+    /// A guest's memory along with its `cabi_realloc` function, used to
+    /// allocate space for values passed to it.
+    pub trait Realloc: Memory {
+        /// Calls the guest's `cabi_realloc`, returning the new allocation.
+        fn realloc(
+            &mut self,
+            old_ptr: u32,
+            old_size: u32,
+            align: u32,
+            new_size: u32,
+        ) -> Result<u32, Error>;
+    }
+
+    /// A guest whose exports can be called.
+    pub trait Instance: Realloc {
+        /// Calls the core wasm function exported as `name`.
+        fn call(&mut self, name: &str, args: &[Val]) -> Result<Vec<Val>, Error>;
+
+        /// Drives the task of the async export `name`, which returned
+        /// `status` when called, until it calls `task.return`, returning the
+        /// values passed to it.
+        ///
+        /// Results which don't fit in core wasm values are passed as a
+        /// pointer into memory, which is read after this returns, so the
+        /// guest must not be resumed in the meantime.
+        fn task_return(&mut self, name: &str, status: Val) -> Result<Vec<Val>, Error> {
+            let _ = status;
+            Err(Error::new(format!("calling the async export `{name}` isn't supported")))
+        }
+
+        /// Returns the representation of the resource, exported as
+        /// `resource` such as `my:pkg/iface#r`, which `handle` refers to.
+        fn resource_rep(&mut self, resource: &str, handle: u32) -> Result<u32, Error> {
+            let _ = handle;
+            Err(Error::new(format!("borrowing `{resource}` isn't supported")))
+        }
+
+        /// Drops `handle`, an owned handle to the resource exported as
+        /// `resource`, running the resource's destructor.
+        fn resource_drop(&mut self, resource: &str, handle: u32) -> Result<(), Error> {
+            let _ = handle;
+            Err(Error::new(format!("dropping `{resource}` isn't supported")))
+        }
+    }
+
+    /// The status returned to a guest by an async import which has already
+    /// written its results.
+    #[doc(hidden)]
+    pub const STATUS_DONE: i32 = (3u32 << 30) as i32;
+
+    macro_rules! handles {
+        ($($(#[$attr:meta])* $name:ident $(<$param:ident>)?;)*) => ($(
+            $(#[$attr])*
+            pub struct $name$(<$param>)? {
+                handle: u32,
+                $(_marker: core::marker::PhantomData<fn() -> $param>,)?
+            }
+
+            impl$(<$param>)? $name$(<$param>)? {
+                /// Creates a value from its handle, an index into a guest's
+                /// table of handles.
+                pub fn from_handle(handle: u32) -> Self {
+                    $name {
+                        handle,
+                        $(_marker: core::marker::PhantomData::<fn() -> $param>,)?
+                    }
+                }
+
+                /// Returns the handle of this value.
+                pub fn handle(&self) -> u32 {
+                    self.handle
+                }
+            }
+
+            impl$(<$param>)? fmt::Debug for $name$(<$param>)? {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.debug_struct(stringify!($name))
+                        .field("handle", &self.handle)
+                        .finish()
+                }
+            }
+        )*)
+    }
+
+    handles! {
+        /// The readable end of a `future` of `T`.
+        FutureReader<T>;
+        /// The readable end of a `stream` of `T`.
+        StreamReader<T>;
+        /// An `error-context`.
+        ErrorContext;
+    }
+
+    fn address(ptr: i32, offset: i32, len: usize) -> Result<u32, Error> {
+        u32::try_from(len)
+            .ok()
+            .and_then(|len| (ptr as u32).checked_add(offset as u32)?.checked_add(len))
+            .map(|end| end - len as u32)
+            .ok_or_else(|| Error::new("out of bounds memory access"))
+    }
+
+    #[doc(hidden)]
+    pub fn load<M: Memory + ?Sized, const N: usize>(
+        memory: &M,
+        ptr: i32,
+        offset: i32,
+    ) -> Result<[u8; N], Error> {
+        let mut bytes = [0; N];
+        memory.read(address(ptr, offset, N)?, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    #[doc(hidden)]
+    pub fn store<M: Memory + ?Sized>(
+        memory: &mut M,
+        ptr: i32,
+        offset: i32,
+        bytes: &[u8],
+    ) -> Result<(), Error> {
+        memory.write(address(ptr, offset, bytes.len())?, bytes)
+    }
+
+    #[doc(hidden)]
+    pub fn list_len(len: usize) -> Result<i32, Error> {
+        u32::try_from(len)
+            .map(|len| len as i32)
+            .map_err(|_| Error::new("list too long"))
+    }
+
+    #[doc(hidden)]
+    pub fn alloc_list<R: Realloc + ?Sized>(
+        cx: &mut R,
+        len: i32,
+        size: usize,
+        align: usize,
+    ) -> Result<i32, Error> {
+        let size = u32::try_from(size)
+            .ok()
+            .and_then(|size| size.checked_mul(len as u32))
+            .ok_or_else(|| Error::new("list too long"))?;
+        if size == 0 {
+            return Ok(align as i32);
+        }
+        let ptr = cx.realloc(0, 0, align as u32, size)?;
+        if ptr % align as u32 != 0 {
+            return Err(Error::new("`cabi_realloc` returned a misaligned pointer"));
+        }
+        address(ptr as i32, 0, size as usize)?;
+        Ok(ptr as i32)
+    }
+
+    /// A type whose lists are copied to and from memory in bulk.
+    #[doc(hidden)]
+    pub trait Primitive: Copy {
+        const SIZE: usize;
+
+        fn from_le(bytes: &[u8]) -> Self;
+
+        fn to_le(self, dst: &mut Vec<u8>);
+    }
+
+    macro_rules! primitive {
+        ($($ty:ident)*) => ($(
+            impl Primitive for $ty {
+                const SIZE: usize = core::mem::size_of::<$ty>();
+
+                fn from_le(bytes: &[u8]) -> $ty {
+                    $ty::from_le_bytes(bytes.try_into().unwrap())
+                }
+
+                fn to_le(self, dst: &mut Vec<u8>) {
+                    dst.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+        )*)
+    }
+
+    primitive!(u8 i8 u16 i16 u32 i32 u64 i64 f32 f64);
+
+    #[doc(hidden)]
+    pub fn load_list<M: Memory + ?Sized, T: Primitive>(
+        memory: &M,
+        ptr: i32,
+        len: i32,
+    ) -> Result<Vec<T>, Error> {
+        if ptr as u32 % T::SIZE as u32 != 0 {
+            return Err(Error::new("misaligned list"));
+        }
+        let size = (len as u32 as usize)
+            .checked_mul(T::SIZE)
+            .ok_or_else(|| Error::new("out of bounds memory access"))?;
+        let mut bytes = vec![0; size];
+        memory.read(address(ptr, 0, size)?, &mut bytes)?;
+        Ok(bytes.chunks_exact(T::SIZE).map(T::from_le).collect())
+    }
+
+    #[doc(hidden)]
+    pub fn store_list<R: Realloc + ?Sized, T: Primitive>(
+        cx: &mut R,
+        list: &[T],
+    ) -> Result<(i32, i32), Error> {
+        let len = list_len(list.len())?;
+        let ptr = alloc_list(cx, len, T::SIZE, T::SIZE)?;
+        let mut bytes = Vec::with_capacity(list.len() * T::SIZE);
+        for val in list {
+            val.to_le(&mut bytes);
+        }
+        cx.write(ptr as u32, &bytes)?;
+        Ok((ptr, len))
+    }
+
+    #[doc(hidden)]
+    pub fn load_string<M: Memory + ?Sized>(
+        memory: &M,
+        ptr: i32,
+        len: i32,
+    ) -> Result<String, Error> {
+        let bytes = load_list::<M, u8>(memory, ptr, len)?;
+        String::from_utf8(bytes).map_err(|_| Error::new("invalid utf-8 in string"))
+    }
+
+    #[doc(hidden)]
+    pub fn store_string<R: Realloc + ?Sized>(cx: &mut R, string: &str) -> Result<(i32, i32), Error> {
+        store_list(cx, string.as_bytes())
+    }
+
+    #[doc(hidden)]
+    pub fn char_lift(val: i32) -> Result<char, Error> {
+        char::from_u32(val as u32).ok_or_else(|| Error::new("invalid `char`"))
+    }
+
+    #[doc(hidden)]
+    pub fn arg<T: TryFrom<Val, Error = Error>>(args: &[Val], nth: usize) -> Result<T, Error> {
+        match args.get(nth) {
+            Some(val) => T::try_from(*val),
+            None => Err(Error::new(format!("missing argument {nth}"))),
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn result<T: TryFrom<Val, Error = Error>>(results: &[Val], nth: usize) -> Result<T, Error> {
+        match results.get(nth) {
+            Some(val) => T::try_from(*val),
+            None => Err(Error::new(format!("missing result {nth}"))),
+        }
+    }
+}
+"#;
@@ -1,9 +1,10 @@
 use crate::bindgen::FunctionBindgen;
+use crate::host::HostFunctionBindgen;
 use crate::{
-    int_repr, to_rust_ident, to_upper_camel_case, wasm_type, AsyncConfig, FnSig, Identifier,
-    InterfaceName, Ownership, RuntimeItem, RustFlagsRepr, RustWasm,
+    case_names, field_names, flag_names, int_repr, param_names, to_rust_ident, to_upper_camel_case,
+    wasm_type, FnSig, Identifier, InterfaceName, Ownership, RuntimeItem, RustFlagsRepr, RustWasm,
 };
-use anyhow::Result;
+use anyhow::{bail, Result};
 use heck::*;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Write as _;
@@ -132,23 +133,38 @@ impl<'i> InterfaceGenerator<'i> {
         &mut self,
         interface: Option<(InterfaceId, &WorldKey)>,
         funcs: impl Iterator<Item = &'a Function> + Clone,
-    ) -> Result<String> {
+    ) -> Result<Option<String>> {
         let mut traits = BTreeMap::new();
         let mut funcs_to_export = Vec::new();
         let mut resources_to_drop = Vec::new();
+        let dyn_exports = self.gen.opts.dyn_exports;
 
         traits.insert(None, ("Guest".to_string(), Vec::new()));
 
-        if let Some((id, _)) = interface {
-            for (name, id) in self.resolve.interfaces[id].types.iter() {
-                match self.resolve.types[*id].kind {
-                    TypeDefKind::Resource => {}
-                    _ => continue,
-                }
-                resources_to_drop.push(name);
-                let camel = name.to_upper_camel_case();
-                traits.insert(Some(*id), (format!("Guest{camel}"), Vec::new()));
+        let types = match (interface, &self.identifier) {
+            (Some((id, _)), _) => self.resolve.interfaces[id]
+                .types
+                .iter()
+                .map(|(name, id)| (name.as_str(), *id))
+                .collect(),
+            (None, Identifier::World(world)) => self.resolve.worlds[*world]
+                .exports
+                .iter()
+                .filter_map(|(key, item)| match (key, item) {
+                    (WorldKey::Name(name), WorldItem::Type(id)) => Some((name.as_str(), *id)),
+                    _ => None,
+                })
+                .collect(),
+            (None, _) => Vec::new(),
+        };
+        for (name, id) in types {
+            match self.resolve.types[id].kind {
+                TypeDefKind::Resource => {}
+                _ => continue,
             }
+            resources_to_drop.push((name, id));
+            let camel = self.type_ident(id);
+            traits.insert(Some(id), (format!("Guest{camel}"), Vec::new()));
         }
 
         for func in funcs {
@@ -156,17 +172,18 @@ impl<'i> InterfaceGenerator<'i> {
                 continue;
             }
 
-            let async_ = match &self.gen.opts.async_ {
-                AsyncConfig::None => false,
-                AsyncConfig::All => true,
-                AsyncConfig::Some { exports, .. } => {
-                    exports.contains(&if let Some((_, key)) = interface {
-                        format!("{}#{}", self.resolve.name_world_key(key), func.name)
-                    } else {
-                        func.name.clone()
-                    })
-                }
-            };
+            let async_ = self.gen.opts.async_.is_async(
+                self.resolve,
+                interface.map(|(_, key)| key),
+                func,
+                false,
+            );
+            if dyn_exports && async_ {
+                bail!(
+                    "cannot export `{}` as async: async exports are not supported with `dyn_exports`",
+                    func.name
+                );
+            }
             let resource = match func.kind {
                 FunctionKind::Freestanding => None,
                 FunctionKind::Method(id)
@@ -175,20 +192,11 @@ impl<'i> InterfaceGenerator<'i> {
             };
 
             funcs_to_export.push((func, resource, async_));
-            let (trait_name, methods) = traits.get_mut(&resource).unwrap();
+            let (trait_for, sig) = self.export_trait_sig(func, async_);
+            let (trait_name, methods) = traits.get_mut(&trait_for).unwrap();
             self.generate_guest_export(func, interface.map(|(_, k)| k), &trait_name, async_);
 
             let prev = mem::take(&mut self.src);
-            let mut sig = FnSig {
-                async_,
-                use_item_name: true,
-                private: true,
-                ..Default::default()
-            };
-            if let FunctionKind::Method(_) = &func.kind {
-                sig.self_arg = Some("&self".into());
-                sig.self_is_first_param = true;
-            }
             self.print_signature(func, true, &sig);
             self.src.push_str(";\n");
             let trait_method = mem::replace(&mut self.src, prev);
@@ -202,19 +210,28 @@ impl<'i> InterfaceGenerator<'i> {
                 &methods,
                 traits
                     .iter()
+                    .filter(|_| !dyn_exports)
                     .map(|(resource, (trait_name, ..))| (resource.unwrap(), trait_name.as_str())),
-            )
+            );
+            if dyn_exports {
+                self.generate_guest_registration(interface.map(|(_, key)| key));
+            }
         }
 
         for (resource, (trait_name, methods)) in traits.iter() {
             uwriteln!(self.src, "pub trait {trait_name}: 'static {{");
             let resource = resource.unwrap();
             let resource_name = self.resolve.types[resource].name.as_ref().unwrap();
-            let (_, interface_name) = interface.unwrap();
-            let module = self.resolve.name_world_key(interface_name);
-            uwriteln!(
-                self.src,
-                r#"
+            let module = match interface {
+                Some((_, key)) => self.resolve.name_world_key(key),
+                None => "$root".to_string(),
+            };
+            // With `dyn_exports` these intrinsics are instead associated
+            // functions of the resource type, see `type_resource`.
+            if !dyn_exports {
+                uwriteln!(
+                    self.src,
+                    r#"
 #[doc(hidden)]
 unsafe fn _resource_new(val: *mut u8) -> u32
     where Self: Sized
@@ -260,13 +277,44 @@ fn _resource_rep(handle: u32) -> *mut u8
 }}
 
                     "#
-            );
+                );
+            }
             for method in methods {
                 self.src.push_str(method);
             }
             uwriteln!(self.src, "}}");
         }
 
+        if dyn_exports {
+            uwriteln!(self.src, "const _: () = {{");
+            for (func, _, async_) in funcs_to_export {
+                self.generate_raw_cabi_export(func, "", "self", async_);
+            }
+            let export_prefix = self.gen.opts.export_prefix.as_deref().unwrap_or("");
+            let module = match self.identifier {
+                Identifier::Interface(_, key) => format!("{}#", self.resolve.name_world_key(key)),
+                Identifier::World(_) => String::new(),
+                Identifier::StreamOrFuturePayload => unreachable!(),
+            };
+            for (name, id) in resources_to_drop {
+                let camel = self.type_ident(id);
+                uwriteln!(
+                    self.src,
+                    r#"
+                    #[doc(hidden)]
+                    #[unsafe(export_name = "{export_prefix}{module}[dtor]{name}")]
+                    #[allow(non_snake_case)]
+                    unsafe extern "C" fn _dtor_{name_snake}(rep: *mut u8) {{
+                        {camel}::dtor(rep)
+                    }}
+                    "#,
+                    name_snake = name.to_snake_case(),
+                );
+            }
+            uwriteln!(self.src, "}};");
+            return Ok(None);
+        }
+
         let macro_name = match interface {
             None => {
                 let world = match self.identifier {
@@ -308,31 +356,28 @@ macro_rules! {macro_name} {{
             let ty = match resource {
                 None => "$ty".to_string(),
                 Some(id) => {
-                    let name = self.resolve.types[id]
-                        .name
-                        .as_ref()
-                        .unwrap()
-                        .to_upper_camel_case();
+                    let name = self.type_ident(id);
                     format!("<$ty as $($path_to_types)*::Guest>::{name}")
                 }
             };
             self.generate_raw_cabi_export(func, &ty, "$($path_to_types)*", async_);
         }
         let export_prefix = self.gen.opts.export_prefix.as_deref().unwrap_or("");
-        for name in resources_to_drop {
+        for (name, id) in resources_to_drop {
+            // Destructors of resources exported from a world aren't prefixed
+            // with an interface, just like the world's functions.
             let module = match self.identifier {
-                Identifier::Interface(_, key) => self.resolve.name_world_key(key),
-                Identifier::World(_) | Identifier::StreamOrFuturePayload => {
-                    unreachable!()
-                }
+                Identifier::Interface(_, key) => format!("{}#", self.resolve.name_world_key(key)),
+                Identifier::World(_) => String::new(),
+                Identifier::StreamOrFuturePayload => unreachable!(),
             };
-            let camel = name.to_upper_camel_case();
+            let camel = self.type_ident(id);
             uwriteln!(
                 self.src,
                 r#"
                 const _: () = {{
                     #[doc(hidden)]
-                    #[unsafe(export_name = "{export_prefix}{module}#[dtor]{name}")]
+                    #[unsafe(export_name = "{export_prefix}{module}[dtor]{name}")]
                     #[allow(non_snake_case)]
                     unsafe extern "C" fn dtor(rep: *mut u8) {{
                         $($path_to_types)*::{camel}::dtor::<
@@ -347,7 +392,44 @@ macro_rules! {macro_name} {{
         uwriteln!(self.src, "}}");
         uwriteln!(self.src, "#[doc(hidden)]");
         uwriteln!(self.src, "{use_vis} use {macro_name};");
-        Ok(macro_name)
+        Ok(Some(macro_name))
+    }
+
+    /// Returns the resource whose trait `func` is a method of, or `None` for
+    /// the `Guest` trait, along with its signature in that trait.
+    fn export_trait_sig(&self, func: &Function, async_: bool) -> (Option<TypeId>, FnSig) {
+        let dyn_exports = self.gen.opts.dyn_exports;
+        let mut sig = FnSig {
+            async_,
+            use_item_name: true,
+            private: true,
+            ..Default::default()
+        };
+        if dyn_exports {
+            sig.self_arg = Some("&self".into());
+        }
+        let trait_for = match func.kind {
+            FunctionKind::Freestanding => None,
+            FunctionKind::Method(id) => {
+                sig.self_arg = Some("&self".into());
+                sig.self_is_first_param = true;
+                Some(id)
+            }
+            // Resource traits can't be used as trait objects if they have
+            // functions without `self`, so with `dyn_exports` these are
+            // moved to the `Guest` trait instead.
+            FunctionKind::Constructor(id) | FunctionKind::Static(id) if dyn_exports => {
+                let resource = self.resolve.types[id].name.as_ref().unwrap();
+                let item = match func.kind {
+                    FunctionKind::Constructor(_) => "new",
+                    _ => func.item_name(),
+                };
+                sig.name = Some(format!("{resource}-{item}"));
+                None
+            }
+            FunctionKind::Constructor(id) | FunctionKind::Static(id) => Some(id),
+        };
+        (trait_for, sig)
     }
 
     fn generate_interface_trait<'a>(
@@ -358,11 +440,7 @@ macro_rules! {macro_name} {{
     ) {
         uwriteln!(self.src, "pub trait {trait_name} {{");
         for (id, trait_name) in resource_traits {
-            let name = self.resolve.types[id]
-                .name
-                .as_ref()
-                .unwrap()
-                .to_upper_camel_case();
+            let name = self.type_ident(id);
             uwriteln!(self.src, "type {name}: {trait_name};");
         }
         for method in methods {
@@ -371,14 +449,585 @@ macro_rules! {macro_name} {{
         uwriteln!(self.src, "}}");
     }
 
+    /// Emits `set_guest`, which registers the implementation of the `Guest`
+    /// trait used by `dyn_exports` bindings.
+    fn generate_guest_registration(&mut self, interface: Option<&WorldKey>) {
+        let guest_cell = self.path_to_guest_cell();
+        let box_path = self.path_to_box();
+        let name = match interface {
+            Some(key) => self.resolve.name_world_key(key),
+            None => match self.identifier {
+                Identifier::World(world) => self.resolve.worlds[world].name.clone(),
+                _ => unreachable!(),
+            },
+        };
+        uwriteln!(
+            self.src,
+            r#"
+static _GUEST: {guest_cell}<dyn Guest> = {guest_cell}::new();
+
+/// Registers `guest` as the implementation of the exports of `{name}`.
+///
+/// This must be called before any of those exports are invoked.
+///
+/// # Panics
+///
+/// Panics if an implementation has already been registered.
+pub fn set_guest(guest: {box_path}<dyn Guest>) {{
+    if !_GUEST.set(guest) {{
+        panic!("an implementation of `{name}` is already registered");
+    }}
+}}
+
+fn _guest() -> &'static dyn Guest {{
+    _GUEST
+        .get()
+        .expect("no implementation of `{name}` is registered; call `set_guest` first")
+}}
+            "#
+        );
+    }
+
     pub fn generate_imports<'a>(
         &mut self,
         funcs: impl Iterator<Item = &'a Function>,
         interface: Option<&WorldKey>,
     ) {
-        for func in funcs {
+        let funcs = funcs.collect::<Vec<_>>();
+        for func in funcs.iter() {
             self.generate_guest_import(func, interface);
         }
+        if self.gen.opts.mocks {
+            self.generate_import_mock(&funcs, interface);
+        }
+    }
+
+    /// Emits the `Host` trait implementing the imported `funcs` in host
+    /// bindings, along with `dispatch` which calls them with core wasm
+    /// values.
+    ///
+    /// Each imported resource gets a `HostX` trait with its functions and
+    /// `drop`, which `Host` extends.
+    pub fn generate_host_imports(&mut self, funcs: &[&Function], interface: Option<&WorldKey>) {
+        let funcs = funcs
+            .iter()
+            .filter(|func| !self.gen.skip.contains(&func.name))
+            .copied()
+            .collect::<Vec<_>>();
+        let resources = self.resources();
+        if funcs.is_empty() && resources.is_empty() {
+            return;
+        }
+        let host = format!("{}host", self.path_to_root());
+        let vec = self.path_to_vec();
+        let module = self.wasm_import_module;
+        let names = funcs
+            .iter()
+            .map(|func| func.name.clone())
+            .chain(resources.iter().map(|id| {
+                let name = self.resolve.types[*id].name.as_ref().unwrap();
+                format!("[resource-drop]{name}")
+            }))
+            .map(|name| format!("{name:?}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        uwriteln!(
+            self.src,
+            r#"
+/// The name of the core wasm module whose functions are dispatched by
+/// [`dispatch`].
+pub const MODULE: &str = "{module}";
+
+/// The names of the functions [`dispatch`] accepts.
+pub const FUNCTIONS: &[&str] = &[{names}];"#
+        );
+        let sig = FnSig {
+            private: true,
+            use_item_name: true,
+            self_arg: Some("&mut self".into()),
+            ..Default::default()
+        };
+        let mut supertraits = Vec::new();
+        for id in resources.iter() {
+            let camel = self.type_ident(*id);
+            let name = self.resolve.types[*id].name.as_ref().unwrap();
+            uwriteln!(
+                self.src,
+                "
+/// The host's implementation of the resource `{name}` imported from
+/// `{module}`.
+pub trait Host{camel} {{"
+            );
+            for func in funcs.iter().filter(|f| f.kind.resource() == Some(*id)) {
+                self.print_signature(func, true, &sig);
+                self.src.push_str(";\n");
+            }
+            uwriteln!(
+                self.src,
+                "/// Called when the guest drops `handle`, its handle to a `{name}`
+                 /// it owns.
+                 fn drop(&mut self, handle: {camel});
+                }}"
+            );
+            supertraits.push(format!("Host{camel}"));
+        }
+        let supertraits = if supertraits.is_empty() {
+            String::new()
+        } else {
+            format!(": {}", supertraits.join(" + "))
+        };
+        uwriteln!(
+            self.src,
+            "
+/// The host's implementation of the functions imported from `{module}`.
+pub trait Host{supertraits} {{"
+        );
+        for func in funcs.iter().filter(|f| f.kind.resource().is_none()) {
+            self.print_signature(func, true, &sig);
+            self.src.push_str(";\n");
+        }
+        uwriteln!(
+            self.src,
+            r#"}}
+
+/// Calls the function `name` of [`Host`] implemented by `cx`, lifting its
+/// arguments from the core wasm values `args` and returning its lowered
+/// results.
+pub fn dispatch<C: Host + {host}::Realloc + ?Sized>(
+    cx: &mut C,
+    name: &str,
+    args: &[{host}::Val],
+) -> ::core::result::Result<{vec}<{host}::Val>, {host}::Error> {{
+    match name {{"#
+        );
+        for func in funcs.iter() {
+            uwriteln!(
+                self.src,
+                "{:?} => _call_{}(cx, args),",
+                func.name,
+                func.name.to_snake_case()
+            );
+        }
+        for id in resources.iter() {
+            let camel = self.type_ident(*id);
+            let name = self.resolve.types[*id].name.as_ref().unwrap();
+            uwriteln!(
+                self.src,
+                "\"[resource-drop]{name}\" => {{
+                    let handle = {host}::arg::<i32>(args, 0)?;
+                    Host{camel}::drop(cx, {camel}::from_handle(handle as u32));
+                    Ok({vec}::new())
+                }}"
+            );
+        }
+        uwriteln!(
+            self.src,
+            r#"        _ => Err({host}::Error::new(format!("`{module}` has no function `{{name}}`"))),
+    }}
+}}"#
+        );
+        for func in funcs {
+            let async_ = self
+                .gen
+                .opts
+                .async_
+                .is_async(self.resolve, interface, func, true);
+            let variant = if async_ {
+                AbiVariant::GuestImportAsync
+            } else {
+                AbiVariant::GuestImport
+            };
+            let sig = self.resolve.wasm_signature(variant, func);
+            uwriteln!(
+                self.src,
+                "fn _call_{}<C: Host + {host}::Realloc + ?Sized>(
+                    cx: &mut C,
+                    args: &[{host}::Val],
+                ) -> ::core::result::Result<{vec}<{host}::Val>, {host}::Error> {{",
+                func.name.to_snake_case()
+            );
+            let mut params = Vec::new();
+            for (i, ty) in sig.params.iter().enumerate() {
+                let ty = crate::host::core_type(*ty);
+                // Async imports always take pointers to their parameters and
+                // results, even if they have none.
+                let unused = async_
+                    && match i {
+                        0 => func.params.is_empty(),
+                        _ => func.results.len() == 0,
+                    };
+                let arg = if unused {
+                    format!("_arg{i}")
+                } else {
+                    format!("arg{i}")
+                };
+                uwriteln!(self.src, "let {arg} = {host}::arg::<{ty}>(args, {i})?;");
+                params.push(arg);
+            }
+            let mut f = HostFunctionBindgen::new(self, params, None, true);
+            abi::call(
+                f.gen.resolve,
+                AbiVariant::GuestImport,
+                LiftLower::LiftArgsLowerResults,
+                func,
+                &mut f,
+                async_,
+            );
+            let src = String::from(f.src);
+            self.src.push_str(&src);
+            self.src.push_str("}\n");
+        }
+    }
+
+    /// Emits functions calling the exported `funcs` in host bindings.
+    ///
+    /// Functions of exported resources become methods of the resource's type,
+    /// along with `drop` dropping a handle to it.
+    pub fn generate_host_exports(&mut self, funcs: &[&Function], interface: Option<&WorldKey>) {
+        let host = format!("{}host", self.path_to_root());
+        let generics = Some(format!("<C: {host}::Instance + ?Sized>"));
+        let funcs = funcs
+            .iter()
+            .filter(|func| !self.gen.skip.contains(&func.name))
+            .copied()
+            .collect::<Vec<_>>();
+        for func in funcs.iter().filter(|f| f.kind.resource().is_none()) {
+            let sig = FnSig {
+                generics: generics.clone(),
+                self_arg: Some("cx: &mut C".into()),
+                ..Default::default()
+            };
+            self.generate_host_export(func, interface, &sig);
+        }
+        for id in self.resources() {
+            let camel = self.type_ident(id);
+            uwriteln!(self.src, "impl {camel} {{");
+            for func in funcs.iter().filter(|f| f.kind.resource() == Some(id)) {
+                let method = matches!(func.kind, FunctionKind::Method(_));
+                let sig = FnSig {
+                    generics: generics.clone(),
+                    self_arg: Some(
+                        if method {
+                            "&self, cx: &mut C"
+                        } else {
+                            "cx: &mut C"
+                        }
+                        .into(),
+                    ),
+                    self_is_first_param: method,
+                    use_item_name: true,
+                    ..Default::default()
+                };
+                self.generate_host_export(func, interface, &sig);
+            }
+            let name = self.resolve.types[id].name.as_ref().unwrap();
+            let resource = match interface {
+                Some(key) => format!("{}#{name}", self.resolve.name_world_key(key)),
+                None => name.clone(),
+            };
+            uwriteln!(
+                self.src,
+                r#"
+/// Drops this handle, running the resource's destructor in the guest.
+pub fn drop<C: {host}::Instance + ?Sized>(self, cx: &mut C) -> ::core::result::Result<(), {host}::Error> {{
+    {host}::Instance::resource_drop(cx, "{resource}", self.handle)
+}}
+}}"#
+            );
+        }
+    }
+
+    fn generate_host_export(&mut self, func: &Function, interface: Option<&WorldKey>, sig: &FnSig) {
+        let host = format!("{}host", self.path_to_root());
+        let params = self.print_docs_and_params(func, false, sig);
+        let start = self.src.len();
+        if let FunctionKind::Constructor(id) = &func.kind {
+            let ty = self.type_name_owned(&Type::Id(*id));
+            self.push_str(&format!(" -> {ty}"));
+        } else {
+            self.print_results(&func.results);
+        }
+        let results = self.src.as_mut_string().split_off(start);
+        uwriteln!(
+            self.src,
+            " -> ::core::result::Result<{}, {host}::Error> {{",
+            results.trim_start_matches(" -> ")
+        );
+        let async_ = self
+            .gen
+            .opts
+            .async_
+            .is_async(self.resolve, interface, func, false);
+        let name = interface.map(|key| self.resolve.name_world_key(key));
+        let mut f = HostFunctionBindgen::new(self, params, name, false);
+        abi::call(
+            f.gen.resolve,
+            AbiVariant::GuestExport,
+            LiftLower::LowerArgsLiftResults,
+            func,
+            &mut f,
+            async_,
+        );
+        let src = String::from(f.src);
+        self.src.push_str(&src);
+        self.src.push_str("}\n");
+    }
+
+    /// Returns the resources defined by the interface or world being
+    /// generated.
+    fn resources(&self) -> Vec<TypeId> {
+        let types = match self.identifier {
+            Identifier::Interface(id, _) => self.resolve.interfaces[id]
+                .types
+                .values()
+                .copied()
+                .collect(),
+            Identifier::World(world) => {
+                let world = &self.resolve.worlds[world];
+                let items = if self.in_import {
+                    &world.imports
+                } else {
+                    &world.exports
+                };
+                items
+                    .values()
+                    .filter_map(|item| match item {
+                        WorldItem::Type(id) => Some(*id),
+                        _ => None,
+                    })
+                    .collect()
+            }
+            Identifier::StreamOrFuturePayload => Vec::new(),
+        };
+        types
+            .into_iter()
+            .filter(|id| matches!(self.resolve.types[*id].kind, TypeDefKind::Resource))
+            .collect()
+    }
+
+    /// Returns the expression calling the mock of the imported `func` when
+    /// `mocks` is enabled.
+    fn mock_call(&mut self, func: &Function) -> String {
+        let skip = match func.kind {
+            FunctionKind::Method(_) => 1,
+            _ => 0,
+        };
+        let args = param_names(func)[skip..].join(", ");
+        let method = to_rust_ident(&self.mock_method_name(func));
+        match func.kind {
+            FunctionKind::Method(id) => {
+                let table = self.mock_table_name(id);
+                format!("{table}.with(|table| table.get(self.handle())).{method}({args})")
+            }
+            _ => format!("_mock().{method}({args})"),
+        }
+    }
+
+    /// Returns the name of the method standing in for `func` in the `Mock`
+    /// trait, or in the `MockX` trait of its resource for methods.
+    fn mock_method_name(&self, func: &Function) -> String {
+        match func.kind {
+            FunctionKind::Freestanding | FunctionKind::Method(_) => func.item_name().to_string(),
+            FunctionKind::Constructor(id) => {
+                let resource = self.resolve.types[id].name.as_ref().unwrap();
+                format!("{resource}-new")
+            }
+            FunctionKind::Static(id) => {
+                let resource = self.resolve.types[id].name.as_ref().unwrap();
+                format!("{resource}-{}", func.item_name())
+            }
+        }
+    }
+
+    /// Returns the name of the thread-local table of mocks of the imported
+    /// resource `id`.
+    fn mock_table_name(&self, id: TypeId) -> String {
+        let resource = self.resolve.types[id].name.as_ref().unwrap();
+        format!("_{}_MOCKS", resource.to_shouty_snake_case())
+    }
+
+    /// Emits a mock trait method with a default implementation which panics,
+    /// where `import_name` is the name of the interface or world importing
+    /// `func`.
+    ///
+    /// Mocks of async imports return a boxed future, borrowing from the mock
+    /// and the arguments, so that mock traits remain object safe.
+    fn generate_mock_method(
+        &mut self,
+        func: &Function,
+        interface: Option<&WorldKey>,
+        import_name: &str,
+    ) {
+        let mut sig = FnSig {
+            private: true,
+            self_arg: Some("&self".into()),
+            name: Some(self.mock_method_name(func)),
+            ..Default::default()
+        };
+        if let FunctionKind::Method(_) = func.kind {
+            sig.self_is_first_param = true;
+        }
+        if self
+            .gen
+            .opts
+            .async_
+            .is_async(self.resolve, interface, func, true)
+        {
+            sig.generics = Some("<'a>".into());
+            sig.self_arg = Some("&'a self".into());
+            sig.lifetime = Some("'a");
+            self.print_docs_and_params(func, false, &sig);
+            let result = match func.kind {
+                FunctionKind::Constructor(id) => self.type_name_owned(&Type::Id(id)),
+                _ => {
+                    let results = func
+                        .results
+                        .iter_types()
+                        .map(|ty| self.type_name_owned(ty))
+                        .collect::<Vec<_>>();
+                    match &results[..] {
+                        [result] => result.clone(),
+                        results => format!("({})", results.join(", ")),
+                    }
+                }
+            };
+            let box_ = self.path_to_box();
+            uwrite!(
+                self.src,
+                " -> ::core::pin::Pin<{box_}<dyn ::core::future::Future<Output = {result}> + 'a>>"
+            );
+        } else {
+            self.print_signature(func, false, &sig);
+        }
+        uwriteln!(
+            self.src,
+            " {{ unimplemented!(\"`{import_name}#{}` isn't mocked\") }}",
+            func.name,
+        );
+    }
+
+    /// Emits `X::from_mock` for the imported resource `id` along with the
+    /// `MockX` trait of its methods and the table mapping handles to mocks.
+    fn generate_resource_mock(&mut self, id: TypeId, name: &str) {
+        let camel = self.type_ident(id);
+        let table = self.mock_table_name(id);
+        let mocks = self.path_to_mocks();
+        let interface = match self.identifier {
+            Identifier::Interface(_, key) => Some(key),
+            _ => None,
+        };
+        let resolve = self.resolve;
+        let funcs = match resolve.types[id].owner {
+            TypeOwner::Interface(owner) => resolve.interfaces[owner]
+                .functions
+                .values()
+                .collect::<Vec<_>>(),
+            TypeOwner::World(world) => resolve.worlds[world]
+                .imports
+                .values()
+                .filter_map(|item| match item {
+                    WorldItem::Function(func) => Some(func),
+                    _ => None,
+                })
+                .collect(),
+            TypeOwner::None => Vec::new(),
+        };
+        let import_name = match (interface, resolve.types[id].owner) {
+            (Some(key), _) => resolve.name_world_key(key),
+            (None, TypeOwner::World(world)) => resolve.worlds[world].name.clone(),
+            (None, _) => unreachable!(),
+        };
+        uwriteln!(
+            self.src,
+            r#"
+impl {camel} {{
+    /// Creates a `{camel}` whose methods call `mock`, for use when not
+    /// compiling to wasm32.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_mock(mock: impl Mock{camel} + 'static) -> Self {{
+        let handle = {table}.with(|table| table.insert({mocks}::Rc::new(mock)));
+        unsafe {{ Self::from_handle(handle) }}
+    }}
+}}
+
+#[cfg(not(target_arch = "wasm32"))]
+{mocks}::thread_local! {{
+    static {table}: {mocks}::MockTable<dyn Mock{camel}> = const {{ {mocks}::MockTable::new() }};
+}}
+
+/// A mock of the methods of `{name}`, called in their place when not
+/// compiling to wasm32.
+///
+/// Methods which aren't implemented panic when called.
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(unused_variables)]
+pub trait Mock{camel} {{"#
+        );
+        for func in funcs {
+            if func.kind != FunctionKind::Method(id) || self.gen.skip.contains(&func.name) {
+                continue;
+            }
+            self.generate_mock_method(func, interface, &import_name);
+        }
+        uwriteln!(self.src, "}}");
+    }
+
+    /// Emits the `Mock` trait of the non-method imports in `funcs` along
+    /// with `set_mock`, which sets the implementation used on this thread.
+    fn generate_import_mock(&mut self, funcs: &[&Function], interface: Option<&WorldKey>) {
+        let funcs = funcs
+            .iter()
+            .filter(|func| {
+                !matches!(func.kind, FunctionKind::Method(_)) && !self.gen.skip.contains(&func.name)
+            })
+            .collect::<Vec<_>>();
+        if funcs.is_empty() {
+            return;
+        }
+        let name = match interface {
+            Some(key) => self.resolve.name_world_key(key),
+            None => match self.identifier {
+                Identifier::World(world) => self.resolve.worlds[world].name.clone(),
+                _ => unreachable!(),
+            },
+        };
+        let mocks = self.path_to_mocks();
+        uwriteln!(
+            self.src,
+            r#"
+/// A mock of the imports of `{name}`, called in their place when not
+/// compiling to wasm32.
+///
+/// Functions which aren't implemented panic when called.
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(unused_variables)]
+pub trait Mock {{"#
+        );
+        for func in funcs {
+            self.generate_mock_method(func, interface, &name);
+        }
+        uwriteln!(
+            self.src,
+            r#"}}
+
+#[cfg(not(target_arch = "wasm32"))]
+{mocks}::thread_local! {{
+    static _MOCK: {mocks}::MockCell<dyn Mock> = const {{ {mocks}::MockCell::new() }};
+}}
+
+/// Sets `mock` as the implementation of the imports of `{name}` on the
+/// current thread, replacing any previous one.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_mock(mock: impl Mock + 'static) {{
+    _MOCK.with(|cell| cell.set({mocks}::Rc::new(mock)));
+}}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn _mock() -> {mocks}::Rc<dyn Mock> {{
+    _MOCK.with(|cell| cell.get("{name}"))
+}}
+            "#
+        );
     }
 
     pub fn finish(&mut self) -> String {
@@ -433,14 +1082,21 @@ macro_rules! {macro_name} {{
         path_to_root
     }
 
+    /// Returns the path to the module defining `FutureReader`,
+    /// `StreamReader` and `ErrorContext`, which host bindings generate in
+    /// their `host` module.
+    fn path_to_async_types(&self) -> String {
+        if self.gen.opts.host {
+            format!("{}host", self.path_to_root())
+        } else {
+            self.gen.async_support_path()
+        }
+    }
+
     pub fn start_append_submodule(&mut self, name: &WorldKey) -> (String, Vec<String>) {
-        let snake = match name {
-            WorldKey::Name(name) => to_rust_ident(name),
-            WorldKey::Interface(id) => {
-                to_rust_ident(self.resolve.interfaces[*id].name.as_ref().unwrap())
-            }
-        };
-        let module_path = crate::compute_module_path(name, &self.resolve, !self.in_import);
+        let module_path =
+            crate::compute_module_path(name, &self.resolve, !self.in_import, &self.gen.renames);
+        let snake = module_path.last().unwrap().clone();
         (snake, module_path)
     }
 
@@ -452,7 +1108,8 @@ macro_rules! {macro_name} {{
         let docs = docs.trim_end();
 
         let path_to_root = self.path_to_root();
-        let used_static = if self.gen.opts.disable_custom_section_link_helpers {
+        let used_static = if self.gen.opts.disable_custom_section_link_helpers || self.gen.opts.host
+        {
             String::new()
         } else {
             format!(
@@ -506,8 +1163,9 @@ macro_rules! {macro_name} {{
                     } else {
                         "()".into()
                     };
+                    let key = self.payload_key(payload_type.as_ref());
 
-                    if !self.gen.future_payloads.contains_key(&name) {
+                    if !self.gen.future_payloads.contains_key(&key) {
                         let ordinal = self.gen.future_payloads.len();
                         let (size, align) = if let Some(payload_type) = payload_type {
                             (
@@ -604,7 +1262,7 @@ pub mod vtable{ordinal} {{
                 #[link_name = "[future-cancel-write-{index}]{func_name}"]
                 fn cancel(_: u32) -> u32;
             }}
-            unsafe {{ cancel(writer) }};
+            unsafe {{ {async_support}::finish_cancel(writer, cancel(writer)) }};
         }}
     }}
 
@@ -621,14 +1279,15 @@ pub mod vtable{ordinal} {{
                 #[link_name = "[future-cancel-read-{index}]{func_name}"]
                 fn cancel(_: u32) -> u32;
             }}
-            unsafe {{ cancel(reader) }};
+            unsafe {{ {async_support}::finish_cancel(reader, cancel(reader)) }};
         }}
     }}
 
     fn close_writable(writer: u32) {{
+        // Off wasm32 both ends are always local, so there's nothing to close.
         #[cfg(not(target_arch = "wasm32"))]
         {{
-            unreachable!();
+            _ = writer;
         }}
 
         #[cfg(target_arch = "wasm32")]
@@ -643,9 +1302,10 @@ pub mod vtable{ordinal} {{
     }}
 
     fn close_readable(reader: u32) {{
+        // Off wasm32 both ends are always local, so there's nothing to close.
         #[cfg(not(target_arch = "wasm32"))]
         {{
-            unreachable!();
+            _ = reader;
         }}
 
         #[cfg(target_arch = "wasm32")]
@@ -667,7 +1327,7 @@ pub mod vtable{ordinal} {{
         fn new() -> (u32, &'static {async_support}::FutureVtable<Self>) {{
             #[cfg(not(target_arch = "wasm32"))]
             {{
-                unreachable!();
+                ({async_support}::native::new_handle(), &VTABLE)
             }}
 
             #[cfg(target_arch = "wasm32")]
@@ -685,7 +1345,7 @@ pub mod vtable{ordinal} {{
                         "#,
                         );
 
-                        self.gen.future_payloads.insert(name, code);
+                        self.gen.future_payloads.insert(key, code);
                     }
                 }
                 TypeDefKind::Stream(payload_type) => {
@@ -694,8 +1354,9 @@ pub mod vtable{ordinal} {{
                     } else {
                         "()".into()
                     };
+                    let key = self.payload_key(payload_type.as_ref());
 
-                    if !self.gen.stream_payloads.contains_key(&name) {
+                    if !self.gen.stream_payloads.contains_key(&key) {
                         let ordinal = self.gen.stream_payloads.len();
                         let (size, align) = if let Some(payload_type) = payload_type {
                             (
@@ -795,7 +1456,7 @@ pub mod vtable{ordinal} {{
                         wit_import,
                         stream,
                         address.add(total * {size}),
-                        u32::try_from(values.len()).unwrap()
+                        u32::try_from(values.len() - total).unwrap()
                     ).await
                 }};
 
@@ -854,7 +1515,7 @@ pub mod vtable{ordinal} {{
                 #[link_name = "[stream-cancel-write-{index}]{func_name}"]
                 fn cancel(_: u32) -> u32;
             }}
-            unsafe {{ cancel(writer) }};
+            unsafe {{ {async_support}::finish_cancel(writer, cancel(writer)) }};
         }}
     }}
 
@@ -871,14 +1532,15 @@ pub mod vtable{ordinal} {{
                 #[link_name = "[stream-cancel-read-{index}]{func_name}"]
                 fn cancel(_: u32) -> u32;
             }}
-            unsafe {{ cancel(reader) }};
+            unsafe {{ {async_support}::finish_cancel(reader, cancel(reader)) }};
         }}
     }}
 
     fn close_writable(writer: u32) {{
+        // Off wasm32 both ends are always local, so there's nothing to close.
         #[cfg(not(target_arch = "wasm32"))]
         {{
-            unreachable!();
+            _ = writer;
         }}
 
         #[cfg(target_arch = "wasm32")]
@@ -893,9 +1555,10 @@ pub mod vtable{ordinal} {{
     }}
 
     fn close_readable(reader: u32) {{
+        // Off wasm32 both ends are always local, so there's nothing to close.
         #[cfg(not(target_arch = "wasm32"))]
         {{
-            unreachable!();
+            _ = reader;
         }}
 
         #[cfg(target_arch = "wasm32")]
@@ -917,32 +1580,213 @@ pub mod vtable{ordinal} {{
         fn new() -> (u32, &'static {async_support}::StreamVtable<Self>) {{
             #[cfg(not(target_arch = "wasm32"))]
             {{
-                unreachable!();
+                ({async_support}::native::new_handle(), &VTABLE)
             }}
 
-            #[cfg(target_arch = "wasm32")]
-            {{
-                #[link(wasm_import_module = "{module}")]
-                extern "C" {{
-                    #[link_name = "[stream-new-{index}]{func_name}"]
-                    fn new() -> u32;
-                }}
-                (unsafe {{ new() }}, &VTABLE)
-            }}
+            #[cfg(target_arch = "wasm32")]
+            {{
+                #[link(wasm_import_module = "{module}")]
+                extern "C" {{
+                    #[link_name = "[stream-new-{index}]{func_name}"]
+                    fn new() -> u32;
+                }}
+                (unsafe {{ new() }}, &VTABLE)
+            }}
+        }}
+    }}
+}}
+                        "#,
+                        );
+
+                        self.gen.stream_payloads.insert(key, code);
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        self.identifier = old_identifier;
+    }
+
+    /// Returns a key identifying the Rust type used for a stream or future
+    /// payload.
+    ///
+    /// Only records, variants, enums, flags and resources are distinct types
+    /// in Rust; everything else, named or not, is keyed by its structure so
+    /// that payloads spelled as the same Rust type, such as `own<r>` and `r`
+    /// or `bytes` and `list<u8>`, map to a single `FuturePayload` or
+    /// `StreamPayload` impl.
+    pub(crate) fn payload_key(&mut self, ty: Option<&Type>) -> String {
+        let old_identifier = mem::replace(&mut self.identifier, Identifier::StreamOrFuturePayload);
+        let mut dst = String::new();
+        match ty {
+            Some(ty) => self.push_payload_key(ty, &mut dst),
+            None => dst.push_str("()"),
+        }
+        self.identifier = old_identifier;
+        dst
+    }
+
+    fn push_payload_key(&mut self, ty: &Type, dst: &mut String) {
+        let id = match ty {
+            Type::Id(id) => *id,
+            other => return dst.push_str(&format!("{other:?}")),
+        };
+        let (kind, tys): (_, Vec<Option<Type>>) = match &self.resolve.types[id].kind {
+            TypeDefKind::Type(t) => return self.push_payload_key(t, dst),
+            TypeDefKind::Handle(Handle::Own(r)) => {
+                return self.push_payload_key(&Type::Id(*r), dst)
+            }
+            TypeDefKind::Handle(Handle::Borrow(r)) => ("borrow", vec![Some(Type::Id(*r))]),
+            TypeDefKind::List(t) => ("list", vec![Some(*t)]),
+            TypeDefKind::Option(t) => ("option", vec![Some(*t)]),
+            TypeDefKind::Result(r) => ("result", vec![r.ok, r.err]),
+            TypeDefKind::Tuple(t) => ("tuple", t.types.iter().copied().map(Some).collect()),
+            TypeDefKind::Future(t) => ("future", vec![*t]),
+            TypeDefKind::Stream(t) => ("stream", vec![*t]),
+            _ => return dst.push_str(&self.type_name_owned(&Type::Id(dealias(self.resolve, id)))),
+        };
+        dst.push_str(kind);
+        dst.push('<');
+        for (i, ty) in tys.iter().enumerate() {
+            if i > 0 {
+                dst.push(',');
+            }
+            match ty {
+                Some(ty) => self.push_payload_key(ty, dst),
+                None => dst.push('_'),
+            }
+        }
+        dst.push('>');
+    }
+
+    /// Emits `wit_future`/`wit_stream` payload impls for `ty` if there aren't
+    /// any yet.
+    ///
+    /// These are used for payload types which appear in no function signature,
+    /// so there are no intrinsics to import for them; streams and futures of
+    /// these types only ever exist within this component.
+    pub(crate) fn generate_local_payloads(&mut self, ty: Option<&Type>) {
+        let old_identifier = mem::replace(&mut self.identifier, Identifier::StreamOrFuturePayload);
+        let name = match ty {
+            Some(ty) => self.type_name_owned(ty),
+            None => "()".into(),
+        };
+        self.identifier = old_identifier;
+        let key = self.payload_key(ty);
+        let async_support = self.gen.async_support_path();
+
+        for (payloads, vtable, payload) in [
+            (
+                &mut self.gen.future_payloads,
+                "FutureVtable",
+                "FuturePayload",
+            ),
+            (
+                &mut self.gen.stream_payloads,
+                "StreamVtable",
+                "StreamPayload",
+            ),
+        ] {
+            if payloads.contains_key(&key) {
+                continue;
+            }
+            let ordinal = payloads.len();
+            let code = format!(
+                r#"
+#[doc(hidden)]
+pub mod vtable{ordinal} {{
+    pub static VTABLE: {async_support}::{vtable}<{name}> = {async_support}::{vtable}::LOCAL;
+
+    impl super::{payload} for {name} {{
+        fn new() -> (u32, &'static {async_support}::{vtable}<Self>) {{
+            ({async_support}::new_local_handle(), &VTABLE)
         }}
     }}
 }}
-                        "#,
-                        );
+                "#,
+            );
+            payloads.insert(key.clone(), code);
+        }
+    }
+
+    /// Emits payload impls for every type in `world` which can be used as a
+    /// stream or future payload, not just those appearing in a signature.
+    pub(crate) fn generate_all_payloads(&mut self, world: WorldId) {
+        self.generate_local_payloads(None);
+        for ty in [
+            Type::Bool,
+            Type::U8,
+            Type::U16,
+            Type::U32,
+            Type::U64,
+            Type::S8,
+            Type::S16,
+            Type::S32,
+            Type::S64,
+            Type::F32,
+            Type::F64,
+            Type::Char,
+            Type::String,
+        ] {
+            self.generate_local_payloads(Some(&ty));
+        }
+
+        let ids = self
+            .resolve
+            .types
+            .iter()
+            .map(|(id, _)| id)
+            .filter(|id| self.is_payload(&Type::Id(*id), world))
+            .collect::<Vec<_>>();
+        for id in ids {
+            self.generate_local_payloads(Some(&Type::Id(id)));
+        }
+    }
 
-                        self.gen.stream_payloads.insert(name, code);
+    /// Returns whether `ty` can be sent through a stream or future created by
+    /// this component, which requires that every type it refers to has an
+    /// owned, lifetime-free Rust definition in the generated bindings.
+    fn is_payload(&self, ty: &Type, world: WorldId) -> bool {
+        let id = match ty {
+            Type::Id(id) => *id,
+            _ => return true,
+        };
+        let typedef = &self.resolve.types[id];
+        if typedef.name.is_some() {
+            let in_scope = match typedef.owner {
+                TypeOwner::Interface(i) => self.gen.interface_names.contains_key(&i),
+                TypeOwner::World(w) => w == world,
+                TypeOwner::None => false,
+            };
+            return in_scope
+                && match typedef.kind {
+                    TypeDefKind::Resource => true,
+                    TypeDefKind::Unknown => false,
+                    _ => {
+                        !self.info(id).has_borrow_handle
+                            && self.modes_of(id).iter().any(|(name, mode)| {
+                                *name == self.result_name(id) && mode.lifetime.is_none()
+                            })
                     }
-                }
-                _ => unreachable!(),
+                };
+        }
+        match &typedef.kind {
+            TypeDefKind::Type(t) | TypeDefKind::List(t) | TypeDefKind::Option(t) => {
+                self.is_payload(t, world)
+            }
+            TypeDefKind::Result(r) => [&r.ok, &r.err]
+                .into_iter()
+                .flatten()
+                .all(|t| self.is_payload(t, world)),
+            TypeDefKind::Tuple(t) => t.types.iter().all(|t| self.is_payload(t, world)),
+            TypeDefKind::Future(t) | TypeDefKind::Stream(t) => {
+                t.iter().all(|t| self.is_payload(t, world))
             }
+            TypeDefKind::Handle(Handle::Own(r)) => self.is_payload(&Type::Id(*r), world),
+            TypeDefKind::ErrorContext => true,
+            _ => false,
         }
-
-        self.identifier = old_identifier;
     }
 
     fn generate_guest_import(&mut self, func: &Function, interface: Option<&WorldKey>) {
@@ -952,15 +1796,11 @@ pub mod vtable{ordinal} {{
 
         self.generate_payloads("[import-payload]", func, interface);
 
-        let async_ = match &self.gen.opts.async_ {
-            AsyncConfig::None => false,
-            AsyncConfig::All => true,
-            AsyncConfig::Some { imports, .. } => imports.contains(&if let Some(key) = interface {
-                format!("{}#{}", self.resolve.name_world_key(key), func.name)
-            } else {
-                func.name.clone()
-            }),
-        };
+        let async_ = self
+            .gen
+            .opts
+            .async_
+            .is_async(self.resolve, interface, func, true);
         let mut sig = FnSig {
             async_,
             ..Default::default()
@@ -968,8 +1808,7 @@ pub mod vtable{ordinal} {{
         match func.kind {
             FunctionKind::Freestanding => {}
             FunctionKind::Method(id) | FunctionKind::Static(id) | FunctionKind::Constructor(id) => {
-                let name = self.resolve.types[id].name.as_ref().unwrap();
-                let name = to_upper_camel_case(name);
+                let name = self.type_ident(id);
                 uwriteln!(self.src, "impl {name} {{");
                 sig.use_item_name = true;
                 if let FunctionKind::Method(_) = &func.kind {
@@ -981,6 +1820,22 @@ pub mod vtable{ordinal} {{
         self.src.push_str("#[allow(unused_unsafe, clippy::all)]\n");
         let params = self.print_signature(func, false, &sig);
         self.src.push_str("{\n");
+        if self.gen.opts.mocks {
+            let mut call = self.mock_call(func);
+            if async_ {
+                call.push_str(".await");
+            }
+            uwriteln!(
+                self.src,
+                "\
+                    #[cfg(not(target_arch = \"wasm32\"))]
+                    {{
+                        return {call};
+                    }}
+                    #[cfg(target_arch = \"wasm32\")]\
+                "
+            );
+        }
         self.src.push_str("unsafe {\n");
 
         self.generate_guest_import_body(&self.wasm_import_module, func, params, async_);
@@ -1065,12 +1920,19 @@ pub mod vtable{ordinal} {{
 
         self.generate_payloads("[export-payload]", func, interface);
 
+        // With `dyn_exports` the implementation is looked up at runtime
+        // rather than being a type parameter.
+        let generics = if self.gen.opts.dyn_exports {
+            String::new()
+        } else {
+            format!("<T: {trait_name}>")
+        };
         uwrite!(
             self.src,
             "\
                 #[doc(hidden)]
                 #[allow(non_snake_case)]
-                pub unsafe fn _export_{name_snake}_cabi<T: {trait_name}>\
+                pub unsafe fn _export_{name_snake}_cabi{generics}\
             ",
         );
         let params = self.print_export_sig(func, async_);
@@ -1153,7 +2015,7 @@ pub mod vtable{ordinal} {{
                 "\
                     #[doc(hidden)]
                     #[allow(non_snake_case)]
-                    pub unsafe fn __post_return_{name_snake}<T: {trait_name}>\
+                    pub unsafe fn __post_return_{name_snake}{generics}\
                 "
             );
             let params = self.print_post_return_sig(func);
@@ -1204,9 +2066,14 @@ pub mod vtable{ordinal} {{
 
         let params = self.print_export_sig(func, async_);
         self.push_str(" {\n");
+        let generics = if self.gen.opts.dyn_exports {
+            String::new()
+        } else {
+            format!("::<{ty}>")
+        };
         uwriteln!(
             self.src,
-            "{path_to_self}::_export_{name_snake}_cabi::<{ty}>({})",
+            "{path_to_self}::_export_{name_snake}_cabi{generics}({})",
             params.join(", ")
         );
         self.push_str("}\n");
@@ -1234,7 +2101,7 @@ pub mod vtable{ordinal} {{
             self.src.push_str("{\n");
             uwriteln!(
                 self.src,
-                "{path_to_self}::__post_return_{name_snake}::<{ty}>({})",
+                "{path_to_self}::__post_return_{name_snake}{generics}({})",
                 params.join(", ")
             );
             self.src.push_str("}\n");
@@ -1287,21 +2154,31 @@ pub mod vtable{ordinal} {{
     ) {
         let mut funcs = super::group_by_resource(funcs.clone());
 
-        let root_methods = funcs.remove(&None).unwrap_or(Vec::new());
+        let mut root_methods = funcs.remove(&None).unwrap_or(Vec::new());
 
         let mut extra_trait_items = String::new();
+        let mut has_resources = false;
         let guest_trait = match interface {
             Some((id, _)) => {
                 let path = self.path_to_interface(id).unwrap();
-                for (name, id) in self.resolve.interfaces[id].types.iter() {
+                for id in self.resolve.interfaces[id].types.values() {
                     match self.resolve.types[*id].kind {
                         TypeDefKind::Resource => {}
                         _ => continue,
                     }
-                    let camel = name.to_upper_camel_case();
-                    uwriteln!(extra_trait_items, "type {camel} = Stub;");
+                    has_resources = true;
+                    let camel = self.type_ident(*id);
+                    let mut resource_methods = funcs.remove(&Some(*id)).unwrap_or(Vec::new());
+                    if self.gen.opts.dyn_exports {
+                        let (methods, others) = resource_methods
+                            .into_iter()
+                            .partition(|f| matches!(f.kind, FunctionKind::Method(_)));
+                        resource_methods = methods;
+                        root_methods.extend::<Vec<_>>(others);
+                    } else {
+                        uwriteln!(extra_trait_items, "type {camel} = Stub;");
+                    }
 
-                    let resource_methods = funcs.remove(&Some(*id)).unwrap_or(Vec::new());
                     let trait_name = format!("{path}::Guest{camel}");
                     self.generate_stub_impl(&trait_name, "", &resource_methods, interface);
                 }
@@ -1313,7 +2190,7 @@ pub mod vtable{ordinal} {{
             }
         };
 
-        if !root_methods.is_empty() || !extra_trait_items.is_empty() {
+        if !root_methods.is_empty() || has_resources {
             self.generate_stub_impl(&guest_trait, &extra_trait_items, &root_methods, interface);
         }
     }
@@ -1332,27 +2209,13 @@ pub mod vtable{ordinal} {{
             if self.gen.skip.contains(&func.name) {
                 continue;
             }
-            let async_ = match &self.gen.opts.async_ {
-                AsyncConfig::None => false,
-                AsyncConfig::All => true,
-                AsyncConfig::Some { exports, .. } => {
-                    exports.contains(&if let Some((_, key)) = interface {
-                        format!("{}#{}", self.resolve.name_world_key(key), func.name)
-                    } else {
-                        func.name.clone()
-                    })
-                }
-            };
-            let mut sig = FnSig {
-                async_,
-                use_item_name: true,
-                private: true,
-                ..Default::default()
-            };
-            if let FunctionKind::Method(_) = &func.kind {
-                sig.self_arg = Some("&self".into());
-                sig.self_is_first_param = true;
-            }
+            let async_ = self.gen.opts.async_.is_async(
+                self.resolve,
+                interface.map(|(_, key)| key),
+                func,
+                false,
+            );
+            let (_, sig) = self.export_trait_sig(func, async_);
             self.print_signature(func, true, &sig);
             self.src.push_str("{ unreachable!() }\n");
         }
@@ -1411,10 +2274,30 @@ pub mod vtable{ordinal} {{
         // }
     }
 
+    /// Returns the name of `func` in generated code, which is its item name
+    /// unless it's been renamed.
+    pub(crate) fn func_ident(&self, func: &Function) -> String {
+        let owner = match self.identifier {
+            Identifier::Interface(_, key) => Some(key),
+            _ => None,
+        };
+        match self.gen.renames.item(self.resolve, owner, &func.name) {
+            Some(name) => name.to_string(),
+            None => to_rust_ident(func.item_name()),
+        }
+    }
+
     fn print_signature(&mut self, func: &Function, params_owned: bool, sig: &FnSig) -> Vec<String> {
         let params = self.print_docs_and_params(func, params_owned, sig);
-        if let FunctionKind::Constructor(_) = &func.kind {
-            self.push_str(" -> Self")
+        if let FunctionKind::Constructor(id) = &func.kind {
+            // Constructors taking `&self` are methods of a `dyn_exports`
+            // `Guest` trait rather than the resource's own trait.
+            if sig.self_arg.is_some() {
+                let ty = self.type_name_owned(&Type::Id(*id));
+                self.push_str(&format!(" -> {ty}"));
+            } else {
+                self.push_str(" -> Self")
+            }
         } else {
             self.print_results(&func.results);
         }
@@ -1442,16 +2325,12 @@ pub mod vtable{ordinal} {{
             self.push_str("async ");
         }
         self.push_str("fn ");
-        let func_name = if sig.use_item_name {
-            if let FunctionKind::Constructor(_) = &func.kind {
-                "new"
-            } else {
-                func.item_name()
-            }
-        } else {
-            &func.name
+        let func_name = match (&sig.name, &func.kind) {
+            (Some(name), _) => to_rust_ident(name),
+            (None, FunctionKind::Constructor(_)) if sig.use_item_name => "new".to_string(),
+            (None, _) => self.func_ident(func),
         };
-        self.push_str(&to_rust_ident(func_name));
+        self.push_str(&func_name);
         if let Some(generics) = &sig.generics {
             self.push_str(generics);
         }
@@ -1461,12 +2340,12 @@ pub mod vtable{ordinal} {{
             self.push_str(",");
         }
         let mut params = Vec::new();
-        for (i, (name, param)) in func.params.iter().enumerate() {
+        let names = param_names(func);
+        for (i, ((_, param), name)) in func.params.iter().zip(names).enumerate() {
             if i == 0 && sig.self_is_first_param {
                 params.push("self".to_string());
                 continue;
             }
-            let name = to_rust_ident(name);
             self.push_str(&name);
             self.push_str(": ");
 
@@ -1492,7 +2371,8 @@ pub mod vtable{ordinal} {{
                     Ownership::Borrowing { .. } => TypeOwnershipStyle::Borrowed,
                 }
             };
-            let mode = self.type_mode_for(param, style, "'_");
+            let lifetime = sig.lifetime.unwrap_or("'_");
+            let mode = self.type_mode_for(param, style, lifetime);
             self.print_ty(param, mode);
             self.push_str(",");
 
@@ -1722,13 +2602,6 @@ pub mod vtable{ordinal} {{
         }
     }
 
-    pub(crate) fn type_name_owned_with_id(&mut self, ty: &Type, id: Identifier<'i>) -> String {
-        let old_identifier = mem::replace(&mut self.identifier, id);
-        let name = self.type_name_owned(ty);
-        self.identifier = old_identifier;
-        name
-    }
-
     fn type_name_owned(&mut self, ty: &Type) -> String {
         self.type_name(
             ty,
@@ -1824,10 +2697,15 @@ pub mod vtable{ordinal} {{
     }
 
     fn type_path_with_name(&self, id: TypeId, name: String) -> String {
-        if let TypeOwner::Interface(id) = self.resolve.types[id].owner {
-            if let Some(path) = self.path_to_interface(id) {
-                return format!("{path}::{name}");
+        match self.resolve.types[id].owner {
+            TypeOwner::Interface(id) => {
+                if let Some(path) = self.path_to_interface(id) {
+                    return format!("{path}::{name}");
+                }
             }
+            // World-level types live at the root of the generated bindings.
+            TypeOwner::World(_) => return format!("{}{name}", self.path_to_root()),
+            TypeOwner::None => {}
         }
         name
     }
@@ -1917,6 +2795,85 @@ pub mod vtable{ordinal} {{
         self.push_str(int_repr(repr));
     }
 
+    /// Returns the serde traits to derive for `ty` rendered with `mode`.
+    ///
+    /// Nothing is derived unless the `serde` option is enabled, or for types
+    /// containing handles, and types which borrow their contents can only be
+    /// serialized.
+    fn serde_derives(&self, ty: TypeId, mode: TypeMode) -> Vec<String> {
+        if !self.gen.opts.serde || self.info(ty).has_resource {
+            return Vec::new();
+        }
+        let mut derives = vec!["::serde::Serialize".to_string()];
+        if mode.lifetime.is_none() {
+            derives.push("::serde::Deserialize".to_string());
+        }
+        derives
+    }
+
+    /// Implements serde's traits for flags, which are serialized as a list of
+    /// the names of the flags which are set.
+    fn print_flags_serde(&mut self, id: TypeId, flags: &Flags) {
+        let name = self.type_ident(id);
+        let string = self.path_to_string();
+        let mut serialize = String::new();
+        let mut deserialize = String::new();
+        for (flag, constant) in flags.flags.iter().zip(flag_names(flags)) {
+            let flag = &flag.name;
+            uwriteln!(
+                serialize,
+                "if self.contains({name}::{constant}) {{ seq.serialize_element(\"{flag}\")?; }}"
+            );
+            uwriteln!(deserialize, "\"{flag}\" => {name}::{constant},");
+        }
+        let names = flags
+            .flags
+            .iter()
+            .map(|f| format!("\"{}\"", f.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        uwriteln!(
+            self.src,
+            r#"
+impl ::serde::Serialize for {name} {{
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error> {{
+        use ::serde::ser::SerializeSeq;
+        let mut seq = serializer.serialize_seq(None)?;
+        {serialize}
+        seq.end()
+    }}
+}}
+
+impl<'de> ::serde::Deserialize<'de> for {name} {{
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::core::result::Result<Self, D::Error> {{
+        struct Visitor;
+
+        impl<'de> ::serde::de::Visitor<'de> for Visitor {{
+            type Value = {name};
+
+            fn expecting(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {{
+                f.write_str("a list of flag names")
+            }}
+
+            fn visit_seq<A: ::serde::de::SeqAccess<'de>>(self, mut seq: A) -> ::core::result::Result<{name}, A::Error> {{
+                let mut flags = {name}::empty();
+                while let Some(flag) = seq.next_element::<{string}>()? {{
+                    flags |= match flag.as_str() {{
+                        {deserialize}
+                        other => return Err(::serde::de::Error::unknown_variant(other, &[{names}])),
+                    }};
+                }}
+                Ok(flags)
+            }}
+        }}
+
+        deserializer.deserialize_seq(Visitor)
+    }}
+}}
+            "#
+        );
+    }
+
     fn modes_of(&self, ty: TypeId) -> Vec<(String, TypeMode)> {
         let info = self.info(ty);
         let mut result = Vec::new();
@@ -1955,6 +2912,7 @@ pub mod vtable{ordinal} {{
 
     fn print_typedef_record(&mut self, id: TypeId, record: &Record, docs: &Docs) {
         let info = self.info(id);
+        let field_names = field_names(record);
         // We use a BTree set to make sure we don't have any duplicates and we have a stable order
         let additional_derives: BTreeSet<String> = self
             .gen
@@ -1966,12 +2924,14 @@ pub mod vtable{ordinal} {{
         for (name, mode) in self.modes_of(id) {
             self.rustdoc(docs);
             let mut derives = additional_derives.clone();
+            let serde = self.serde_derives(id, mode);
             if info.is_copy() {
                 self.push_str("#[repr(C)]\n");
                 derives.extend(["Copy", "Clone"].into_iter().map(|s| s.to_string()));
             } else if info.is_clone() {
                 derives.insert("Clone".to_string());
             }
+            derives.extend(serde.iter().cloned());
             if !derives.is_empty() {
                 self.push_str("#[derive(");
                 self.push_str(&derives.into_iter().collect::<Vec<_>>().join(", "));
@@ -1980,10 +2940,13 @@ pub mod vtable{ordinal} {{
             self.push_str(&format!("pub struct {}", name));
             self.print_generics(mode.lifetime);
             self.push_str(" {\n");
-            for field in record.fields.iter() {
+            for (field, field_name) in record.fields.iter().zip(&field_names) {
                 self.rustdoc(&field.docs);
+                if !serde.is_empty() {
+                    uwriteln!(self.src, "#[serde(rename = \"{}\")]", field.name);
+                }
                 self.push_str("pub ");
-                self.push_str(&to_rust_ident(&field.name));
+                self.push_str(field_name);
                 self.push_str(": ");
                 let mode = self.filter_mode(&field.ty, mode);
                 self.print_ty(&field.ty, mode);
@@ -2001,12 +2964,8 @@ pub mod vtable{ordinal} {{
                 "fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {\n",
             );
             self.push_str(&format!("f.debug_struct(\"{}\")", name));
-            for field in record.fields.iter() {
-                self.push_str(&format!(
-                    ".field(\"{}\", &self.{})",
-                    field.name,
-                    to_rust_ident(&field.name)
-                ));
+            for (field, field_name) in record.fields.iter().zip(&field_names) {
+                self.push_str(&format!(".field(\"{}\", &self.{field_name})", field.name));
             }
             self.push_str(".finish()\n");
             self.push_str("}\n");
@@ -2044,7 +3003,7 @@ pub mod vtable{ordinal} {{
             variant
                 .cases
                 .iter()
-                .map(|c| (c.name.to_upper_camel_case(), &c.docs, c.ty.as_ref())),
+                .map(|c| (c.name.as_str(), &c.docs, c.ty.as_ref())),
             docs,
         );
     }
@@ -2052,12 +3011,13 @@ pub mod vtable{ordinal} {{
     fn print_rust_enum<'b>(
         &mut self,
         id: TypeId,
-        cases: impl IntoIterator<Item = (String, &'b Docs, Option<&'b Type>)> + Clone,
+        cases: impl IntoIterator<Item = (&'b str, &'b Docs, Option<&'b Type>)> + Clone,
         docs: &Docs,
     ) where
         Self: Sized,
     {
         let info = self.info(id);
+        let case_names = case_names(cases.clone().into_iter().map(|(name, ..)| name));
         // We use a BTree set to make sure we don't have any duplicates and have a stable order
         let additional_derives: BTreeSet<String> = self
             .gen
@@ -2069,11 +3029,13 @@ pub mod vtable{ordinal} {{
         for (name, mode) in self.modes_of(id) {
             self.rustdoc(docs);
             let mut derives = additional_derives.clone();
+            let serde = self.serde_derives(id, mode);
             if info.is_copy() {
                 derives.extend(["Copy", "Clone"].into_iter().map(|s| s.to_string()));
             } else if info.is_clone() {
                 derives.insert("Clone".to_string());
             }
+            derives.extend(serde.iter().cloned());
             if !derives.is_empty() {
                 self.push_str("#[derive(");
                 self.push_str(&derives.into_iter().collect::<Vec<_>>().join(", "));
@@ -2082,9 +3044,13 @@ pub mod vtable{ordinal} {{
             self.push_str(&format!("pub enum {name}"));
             self.print_generics(mode.lifetime);
             self.push_str(" {\n");
-            for (case_name, docs, payload) in cases.clone() {
+            for ((wit_name, docs, payload), case_name) in cases.clone().into_iter().zip(&case_names)
+            {
                 self.rustdoc(docs);
-                self.push_str(&case_name);
+                if !serde.is_empty() {
+                    uwriteln!(self.src, "#[serde(rename = \"{wit_name}\")]");
+                }
+                self.push_str(case_name);
                 if let Some(ty) = payload {
                     self.push_str("(");
                     let mode = self.filter_mode(ty, mode);
@@ -2101,7 +3067,8 @@ pub mod vtable{ordinal} {{
                 cases
                     .clone()
                     .into_iter()
-                    .map(|(name, _docs, ty)| (name, ty)),
+                    .zip(case_names.iter().cloned())
+                    .map(|((_name, _docs, ty), case_name)| (case_name, ty)),
             );
 
             if info.error {
@@ -2197,7 +3164,6 @@ pub mod vtable{ordinal} {{
     fn print_typedef_enum(
         &mut self,
         id: TypeId,
-        name: &str,
         enum_: &Enum,
         docs: &Docs,
         attrs: &[String],
@@ -2207,7 +3173,8 @@ pub mod vtable{ordinal} {{
     {
         let info = self.info(id);
 
-        let name = to_upper_camel_case(name);
+        let name = self.type_ident(id);
+        let case_names = case_names(enum_.cases.iter().map(|c| c.name.as_str()));
         self.rustdoc(docs);
         for attr in attrs {
             self.push_str(&format!("{}\n", attr));
@@ -2228,14 +3195,19 @@ pub mod vtable{ordinal} {{
                 .into_iter()
                 .map(|s| s.to_string()),
         );
+        let serde = self.serde_derives(id, TypeMode::owned());
+        derives.extend(serde.iter().cloned());
         self.push_str("#[derive(");
         self.push_str(&derives.into_iter().collect::<Vec<_>>().join(", "));
         self.push_str(")]\n");
         self.push_str(&format!("pub enum {name} {{\n"));
-        for case in enum_.cases.iter() {
+        for (case, case_name) in enum_.cases.iter().zip(&case_names) {
             self.rustdoc(&case.docs);
+            if !serde.is_empty() {
+                uwriteln!(self.src, "#[serde(rename = \"{}\")]", case.name);
+            }
             self.push_str(&case_attr(case));
-            self.push_str(&case.name.to_upper_camel_case());
+            self.push_str(case_name);
             self.push_str(",\n");
         }
         self.push_str("}\n");
@@ -2249,10 +3221,10 @@ pub mod vtable{ordinal} {{
 
             self.push_str("pub fn name(&self) -> &'static str {\n");
             self.push_str("match self {\n");
-            for case in enum_.cases.iter() {
+            for (case, case_name) in enum_.cases.iter().zip(&case_names) {
                 self.push_str(&name);
                 self.push_str("::");
-                self.push_str(&case.name.to_upper_camel_case());
+                self.push_str(case_name);
                 self.push_str(" => \"");
                 self.push_str(case.name.as_str());
                 self.push_str("\",\n");
@@ -2262,10 +3234,10 @@ pub mod vtable{ordinal} {{
 
             self.push_str("pub fn message(&self) -> &'static str {\n");
             self.push_str("match self {\n");
-            for case in enum_.cases.iter() {
+            for (case, case_name) in enum_.cases.iter().zip(&case_names) {
                 self.push_str(&name);
                 self.push_str("::");
-                self.push_str(&case.name.to_upper_camel_case());
+                self.push_str(case_name);
                 self.push_str(" => \"");
                 if let Some(contents) = &case.docs.contents {
                     self.push_str(contents.trim());
@@ -2311,10 +3283,7 @@ pub mod vtable{ordinal} {{
             self.print_rust_enum_debug(
                 TypeMode::owned(),
                 &name,
-                enum_
-                    .cases
-                    .iter()
-                    .map(|c| (c.name.to_upper_camel_case(), None)),
+                case_names.into_iter().map(|case_name| (case_name, None)),
             )
         }
     }
@@ -2329,10 +3298,10 @@ pub mod vtable{ordinal} {{
             self.push_str(";\n");
         }
 
-        if self.is_exported_resource(id) {
+        // Host bindings pass every borrow as the resource's handle.
+        if self.is_exported_resource(id) && !self.gen.opts.host {
             self.rustdoc(docs);
-            let name = self.resolve.types[id].name.as_ref().unwrap();
-            let name = name.to_upper_camel_case();
+            let name = self.type_ident(id);
             self.push_str(&format!("pub type {name}Borrow<'a>"));
             self.push_str(" = ");
             self.print_ty(ty, TypeMode::owned());
@@ -2341,9 +3310,19 @@ pub mod vtable{ordinal} {{
         }
     }
 
+    /// Returns the name of the named type `id` in generated code, which is
+    /// its name in upper camel case unless it's been renamed.
+    pub(crate) fn type_ident(&self, id: TypeId) -> String {
+        let world = self.gen.world.unwrap();
+        match self.gen.renames.ty(self.resolve, world, id) {
+            Some(name) => name.to_string(),
+            None => to_upper_camel_case(self.resolve.types[id].name.as_ref().unwrap()),
+        }
+    }
+
     fn param_name(&self, ty: TypeId) -> String {
         let info = self.info(ty);
-        let name = to_upper_camel_case(self.resolve.types[ty].name.as_ref().unwrap());
+        let name = self.type_ident(ty);
         if self.uses_two_names(&info) {
             format!("{}Param", name)
         } else {
@@ -2353,7 +3332,7 @@ pub mod vtable{ordinal} {{
 
     fn result_name(&self, ty: TypeId) -> String {
         let info = self.info(ty);
-        let name = to_upper_camel_case(self.resolve.types[ty].name.as_ref().unwrap());
+        let name = self.type_ident(ty);
         if self.uses_two_names(&info) {
             format!("{}Result", name)
         } else {
@@ -2450,7 +3429,13 @@ pub mod vtable{ordinal} {{
     }
 
     fn info(&self, ty: TypeId) -> TypeInfo {
-        self.gen.types.get(ty)
+        let mut info = self.gen.types.get(ty);
+        // Host bindings pass borrowed handles by value, so they don't need a
+        // lifetime.
+        if self.gen.opts.host {
+            info.has_borrow_handle = false;
+        }
+        info
     }
 
     fn print_borrowed_str(&mut self, lifetime: &'static str) {
@@ -2529,6 +3514,14 @@ pub mod vtable{ordinal} {{
         self.path_from_runtime_module(RuntimeItem::BoxType, "Box")
     }
 
+    fn path_to_guest_cell(&mut self) -> String {
+        self.path_from_runtime_module(RuntimeItem::GuestCell, "GuestCell")
+    }
+
+    fn path_to_mocks(&mut self) -> String {
+        self.path_from_runtime_module(RuntimeItem::Mocks, "mocks")
+    }
+
     pub fn path_to_std_alloc_module(&mut self) -> String {
         self.path_from_runtime_module(RuntimeItem::StdAllocModule, "alloc")
     }
@@ -2558,9 +3551,37 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.print_typedef_record(id, record, docs);
     }
 
-    fn type_resource(&mut self, _id: TypeId, name: &str, docs: &Docs) {
+    fn type_resource(&mut self, id: TypeId, name: &str, docs: &Docs) {
         self.rustdoc(docs);
-        let camel = to_upper_camel_case(name);
+        let camel = self.type_ident(id);
+        if self.gen.opts.host {
+            // Host bindings only see the handles the embedder's runtime hands
+            // out, which carry no destructor of their own.
+            uwriteln!(
+                self.src,
+                r#"
+                    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+                    #[repr(transparent)]
+                    pub struct {camel} {{
+                        handle: u32,
+                    }}
+
+                    impl {camel} {{
+                        /// Creates a `{camel}` from its handle, an index into
+                        /// a guest's table of handles.
+                        pub fn from_handle(handle: u32) -> Self {{
+                            Self {{ handle }}
+                        }}
+
+                        /// Returns the handle of this `{camel}`.
+                        pub fn handle(&self) -> u32 {{
+                            self.handle
+                        }}
+                    }}
+                "#
+            );
+            return;
+        }
         let resource = self.path_to_resource();
 
         let wasm_import_module = if self.in_import {
@@ -2595,11 +3616,160 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
                     }}
                 "#
             );
+            if self.gen.opts.mocks {
+                self.generate_resource_mock(id, name);
+            }
             self.wasm_import_module.to_string()
+        } else if self.gen.opts.dyn_exports {
+            let module = match self.identifier {
+                Identifier::Interface(_, key) => self.resolve.name_world_key(key),
+                Identifier::World(_) => "$root".to_string(),
+                Identifier::StreamOrFuturePayload => unreachable!(),
+            };
+            let box_path = self.path_to_box();
+            uwriteln!(
+                self.src,
+                r#"
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct {camel} {{
+    handle: {resource}<{camel}>,
+}}
+
+type _{camel}Rep = Option<{box_path}<dyn Guest{camel}>>;
+
+impl {camel} {{
+    /// Creates a new resource from the specified representation.
+    ///
+    /// This function will create a new resource handle by moving `val` onto
+    /// the heap and then passing that heap pointer to the component model to
+    /// create a handle. The owned handle is then returned as `{camel}`.
+    pub fn new<T: Guest{camel}>(val: T) -> Self {{
+        let val: _{camel}Rep = Some({box_path}::new(val));
+        let ptr: *mut _{camel}Rep =
+            {box_path}::into_raw({box_path}::new(val));
+        unsafe {{
+            Self::from_handle(Self::_resource_new(ptr.cast()))
+        }}
+    }}
+
+    /// Gets access to the implementation of this resource.
+    pub fn get(&self) -> &dyn Guest{camel} {{
+        let ptr = unsafe {{ &*self.as_ptr() }};
+        &**ptr.as_ref().unwrap()
+    }}
+
+    /// Gets mutable access to the implementation of this resource.
+    pub fn get_mut(&mut self) -> &mut dyn Guest{camel} {{
+        let ptr = unsafe {{ &mut *self.as_ptr() }};
+        &mut **ptr.as_mut().unwrap()
+    }}
+
+    /// Consumes this resource and returns its implementation.
+    pub fn into_inner(self) -> {box_path}<dyn Guest{camel}> {{
+        let ptr = unsafe {{ &mut *self.as_ptr() }};
+        ptr.take().unwrap()
+    }}
+
+    #[doc(hidden)]
+    pub unsafe fn from_handle(handle: u32) -> Self {{
+        Self {{
+            handle: {resource}::from_handle(handle),
+        }}
+    }}
+
+    #[doc(hidden)]
+    pub fn take_handle(&self) -> u32 {{
+        {resource}::take_handle(&self.handle)
+    }}
+
+    #[doc(hidden)]
+    pub fn handle(&self) -> u32 {{
+        {resource}::handle(&self.handle)
+    }}
+
+    #[doc(hidden)]
+    pub unsafe fn dtor(handle: *mut u8) {{
+        let _ = {box_path}::from_raw(handle as *mut _{camel}Rep);
+    }}
+
+    fn as_ptr(&self) -> *mut _{camel}Rep {{
+        Self::_resource_rep(self.handle()).cast()
+    }}
+
+    unsafe fn _resource_new(val: *mut u8) -> u32 {{
+        #[cfg(not(target_arch = "wasm32"))]
+        {{
+            let _ = val;
+            unreachable!();
+        }}
+
+        #[cfg(target_arch = "wasm32")]
+        {{
+            #[link(wasm_import_module = "[export]{module}")]
+            extern "C" {{
+                #[link_name = "[resource-new]{name}"]
+                fn new(_: *mut u8) -> u32;
+            }}
+            new(val)
+        }}
+    }}
+
+    fn _resource_rep(handle: u32) -> *mut u8 {{
+        #[cfg(not(target_arch = "wasm32"))]
+        {{
+            let _ = handle;
+            unreachable!();
+        }}
+
+        #[cfg(target_arch = "wasm32")]
+        {{
+            #[link(wasm_import_module = "[export]{module}")]
+            extern "C" {{
+                #[link_name = "[resource-rep]{name}"]
+                fn rep(_: u32) -> *mut u8;
+            }}
+            unsafe {{
+                rep(handle)
+            }}
+        }}
+    }}
+}}
+
+/// A borrowed version of [`{camel}`] which represents a borrowed value
+/// with the lifetime `'a`.
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct {camel}Borrow<'a> {{
+    rep: *mut u8,
+    _marker: core::marker::PhantomData<&'a {camel}>,
+}}
+
+impl<'a> {camel}Borrow<'a>{{
+    #[doc(hidden)]
+    pub unsafe fn lift(rep: usize) -> Self {{
+        Self {{
+            rep: rep as *mut u8,
+            _marker: core::marker::PhantomData,
+        }}
+    }}
+
+    /// Gets access to the implementation of this resource.
+    pub fn get(&self) -> &dyn Guest{camel} {{
+        let ptr = unsafe {{ &*self.rep.cast::<_{camel}Rep>() }};
+        &**ptr.as_ref().unwrap()
+    }}
+
+    // NB: mutable access is not allowed due to the component model allowing
+    // multiple borrows of the same resource.
+}}
+                "#
+            );
+            format!("[export]{module}")
         } else {
             let module = match self.identifier {
                 Identifier::Interface(_, key) => self.resolve.name_world_key(key),
-                Identifier::World(_) => unimplemented!("resource exports from worlds"),
+                Identifier::World(_) => "$root".to_string(),
                 Identifier::StreamOrFuturePayload => unreachable!(),
             };
             let box_path = self.path_to_box();
@@ -2731,6 +3901,12 @@ impl<'a> {camel}Borrow<'a>{{
             format!("[export]{module}")
         };
 
+        let native_drop = if self.in_import && self.gen.opts.mocks {
+            let table = self.mock_table_name(id);
+            format!("{{ let _ = {table}.try_with(|table| table.remove(_handle)); }}")
+        } else {
+            "unreachable!();".to_string()
+        };
         let wasm_resource = self.path_to_wasm_resource();
         uwriteln!(
             self.src,
@@ -2739,7 +3915,7 @@ impl<'a> {camel}Borrow<'a>{{
                      #[inline]
                      unsafe fn drop(_handle: u32) {{
                          #[cfg(not(target_arch = "wasm32"))]
-                         unreachable!();
+                         {native_drop}
 
                          #[cfg(target_arch = "wasm32")]
                          {{
@@ -2772,7 +3948,7 @@ impl<'a> {camel}Borrow<'a>{{
         }
     }
 
-    fn type_flags(&mut self, _id: TypeId, name: &str, flags: &Flags, docs: &Docs) {
+    fn type_flags(&mut self, id: TypeId, _name: &str, flags: &Flags, docs: &Docs) {
         self.src.push_str(&format!(
             "{bitflags}::bitflags! {{\n",
             bitflags = self.gen.bitflags_path()
@@ -2781,18 +3957,19 @@ impl<'a> {camel}Borrow<'a>{{
         let repr = RustFlagsRepr::new(flags);
         self.src.push_str(&format!(
             "#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]\npub struct {}: {repr} {{\n",
-            name.to_upper_camel_case(),
+            self.type_ident(id),
         ));
-        for (i, flag) in flags.flags.iter().enumerate() {
+        for (i, (flag, constant)) in flags.flags.iter().zip(flag_names(flags)).enumerate() {
             self.rustdoc(&flag.docs);
-            self.src.push_str(&format!(
-                "const {} = 1 << {};\n",
-                flag.name.to_shouty_snake_case(),
-                i,
-            ));
+            self.src
+                .push_str(&format!("const {constant} = 1 << {i};\n"));
         }
         self.src.push_str("}\n");
         self.src.push_str("}\n");
+
+        if !self.serde_derives(id, TypeMode::owned()).is_empty() {
+            self.print_flags_serde(id, flags);
+        }
     }
 
     fn type_variant(&mut self, id: TypeId, _name: &str, variant: &Variant, docs: &Docs) {
@@ -2807,14 +3984,14 @@ impl<'a> {camel}Borrow<'a>{{
         self.print_typedef_result(id, result, docs);
     }
 
-    fn type_enum(&mut self, id: TypeId, name: &str, enum_: &Enum, docs: &Docs) {
-        self.print_typedef_enum(id, name, enum_, docs, &[], Box::new(|_| String::new()));
+    fn type_enum(&mut self, id: TypeId, _name: &str, enum_: &Enum, docs: &Docs) {
+        self.print_typedef_enum(id, enum_, docs, &[], Box::new(|_| String::new()));
 
-        let name = to_upper_camel_case(name);
+        let name = self.type_ident(id);
         let mut cases = String::new();
         let repr = int_repr(enum_.tag());
-        for (i, case) in enum_.cases.iter().enumerate() {
-            let case = case.name.to_upper_camel_case();
+        let case_names = case_names(enum_.cases.iter().map(|c| c.name.as_str()));
+        for (i, case) in case_names.iter().enumerate() {
             cases.push_str(&format!("{i} => {name}::{case},\n"));
         }
         uwriteln!(
@@ -2852,15 +4029,15 @@ impl<'a> {camel}Borrow<'a>{{
         }
     }
 
-    fn type_future(&mut self, _id: TypeId, name: &str, ty: &Option<Type>, docs: &Docs) {
-        let async_support = self.gen.async_support_path();
+    fn type_future(&mut self, id: TypeId, _name: &str, ty: &Option<Type>, docs: &Docs) {
+        let async_support = self.path_to_async_types();
         let mode = TypeMode {
             style: TypeOwnershipStyle::Owned,
             lists_borrowed: false,
             lifetime: None,
         };
         self.rustdoc(docs);
-        self.push_str(&format!("pub type {}", name.to_upper_camel_case()));
+        self.push_str(&format!("pub type {}", self.type_ident(id)));
         self.print_generics(mode.lifetime);
         self.push_str(" = ");
         self.push_str(&format!("{async_support}::FutureReader<"));
@@ -2869,15 +4046,15 @@ impl<'a> {camel}Borrow<'a>{{
         self.push_str(";\n");
     }
 
-    fn type_stream(&mut self, _id: TypeId, name: &str, ty: &Option<Type>, docs: &Docs) {
-        let async_support = self.gen.async_support_path();
+    fn type_stream(&mut self, id: TypeId, _name: &str, ty: &Option<Type>, docs: &Docs) {
+        let async_support = self.path_to_async_types();
         let mode = TypeMode {
             style: TypeOwnershipStyle::Owned,
             lists_borrowed: false,
             lifetime: None,
         };
         self.rustdoc(docs);
-        self.push_str(&format!("pub type {}", name.to_upper_camel_case()));
+        self.push_str(&format!("pub type {}", self.type_ident(id)));
         self.print_generics(mode.lifetime);
         self.push_str(" = ");
         self.push_str(&format!("{async_support}::StreamReader<"));
@@ -2886,18 +4063,18 @@ impl<'a> {camel}Borrow<'a>{{
         self.push_str(";\n");
     }
 
-    fn type_error_context(&mut self, _id: TypeId, name: &str, docs: &Docs) {
-        let async_support = self.gen.async_support_path();
+    fn type_error_context(&mut self, id: TypeId, _name: &str, docs: &Docs) {
+        let async_support = self.path_to_async_types();
         self.rustdoc(docs);
-        self.push_str(&format!("pub type {} = ", name.to_upper_camel_case()));
+        self.push_str(&format!("pub type {} = ", self.type_ident(id)));
         self.push_str(&format!("{async_support}::ErrorContext"));
         self.push_str(";\n");
     }
 
-    fn type_builtin(&mut self, _id: TypeId, name: &str, ty: &Type, docs: &Docs) {
+    fn type_builtin(&mut self, id: TypeId, _name: &str, ty: &Type, docs: &Docs) {
         self.rustdoc(docs);
         self.src
-            .push_str(&format!("pub type {}", name.to_upper_camel_case()));
+            .push_str(&format!("pub type {}", self.type_ident(id)));
         self.src.push_str(" = ");
         self.print_ty(ty, TypeMode::owned());
         self.src.push_str(";\n");
@@ -2924,15 +4101,14 @@ impl<'a, 'b> wit_bindgen_core::AnonymousTypeGenerator<'a> for AnonTypeGenerator<
             Handle::Own(ty) => {
                 self.interface.print_ty(&Type::Id(*ty), self.mode);
             }
+            Handle::Borrow(ty) if self.interface.gen.opts.host => {
+                self.interface.print_ty(&Type::Id(*ty), self.mode);
+            }
             Handle::Borrow(ty) => {
                 assert!(self.mode.lifetime.is_some());
                 let lt = self.mode.lifetime.unwrap();
                 if self.interface.is_exported_resource(*ty) {
-                    let camel = self.resolve.types[*ty]
-                        .name
-                        .as_deref()
-                        .unwrap()
-                        .to_upper_camel_case();
+                    let camel = self.interface.type_ident(*ty);
                     let name = format!("{camel}Borrow");
                     self.interface
                         .push_str(&self.interface.type_path_with_name(*ty, name));
@@ -2983,7 +4159,7 @@ impl<'a, 'b> wit_bindgen_core::AnonymousTypeGenerator<'a> for AnonTypeGenerator<
     }
 
     fn anonymous_type_future(&mut self, _id: TypeId, ty: &Option<Type>, _docs: &Docs) {
-        let async_support = self.interface.gen.async_support_path();
+        let async_support = self.interface.path_to_async_types();
         let mode = TypeMode {
             style: TypeOwnershipStyle::Owned,
             lists_borrowed: false,
@@ -2996,7 +4172,7 @@ impl<'a, 'b> wit_bindgen_core::AnonymousTypeGenerator<'a> for AnonTypeGenerator<
     }
 
     fn anonymous_type_stream(&mut self, _id: TypeId, ty: &Option<Type>, _docs: &Docs) {
-        let async_support = self.interface.gen.async_support_path();
+        let async_support = self.interface.path_to_async_types();
         let mode = TypeMode {
             style: TypeOwnershipStyle::Owned,
             lists_borrowed: false,
@@ -3009,7 +4185,7 @@ impl<'a, 'b> wit_bindgen_core::AnonymousTypeGenerator<'a> for AnonTypeGenerator<
     }
 
     fn anonymous_type_error_context(&mut self) {
-        let async_support = self.interface.gen.async_support_path();
+        let async_support = self.interface.path_to_async_types();
         self.interface
             .push_str(&format!("{async_support}::ErrorContext"));
     }
@@ -8,11 +8,14 @@ use std::mem;
 use std::str::FromStr;
 use wit_bindgen_core::abi::{Bitcast, WasmType};
 use wit_bindgen_core::{
-    name_package_module, uwrite, uwriteln, wit_parser::*, Files, InterfaceGenerator as _, Source,
-    Types, WorldGenerator,
+    name_package_module, uwrite, uwriteln, wit_parser::*, Case, Files, InterfaceGenerator as _,
+    Keywords, Renames, Source, Types, WorldGenerator,
 };
 
+pub use wit_bindgen_core::AsyncConfig;
+
 mod bindgen;
+mod host;
 mod interface;
 
 struct InterfaceName {
@@ -42,6 +45,7 @@ struct RustWasm {
     // are required to be used.
     generated_interfaces: HashSet<String>,
     world: Option<WorldId>,
+    renames: Renames,
 
     rt_module: IndexSet<RuntimeItem>,
     export_macros: Vec<(String, String)>,
@@ -51,6 +55,10 @@ struct RustWasm {
 
     future_payloads: IndexMap<String, String>,
     stream_payloads: IndexMap<String, String>,
+
+    /// Why host bindings can't be generated for the world, reported by the
+    /// first generation step which can fail.
+    host_error: Option<anyhow::Error>,
 }
 
 #[derive(Default)]
@@ -102,6 +110,8 @@ enum RuntimeItem {
     AsF64,
     ResourceType,
     BoxType,
+    GuestCell,
+    Mocks,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -122,52 +132,6 @@ fn parse_with(s: &str) -> Result<(String, WithOption), String> {
     Ok((k.to_string(), v))
 }
 
-#[derive(Default, Debug, Clone)]
-pub enum AsyncConfig {
-    #[default]
-    None,
-    Some {
-        imports: Vec<String>,
-        exports: Vec<String>,
-    },
-    All,
-}
-
-#[cfg(feature = "clap")]
-fn parse_async(s: &str) -> Result<AsyncConfig, String> {
-    Ok(match s {
-        "none" => AsyncConfig::None,
-        "all" => AsyncConfig::All,
-        _ => {
-            if let Some(values) = s.strip_prefix("some=") {
-                let mut imports = Vec::new();
-                let mut exports = Vec::new();
-                for value in values.split(',') {
-                    let error = || {
-                        Err(format!(
-                            "expected string of form `import:<name>` or `export:<name>`; got `{value}`"
-                        ))
-                    };
-                    if let Some((k, v)) = value.split_once(":") {
-                        match k {
-                            "import" => imports.push(v.into()),
-                            "export" => exports.push(v.into()),
-                            _ => return error(),
-                        }
-                    } else {
-                        return error();
-                    }
-                }
-                AsyncConfig::Some { imports, exports }
-            } else {
-                return Err(format!(
-                    "expected string of form `none`, `all`, or `some=<value>[,<value>...]`; got `{s}`"
-                ));
-            }
-        }
-    })
-}
-
 #[derive(Default, Debug, Clone)]
 #[cfg_attr(feature = "clap", derive(clap::Args))]
 pub struct Opts {
@@ -237,6 +201,36 @@ pub struct Opts {
     #[cfg_attr(feature = "clap", arg(long = "additional_derive_attribute", short = 'd', default_values_t = Vec::<String>::new()))]
     pub additional_derive_attributes: Vec<String>,
 
+    /// Whether to implement `serde::Serialize` and `serde::Deserialize` for
+    /// generated types, which requires the crate using the bindings to
+    /// depend on `serde` with its `derive` feature.
+    ///
+    /// WIT values are mapped to the serde data model, and so for example to
+    /// JSON, as follows:
+    ///
+    /// * records are maps from WIT field names to values, e.g.
+    ///   `{"field-name": 1}`,
+    /// * variants are externally tagged by their WIT case name, with cases
+    ///   without a payload being just the name, e.g. `{"case-name": 1}` and
+    ///   `"empty-case"`,
+    /// * enums are their WIT case name, e.g. `"case-name"`,
+    /// * flags are a list of the names of the flags which are set, e.g.
+    ///   `["read", "write"]`,
+    /// * lists and tuples are sequences, and `list<u8>` is a sequence of
+    ///   numbers,
+    /// * options are `null` or their payload, so `option<option<T>>` can't
+    ///   represent `some(none)`,
+    /// * results are `{"Ok": ...}` or `{"Err": ...}`, with `null` in place
+    ///   of a missing payload,
+    /// * `char` and `string` are strings.
+    ///
+    /// Types which contain resources, handles, streams, futures or
+    /// `error-context`s can't be serialized and don't implement either
+    /// trait. Types with borrowed contents, generated for imports when using
+    /// `Ownership::Borrowing`, only implement `Serialize`.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub serde: bool,
+
     /// Remapping of interface names to rust module names.
     ///
     /// Argument must be of the form `k=v` and this option can be passed
@@ -245,6 +239,15 @@ pub struct Opts {
     #[cfg_attr(feature = "clap", arg(long, value_parser = parse_with, value_delimiter = ','))]
     pub with: Vec<(String, WithOption)>,
 
+    /// Rename the interface or item `K` to `V` in the generated source code.
+    ///
+    /// `K` is either an interface such as `foo:bar/baz`, an item within an
+    /// interface such as `foo:bar/baz.qux`, or an item defined in the world.
+    /// A renamed interface only changes the name of its own module, not of
+    /// the namespace and package modules it's nested in.
+    #[cfg_attr(feature = "clap", arg(long, name = "K=V", value_parser = Renames::parse))]
+    pub rename: Vec<(String, String)>,
+
     /// Indicates that all interfaces not specified in `with` should be
     /// generated.
     #[cfg_attr(feature = "clap", arg(long))]
@@ -278,6 +281,67 @@ pub struct Opts {
     #[cfg_attr(feature = "clap", arg(long))]
     pub generate_unused_types: bool,
 
+    /// Whether to generate `wit_future`/`wit_stream` payload impls for every
+    /// type which can be a payload, rather than only for payloads appearing
+    /// in function signatures (false by default).
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub generate_all_payloads: bool,
+
+    /// Whether to dispatch exports through a trait object registered at
+    /// runtime with a generated `set_guest` function, instead of through a
+    /// type passed to the `export!` macro.
+    ///
+    /// In this mode the `Guest` traits take `&self`, exported resources are
+    /// backed by `Box<dyn GuestX>`, resource constructors and static
+    /// functions become methods of the `Guest` trait, and the exported
+    /// symbols are emitted directly without an `export!` macro.
+    ///
+    /// Except on `wasm` targets without threads, the registered
+    /// implementation is tied to the thread which set it, which uses the
+    /// standard library. With `std_feature` that code is only compiled with
+    /// the `std` feature, and building for such targets without it is an
+    /// error.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub dyn_exports: bool,
+
+    /// Whether to route calls to imported functions through generated mock
+    /// traits when not compiling to `wasm32`, so guest code can be tested
+    /// natively with `cargo test`.
+    ///
+    /// Each module with imports gets a `Mock` trait with a method per
+    /// function, and a `set_mock` function setting the implementation used
+    /// on the current thread. Imported resources get a `MockX` trait with
+    /// their methods, and instances are created from an implementation with
+    /// `X::from_mock`. Resource constructors and static functions are
+    /// methods of `Mock` named `x_new` and `x_{name}`. Mocks of async
+    /// imports return a boxed future borrowing from the mock and the
+    /// arguments.
+    ///
+    /// Mocks use the standard library, so `std` must be available when not
+    /// compiling to `wasm32`.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub mocks: bool,
+
+    /// Whether to generate bindings for a host embedding components rather
+    /// than for a guest.
+    ///
+    /// Imported interfaces get a `Host` trait to implement and a `dispatch`
+    /// function calling it with core wasm values, and exported functions
+    /// become functions calling into a guest. These work against the
+    /// `Memory`, `Realloc` and `Instance` traits of the generated `host`
+    /// module, which can be implemented for any wasm runtime. Flags use the
+    /// `bitflags` crate unless `bitflags_path` is set.
+    ///
+    /// Resources, futures, streams and `error-context`s are represented by
+    /// their handles, indices into tables managed by the embedder's runtime,
+    /// and borrows of resources are passed as handles too. Imported
+    /// resources get a `HostX` trait with their functions and `drop`, and
+    /// exported resources get methods calling into the guest. Async imports run to completion before `dispatch` returns, and calls
+    /// to async exports wait for their task to return through
+    /// `Instance::task_return`.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub host: bool,
+
     /// Whether or not to generate helper function/constants to help link custom
     /// sections into the final output.
     ///
@@ -292,9 +356,14 @@ pub struct Opts {
     ///     - none
     ///     - all
     ///     - some=<value>[,<value>...], where each <value> is of the form:
-    ///         - import:<name> or
-    ///         - export:<name>
-    #[cfg_attr(feature = "clap", arg(long = "async", value_parser = parse_async, default_value = "none"))]
+    ///         - import:<selector> or
+    ///         - export:<selector>
+    ///
+    /// A <selector> names a function (`f`, `iface#f`), a resource's
+    /// functions (`iface#res`) or one of them (`iface#res.method`,
+    /// `iface#res.constructor`), or a whole interface (`iface`), and may use
+    /// `*` as a wildcard (`wasi:http/*`, `iface#res.*`).
+    #[cfg_attr(feature = "clap", arg(long = "async", value_parser = AsyncConfig::parse, default_value = "none"))]
     pub async_: AsyncConfig,
 }
 
@@ -389,10 +458,19 @@ impl RustWasm {
     }
 
     fn bitflags_path(&self) -> String {
-        self.opts
-            .bitflags_path
-            .to_owned()
-            .unwrap_or(format!("{}::bitflags", self.runtime_path()))
+        match &self.opts.bitflags_path {
+            Some(path) => path.clone(),
+            None if self.opts.host => "::bitflags".to_string(),
+            None => format!("{}::bitflags", self.runtime_path()),
+        }
+    }
+
+    /// Fails with the reason host bindings can't be generated, if any.
+    fn check_host(&mut self) -> Result<()> {
+        match self.host_error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
     }
 
     fn async_support_path(&self) -> String {
@@ -422,7 +500,7 @@ impl RustWasm {
                 }
             }
             InterfaceGeneration::Generate => {
-                let path = compute_module_path(name, resolve, is_export).join("::");
+                let path = compute_module_path(name, resolve, is_export, &self.renames).join("::");
 
                 InterfaceName {
                     remapped: false,
@@ -438,26 +516,24 @@ impl RustWasm {
     }
 
     fn finish_runtime_module(&mut self) {
-        if self.rt_module.is_empty() {
-            return;
-        }
-
-        // As above, disable rustfmt, as we use prettyplease.
-        if self.opts.format {
-            uwriteln!(self.src, "#[rustfmt::skip]");
-        }
+        if !self.rt_module.is_empty() {
+            // As above, disable rustfmt, as we use prettyplease.
+            if self.opts.format {
+                uwriteln!(self.src, "#[rustfmt::skip]");
+            }
 
-        self.src.push_str("mod _rt {\n");
-        self.src.push_str("#![allow(dead_code, clippy::all)]\n");
-        let mut emitted = IndexSet::new();
-        while !self.rt_module.is_empty() {
-            for item in mem::take(&mut self.rt_module) {
-                if emitted.insert(item) {
-                    self.emit_runtime_item(item);
+            self.src.push_str("mod _rt {\n");
+            self.src.push_str("#![allow(dead_code, clippy::all)]\n");
+            let mut emitted = IndexSet::new();
+            while !self.rt_module.is_empty() {
+                for item in mem::take(&mut self.rt_module) {
+                    if emitted.insert(item) {
+                        self.emit_runtime_item(item);
+                    }
                 }
             }
+            self.src.push_str("}\n");
         }
-        self.src.push_str("}\n");
 
         if !self.future_payloads.is_empty() {
             let async_support = self.async_support_path();
@@ -548,6 +624,173 @@ pub mod wit_stream {{
                 self.rt_module.insert(RuntimeItem::AllocCrate);
                 uwriteln!(self.src, "pub use alloc_crate::boxed::Box;");
             }
+            RuntimeItem::GuestCell => {
+                self.rt_module.insert(RuntimeItem::BoxType);
+                self.src.push_str(
+                    "\
+pub use guest_cell::GuestCell;
+
+#[cfg(all(target_family = \"wasm\", not(target_feature = \"atomics\")))]
+mod guest_cell {
+    use super::Box;
+
+    /// Holds the implementation of an interface's exports registered at
+    /// runtime.
+    pub struct GuestCell<T: ?Sized>(core::cell::OnceCell<Box<T>>);
+
+    // Without threads there's no way to race on the cell.
+    unsafe impl<T: ?Sized> Sync for GuestCell<T> {}
+
+    impl<T: ?Sized> GuestCell<T> {
+        pub const fn new() -> Self {
+            Self(core::cell::OnceCell::new())
+        }
+
+        pub fn set(&self, val: Box<T>) -> bool {
+            self.0.set(val).is_ok()
+        }
+
+        pub fn get(&self) -> Option<&T> {
+            self.0.get().map(|val| &**val)
+        }
+    }
+}
+
+",
+                );
+                // Off single-threaded wasm the cell records the thread which
+                // owns it, which needs `std`.
+                if self.opts.std_feature {
+                    self.src.push_str(
+                        "\
+#[cfg(all(
+    not(all(target_family = \"wasm\", not(target_feature = \"atomics\"))),
+    not(feature = \"std\"),
+))]
+compile_error!(\"`dyn_exports` bindings require the `std` feature on targets with threads\");
+
+#[cfg(feature = \"std\")]
+",
+                    );
+                }
+                self.src.push_str(
+                    "\
+#[cfg(not(all(target_family = \"wasm\", not(target_feature = \"atomics\"))))]
+mod guest_cell {
+    extern crate std;
+
+    use super::Box;
+    use std::sync::OnceLock;
+    use std::thread::{self, ThreadId};
+
+    /// Holds the implementation of an interface's exports registered at
+    /// runtime.
+    ///
+    /// The implementation belongs to the thread which registered it and is
+    /// only ever accessed from that thread.
+    pub struct GuestCell<T: ?Sized> {
+        owner: OnceLock<ThreadId>,
+        val: core::cell::OnceCell<Box<T>>,
+    }
+
+    // `val` is only touched by the thread recorded in `owner`, which is
+    // claimed atomically before `val` is set.
+    unsafe impl<T: ?Sized> Sync for GuestCell<T> {}
+
+    impl<T: ?Sized> GuestCell<T> {
+        pub const fn new() -> Self {
+            Self {
+                owner: OnceLock::new(),
+                val: core::cell::OnceCell::new(),
+            }
+        }
+
+        pub fn set(&self, val: Box<T>) -> bool {
+            if self.owner.set(thread::current().id()).is_err() {
+                return false;
+            }
+            self.val.set(val).is_ok()
+        }
+
+        pub fn get(&self) -> Option<&T> {
+            let owner = *self.owner.get()?;
+            assert!(
+                owner == thread::current().id(),
+                \"exports can only be called on the thread which registered their implementation\"
+            );
+            self.val.get().map(|val| &**val)
+        }
+    }
+}
+                    ",
+                );
+            }
+            RuntimeItem::Mocks => {
+                self.src.push_str(
+                    "\
+/// Storage for the mock implementations of imports used by `mocks` bindings
+/// when not compiling to wasm32.
+#[cfg(not(target_arch = \"wasm32\"))]
+pub mod mocks {
+    extern crate std;
+
+    use std::cell::RefCell;
+    use std::vec::Vec;
+
+    pub use std::rc::Rc;
+    pub use std::thread_local;
+
+    /// Holds the mock of an interface's imports set on the current thread.
+    pub struct MockCell<T: ?Sized>(RefCell<Option<Rc<T>>>);
+
+    impl<T: ?Sized> MockCell<T> {
+        pub const fn new() -> Self {
+            Self(RefCell::new(None))
+        }
+
+        pub fn set(&self, val: Rc<T>) {
+            *self.0.borrow_mut() = Some(val);
+        }
+
+        pub fn get(&self, name: &str) -> Rc<T> {
+            self.0.borrow().clone().unwrap_or_else(|| {
+                panic!(\"no mock of `{name}` is set; call `set_mock` first\")
+            })
+        }
+    }
+
+    /// Maps the handles of mocked resources to their implementations.
+    pub struct MockTable<T: ?Sized>(RefCell<Vec<Option<Rc<T>>>>);
+
+    impl<T: ?Sized> MockTable<T> {
+        pub const fn new() -> Self {
+            Self(RefCell::new(Vec::new()))
+        }
+
+        pub fn insert(&self, val: Rc<T>) -> u32 {
+            let mut table = self.0.borrow_mut();
+            table.push(Some(val));
+            u32::try_from(table.len()).unwrap()
+        }
+
+        pub fn get(&self, handle: u32) -> Rc<T> {
+            self.0
+                .borrow()
+                .get((handle as usize).wrapping_sub(1))
+                .and_then(|val| val.clone())
+                .unwrap_or_else(|| panic!(\"handle {handle} isn't a live mocked resource\"))
+        }
+
+        pub fn remove(&self, handle: u32) {
+            if let Some(val) = self.0.borrow_mut().get_mut((handle as usize).wrapping_sub(1)) {
+                *val = None;
+            }
+        }
+    }
+}
+                    ",
+                );
+            }
             RuntimeItem::VecType => {
                 self.rt_module.insert(RuntimeItem::AllocCrate);
                 uwriteln!(self.src, "pub use alloc_crate::vec::Vec;");
@@ -1039,9 +1282,15 @@ impl WorldGenerator for RustWasm {
                 self.opts.additional_derive_attributes
             );
         }
+        if self.opts.serde {
+            uwriteln!(self.src_preamble, "//   * serde");
+        }
         for (k, v) in self.opts.with.iter() {
             uwriteln!(self.src_preamble, "//   * with {k:?} = {v}");
         }
+        for (k, v) in self.opts.rename.iter() {
+            uwriteln!(self.src_preamble, "//   * rename {k:?} = {v:?}");
+        }
         if let Some(type_section_suffix) = &self.opts.type_section_suffix {
             uwriteln!(
                 self.src_preamble,
@@ -1070,6 +1319,18 @@ impl WorldGenerator for RustWasm {
         if self.opts.generate_unused_types {
             uwriteln!(self.src_preamble, "//   * generate_unused_types");
         }
+        if self.opts.generate_all_payloads {
+            uwriteln!(self.src_preamble, "//   * generate_all_payloads");
+        }
+        if self.opts.dyn_exports {
+            uwriteln!(self.src_preamble, "//   * dyn_exports");
+        }
+        if self.opts.mocks {
+            uwriteln!(self.src_preamble, "//   * mocks");
+        }
+        if self.opts.host {
+            uwriteln!(self.src_preamble, "//   * host");
+        }
         if self.opts.disable_custom_section_link_helpers {
             uwriteln!(
                 self.src_preamble,
@@ -1078,6 +1339,13 @@ impl WorldGenerator for RustWasm {
         }
         self.types.analyze(resolve);
         self.world = Some(world);
+        for (from, to) in self.opts.rename.iter() {
+            self.renames.insert(from, to);
+        }
+        self.renames.warn_unmatched(resolve, world);
+        if self.opts.host {
+            self.host_error = host::check_opts(&self.opts).err();
+        }
 
         let world = &resolve.worlds[world];
         // Specify that all imports local to the world's package should be generated
@@ -1105,6 +1373,7 @@ impl WorldGenerator for RustWasm {
         id: InterfaceId,
         _files: &mut Files,
     ) -> Result<()> {
+        self.check_host()?;
         self.interface_last_seen_as_import.insert(id, true);
         let wasm_import_module = resolve.name_world_key(name);
         let mut gen = self.interface(
@@ -1119,7 +1388,12 @@ impl WorldGenerator for RustWasm {
         }
         gen.types(id);
 
-        gen.generate_imports(resolve.interfaces[id].functions.values(), Some(name));
+        let funcs = resolve.interfaces[id].functions.values();
+        if gen.gen.opts.host {
+            gen.generate_host_imports(&funcs.collect::<Vec<_>>(), Some(name));
+        } else {
+            gen.generate_imports(funcs, Some(name));
+        }
 
         let docs = &resolve.interfaces[id].docs;
 
@@ -1136,10 +1410,20 @@ impl WorldGenerator for RustWasm {
         _files: &mut Files,
     ) {
         self.import_funcs_called = true;
+        if self.host_error.is_some() {
+            return;
+        }
 
         let mut gen = self.interface(Identifier::World(world), "$root", resolve, true);
 
-        gen.generate_imports(funcs.iter().map(|(_, func)| *func), None);
+        if gen.gen.opts.host {
+            gen.generate_host_imports(
+                &funcs.iter().map(|(_, func)| *func).collect::<Vec<_>>(),
+                None,
+            );
+        } else {
+            gen.generate_imports(funcs.iter().map(|(_, func)| *func), None);
+        }
 
         let src = gen.finish();
         self.src.push_str(&src);
@@ -1152,6 +1436,7 @@ impl WorldGenerator for RustWasm {
         id: InterfaceId,
         _files: &mut Files,
     ) -> Result<()> {
+        self.check_host()?;
         self.interface_last_seen_as_import.insert(id, false);
         let wasm_import_module = format!("[export]{}", resolve.name_world_key(name));
         let mut gen = self.interface(
@@ -1165,14 +1450,23 @@ impl WorldGenerator for RustWasm {
             return Ok(());
         }
         gen.types(id);
+        if gen.gen.opts.host {
+            let funcs = resolve.interfaces[id].functions.values();
+            gen.generate_host_exports(&funcs.collect::<Vec<_>>(), Some(name));
+            let docs = &resolve.interfaces[id].docs;
+            gen.finish_append_submodule(&snake, module_path, docs);
+            return Ok(());
+        }
         let macro_name =
             gen.generate_exports(Some((id, name)), resolve.interfaces[id].functions.values())?;
 
         let docs = &resolve.interfaces[id].docs;
 
         gen.finish_append_submodule(&snake, module_path, docs);
-        self.export_macros
-            .push((macro_name, self.interface_names[&id].path.clone()));
+        if let Some(macro_name) = macro_name {
+            self.export_macros
+                .push((macro_name, self.interface_names[&id].path.clone()));
+        }
 
         if self.opts.stubs {
             let world_id = self.world.unwrap();
@@ -1196,11 +1490,20 @@ impl WorldGenerator for RustWasm {
         funcs: &[(&str, &Function)],
         _files: &mut Files,
     ) -> Result<()> {
+        self.check_host()?;
         let mut gen = self.interface(Identifier::World(world), "[export]$root", resolve, false);
+        if gen.gen.opts.host {
+            gen.generate_host_exports(&funcs.iter().map(|f| f.1).collect::<Vec<_>>(), None);
+            let src = gen.finish();
+            self.src.push_str(&src);
+            return Ok(());
+        }
         let macro_name = gen.generate_exports(None, funcs.iter().map(|f| f.1))?;
         let src = gen.finish();
         self.src.push_str(&src);
-        self.export_macros.push((macro_name, String::new()));
+        if let Some(macro_name) = macro_name {
+            self.export_macros.push((macro_name, String::new()));
+        }
 
         if self.opts.stubs {
             let mut gen = self.interface(Identifier::World(world), "[export]$root", resolve, false);
@@ -1218,6 +1521,9 @@ impl WorldGenerator for RustWasm {
         types: &[(&str, TypeId)],
         _files: &mut Files,
     ) {
+        if self.host_error.is_some() {
+            return;
+        }
         let mut gen = self.interface(Identifier::World(world), "$root", resolve, true);
         for (name, ty) in types {
             gen.define_type(name, *ty);
@@ -1226,6 +1532,23 @@ impl WorldGenerator for RustWasm {
         self.src.push_str(&src);
     }
 
+    fn export_types(
+        &mut self,
+        resolve: &Resolve,
+        world: WorldId,
+        types: &[(&str, TypeId)],
+        _files: &mut Files,
+    ) -> Result<()> {
+        self.check_host()?;
+        let mut gen = self.interface(Identifier::World(world), "[export]$root", resolve, false);
+        for (name, ty) in types {
+            gen.define_type(name, *ty);
+        }
+        let src = gen.finish();
+        self.src.push_str(&src);
+        Ok(())
+    }
+
     fn finish_imports(&mut self, resolve: &Resolve, world: WorldId, files: &mut Files) {
         if !self.import_funcs_called {
             // We call `import_funcs` even if the world doesn't import any
@@ -1236,6 +1559,7 @@ impl WorldGenerator for RustWasm {
     }
 
     fn finish(&mut self, resolve: &Resolve, world: WorldId, files: &mut Files) -> Result<()> {
+        self.check_host()?;
         let name = &resolve.worlds[world].name;
 
         let imports = mem::take(&mut self.import_modules);
@@ -1243,7 +1567,15 @@ impl WorldGenerator for RustWasm {
         let exports = mem::take(&mut self.export_modules);
         self.emit_modules(exports);
 
+        if self.opts.generate_all_payloads {
+            self.interface(Identifier::StreamOrFuturePayload, "$root", resolve, true)
+                .generate_all_payloads(world);
+        }
+
         self.finish_runtime_module();
+        if self.opts.host {
+            self.src.push_str(host::RUNTIME);
+        }
         self.finish_export_macro(resolve, world);
 
         // This is a bit tricky, but we sometimes want to "split" the `world` in
@@ -1296,18 +1628,21 @@ impl WorldGenerator for RustWasm {
         } else {
             (resolve, world)
         };
-        self.emit_custom_section(
-            resolve_to_encode,
-            world_to_encode,
-            "encoded world",
-            if self.opts.disable_custom_section_link_helpers {
-                None
-            } else {
-                Some("__link_custom_section_describing_imports")
-            },
-        );
+        // Hosts don't embed the component type, and have no stubs either.
+        if !self.opts.host {
+            self.emit_custom_section(
+                resolve_to_encode,
+                world_to_encode,
+                "encoded world",
+                if self.opts.disable_custom_section_link_helpers {
+                    None
+                } else {
+                    Some("__link_custom_section_describing_imports")
+                },
+            );
+        }
 
-        if self.opts.stubs {
+        if self.opts.stubs && !self.opts.host {
             self.src.push_str("\n#[derive(Debug)]\npub struct Stub;\n");
         }
 
@@ -1346,23 +1681,30 @@ impl WorldGenerator for RustWasm {
     }
 }
 
-fn compute_module_path(name: &WorldKey, resolve: &Resolve, is_export: bool) -> Vec<String> {
+fn compute_module_path(
+    name: &WorldKey,
+    resolve: &Resolve,
+    is_export: bool,
+    renames: &Renames,
+) -> Vec<String> {
     let mut path = Vec::new();
     if is_export {
         path.push("exports".to_string());
     }
-    match name {
-        WorldKey::Name(name) => {
-            path.push(to_rust_ident(name));
-        }
+    let module = match name {
+        WorldKey::Name(name) => to_rust_ident(name),
         WorldKey::Interface(id) => {
             let iface = &resolve.interfaces[*id];
             let pkg = iface.package.unwrap();
             let pkgname = resolve.packages[pkg].name.clone();
             path.push(to_rust_ident(&pkgname.namespace));
             path.push(name_package_module(resolve, pkg));
-            path.push(to_rust_ident(iface.name.as_ref().unwrap()));
+            to_rust_ident(iface.name.as_ref().unwrap())
         }
+    };
+    match renames.interface(resolve, name) {
+        Some(rename) => path.push(rename.to_string()),
+        None => path.push(module),
     }
     path
 }
@@ -1475,64 +1817,57 @@ struct FnSig {
     generics: Option<String>,
     self_arg: Option<String>,
     self_is_first_param: bool,
+    /// Overrides the name of the function, which is otherwise derived from
+    /// the WIT function's name.
+    name: Option<String>,
+    /// Lifetime of borrowed parameters, which is elided if not specified.
+    lifetime: Option<&'static str>,
 }
 
 pub fn to_rust_ident(name: &str) -> String {
-    match name {
-        // Escape Rust keywords.
-        // Source: https://doc.rust-lang.org/reference/keywords.html
-        "as" => "as_".into(),
-        "break" => "break_".into(),
-        "const" => "const_".into(),
-        "continue" => "continue_".into(),
-        "crate" => "crate_".into(),
-        "else" => "else_".into(),
-        "enum" => "enum_".into(),
-        "extern" => "extern_".into(),
-        "false" => "false_".into(),
-        "fn" => "fn_".into(),
-        "for" => "for_".into(),
-        "if" => "if_".into(),
-        "impl" => "impl_".into(),
-        "in" => "in_".into(),
-        "let" => "let_".into(),
-        "loop" => "loop_".into(),
-        "match" => "match_".into(),
-        "mod" => "mod_".into(),
-        "move" => "move_".into(),
-        "mut" => "mut_".into(),
-        "pub" => "pub_".into(),
-        "ref" => "ref_".into(),
-        "return" => "return_".into(),
-        "self" => "self_".into(),
-        "static" => "static_".into(),
-        "struct" => "struct_".into(),
-        "super" => "super_".into(),
-        "trait" => "trait_".into(),
-        "true" => "true_".into(),
-        "type" => "type_".into(),
-        "unsafe" => "unsafe_".into(),
-        "use" => "use_".into(),
-        "where" => "where_".into(),
-        "while" => "while_".into(),
-        "async" => "async_".into(),
-        "await" => "await_".into(),
-        "dyn" => "dyn_".into(),
-        "abstract" => "abstract_".into(),
-        "become" => "become_".into(),
-        "box" => "box_".into(),
-        "do" => "do_".into(),
-        "final" => "final_".into(),
-        "macro" => "macro_".into(),
-        "override" => "override_".into(),
-        "priv" => "priv_".into(),
-        "typeof" => "typeof_".into(),
-        "unsized" => "unsized_".into(),
-        "virtual" => "virtual_".into(),
-        "yield" => "yield_".into(),
-        "try" => "try_".into(),
-        s => s.to_snake_case(),
-    }
+    Keywords::RUST.ident(name, Case::Snake)
+}
+
+/// Returns the names of the fields of `record`, which are kept distinct even
+/// when several WIT names map to the same Rust identifier.
+fn field_names(record: &Record) -> Vec<String> {
+    let mut scope = Keywords::RUST.scope();
+    record
+        .fields
+        .iter()
+        .map(|field| scope.ident(&field.name, Case::Snake))
+        .collect()
+}
+
+/// Returns the names of the constants of `flags`, kept distinct like
+/// [`field_names`].
+fn flag_names(flags: &Flags) -> Vec<String> {
+    let mut scope = Keywords::RUST.scope();
+    flags
+        .flags
+        .iter()
+        .map(|flag| scope.unique(&flag.name.to_shouty_snake_case()))
+        .collect()
+}
+
+/// Returns the names of the parameters of `func`, kept distinct like
+/// [`field_names`].
+fn param_names(func: &Function) -> Vec<String> {
+    let mut scope = Keywords::RUST.scope();
+    func.params
+        .iter()
+        .map(|(name, _)| scope.ident(name, Case::Snake))
+        .collect()
+}
+
+/// Returns the names of the cases of a variant or enum, kept distinct like
+/// [`field_names`].
+fn case_names<'a>(cases: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut scope = Keywords::RUST.scope();
+    cases
+        .into_iter()
+        .map(|case| scope.unique(&case.to_upper_camel_case()))
+        .collect()
 }
 
 fn to_upper_camel_case(name: &str) -> String {
@@ -52,6 +52,51 @@ mod codegen_tests {
                     fn works() {}
                 }
 
+                mod serde_ {
+                    wit_bindgen::generate!({
+                        path: $test,
+                        ownership: Borrowing {
+                            duplicate_if_necessary: true
+                        },
+                        stubs,
+                        export_prefix: "[serde]",
+                        generate_all,
+                        serde: true,
+                    });
+
+                    #[test]
+                    fn works() {}
+                }
+
+                mod dyn_exports {
+                    wit_bindgen::generate!({
+                        path: $test,
+                        stubs,
+                        export_prefix: "[dyn]",
+                        generate_all,
+                        dyn_exports: true,
+                    });
+
+                    #[test]
+                    fn works() {}
+                }
+
+                mod mocks {
+                    wit_bindgen::generate!({
+                        path: $test,
+                        ownership: Borrowing {
+                            duplicate_if_necessary: true
+                        },
+                        stubs,
+                        export_prefix: "[mocks]",
+                        generate_all,
+                        mocks: true,
+                    });
+
+                    #[test]
+                    fn works() {}
+                }
+
                 mod async_ {
                     wit_bindgen::generate!({
                         path: $test,
@@ -64,6 +109,20 @@ mod codegen_tests {
                     #[test]
                     fn works() {}
                 }
+
+                mod async_mocks {
+                    wit_bindgen::generate!({
+                        path: $test,
+                        stubs,
+                        export_prefix: "[async-mocks]",
+                        generate_all,
+                        async: true,
+                        mocks: true,
+                    });
+
+                    #[test]
+                    fn works() {}
+                }
             }
 
         };
@@ -524,6 +583,86 @@ mod with_and_resources {
     }
 }
 
+mod renames {
+    wit_bindgen::generate!({
+        inline: "
+            package my:inline;
+
+            interface foo {
+                record point {
+                    x-y: u32,
+                    X-Y: u32,
+                }
+
+                variant shape {
+                    a-b(point),
+                    A-B,
+                }
+
+                enum mode {
+                    c-d,
+                    C-D,
+                }
+
+                resource thing {
+                    get: func() -> u32;
+                }
+
+                make: func(x-y: u32, X-Y: u32) -> shape;
+                take: func(m: mode) -> point;
+            }
+
+            world baz {
+                import foo;
+                export foo;
+            }
+        ",
+        rename: {
+            "my:inline/foo": "bar",
+            "my:inline/foo.point": "Pt",
+            "my:inline/foo.thing": "Widget",
+            "my:inline/foo.make": "create",
+        },
+    });
+
+    use exports::my::inline::bar::{Guest, GuestWidget, Mode, Pt, Shape};
+
+    struct Component;
+
+    impl Guest for Component {
+        type Widget = MyWidget;
+
+        fn create(x_y: u32, x_y0: u32) -> Shape {
+            Shape::AB(Pt { x_y, x_y0 })
+        }
+
+        fn take(m: Mode) -> Pt {
+            match m {
+                Mode::CD => Pt { x_y: 0, x_y0: 1 },
+                Mode::CD0 => Pt { x_y: 1, x_y0: 0 },
+            }
+        }
+    }
+
+    struct MyWidget;
+
+    impl GuestWidget for MyWidget {
+        fn get(&self) -> u32 {
+            0
+        }
+    }
+
+    export!(Component);
+
+    fn call_imports(widget: &my::inline::bar::Widget) -> u32 {
+        let my::inline::bar::Pt { x_y, x_y0 } = my::inline::bar::take(my::inline::bar::Mode::CD0);
+        match my::inline::bar::create(x_y, x_y0) {
+            my::inline::bar::Shape::AB(pt) => pt.x_y + widget.get(),
+            my::inline::bar::Shape::AB0 => widget.get(),
+        }
+    }
+}
+
 #[allow(unused)]
 mod generate_unused_types {
     use exports::foo::bar::component::UnusedEnum;
@@ -653,3 +792,1222 @@ mod generate_custom_section_link_helpers {
         disable_custom_section_link_helpers: true,
     });
 }
+
+/// Async imports, streams, futures and error-contexts all run off wasm32
+/// against mocks of the imports.
+mod native_async {
+    wit_bindgen::generate!({
+        inline: r#"
+            package my:native;
+
+            interface fetch {
+                resource session {
+                    constructor(base: string);
+                    get: func(path: string) -> result<list<u8>, string>;
+                }
+
+                get: func(url: string) -> result<list<u8>, string>;
+                chunks: func(n: u32) -> stream<u8>;
+                later: func(x: future<u32>) -> u32;
+                fail: func(message: string) -> error-context;
+                describe: func(s: borrow<session>) -> string;
+            }
+
+            world not-used-name {
+                import fetch;
+            }
+        "#,
+        async: true,
+        generate_all,
+        mocks: true,
+    });
+
+    use futures::future::{Either, LocalBoxFuture};
+    use futures::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, SinkExt, StreamExt};
+    use my::native::fetch;
+    use std::mem::MaybeUninit;
+    use std::time::Duration;
+    use wit_bindgen::rt::async_support::{self, futures, native, FutureReader, StreamReader};
+
+    /// Serves bodies equal to the requested URL.
+    struct Echo;
+
+    impl fetch::Mock for Echo {
+        fn get<'a>(&'a self, url: &'a str) -> LocalBoxFuture<'a, Result<Vec<u8>, String>> {
+            Box::pin(async move { Ok(url.as_bytes().to_vec()) })
+        }
+    }
+
+    struct Session(String);
+
+    impl fetch::MockSession for Session {
+        fn get<'a>(&'a self, path: &'a str) -> LocalBoxFuture<'a, Result<Vec<u8>, String>> {
+            Box::pin(async move { Ok(format!("{}/{path}", self.0).into_bytes()) })
+        }
+    }
+
+    struct Fetch;
+
+    impl fetch::Mock for Fetch {
+        fn session_new<'a>(&'a self, base: &'a str) -> LocalBoxFuture<'a, fetch::Session> {
+            Box::pin(async move { fetch::Session::from_mock(Session(base.to_string())) })
+        }
+
+        fn get<'a>(&'a self, url: &'a str) -> LocalBoxFuture<'a, Result<Vec<u8>, String>> {
+            Box::pin(async move {
+                match url {
+                    "ok" => Ok(b"body".to_vec()),
+                    _ => Err(format!("unknown url {url}")),
+                }
+            })
+        }
+
+        fn chunks<'a>(&'a self, n: u32) -> LocalBoxFuture<'a, StreamReader<u8>> {
+            Box::pin(async move {
+                let (mut tx, rx) = wit_stream::new::<u8>();
+                async_support::spawn(async move {
+                    for i in 0..n {
+                        tx.send(vec![i as u8]).await.unwrap();
+                    }
+                });
+                rx
+            })
+        }
+
+        fn later<'a>(&'a self, x: FutureReader<u32>) -> LocalBoxFuture<'a, u32> {
+            Box::pin(async move { x.await.unwrap() * 2 })
+        }
+
+        fn fail<'a>(&'a self, message: &'a str) -> LocalBoxFuture<'a, async_support::ErrorContext> {
+            Box::pin(async move { async_support::error_context_new(message) })
+        }
+
+        fn describe<'a>(&'a self, s: &'a fetch::Session) -> LocalBoxFuture<'a, String> {
+            Box::pin(async move {
+                let body = s.get("about").await.unwrap();
+                format!("session at {}", String::from_utf8(body).unwrap())
+            })
+        }
+    }
+
+    #[test]
+    fn mocked_imports() {
+        fetch::set_mock(Fetch);
+
+        let (body, missing, chunks, doubled, error) = async_support::block_on(async {
+            let body = fetch::get("ok").await;
+            let missing = fetch::get("nope").await;
+
+            let mut chunks = Vec::new();
+            let mut rx = fetch::chunks(3).await;
+            while let Some(chunk) = rx.next().await {
+                chunks.extend(chunk);
+            }
+
+            let (tx, rx) = wit_future::new::<u32>();
+            async_support::spawn(async move { tx.write(21).await });
+            let doubled = fetch::later(rx).await;
+
+            let error = fetch::fail("oops").await.debug_message();
+            (body, missing, chunks, doubled, error)
+        });
+
+        assert_eq!(body, Ok(b"body".to_vec()));
+        assert_eq!(missing, Err("unknown url nope".to_string()));
+        assert_eq!(chunks, [0, 1, 2]);
+        assert_eq!(doubled, 42);
+        assert_eq!(error, "oops");
+
+        async_support::task_backpressure(true);
+        assert!(native::backpressure());
+    }
+
+    #[test]
+    fn mocked_resources() {
+        fetch::set_mock(Fetch);
+
+        async_support::block_on(async {
+            let session = fetch::Session::new("https://example.com").await;
+            assert_eq!(
+                session.get("index").await.unwrap(),
+                b"https://example.com/index"
+            );
+            assert_eq!(
+                fetch::describe(&session).await,
+                "session at https://example.com/about"
+            );
+        });
+    }
+
+    #[test]
+    fn borrowed_futures() {
+        fetch::set_mock(Echo);
+
+        let urls = ["a", "b", "c"];
+        let bodies = std::cell::RefCell::new(Vec::new());
+        let total = async_support::block_on(async {
+            let scope = async_support::LocalScope::new();
+            for url in &urls {
+                let bodies = &bodies;
+                scope.spawn(async move {
+                    let body = fetch::get(url).await.unwrap();
+                    bodies.borrow_mut().push(body);
+                });
+            }
+            scope
+                .run(async { fetch::get("main").await.unwrap().len() })
+                .await
+        });
+
+        let mut bodies = bodies.into_inner();
+        bodies.sort();
+        assert_eq!(bodies, [b"a", b"b", b"c"]);
+        assert_eq!(total, 4);
+    }
+
+    #[test]
+    fn spawn_before_block_on() {
+        let (tx, rx) = wit_future::new::<u32>();
+        async_support::spawn(async move { tx.write(7).await });
+        assert_eq!(async_support::block_on(async { rx.await }), Some(7));
+    }
+
+    #[test]
+    fn timers_and_select() {
+        /// Takes a second per byte of the URL to respond.
+        struct Slow;
+
+        impl fetch::Mock for Slow {
+            fn get<'a>(&'a self, url: &'a str) -> LocalBoxFuture<'a, Result<Vec<u8>, String>> {
+                Box::pin(async move {
+                    async_support::sleep(Duration::from_secs(url.len() as u64)).await;
+                    Ok(url.as_bytes().to_vec())
+                })
+            }
+        }
+
+        fetch::set_mock(Slow);
+        async_support::block_on(async {
+            match async_support::select(fetch::get("slow"), fetch::get("f")).await {
+                Either::Right(body) => assert_eq!(body.unwrap(), b"f"),
+                Either::Left(_) => panic!("slow request finished first"),
+            }
+
+            let body = async_support::timeout(Duration::from_secs(2), fetch::get("abc")).await;
+            assert_eq!(body, Err(async_support::Elapsed));
+
+            let (a, b) = async_support::join(fetch::get("a"), fetch::get("bb")).await;
+            assert_eq!((a.unwrap(), b.unwrap()), (b"a".to_vec(), b"bb".to_vec()));
+        });
+    }
+
+    #[test]
+    fn byte_streams() {
+        /// Streams `n` chunks of three bytes followed by `end`.
+        struct Chunks;
+
+        impl fetch::Mock for Chunks {
+            fn chunks<'a>(&'a self, n: u32) -> LocalBoxFuture<'a, StreamReader<u8>> {
+                Box::pin(async move {
+                    let (mut tx, rx) = wit_stream::new::<u8>();
+                    async_support::spawn(async move {
+                        for i in 0..n {
+                            assert_eq!(tx.write(&[i as u8; 3]).await, 3);
+                        }
+                        tx.write_bytes(b"end"[..].into()).await;
+                    });
+                    rx
+                })
+            }
+        }
+
+        fetch::set_mock(Chunks);
+        async_support::block_on(async {
+            let mut rx = fetch::chunks(2).await;
+            let mut buf = [MaybeUninit::uninit(); 2];
+            assert_eq!(rx.read(&mut buf).await, Some(2));
+            assert_eq!(buf.map(|b| unsafe { b.assume_init() }), [0, 0]);
+            let mut rest = Vec::new();
+            rx.read_to_end(&mut rest).await.unwrap();
+            assert_eq!(rest, b"\0\x01\x01\x01end");
+
+            let mut rx = fetch::chunks(1).await;
+            assert_eq!(rx.read_bytes().await.unwrap(), b"\0\0\0"[..]);
+            let mut line = String::new();
+            rx.read_line(&mut line).await.unwrap();
+            assert_eq!(line, "end");
+        });
+    }
+
+    #[test]
+    fn writes_after_close() {
+        async_support::block_on(async {
+            let (mut tx, rx) = wit_stream::new::<u8>();
+            drop(rx);
+            assert_eq!(tx.write(b"abc").await, 0);
+            let err = tx.write_all(b"abc").await.unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+
+            // A chunk still in flight when the reader goes away is reported by
+            // the next write.
+            let (mut tx, rx) = wit_stream::new::<u8>();
+            tx.write_all(b"abc").await.unwrap();
+            drop(rx);
+            let err = tx.write_all(b"def").await.unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "`my:native/fetch#fail` isn't mocked")]
+    fn missing_mock() {
+        fetch::set_mock(Echo);
+        async_support::block_on(async {
+            let _ = fetch::fail("oops").await;
+        });
+    }
+}
+
+/// Streams and futures of types which never appear as a payload in a
+/// signature can still be created and used within a component.
+mod all_payloads {
+    wit_bindgen::generate!({
+        inline: r#"
+            package my:payloads;
+
+            interface shapes {
+                record point {
+                    x: s32,
+                    y: s32,
+                }
+                type alias-of-point = point;
+                variant shape {
+                    dot(point),
+                    line(tuple<point, point>),
+                }
+
+                points: func() -> stream<alias-of-point>;
+                area: func(s: shape) -> f64;
+            }
+
+            world not-used-name {
+                record label {
+                    text: string,
+                }
+                import shapes;
+                import describe: func(l: label) -> string;
+            }
+        "#,
+        async: true,
+        generate_all,
+        generate_all_payloads: true,
+    });
+
+    use futures::{SinkExt, StreamExt};
+    use my::payloads::shapes::{Point, Shape};
+    use wit_bindgen::rt::async_support::{self, futures};
+
+    #[test]
+    fn local_payloads() {
+        async_support::block_on(async {
+            let (mut tx, mut rx) = wit_stream::new::<Shape>();
+            async_support::spawn(async move {
+                let p = Point { x: 1, y: 2 };
+                tx.send(vec![Shape::Dot(p), Shape::Line((p, p))])
+                    .await
+                    .unwrap();
+            });
+            let shapes = rx.next().await.unwrap();
+            assert_eq!(shapes.len(), 2);
+            assert!(matches!(shapes[0], Shape::Dot(Point { x: 1, y: 2 })));
+
+            let (tx, rx) = wit_future::new::<Label>();
+            async_support::spawn(async move { tx.write(Label { text: "hi".into() }).await });
+            assert_eq!(rx.await.unwrap().text, "hi");
+
+            let (tx, rx) = wit_future::new::<(Point, Point)>();
+            drop(tx);
+            assert!(rx.await.is_none());
+
+            let (tx, rx) = wit_future::new::<String>();
+            async_support::spawn(async move { tx.write("done".into()).await });
+            assert_eq!(rx.await.unwrap(), "done");
+        });
+    }
+}
+
+/// Resources and aliases of anonymous types share their payload impls with
+/// the Rust types they're spelled as, such as `own<blob>` and `list<u8>`.
+mod all_payloads_aliased {
+    wit_bindgen::generate!({
+        inline: r#"
+            package my:aliased;
+
+            interface blobs {
+                resource blob {
+                    constructor();
+                }
+                type bytes = list<u8>;
+                type maybe-blob = option<blob>;
+
+                read: func(b: borrow<blob>) -> stream<u8>;
+                chunks: func() -> stream<list<u8>>;
+                contents: func(b: bytes) -> future<bytes>;
+                take: func() -> future<own<blob>>;
+                maybe: func() -> future<option<blob>>;
+            }
+
+            world not-used-name {
+                import blobs;
+            }
+        "#,
+        async: true,
+        generate_all,
+        generate_all_payloads: true,
+    });
+
+    use wit_bindgen::rt::async_support;
+
+    #[test]
+    fn aliased_payloads() {
+        async_support::block_on(async {
+            let (tx, rx) = wit_future::new::<my::aliased::blobs::Bytes>();
+            async_support::spawn(async move { tx.write(vec![1, 2]).await });
+            assert_eq!(rx.await.unwrap(), [1, 2]);
+        });
+    }
+}
+
+/// Functions, resources and interfaces can be selected for the async ABI by
+/// name or with wildcards.
+#[allow(unused)]
+mod async_selectors {
+    wit_bindgen::generate!({
+        inline: r#"
+            package my:selectors;
+
+            interface files {
+                resource file {
+                    constructor(path: string);
+                    read: func(len: u32) -> list<u8>;
+                    size: func() -> u64;
+                    open: static func(path: string) -> file;
+                }
+                resolve: func(path: string) -> string;
+            }
+
+            interface clock {
+                now: func() -> u64;
+                wait: func(nanos: u64);
+            }
+
+            interface server {
+                use files.{file};
+
+                resource session {
+                    constructor();
+                    send: func(f: borrow<file>) -> u32;
+                    id: func() -> u32;
+                }
+                serve: func(port: u16);
+                stop: func();
+            }
+
+            world not-used-name {
+                import files;
+                import clock;
+                import log: func(msg: string);
+                export server;
+            }
+        "#,
+        async: {
+            imports: [
+                "my:selectors/files#file.read",
+                "my:selectors/files#[static]file.open",
+                "my:selectors/clock",
+            ],
+            exports: [
+                "my:selectors/server#session",
+                "my:selectors/server#s*e",
+            ],
+        },
+    });
+
+    use exports::my::selectors::server::{Guest, GuestSession};
+    use my::selectors::{clock, files};
+
+    struct Component;
+
+    impl Guest for Component {
+        type Session = Session;
+
+        async fn serve(port: u16) {
+            let file = files::File::open("index.html").await;
+            let _ = file.read(file.size() as u32).await;
+            clock::wait(clock::now().await).await;
+            log(&files::resolve("index.html"));
+        }
+
+        fn stop() {}
+    }
+
+    struct Session;
+
+    impl GuestSession for Session {
+        async fn new() -> Self {
+            Session
+        }
+
+        async fn send(&self, f: &files::File) -> u32 {
+            f.read(1).await.len() as u32
+        }
+
+        async fn id(&self) -> u32 {
+            0
+        }
+    }
+
+    export!(Component);
+}
+
+/// Exports can be implemented by a trait object registered at runtime.
+mod dyn_exports {
+    wit_bindgen::generate!({
+        inline: r#"
+            package my:plugins;
+
+            interface calc {
+                resource accumulator {
+                    constructor(start: u32);
+                    add: func(n: u32);
+                    total: func() -> u32;
+                    merge: static func(a: accumulator, b: accumulator) -> accumulator;
+                }
+                add: func(a: u32, b: u32) -> u32;
+                describe: func(acc: borrow<accumulator>) -> string;
+            }
+
+            world not-used-name {
+                export calc;
+            }
+        "#,
+        dyn_exports: true,
+    });
+
+    use exports::my::plugins::calc::{self, Accumulator, AccumulatorBorrow, GuestAccumulator};
+    use std::cell::Cell;
+
+    struct Calc {
+        offset: u32,
+    }
+
+    impl calc::Guest for Calc {
+        fn accumulator_new(&self, start: u32) -> Accumulator {
+            Accumulator::new(Acc(Cell::new(start)))
+        }
+
+        fn accumulator_merge(&self, a: Accumulator, b: Accumulator) -> Accumulator {
+            self.accumulator_new(a.get().total() + b.get().total())
+        }
+
+        fn add(&self, a: u32, b: u32) -> u32 {
+            a + b + self.offset
+        }
+
+        fn describe(&self, acc: AccumulatorBorrow<'_>) -> String {
+            format!("total: {}", acc.get().total())
+        }
+    }
+
+    struct Acc(Cell<u32>);
+
+    impl GuestAccumulator for Acc {
+        fn add(&self, n: u32) {
+            self.0.set(self.0.get() + n);
+        }
+
+        fn total(&self) -> u32 {
+            self.0.get()
+        }
+    }
+
+    #[test]
+    fn registered_at_runtime() {
+        calc::set_guest(Box::new(Calc { offset: 10 }));
+        assert_eq!(unsafe { calc::_export_add_cabi(1, 2) }, 13);
+
+        // The implementation isn't `Send`, so other threads can't reach it.
+        let other = std::thread::spawn(|| unsafe { calc::_export_add_cabi(1, 2) });
+        assert!(other.join().is_err());
+    }
+}
+
+/// Generated types follow the documented JSON mapping of WIT values.
+mod serde_json_mapping {
+    wit_bindgen::generate!({
+        inline: r#"
+            package my:json;
+
+            interface shapes {
+                resource canvas;
+
+                flags style {
+                    bold,
+                    dashed-line,
+                }
+                enum unit {
+                    px,
+                    em-size,
+                }
+                record point {
+                    x-pos: s32,
+                    y-pos: s32,
+                }
+                variant shape {
+                    empty,
+                    dot(point),
+                    polygon(list<point>),
+                }
+                record drawing {
+                    name: string,
+                    shapes: list<shape>,
+                    style: style,
+                    unit: unit,
+                    scale: option<f32>,
+                    origin: tuple<s32, s32>,
+                    status: result<u32, string>,
+                }
+                record layer {
+                    canvas: canvas,
+                    drawing: drawing,
+                }
+
+                draw: func(d: drawing, l: layer);
+            }
+
+            world not-used-name {
+                import shapes;
+            }
+        "#,
+        serde: true,
+    });
+
+    use my::json::shapes::{Drawing, Point, Shape, Style, Unit};
+
+    #[test]
+    fn round_trip() {
+        let drawing = Drawing {
+            name: "d".to_string(),
+            shapes: vec![
+                Shape::Empty,
+                Shape::Dot(Point {
+                    x_pos: 1,
+                    y_pos: -2,
+                }),
+                Shape::Polygon(vec![Point { x_pos: 0, y_pos: 0 }]),
+            ],
+            style: Style::BOLD | Style::DASHED_LINE,
+            unit: Unit::EmSize,
+            scale: None,
+            origin: (3, 4),
+            status: Err("oops".to_string()),
+        };
+
+        let json = serde_json::to_value(&drawing).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "name": "d",
+                "shapes": [
+                    "empty",
+                    {"dot": {"x-pos": 1, "y-pos": -2}},
+                    {"polygon": [{"x-pos": 0, "y-pos": 0}]},
+                ],
+                "style": ["bold", "dashed-line"],
+                "unit": "em-size",
+                "scale": null,
+                "origin": [3, 4],
+                "status": {"Err": "oops"},
+            })
+        );
+
+        let back: Drawing = serde_json::from_value(json).unwrap();
+        assert_eq!(format!("{back:?}"), format!("{drawing:?}"));
+
+        let err = serde_json::from_str::<Style>(r#"["italic"]"#).unwrap_err();
+        assert!(err.to_string().contains("unknown variant `italic`"));
+    }
+}
+
+/// Imports can be mocked when not compiling to wasm32.
+#[cfg(not(target_arch = "wasm32"))]
+mod mocks {
+    wit_bindgen::generate!({
+        inline: r#"
+            package my:kv;
+
+            interface store {
+                resource bucket {
+                    open: static func(name: string) -> result<bucket, string>;
+                    get: func(key: string) -> option<list<u8>>;
+                }
+                ping: func() -> u32;
+            }
+
+            world not-used-name {
+                import store;
+                import log: func(msg: string);
+            }
+        "#,
+        mocks: true,
+    });
+
+    use my::kv::store::{self, Bucket, MockBucket};
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    struct Store;
+
+    impl store::Mock for Store {
+        fn bucket_open(&self, name: &str) -> Result<Bucket, String> {
+            match name {
+                "data" => Ok(Bucket::from_mock(Data(HashMap::from([(
+                    "a".to_string(),
+                    vec![1, 2],
+                )])))),
+                _ => Err(format!("no bucket `{name}`")),
+            }
+        }
+    }
+
+    struct Data(HashMap<String, Vec<u8>>);
+
+    impl MockBucket for Data {
+        fn get(&self, key: &str) -> Option<Vec<u8>> {
+            self.0.get(key).cloned()
+        }
+    }
+
+    struct Log(Rc<RefCell<Vec<String>>>);
+
+    impl Mock for Log {
+        fn log(&self, msg: &str) {
+            self.0.borrow_mut().push(msg.to_string());
+        }
+    }
+
+    #[test]
+    fn imports_call_mocks() {
+        store::set_mock(Store);
+        let bucket = Bucket::open("data").unwrap();
+        assert_eq!(bucket.get("a"), Some(vec![1, 2]));
+        assert_eq!(bucket.get("b"), None);
+        drop(bucket);
+        assert_eq!(Bucket::open("x").unwrap_err(), "no bucket `x`");
+
+        let messages = Rc::new(RefCell::new(Vec::new()));
+        set_mock(Log(messages.clone()));
+        log("hello");
+        assert_eq!(*messages.borrow(), ["hello"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`my:kv/store#ping` isn't mocked")]
+    fn unmocked_imports_panic() {
+        store::set_mock(Store);
+        store::ping();
+    }
+}
+
+mod host_bindings {
+    wit_bindgen::generate!({
+        inline: r#"
+            package my:calc;
+
+            interface types {
+                record point { x: s32, y: s32, label: string }
+                variant shape { dot(point), path(list<point>), empty }
+                flags mode { fast, exact }
+            }
+
+            interface host-api {
+                use types.{point, shape, mode};
+                describe: func(s: shape, m: mode) -> string;
+                origin: func() -> point;
+            }
+
+            interface names {
+                echo: func(s: string) -> string;
+            }
+
+            world not-used-name {
+                use types.{point};
+                import host-api;
+                export names;
+                export sum: func(xs: list<u32>) -> u64;
+                export label: func(p: point) -> string;
+            }
+        "#,
+        host: true,
+        bitflags_path: "wit_bindgen::bitflags",
+    });
+
+    use host::{Error, Instance, Memory, Realloc, Val};
+    use my::calc::host_api::{self, Host};
+    use my::calc::types::{Mode, Shape};
+
+    /// An instance whose exports are implemented in Rust over a plain
+    /// vector of bytes.
+    #[derive(Default)]
+    struct Fake {
+        memory: Vec<u8>,
+        calls: Vec<String>,
+    }
+
+    impl Fake {
+        fn read_u32(&self, offset: u32) -> u32 {
+            let mut bytes = [0; 4];
+            self.read(offset, &mut bytes).unwrap();
+            u32::from_le_bytes(bytes)
+        }
+
+        fn read_string(&self, offset: u32) -> String {
+            let mut bytes = vec![0; self.read_u32(offset + 4) as usize];
+            self.read(self.read_u32(offset), &mut bytes).unwrap();
+            String::from_utf8(bytes).unwrap()
+        }
+
+        fn alloc_bytes(&mut self, bytes: &[u8]) -> u32 {
+            let ptr = self.realloc(0, 0, 1, bytes.len() as u32).unwrap();
+            self.write(ptr, bytes).unwrap();
+            ptr
+        }
+    }
+
+    impl Memory for Fake {
+        fn read(&self, offset: u32, dst: &mut [u8]) -> Result<(), Error> {
+            let src = self
+                .memory
+                .get(offset as usize..offset as usize + dst.len())
+                .ok_or_else(|| Error::new("out of bounds"))?;
+            dst.copy_from_slice(src);
+            Ok(())
+        }
+
+        fn write(&mut self, offset: u32, src: &[u8]) -> Result<(), Error> {
+            let dst = self
+                .memory
+                .get_mut(offset as usize..offset as usize + src.len())
+                .ok_or_else(|| Error::new("out of bounds"))?;
+            dst.copy_from_slice(src);
+            Ok(())
+        }
+    }
+
+    impl Realloc for Fake {
+        fn realloc(&mut self, _: u32, _: u32, align: u32, size: u32) -> Result<u32, Error> {
+            let ptr = self.memory.len().next_multiple_of(align as usize);
+            self.memory.resize(ptr + size as usize, 0);
+            Ok(ptr as u32)
+        }
+    }
+
+    impl Instance for Fake {
+        fn call(&mut self, name: &str, args: &[Val]) -> Result<Vec<Val>, Error> {
+            self.calls.push(name.to_string());
+            match (name, args) {
+                ("sum", [Val::I32(ptr), Val::I32(len)]) => {
+                    let sum = (0..*len as u32)
+                        .map(|i| u64::from(self.read_u32(*ptr as u32 + i * 4)))
+                        .sum::<u64>();
+                    Ok(vec![Val::I64(sum as i64)])
+                }
+                ("label", [Val::I32(x), Val::I32(y), Val::I32(ptr), Val::I32(len)]) => {
+                    let mut label = vec![0; *len as usize];
+                    self.read(*ptr as u32, &mut label).unwrap();
+                    let label = format!("{}@{x},{y}", String::from_utf8(label).unwrap());
+                    let string = self.alloc_bytes(label.as_bytes());
+                    let ret = self.realloc(0, 0, 4, 8)?;
+                    self.write(ret, &string.to_le_bytes())?;
+                    self.write(ret + 4, &(label.len() as u32).to_le_bytes())?;
+                    Ok(vec![Val::I32(ret as i32)])
+                }
+                ("my:calc/names#echo", [Val::I32(ptr), Val::I32(len)]) => {
+                    let ret = self.realloc(0, 0, 4, 8)?;
+                    self.write(ret, &ptr.to_le_bytes())?;
+                    self.write(ret + 4, &len.to_le_bytes())?;
+                    Ok(vec![Val::I32(ret as i32)])
+                }
+                ("cabi_post_label" | "cabi_post_my:calc/names#echo", [Val::I32(_)]) => {
+                    Ok(Vec::new())
+                }
+                _ => Err(Error::new(format!("unexpected call to `{name}`"))),
+            }
+        }
+    }
+
+    impl Host for Fake {
+        fn describe(&mut self, s: Shape, m: Mode) -> String {
+            match s {
+                Shape::Dot(p) => format!("{} {m:?}", p.label),
+                Shape::Path(points) => format!("{} points", points.len()),
+                Shape::Empty => String::new(),
+            }
+        }
+
+        fn origin(&mut self) -> Point {
+            Point {
+                x: 0,
+                y: -1,
+                label: "origin".to_string(),
+            }
+        }
+    }
+
+    #[test]
+    fn exports_are_called() {
+        let mut fake = Fake::default();
+        assert_eq!(
+            sum(&mut fake, &[1, 2, u32::MAX]).unwrap(),
+            3 + u64::from(u32::MAX)
+        );
+        let point = Point {
+            x: 1,
+            y: 2,
+            label: "p".to_string(),
+        };
+        assert_eq!(label(&mut fake, &point).unwrap(), "p@1,2");
+        assert_eq!(fake.calls, ["sum", "label", "cabi_post_label"]);
+    }
+
+    #[test]
+    fn interface_exports_are_called() {
+        let mut fake = Fake::default();
+        assert_eq!(
+            exports::my::calc::names::echo(&mut fake, "hi").unwrap(),
+            "hi"
+        );
+        assert_eq!(
+            fake.calls,
+            ["my:calc/names#echo", "cabi_post_my:calc/names#echo"]
+        );
+    }
+
+    #[test]
+    fn imports_are_dispatched() {
+        let mut fake = Fake::default();
+        assert_eq!(host_api::MODULE, "my:calc/host-api");
+
+        // `origin` returns its point through a pointer passed by the guest.
+        let ret = fake.realloc(0, 0, 4, 16).unwrap();
+        let results = host_api::dispatch(&mut fake, "origin", &[Val::I32(ret as i32)]).unwrap();
+        assert!(results.is_empty());
+        assert_eq!(fake.read_u32(ret), 0);
+        assert_eq!(fake.read_u32(ret + 4) as i32, -1);
+        assert_eq!(fake.read_string(ret + 8), "origin");
+
+        // A `shape::dot` with the `fast` flag.
+        let label = fake.alloc_bytes(b"here");
+        let ret = fake.realloc(0, 0, 4, 8).unwrap();
+        let args = [0, 3, 4, label as i32, 4, 1, ret as i32].map(Val::I32);
+        host_api::dispatch(&mut fake, "describe", &args).unwrap();
+        assert_eq!(fake.read_string(ret), "here Mode(FAST)");
+
+        // A `shape::path` of two zeroed points.
+        let points = fake.realloc(0, 0, 4, 32).unwrap();
+        let args = [1, points as i32, 2, 0, 0, 0, ret as i32].map(Val::I32);
+        host_api::dispatch(&mut fake, "describe", &args).unwrap();
+        assert_eq!(fake.read_string(ret), "2 points");
+    }
+
+    #[test]
+    fn invalid_values_are_errors() {
+        let mut fake = Fake::default();
+        let args = [7, 0, 0, 0, 0, 0, 0].map(Val::I32);
+        let err = host_api::dispatch(&mut fake, "describe", &args).unwrap_err();
+        assert_eq!(err.to_string(), "invalid discriminant");
+
+        let args = [1, 0, 1, 0, 0, 0, 0].map(Val::I32);
+        assert!(host_api::dispatch(&mut fake, "describe", &args).is_err());
+
+        let err = host_api::dispatch(&mut fake, "missing", &[]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`my:calc/host-api` has no function `missing`"
+        );
+    }
+}
+
+mod host_handles {
+    wit_bindgen::generate!({
+        inline: r#"
+            package my:store;
+
+            interface kv {
+                resource bucket {
+                    constructor(name: string);
+                    name: func() -> string;
+                }
+                fetch: func(b: borrow<bucket>, key: string) -> string;
+                sizes: func(bs: list<borrow<bucket>>) -> list<u32>;
+                watch: func(f: future<u32>, s: stream<u8>) -> error-context;
+            }
+
+            interface counter {
+                resource cell {
+                    constructor(init: u32);
+                    bump: func(by: u32) -> u32;
+                }
+                shout: func(s: string) -> string;
+            }
+
+            world not-used-name {
+                import kv;
+                export counter;
+            }
+        "#,
+        host: true,
+        async: {
+            imports: ["my:store/kv#fetch"],
+            exports: ["my:store/counter#shout"],
+        },
+    });
+
+    use exports::my::store::counter::{self, Cell};
+    use host::{Error, ErrorContext, FutureReader, Instance, Memory, Realloc, StreamReader, Val};
+    use my::store::kv::{self, Bucket, Host, HostBucket};
+
+    /// An instance with a table of `cell`s whose representations are their
+    /// values, and a host with a table of `bucket`s.
+    #[derive(Default)]
+    struct Fake {
+        memory: Vec<u8>,
+        cells: Vec<Option<u32>>,
+        buckets: Vec<String>,
+        dropped: Vec<u32>,
+        calls: Vec<String>,
+        shouted: Option<(u32, u32)>,
+    }
+
+    impl Fake {
+        fn read_u32(&self, offset: u32) -> u32 {
+            let mut bytes = [0; 4];
+            self.read(offset, &mut bytes).unwrap();
+            u32::from_le_bytes(bytes)
+        }
+
+        fn read_string(&self, ptr: u32, len: u32) -> String {
+            let mut bytes = vec![0; len as usize];
+            self.read(ptr, &mut bytes).unwrap();
+            String::from_utf8(bytes).unwrap()
+        }
+
+        fn alloc_bytes(&mut self, bytes: &[u8]) -> u32 {
+            let ptr = self.realloc(0, 0, 1, bytes.len() as u32).unwrap();
+            self.write(ptr, bytes).unwrap();
+            ptr
+        }
+
+        fn cell(&self, handle: u32) -> Result<u32, Error> {
+            self.cells
+                .get(handle as usize)
+                .copied()
+                .flatten()
+                .ok_or_else(|| Error::new("invalid handle"))
+        }
+    }
+
+    impl Memory for Fake {
+        fn read(&self, offset: u32, dst: &mut [u8]) -> Result<(), Error> {
+            let src = self
+                .memory
+                .get(offset as usize..offset as usize + dst.len())
+                .ok_or_else(|| Error::new("out of bounds"))?;
+            dst.copy_from_slice(src);
+            Ok(())
+        }
+
+        fn write(&mut self, offset: u32, src: &[u8]) -> Result<(), Error> {
+            let dst = self
+                .memory
+                .get_mut(offset as usize..offset as usize + src.len())
+                .ok_or_else(|| Error::new("out of bounds"))?;
+            dst.copy_from_slice(src);
+            Ok(())
+        }
+    }
+
+    impl Realloc for Fake {
+        fn realloc(&mut self, _: u32, _: u32, align: u32, size: u32) -> Result<u32, Error> {
+            let ptr = self.memory.len().next_multiple_of(align as usize);
+            self.memory.resize(ptr + size as usize, 0);
+            Ok(ptr as u32)
+        }
+    }
+
+    impl Instance for Fake {
+        fn call(&mut self, name: &str, args: &[Val]) -> Result<Vec<Val>, Error> {
+            self.calls.push(name.to_string());
+            match (name, args) {
+                ("my:store/counter#[constructor]cell", [Val::I32(init)]) => {
+                    self.cells.push(Some(*init as u32));
+                    Ok(vec![Val::I32(self.cells.len() as i32 - 1)])
+                }
+                ("my:store/counter#[method]cell.bump", [Val::I32(rep), Val::I32(by)]) => {
+                    let cell = self
+                        .cells
+                        .iter_mut()
+                        .flatten()
+                        .find(|cell| **cell == *rep as u32)
+                        .unwrap();
+                    *cell += *by as u32;
+                    Ok(vec![Val::I32(*cell as i32)])
+                }
+                // The task of `shout` waits for its string to be uppercased
+                // in `task_return`.
+                ("[async]my:store/counter#shout", [Val::I32(ptr), Val::I32(len)]) => {
+                    self.shouted = Some((*ptr as u32, *len as u32));
+                    Ok(vec![Val::I32(1)])
+                }
+                _ => Err(Error::new(format!("unexpected call to `{name}`"))),
+            }
+        }
+
+        fn task_return(&mut self, name: &str, status: Val) -> Result<Vec<Val>, Error> {
+            assert_eq!(name, "[async]my:store/counter#shout");
+            assert_eq!(status, Val::I32(1));
+            let (ptr, len) = self.shouted.take().unwrap();
+            let string = self.read_string(ptr, len);
+            let ptr = self.alloc_bytes(string.to_uppercase().as_bytes());
+            Ok(vec![Val::I32(ptr as i32), Val::I32(string.len() as i32)])
+        }
+
+        fn resource_rep(&mut self, resource: &str, handle: u32) -> Result<u32, Error> {
+            assert_eq!(resource, "my:store/counter#cell");
+            self.cell(handle)
+        }
+
+        fn resource_drop(&mut self, resource: &str, handle: u32) -> Result<(), Error> {
+            assert_eq!(resource, "my:store/counter#cell");
+            self.cell(handle)?;
+            self.cells[handle as usize] = None;
+            Ok(())
+        }
+    }
+
+    impl HostBucket for Fake {
+        fn new(&mut self, name: String) -> Bucket {
+            self.buckets.push(name);
+            Bucket::from_handle(self.buckets.len() as u32 - 1)
+        }
+
+        fn name(&mut self, self_: Bucket) -> String {
+            self.buckets[self_.handle() as usize].clone()
+        }
+
+        fn drop(&mut self, handle: Bucket) {
+            self.dropped.push(handle.handle());
+        }
+    }
+
+    impl Host for Fake {
+        fn fetch(&mut self, b: Bucket, key: String) -> String {
+            format!("{}/{key}", self.buckets[b.handle() as usize])
+        }
+
+        fn sizes(&mut self, bs: Vec<Bucket>) -> Vec<u32> {
+            bs.iter()
+                .map(|b| self.buckets[b.handle() as usize].len() as u32)
+                .collect()
+        }
+
+        fn watch(&mut self, f: FutureReader<u32>, s: StreamReader<u8>) -> ErrorContext {
+            ErrorContext::from_handle(f.handle() + s.handle())
+        }
+    }
+
+    #[test]
+    fn imported_resources_are_dispatched() {
+        let mut fake = Fake::default();
+        let name = fake.alloc_bytes(b"cache");
+        let args = [name as i32, 5].map(Val::I32);
+        let results = kv::dispatch(&mut fake, "[constructor]bucket", &args).unwrap();
+        assert_eq!(results, [Val::I32(0)]);
+
+        let ret = fake.realloc(0, 0, 4, 8).unwrap();
+        let args = [0, ret as i32].map(Val::I32);
+        kv::dispatch(&mut fake, "[method]bucket.name", &args).unwrap();
+        let value = fake.read_string(fake.read_u32(ret), fake.read_u32(ret + 4));
+        assert_eq!(value, "cache");
+
+        let results = kv::dispatch(&mut fake, "[resource-drop]bucket", &[Val::I32(0)]).unwrap();
+        assert!(results.is_empty());
+        assert_eq!(fake.dropped, [0]);
+    }
+
+    #[test]
+    fn borrows_in_lists_are_lifted() {
+        let mut fake = Fake::default();
+        fake.buckets.extend(["a".to_string(), "bcd".to_string()]);
+        let list = fake.alloc_bytes(&[1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0]);
+        let ret = fake.realloc(0, 0, 4, 8).unwrap();
+        let args = [list as i32, 3, ret as i32].map(Val::I32);
+        kv::dispatch(&mut fake, "sizes", &args).unwrap();
+        let (ptr, len) = (fake.read_u32(ret), fake.read_u32(ret + 4));
+        let sizes = (0..len)
+            .map(|i| fake.read_u32(ptr + i * 4))
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, [3, 1, 3]);
+    }
+
+    #[test]
+    fn async_imports_complete_immediately() {
+        let mut fake = Fake::default();
+        fake.buckets.push("cache".to_string());
+        let key = fake.alloc_bytes(b"k");
+        let params = fake.realloc(0, 0, 4, 12).unwrap();
+        fake.write(params, &0u32.to_le_bytes()).unwrap();
+        fake.write(params + 4, &key.to_le_bytes()).unwrap();
+        fake.write(params + 8, &1u32.to_le_bytes()).unwrap();
+        let ret = fake.realloc(0, 0, 4, 8).unwrap();
+        let args = [params as i32, ret as i32].map(Val::I32);
+        let results = kv::dispatch(&mut fake, "fetch", &args).unwrap();
+        assert_eq!(results, [Val::I32(host::STATUS_DONE)]);
+        let value = fake.read_string(fake.read_u32(ret), fake.read_u32(ret + 4));
+        assert_eq!(value, "cache/k");
+    }
+
+    #[test]
+    fn futures_streams_and_error_contexts_are_handles() {
+        let mut fake = Fake::default();
+        let args = [3, 4].map(Val::I32);
+        let results = kv::dispatch(&mut fake, "watch", &args).unwrap();
+        assert_eq!(results, [Val::I32(7)]);
+    }
+
+    #[test]
+    fn exported_resources_are_called() {
+        let mut fake = Fake::default();
+        fake.cells.push(None);
+        let cell = Cell::new(&mut fake, 5).unwrap();
+        assert_eq!(cell.handle(), 1);
+        assert_eq!(cell.bump(&mut fake, 2).unwrap(), 7);
+        cell.drop(&mut fake).unwrap();
+        assert_eq!(fake.cells, [None, None]);
+        assert_eq!(
+            fake.calls,
+            [
+                "my:store/counter#[constructor]cell",
+                "my:store/counter#[method]cell.bump",
+            ]
+        );
+    }
+
+    #[test]
+    fn async_exports_wait_for_their_task() {
+        let mut fake = Fake::default();
+        assert_eq!(counter::shout(&mut fake, "hi").unwrap(), "HI");
+        assert_eq!(fake.calls, ["[async]my:store/counter#shout"]);
+    }
+}
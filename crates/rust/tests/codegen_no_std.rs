@@ -21,6 +21,7 @@ mod codegen_tests {
         (resources_with_streams $name:tt $test:tt) => {};
         (resources_with_futures $name:tt $test:tt) => {};
         (error_context $name:tt $test:tt) => {};
+        (async_handles_in_aggregates $name:tt $test:tt) => {};
 
         ($id:ident $name:tt $test:tt) => {
             mod $id {
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use heck::{ToLowerCamelCase, ToShoutySnakeCase, ToUpperCamelCase};
+use heck::{ToShoutySnakeCase, ToUpperCamelCase};
 use std::{
     collections::{HashMap, HashSet},
     fmt::Write,
@@ -14,7 +14,7 @@ use wit_bindgen_core::{
         Result_, SizeAlign, Tuple, Type, TypeDef, TypeDefKind, TypeId, TypeOwner, Variant, WorldId,
         WorldKey,
     },
-    Direction, Files, InterfaceGenerator as _, Ns, Source, WorldGenerator,
+    Case, Direction, Files, InterfaceGenerator as _, Keywords, Ns, Renames, Source, WorldGenerator,
 };
 
 const IMPORTS: &str = "\
@@ -33,6 +33,15 @@ pub struct Opts {
     /// Whether or not to generate a stub class for exported functions
     #[cfg_attr(feature = "clap", arg(long))]
     pub generate_stub: bool,
+
+    /// Rename the interface or item `K` to `V` in the generated source code.
+    ///
+    /// `K` is either an interface such as `foo:bar/baz`, an item within an
+    /// interface such as `foo:bar/baz.qux`, or an item defined in the world.
+    /// A renamed interface only changes the name of its own class, not of the
+    /// package it's placed in.
+    #[cfg_attr(feature = "clap", arg(long, name = "K=V", value_parser = Renames::parse))]
+    pub rename: Vec<(String, String)>,
 }
 
 impl Opts {
@@ -53,6 +62,8 @@ struct InterfaceFragment {
 pub struct TeaVmJava {
     opts: Opts,
     name: String,
+    world: Option<WorldId>,
+    renames: Renames,
     return_area_size: usize,
     return_area_align: usize,
     tuple_counts: HashSet<usize>,
@@ -69,13 +80,19 @@ impl TeaVmJava {
         format!("{}.", self.name)
     }
 
-    fn interface<'a>(&'a mut self, resolve: &'a Resolve, name: &'a str) -> InterfaceGenerator<'a> {
+    fn interface<'a>(
+        &'a mut self,
+        resolve: &'a Resolve,
+        name: &'a str,
+        key: Option<&'a WorldKey>,
+    ) -> InterfaceGenerator<'a> {
         InterfaceGenerator {
             src: String::new(),
             stub: String::new(),
             gen: self,
             resolve,
             name,
+            key,
         }
     }
 }
@@ -83,7 +100,13 @@ impl TeaVmJava {
 impl WorldGenerator for TeaVmJava {
     fn preprocess(&mut self, resolve: &Resolve, world: WorldId) {
         self.name = world_name(resolve, world);
+        self.world = Some(world);
         self.sizes.fill(resolve);
+
+        for (from, to) in self.opts.rename.iter() {
+            self.renames.insert(from, to);
+        }
+        self.renames.warn_unmatched(resolve, world);
     }
 
     fn import_interface(
@@ -93,9 +116,9 @@ impl WorldGenerator for TeaVmJava {
         id: InterfaceId,
         _files: &mut Files,
     ) -> Result<()> {
-        let name = interface_name(resolve, key, Direction::Import);
+        let name = interface_name(&self.renames, resolve, key, Direction::Import);
         self.interface_names.insert(id, name.clone());
-        let mut gen = self.interface(resolve, &name);
+        let mut gen = self.interface(resolve, &name, Some(key));
         gen.types(id);
 
         for (_, func) in resolve.interfaces[id].functions.iter() {
@@ -115,7 +138,7 @@ impl WorldGenerator for TeaVmJava {
         _files: &mut Files,
     ) {
         let name = world_name(resolve, world);
-        let mut gen = self.interface(resolve, &name);
+        let mut gen = self.interface(resolve, &name, None);
 
         for (_, func) in funcs {
             gen.import("$root", func);
@@ -131,9 +154,9 @@ impl WorldGenerator for TeaVmJava {
         id: InterfaceId,
         _files: &mut Files,
     ) -> Result<()> {
-        let name = interface_name(resolve, key, Direction::Export);
+        let name = interface_name(&self.renames, resolve, key, Direction::Export);
         self.interface_names.insert(id, name.clone());
-        let mut gen = self.interface(resolve, &name);
+        let mut gen = self.interface(resolve, &name, Some(key));
         gen.types(id);
 
         for (_, func) in resolve.interfaces[id].functions.iter() {
@@ -152,7 +175,7 @@ impl WorldGenerator for TeaVmJava {
         _files: &mut Files,
     ) -> Result<()> {
         let name = world_name(resolve, world);
-        let mut gen = self.interface(resolve, &name);
+        let mut gen = self.interface(resolve, &name, None);
 
         for (_, func) in funcs {
             gen.export(None, func);
@@ -170,13 +193,31 @@ impl WorldGenerator for TeaVmJava {
         _files: &mut Files,
     ) {
         let name = world_name(resolve, world);
-        let mut gen = self.interface(resolve, &name);
+        let mut gen = self.interface(resolve, &name, None);
+
+        for (ty_name, ty) in types {
+            gen.define_type(ty_name, *ty);
+        }
+
+        gen.add_world_fragment();
+    }
+
+    fn export_types(
+        &mut self,
+        resolve: &Resolve,
+        world: WorldId,
+        types: &[(&str, TypeId)],
+        _files: &mut Files,
+    ) -> Result<()> {
+        let name = world_name(resolve, world);
+        let mut gen = self.interface(resolve, &name, None);
 
         for (ty_name, ty) in types {
             gen.define_type(ty_name, *ty);
         }
 
         gen.add_world_fragment();
+        Ok(())
     }
 
     fn finish(&mut self, resolve: &Resolve, id: WorldId, files: &mut Files) -> Result<()> {
@@ -444,9 +485,33 @@ struct InterfaceGenerator<'a> {
     gen: &'a mut TeaVmJava,
     resolve: &'a Resolve,
     name: &'a str,
+    key: Option<&'a WorldKey>,
 }
 
 impl InterfaceGenerator<'_> {
+    /// Returns the name of the Java method generated for `func`, honoring
+    /// renames.
+    fn func_ident(&self, func: &Function) -> String {
+        match self.gen.renames.item(self.resolve, self.key, &func.name) {
+            Some(rename) => rename.to_string(),
+            None => func.name.to_java_ident(),
+        }
+    }
+
+    /// Returns the name of the Java class generated for the named type `id`,
+    /// honoring renames.
+    fn type_ident(&self, id: TypeId) -> String {
+        let world = self.gen.world.unwrap();
+        match self.gen.renames.ty(self.resolve, world, id) {
+            Some(rename) => rename.to_string(),
+            None => self.resolve.types[id]
+                .name
+                .as_ref()
+                .unwrap()
+                .to_upper_camel_case(),
+        }
+    }
+
     fn qualifier(&self, when: bool, ty: &TypeDef) -> String {
         if let TypeOwner::Interface(id) = &ty.owner {
             if let Some(name) = self.gen.interface_names.get(id) {
@@ -486,14 +551,7 @@ impl InterfaceGenerator<'_> {
             todo!("resources");
         }
 
-        let mut bindgen = FunctionBindgen::new(
-            self,
-            &func.name,
-            func.params
-                .iter()
-                .map(|(name, _)| name.to_java_ident())
-                .collect(),
-        );
+        let mut bindgen = FunctionBindgen::new(self, &func.name, param_names(func).into());
 
         abi::call(
             bindgen.gen.resolve,
@@ -717,12 +775,8 @@ impl InterfaceGenerator<'_> {
                         format!("{}Result<{ok}, {err}>", self.gen.qualifier())
                     }
                     _ => {
-                        if let Some(name) = &ty.name {
-                            format!(
-                                "{}{}",
-                                self.qualifier(qualifier, ty),
-                                name.to_upper_camel_case()
-                            )
+                        if ty.name.is_some() {
+                            format!("{}{}", self.qualifier(qualifier, ty), self.type_ident(*id))
                         } else {
                             unreachable!()
                         }
@@ -790,7 +844,7 @@ impl InterfaceGenerator<'_> {
     }
 
     fn sig_string(&mut self, func: &Function, qualifier: bool) -> String {
-        let name = func.name.to_java_ident();
+        let name = self.func_ident(func);
 
         let result_type = match func.results.len() {
             0 => "void".into(),
@@ -814,9 +868,9 @@ impl InterfaceGenerator<'_> {
         let params = func
             .params
             .iter()
-            .map(|(name, ty)| {
+            .zip(param_names(func))
+            .map(|((_, ty), name)| {
                 let ty = self.type_name_with_qualifier(ty, qualifier);
-                let name = name.to_java_ident();
                 format!("{ty} {name}")
             })
             .collect::<Vec<_>>()
@@ -831,31 +885,23 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.resolve
     }
 
-    fn type_record(&mut self, _id: TypeId, name: &str, record: &Record, docs: &Docs) {
+    fn type_record(&mut self, id: TypeId, _name: &str, record: &Record, docs: &Docs) {
         self.print_docs(docs);
 
-        let name = name.to_upper_camel_case();
+        let name = self.type_ident(id);
+        let field_names = field_names(record);
 
         let parameters = record
             .fields
             .iter()
-            .map(|field| {
-                format!(
-                    "{} {}",
-                    self.type_name(&field.ty),
-                    field.name.to_java_ident()
-                )
-            })
+            .zip(&field_names)
+            .map(|(field, field_name)| format!("{} {field_name}", self.type_name(&field.ty)))
             .collect::<Vec<_>>()
             .join(", ");
 
-        let assignments = record
-            .fields
+        let assignments = field_names
             .iter()
-            .map(|field| {
-                let name = field.name.to_java_ident();
-                format!("this.{name} = {name};")
-            })
+            .map(|name| format!("this.{name} = {name};"))
             .collect::<Vec<_>>()
             .join("\n");
 
@@ -865,12 +911,9 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
             record
                 .fields
                 .iter()
-                .map(|field| {
-                    format!(
-                        "public final {} {};",
-                        self.type_name(&field.ty),
-                        field.name.to_java_ident()
-                    )
+                .zip(&field_names)
+                .map(|(field, field_name)| {
+                    format!("public final {} {field_name};", self.type_name(&field.ty))
                 })
                 .collect::<Vec<_>>()
                 .join("\n")
@@ -895,10 +938,10 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         todo!()
     }
 
-    fn type_flags(&mut self, _id: TypeId, name: &str, flags: &Flags, docs: &Docs) {
+    fn type_flags(&mut self, id: TypeId, _name: &str, flags: &Flags, docs: &Docs) {
         self.print_docs(docs);
 
-        let name = name.to_upper_camel_case();
+        let name = self.type_ident(id);
 
         let ty = match flags.repr() {
             FlagsRepr::U8 => "byte",
@@ -908,12 +951,10 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
             repr => todo!("flags {repr:?}"),
         };
 
-        let flags = flags
-            .flags
-            .iter()
+        let flags = constant_names(flags.flags.iter().map(|flag| flag.name.as_str()))
+            .into_iter()
             .enumerate()
-            .map(|(i, flag)| {
-                let flag_name = flag.name.to_shouty_snake_case();
+            .map(|(i, flag_name)| {
                 let suffix = if matches!(flags.repr(), FlagsRepr::U32(2)) {
                     "L"
                 } else {
@@ -946,23 +987,26 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.type_name(&Type::Id(id));
     }
 
-    fn type_variant(&mut self, _id: TypeId, name: &str, variant: &Variant, docs: &Docs) {
+    fn type_variant(&mut self, id: TypeId, _name: &str, variant: &Variant, docs: &Docs) {
         self.print_docs(docs);
 
-        let name = name.to_upper_camel_case();
+        let name = self.type_ident(id);
         let tag_type = int_type(variant.tag());
+        let constructor_names = case_constructors(variant);
+        let accessor_names = case_accessors(variant);
+        let tag_names = constant_names(variant.cases.iter().map(|case| case.name.as_str()));
 
         let constructors = variant
             .cases
             .iter()
-            .map(|case| {
-                let case_name = case.name.to_java_ident();
-                let tag = case.name.to_shouty_snake_case();
+            .zip(&constructor_names)
+            .zip(&tag_names)
+            .map(|((case, case_name), tag)| {
                 let (parameter, argument) = if let Some(ty) = self.non_empty_type(case.ty.as_ref())
                 {
                     (
                         format!("{} {case_name}", self.type_name(ty)),
-                        case_name.deref(),
+                        case_name.as_str(),
                     )
                 } else {
                     (String::new(), "null")
@@ -981,13 +1025,13 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         let accessors = variant
             .cases
             .iter()
-            .filter_map(|case| {
+            .zip(&accessor_names)
+            .zip(&tag_names)
+            .filter_map(|((case, accessor), tag)| {
                 self.non_empty_type(case.ty.as_ref()).map(|ty| {
-                    let case_name = case.name.to_upper_camel_case();
-                    let tag = case.name.to_shouty_snake_case();
                     let ty = self.type_name(ty);
                     format!(
-                        r#"public {ty} get{case_name}() {{
+                        r#"public {ty} {accessor}() {{
                                if (this.tag == {tag}) {{
                                    return ({ty}) this.value;
                                }} else {{
@@ -1001,14 +1045,10 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
             .collect::<Vec<_>>()
             .join("\n");
 
-        let tags = variant
-            .cases
+        let tags = tag_names
             .iter()
             .enumerate()
-            .map(|(i, case)| {
-                let tag = case.name.to_shouty_snake_case();
-                format!("public static final {tag_type} {tag} = {i};")
-            })
+            .map(|(i, tag)| format!("public static final {tag_type} {tag} = {i};"))
             .collect::<Vec<_>>()
             .join("\n");
 
@@ -1040,17 +1080,12 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.type_name(&Type::Id(id));
     }
 
-    fn type_enum(&mut self, _id: TypeId, name: &str, enum_: &Enum, docs: &Docs) {
+    fn type_enum(&mut self, id: TypeId, _name: &str, enum_: &Enum, docs: &Docs) {
         self.print_docs(docs);
 
-        let name = name.to_upper_camel_case();
+        let name = self.type_ident(id);
 
-        let cases = enum_
-            .cases
-            .iter()
-            .map(|case| case.name.to_shouty_snake_case())
-            .collect::<Vec<_>>()
-            .join(", ");
+        let cases = constant_names(enum_.cases.iter().map(|case| case.name.as_str())).join(", ");
 
         uwrite!(
             self.src,
@@ -1143,9 +1178,11 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
         }
     }
 
+    /// Lowers a variant whose cases are given as the name of their accessor
+    /// and their payload type.
     fn lower_variant(
         &mut self,
-        cases: &[(&str, Option<Type>)],
+        cases: &[(String, Option<Type>)],
         lowered_types: &[WasmType],
         op: &str,
         results: &mut Vec<String>,
@@ -1180,12 +1217,11 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
             .zip(payloads)
             .enumerate()
             .map(
-                |(i, (((name, ty), Block { body, results, .. }), payload))| {
+                |(i, (((accessor, ty), Block { body, results, .. }), payload))| {
                     let payload = if let Some(ty) = self.gen.non_empty_type(ty.as_ref()) {
                         let ty = self.gen.type_name(ty);
-                        let name = name.to_upper_camel_case();
 
-                        format!("{ty} {payload} = ({op}).get{name}();")
+                        format!("{ty} {payload} = ({op}).{accessor}();")
                     } else {
                         String::new()
                     };
@@ -1224,10 +1260,12 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
         );
     }
 
+    /// Lifts a variant whose cases are given as the name of their constructor
+    /// and their payload type.
     fn lift_variant(
         &mut self,
         ty: &Type,
-        cases: &[(&str, Option<Type>)],
+        cases: &[(String, Option<Type>)],
         op: &str,
         results: &mut Vec<String>,
     ) {
@@ -1244,7 +1282,7 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
             .iter()
             .zip(blocks)
             .enumerate()
-            .map(|(i, ((case_name, case_ty), Block { body, results, .. }))| {
+            .map(|(i, ((method, case_ty), Block { body, results, .. }))| {
                 let payload = if self.gen.non_empty_type(case_ty.as_ref()).is_some() {
                     results.into_iter().next().unwrap()
                 } else if generics_position.is_some() {
@@ -1257,8 +1295,6 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
                     String::new()
                 };
 
-                let method = case_name.to_java_ident();
-
                 let call = if let Some(position) = generics_position {
                     let (ty, generics) = ty.split_at(position);
                     format!("{ty}.{generics}{method}")
@@ -1395,8 +1431,8 @@ impl Bindgen for FunctionBindgen<'_, '_> {
 
             Instruction::RecordLower { record, .. } => {
                 let op = &operands[0];
-                for field in record.fields.iter() {
-                    results.push(format!("({op}).{}", field.name.to_java_ident()));
+                for name in field_names(record) {
+                    results.push(format!("({op}).{name}"));
                 }
             }
             Instruction::RecordLift { ty, .. } | Instruction::TupleLift { ty, .. } => {
@@ -1427,10 +1463,9 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 results: lowered_types,
                 ..
             } => self.lower_variant(
-                &variant
-                    .cases
-                    .iter()
-                    .map(|case| (case.name.deref(), case.ty))
+                &case_accessors(variant)
+                    .into_iter()
+                    .zip(variant.cases.iter().map(|case| case.ty))
                     .collect::<Vec<_>>(),
                 lowered_types,
                 &operands[0],
@@ -1439,10 +1474,9 @@ impl Bindgen for FunctionBindgen<'_, '_> {
 
             Instruction::VariantLift { variant, ty, .. } => self.lift_variant(
                 &Type::Id(*ty),
-                &variant
-                    .cases
-                    .iter()
-                    .map(|case| (case.name.deref(), case.ty))
+                &case_constructors(variant)
+                    .into_iter()
+                    .zip(variant.cases.iter().map(|case| case.ty))
                     .collect::<Vec<_>>(),
                 &operands[0],
                 results,
@@ -1560,7 +1594,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 result,
                 ..
             } => self.lower_variant(
-                &[("ok", result.ok), ("err", result.err)],
+                &[("getOk".into(), result.ok), ("getErr".into(), result.err)],
                 lowered_types,
                 &operands[0],
                 results,
@@ -1568,7 +1602,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
 
             Instruction::ResultLift { result, ty } => self.lift_variant(
                 &Type::Id(*ty),
-                &[("ok", result.ok), ("err", result.err)],
+                &[("ok".into(), result.ok), ("err".into(), result.err)],
                 &operands[0],
                 results,
             ),
@@ -1826,7 +1860,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 };
 
                 let module = self.gen.name;
-                let name = func.name.to_java_ident();
+                let name = self.gen.func_ident(func);
 
                 let args = operands.join(", ");
 
@@ -2053,6 +2087,8 @@ impl Bindgen for FunctionBindgen<'_, '_> {
             Instruction::AsyncMalloc { .. }
             | Instruction::AsyncPostCallInterface { .. }
             | Instruction::AsyncCallReturn { .. }
+            | Instruction::AsyncTaskReturn { .. }
+            | Instruction::CallPostReturn { .. }
             | Instruction::FutureLower { .. }
             | Instruction::FutureLift { .. }
             | Instruction::StreamLower { .. }
@@ -2253,7 +2289,12 @@ fn world_name(resolve: &Resolve, world: WorldId) -> String {
     )
 }
 
-fn interface_name(resolve: &Resolve, name: &WorldKey, direction: Direction) -> String {
+fn interface_name(
+    renames: &Renames,
+    resolve: &Resolve,
+    name: &WorldKey,
+    direction: Direction,
+) -> String {
     let pkg = match name {
         WorldKey::Name(_) => None,
         WorldKey::Interface(id) => {
@@ -2262,11 +2303,15 @@ fn interface_name(resolve: &Resolve, name: &WorldKey, direction: Direction) -> S
         }
     };
 
-    let name = match name {
-        WorldKey::Name(name) => name,
-        WorldKey::Interface(id) => resolve.interfaces[*id].name.as_ref().unwrap(),
-    }
-    .to_upper_camel_case();
+    let name = match (renames.interface(resolve, name), name) {
+        (Some(rename), _) => rename.to_string(),
+        (None, WorldKey::Name(name)) => name.to_upper_camel_case(),
+        (None, WorldKey::Interface(id)) => resolve.interfaces[*id]
+            .name
+            .as_ref()
+            .unwrap()
+            .to_upper_camel_case(),
+    };
 
     format!(
         "wit.{}.{}{name}",
@@ -2307,17 +2352,60 @@ trait ToJavaIdent: ToOwned {
 
 impl ToJavaIdent for str {
     fn to_java_ident(&self) -> String {
-        // Escape Java keywords
-        // Source: https://docs.oracle.com/javase/tutorial/java/nutsandbolts/_keywords.html
-        match self {
-            "abstract" | "continue" | "for" | "new" | "switch" | "assert" | "default" | "goto"
-            | "package" | "synchronized" | "boolean" | "do" | "if" | "private" | "this"
-            | "break" | "double" | "implements" | "protected" | "throw" | "byte" | "else"
-            | "import" | "public" | "throws" | "case" | "enum" | "instanceof" | "return"
-            | "transient" | "catch" | "extends" | "int" | "short" | "try" | "char" | "final"
-            | "interface" | "static" | "void" | "class" | "finally" | "long" | "strictfp"
-            | "volatile" | "const" | "float" | "native" | "super" | "while" => format!("{self}_"),
-            _ => self.to_lower_camel_case(),
-        }
+        Keywords::JAVA.ident(self, Case::LowerCamel)
     }
 }
+
+/// Returns the names of the fields of `record`, which are kept distinct even
+/// when several WIT names map to the same Java identifier.
+fn field_names(record: &Record) -> Vec<String> {
+    let mut scope = Keywords::JAVA.scope();
+    record
+        .fields
+        .iter()
+        .map(|field| scope.ident(&field.name, Case::LowerCamel))
+        .collect()
+}
+
+/// Returns the names of the parameters of `func`, kept distinct like
+/// [`field_names`].
+fn param_names(func: &Function) -> Vec<String> {
+    let mut scope = Keywords::JAVA.scope();
+    func.params
+        .iter()
+        .map(|(name, _)| scope.ident(name, Case::LowerCamel))
+        .collect()
+}
+
+/// Returns the names of the static methods constructing each case of
+/// `variant`, kept distinct like [`field_names`].
+fn case_constructors(variant: &Variant) -> Vec<String> {
+    let mut scope = Keywords::JAVA.scope();
+    variant
+        .cases
+        .iter()
+        .map(|case| scope.ident(&case.name, Case::LowerCamel))
+        .collect()
+}
+
+/// Returns the names of the `get*` accessors of the cases of `variant`, kept
+/// distinct like [`field_names`].
+fn case_accessors(variant: &Variant) -> Vec<String> {
+    let mut scope = Keywords::JAVA.scope();
+    variant
+        .cases
+        .iter()
+        .map(|case| format!("get{}", scope.unique(&case.name.to_upper_camel_case())))
+        .collect()
+}
+
+/// Returns the names of the constants generated for the flags of a WIT flags,
+/// the cases of an enum or the tags of a variant, kept distinct like
+/// [`field_names`].
+fn constant_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut scope = Keywords::JAVA.scope();
+    names
+        .into_iter()
+        .map(|name| scope.unique(&name.to_shouty_snake_case()))
+        .collect()
+}
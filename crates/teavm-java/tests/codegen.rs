@@ -37,6 +37,7 @@ macro_rules! codegen_test {
     (resources_with_streams $name:tt $test:tt) => {};
     (resources_with_futures $name:tt $test:tt) => {};
     (error_context $name:tt $test:tt) => {};
+    (async_handles_in_aggregates $name:tt $test:tt) => {};
 
     ($id:ident $name:tt $test:tt) => {
         #[test]
@@ -47,6 +48,7 @@ macro_rules! codegen_test {
                 |resolve, world, files| {
                     wit_bindgen_teavm_java::Opts {
                         generate_stub: true,
+                        rename: Vec::new(),
                     }
                     .build()
                     .generate(resolve, world, files)
@@ -6,7 +6,7 @@ use std::process::Command;
 use wasm_encoder::{Encode, Section};
 use wit_bindgen_core::Files;
 use wit_component::StringEncoding;
-use wit_parser::{ManglingAndAbi, Resolve, WorldId};
+use wit_parser::{ManglingAndAbi, Resolve, WorldId, WorldItem};
 
 /// Returns a suitable directory to place output for tests within.
 ///
@@ -140,6 +140,56 @@ pub fn run_component_codegen_test(
     verify(&dir, &world_name);
 }
 
+/// Returns a world which exports a resource and other types directly rather
+/// than through an interface.
+///
+/// WIT syntax can't export types from a world, but such worlds can still be
+/// constructed, so this moves a world's types and resource functions to its
+/// exports.
+pub fn world_exporting_types() -> (Resolve, WorldId) {
+    let mut resolve = Resolve::default();
+    let pkg = resolve
+        .push_str(
+            "test.wit",
+            r#"
+                package a:b;
+
+                world exported-types {
+                    resource counter {
+                        constructor(start: u32);
+                        add: func(n: amount) -> point;
+                        merge: static func(a: counter, b: counter) -> counter;
+                    }
+                    type amount = u32;
+                    record point {
+                        x: amount,
+                        y: amount,
+                    }
+                    enum mode {
+                        up,
+                        down,
+                    }
+
+                    export make: func(p: point, m: mode) -> counter;
+                }
+            "#,
+        )
+        .unwrap();
+    let world = resolve.select_world(pkg, None).unwrap();
+    let imports = std::mem::take(&mut resolve.worlds[world].imports);
+    for (key, item) in imports {
+        let dst = match &item {
+            WorldItem::Type(_) => &mut resolve.worlds[world].exports,
+            WorldItem::Function(f) if f.kind.resource().is_some() => {
+                &mut resolve.worlds[world].exports
+            }
+            _ => &mut resolve.worlds[world].imports,
+        };
+        dst.insert(key, item);
+    }
+    (resolve, world)
+}
+
 fn parse_wit(path: &Path) -> (Resolve, WorldId) {
     let mut resolve = Resolve::default();
     let (pkg, _files) = resolve.push_path(path).unwrap();
@@ -1,9 +1,15 @@
 use anyhow::{bail, Context, Error, Result};
 use clap::Parser;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::str;
+use std::time::{Duration, SystemTime};
 use wit_bindgen_core::{wit_parser, Files, WorldGenerator};
-use wit_parser::Resolve;
+use wit_component::DecodedWasm;
+use wit_parser::{PackageId, Resolve};
 
 /// Helper for passing VERSION to opt.
 /// If CARGO_VERSION_INFO is set, use it, otherwise use CARGO_PKG_VERSION.
@@ -38,7 +44,7 @@ enum Opt {
         #[clap(flatten)]
         args: Common,
     },
-    /// Generates bindings for C/CPP guest modules.
+    /// Generates bindings for C guest modules.
     #[cfg(feature = "c")]
     C {
         #[clap(flatten)]
@@ -47,6 +53,15 @@ enum Opt {
         args: Common,
     },
 
+    /// Generates bindings for C++ guest modules.
+    #[cfg(feature = "cpp")]
+    Cpp {
+        #[clap(flatten)]
+        opts: wit_bindgen_cpp::Opts,
+        #[clap(flatten)]
+        args: Common,
+    },
+
     /// Generates bindings for TeaVM-based Java guest modules.
     #[cfg(feature = "teavm-java")]
     TeavmJava {
@@ -72,11 +87,95 @@ enum Opt {
         #[clap(flatten)]
         args: Common,
     },
+
+    /// Generates all bindings listed in a `wit-bindgen.toml` manifest.
+    ///
+    /// Each `[[target]]` in the manifest names a language subcommand along
+    /// with the world, output directory, features, and generator options to
+    /// use for it. WIT inputs shared between targets are only parsed once.
+    Build {
+        /// Path to the manifest describing the bindings to generate.
+        #[clap(value_name = "MANIFEST", default_value = "wit-bindgen.toml")]
+        manifest: PathBuf,
+
+        /// Indicates that no files are written and instead files are checked
+        /// if they're up-to-date with the source files for every target.
+        #[clap(long)]
+        check: bool,
+
+        /// Updates out-of-date files and removes files which are no longer
+        /// generated for every target.
+        #[clap(long, conflicts_with = "check")]
+        bless: bool,
+
+        /// Removes files which are no longer generated for every target, and
+        /// records generated files in each output directory.
+        #[clap(long, conflicts_with = "check")]
+        clean: bool,
+    },
+}
+
+impl Opt {
+    /// Name of the subcommand selecting this generator, or `None` for
+    /// `build`.
+    fn language(&self) -> Option<&'static str> {
+        Some(match self {
+            #[cfg(feature = "markdown")]
+            Opt::Markdown { .. } => "markdown",
+            #[cfg(feature = "moonbit")]
+            Opt::Moonbit { .. } => "moonbit",
+            #[cfg(feature = "c")]
+            Opt::C { .. } => "c",
+            #[cfg(feature = "cpp")]
+            Opt::Cpp { .. } => "cpp",
+            #[cfg(feature = "rust")]
+            Opt::Rust { .. } => "rust",
+            #[cfg(feature = "teavm-java")]
+            Opt::TeavmJava { .. } => "teavm-java",
+            #[cfg(feature = "go")]
+            Opt::TinyGo { .. } => "tiny-go",
+            #[cfg(feature = "csharp")]
+            Opt::CSharp { .. } => "c-sharp",
+            Opt::Build { .. } => return None,
+        })
+    }
+
+    /// Returns the generator selected by this command along with the options
+    /// common to all generators, or `None` for `build`.
+    fn generator(self) -> Option<(Box<dyn WorldGenerator>, Common)> {
+        let language = self.language()?;
+        let (generator, mut args) = match self {
+            #[cfg(feature = "markdown")]
+            Opt::Markdown { opts, args } => (opts.build(), args),
+            #[cfg(feature = "moonbit")]
+            Opt::Moonbit { opts, args } => (opts.build(), args),
+            #[cfg(feature = "c")]
+            Opt::C { opts, args } => (opts.build(), args),
+            #[cfg(feature = "cpp")]
+            Opt::Cpp { opts, args } => (opts.build(), args),
+            #[cfg(feature = "rust")]
+            Opt::Rust { opts, args } => (opts.build(), args),
+            #[cfg(feature = "teavm-java")]
+            Opt::TeavmJava { opts, args } => (opts.build(), args),
+            #[cfg(feature = "go")]
+            Opt::TinyGo { opts, args } => (opts.build(), args),
+            #[cfg(feature = "csharp")]
+            Opt::CSharp { opts, args } => (opts.build(), args),
+            Opt::Build { .. } => return None,
+        };
+        args.language = language;
+        Some((generator, args))
+    }
 }
 
 #[derive(Debug, Parser)]
 struct Common {
     /// Where to place output files
+    ///
+    /// With `--clean` or `--bless`, generated files are recorded in a
+    /// `.wit-bindgen-files` file in this directory so that `--check` and
+    /// `--clean` can find files which are no longer generated. Once it exists
+    /// that file is kept up to date by every run.
     #[clap(long = "out-dir")]
     out_dir: Option<PathBuf>,
 
@@ -86,6 +185,9 @@ struct Common {
     /// file itself, or a `*.wasm` file which is a wasm-encoded WIT package.
     /// Most of the time it's likely to be a directory containing `*.wit` files
     /// with an optional `deps` folder inside of it.
+    ///
+    /// If this is `-` then a single WIT file, or a wasm-encoded WIT package,
+    /// is read from stdin.
     #[clap(value_name = "WIT", index = 1)]
     wit: PathBuf,
 
@@ -103,9 +205,67 @@ struct Common {
 
     /// Indicates that no files are written and instead files are checked if
     /// they're up-to-date with the source files.
+    ///
+    /// All out-of-date files are reported along with a diff of their changes.
+    /// If `--out-dir` has a `.wit-bindgen-files` file, written by `--clean`
+    /// or `--bless`, then files which were generated previously but no longer
+    /// would be are reported too, as is that file if it's out of date.
+    /// Without it such files can't be told apart from other files in
+    /// `--out-dir` and aren't reported.
     #[clap(long)]
     check: bool,
 
+    /// Updates files which are out-of-date and removes files in `--out-dir`
+    /// which were generated previously but no longer would be.
+    ///
+    /// This is the counterpart to `--check` for updating files it complains
+    /// about, and implies `--clean`.
+    #[clap(long, conflicts_with = "check")]
+    bless: bool,
+
+    /// Removes files in `--out-dir` which were generated previously but no
+    /// longer would be.
+    ///
+    /// Generated files are recorded in a `.wit-bindgen-files` file in the
+    /// output directory, and only files recorded there by a previous run are
+    /// ever removed.
+    #[clap(long, requires = "out_dir", conflicts_with = "check")]
+    clean: bool,
+
+    /// Keeps running and regenerates bindings whenever any of the WIT files
+    /// they were generated from changes, including those in `deps`.
+    ///
+    /// Only output files whose contents changed are rewritten.
+    #[clap(long, conflicts_with_all = ["check", "bless"])]
+    watch: bool,
+
+    /// Writes a JSON description of the generated files to this path.
+    ///
+    /// The description lists the path and SHA-256 hash of every generated
+    /// file, the world they were generated for, and every WIT file read to
+    /// generate them, for use by build systems tracking dependencies.
+    #[clap(long, value_name = "PATH")]
+    emit_manifest: Option<PathBuf>,
+
+    /// Prints the JSON description of `--emit-manifest` to stdout instead of
+    /// writing any files.
+    #[clap(long, conflicts_with_all = ["check", "bless", "watch"])]
+    list_files: bool,
+
+    /// Writes the generated file to stdout instead of `--out-dir`.
+    ///
+    /// This requires the generator to produce a single file unless the name
+    /// of the file to write is passed, as in `--stdout=bindings.md`, in which
+    /// case the other files are discarded.
+    #[clap(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        require_equals = true,
+        conflicts_with_all = ["out_dir", "check", "bless", "clean", "watch", "list_files"],
+    )]
+    stdout: Option<Option<String>>,
+
     /// Comma-separated list of features that should be enabled when processing
     /// WIT files.
     ///
@@ -118,60 +278,188 @@ struct Common {
     /// This enables using `@unstable` annotations in WIT files.
     #[clap(long)]
     all_features: bool,
+
+    /// Name of the subcommand which selected the generator, used to tell
+    /// apart the files recorded for each generator in an output directory.
+    #[clap(skip)]
+    language: &'static str,
+}
+
+impl Common {
+    /// Returns where the generated file `name` is written.
+    fn output_path(&self, name: &str) -> PathBuf {
+        match &self.out_dir {
+            Some(path) => path.join(name),
+            None => name.into(),
+        }
+    }
 }
 
 fn main() -> Result<()> {
+    let opt = Opt::parse();
+    if let Opt::Build {
+        manifest,
+        check,
+        bless,
+        clean,
+    } = &opt
+    {
+        return build(manifest, *check, *bless, *clean);
+    }
+    let (generator, opt) = opt.generator().unwrap();
+    if opt.watch && opt.wit == Path::new("-") {
+        bail!("`--watch` cannot be used when reading WIT from stdin");
+    }
+    if opt.watch {
+        // Generators are consumed by generation, so each run re-parses the
+        // command line to get a fresh one.
+        return watch(&opt, || Opt::parse().generator().unwrap().0);
+    }
     let mut files = Files::default();
-    let (generator, opt) = match Opt::parse() {
-        #[cfg(feature = "markdown")]
-        Opt::Markdown { opts, args } => (opts.build(), args),
-        #[cfg(feature = "moonbit")]
-        Opt::Moonbit { opts, args } => (opts.build(), args),
-        #[cfg(feature = "c")]
-        Opt::C { opts, args } => (opts.build(), args),
-        #[cfg(feature = "rust")]
-        Opt::Rust { opts, args } => (opts.build(), args),
-        #[cfg(feature = "teavm-java")]
-        Opt::TeavmJava { opts, args } => (opts.build(), args),
-        #[cfg(feature = "go")]
-        Opt::TinyGo { opts, args } => (opts.build(), args),
-        #[cfg(feature = "csharp")]
-        Opt::CSharp { opts, args } => (opts.build(), args),
+    let inputs = gen_world(generator, &opt, &mut Resolves::default(), &mut files)
+        .map_err(attach_with_context)?;
+    emit_manifest(&opt, &inputs, &files)?;
+    if opt.list_files {
+        return Ok(());
+    }
+    if let Some(name) = &opt.stdout {
+        return write_stdout(name.as_deref(), &files);
+    }
+    let mut stale = write_files(&opt, &files)?;
+
+    let mut out_dirs = OutDirs::default();
+    out_dirs.add(&opt, &inputs, &files);
+    stale.extend(out_dirs.finish()?);
+    bail_if_stale(stale)
+}
+
+/// Regenerates bindings for `opt` each time a WIT file they depend on changes.
+///
+/// Errors are reported without exiting so that they can be fixed in place.
+fn watch(opt: &Common, generator: impl Fn() -> Box<dyn WorldGenerator>) -> Result<()> {
+    let mut sources = BTreeSet::new();
+    loop {
+        let mut resolves = Resolves::default();
+        let mut files = Files::default();
+        let result = gen_world(generator(), opt, &mut resolves, &mut files)
+            .map_err(attach_with_context)
+            .and_then(|inputs| {
+                emit_manifest(opt, &inputs, &files)?;
+                write_files(opt, &files)?;
+                let mut out_dirs = OutDirs::default();
+                out_dirs.add(opt, &inputs, &files);
+                out_dirs.finish()
+            });
+        if let Err(e) = result {
+            eprintln!("error: {e:?}");
+        }
+
+        // If the WIT failed to parse then nothing was loaded, so keep watching
+        // whatever was loaded last time to pick up the fix.
+        if !resolves.cache.is_empty() {
+            sources = resolves.sources();
+        }
+        sources.insert(opt.wit.clone());
+        eprintln!("Watching {} files for changes...", sources.len());
+        wait_for_change(&sources);
+    }
+}
+
+/// Blocks until any of `paths`, or a directory containing one of them, is
+/// modified.
+///
+/// Directories are included so that files added to `deps`, or replaced by
+/// editors which write to a temporary file and rename it, are noticed too.
+/// Polling is used as it works the same everywhere and the set of files is
+/// small.
+fn wait_for_change(paths: &BTreeSet<PathBuf>) {
+    let watched = paths
+        .iter()
+        .flat_map(|p| [Some(p.as_path()), p.parent()])
+        .flatten()
+        .filter(|p| !p.as_os_str().is_empty())
+        .collect::<BTreeSet<_>>();
+    let mtimes = || {
+        watched
+            .iter()
+            .map(|p| p.metadata().and_then(|m| m.modified()).ok())
+            .collect::<Vec<Option<SystemTime>>>()
     };
+    let before = mtimes();
+    loop {
+        std::thread::sleep(Duration::from_millis(250));
+        if mtimes() != before {
+            // Give editors and tools touching several files a moment to finish
+            // before regenerating.
+            std::thread::sleep(Duration::from_millis(100));
+            return;
+        }
+    }
+}
 
-    gen_world(generator, &opt, &mut files).map_err(attach_with_context)?;
+/// Runs every target listed in the manifest at `path`.
+fn build(path: &Path, check: bool, bless: bool, clean: bool) -> Result<()> {
+    let contents =
+        std::fs::read_to_string(path).with_context(|| format!("failed to read {path:?}"))?;
+    let manifest: Manifest =
+        toml::from_str(&contents).with_context(|| format!("failed to parse {path:?}"))?;
+    let root = path.parent().unwrap_or(Path::new(""));
+
+    let mut resolves = Resolves::default();
+    let mut out_dirs = OutDirs::default();
+    let mut stale = Vec::new();
+    for (i, target) in manifest.target.iter().enumerate() {
+        let (generator, mut opt) = target
+            .parse(&manifest, root)
+            .with_context(|| format!("invalid target {i} in {path:?}"))?;
+        opt.check |= check;
+        opt.bless |= bless;
+        opt.clean |= clean;
+
+        let mut files = Files::default();
+        let inputs = gen_world(generator, &opt, &mut resolves, &mut files)
+            .map_err(attach_with_context)
+            .with_context(|| format!("failed to generate bindings for target {i} in {path:?}"))?;
+        emit_manifest(&opt, &inputs, &files)?;
+        if opt.list_files {
+            continue;
+        }
+        stale.extend(write_files(&opt, &files)?);
+        out_dirs.add(&opt, &inputs, &files);
+    }
+
+    // Orphans are only looked for once all targets are generated since
+    // multiple targets may share an output directory.
+    stale.extend(out_dirs.finish()?);
+    bail_if_stale(stale)
+}
 
+/// Writes `files` to the output directory specified in `opt`, or checks that
+/// they're up-to-date if `--check` was passed.
+///
+/// Files which are already up-to-date aren't rewritten, leaving their
+/// modification times alone so build systems don't rebuild needlessly.
+///
+/// Returns a description of each file which is out of date, which is only ever
+/// non-empty with `--check`.
+fn write_files(opt: &Common, files: &Files) -> Result<Vec<String>> {
+    let mut stale = Vec::new();
     for (name, contents) in files.iter() {
-        let dst = match &opt.out_dir {
-            Some(path) => path.join(name),
-            None => name.into(),
+        let dst = opt.output_path(name);
+        let prev = match std::fs::read(&dst) {
+            Ok(prev) => Some(prev),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e).with_context(|| format!("failed to read {:?}", dst)),
         };
-        eprintln!("Generating {:?}", dst);
-
+        if prev.as_deref() == Some(contents) {
+            continue;
+        }
         if opt.check {
-            let prev = std::fs::read(&dst).with_context(|| format!("failed to read {:?}", dst))?;
-            if prev != contents {
-                // The contents differ. If it looks like textual contents, do a
-                // line-by-line comparison so that we can tell users what the
-                // problem is directly.
-                if let (Ok(utf8_prev), Ok(utf8_contents)) =
-                    (str::from_utf8(&prev), str::from_utf8(contents))
-                {
-                    if !utf8_prev
-                        .chars()
-                        .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
-                        && utf8_prev.lines().eq(utf8_contents.lines())
-                    {
-                        bail!("{} differs only in line endings (CRLF vs. LF). If this is a text file, configure git to mark the file as `text eol=lf`.", dst.display());
-                    }
-                }
-                // The contents are binary or there are other differences; just
-                // issue a generic error.
-                bail!("not up to date: {}", dst.display());
-            }
+            stale.push(describe_stale(&dst, prev.as_deref(), contents));
             continue;
         }
 
+        eprintln!("Generating {:?}", dst);
         if let Some(parent) = dst.parent() {
             std::fs::create_dir_all(parent)
                 .with_context(|| format!("failed to create {:?}", parent))?;
@@ -179,9 +467,510 @@ fn main() -> Result<()> {
         std::fs::write(&dst, contents).with_context(|| format!("failed to write {:?}", dst))?;
     }
 
+    Ok(stale)
+}
+
+/// Writes the file `name` from `files`, or the only file if `name` is `None`,
+/// to stdout.
+fn write_stdout(name: Option<&str>, files: &Files) -> Result<()> {
+    let contents = match name {
+        Some(name) => match files.iter().find(|(n, _)| *n == name) {
+            Some((_, contents)) => contents,
+            None => bail!(
+                "no file named `{name}` was generated, expected one of: {}",
+                files.iter().map(|(n, _)| n).collect::<Vec<_>>().join(", ")
+            ),
+        },
+        None => {
+            let mut iter = files.iter();
+            match (iter.next(), iter.next()) {
+                (Some((_, contents)), None) => contents,
+                _ => bail!(
+                    "`--stdout` requires a single generated file but {} were generated, \
+                     pass `--stdout=FILE` to pick one of: {}",
+                    files.iter().count(),
+                    files.iter().map(|(n, _)| n).collect::<Vec<_>>().join(", ")
+                ),
+            }
+        }
+    };
+    std::io::stdout()
+        .write_all(contents)
+        .context("failed to write to stdout")
+}
+
+/// JSON description of a single run of a generator, written by
+/// `--emit-manifest` and `--list-files`.
+#[derive(Serialize)]
+struct FileList<'a> {
+    /// Fully qualified name of the world bindings were generated for.
+    world: &'a str,
+    files: Vec<FileListEntry>,
+    /// Every WIT file read while generating, including dependencies.
+    inputs: &'a [PathBuf],
+}
+
+#[derive(Serialize)]
+struct FileListEntry {
+    path: PathBuf,
+    sha256: String,
+}
+
+impl<'a> FileList<'a> {
+    fn new(opt: &Common, inputs: &'a Inputs, files: &Files) -> FileList<'a> {
+        let files = files
+            .iter()
+            .map(|(name, contents)| FileListEntry {
+                path: opt.output_path(name),
+                sha256: Sha256::digest(contents)
+                    .iter()
+                    .map(|b| format!("{b:02x}"))
+                    .collect(),
+            })
+            .collect();
+        FileList {
+            world: &inputs.world,
+            files,
+            inputs: &inputs.sources,
+        }
+    }
+}
+
+/// Handles `--emit-manifest` and `--list-files` for `files`.
+fn emit_manifest(opt: &Common, inputs: &Inputs, files: &Files) -> Result<()> {
+    if opt.emit_manifest.is_none() && !opt.list_files {
+        return Ok(());
+    }
+    let mut json = serde_json::to_string_pretty(&FileList::new(opt, inputs, files))?;
+    json.push('\n');
+    if opt.list_files {
+        print!("{json}");
+    }
+    if let Some(path) = &opt.emit_manifest {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {:?}", parent))?;
+        }
+        std::fs::write(path, json).with_context(|| format!("failed to write {:?}", path))?;
+    }
+    Ok(())
+}
+
+/// Describes why `dst`, which previously contained `prev`, doesn't match the
+/// freshly generated `contents`.
+fn describe_stale(dst: &Path, prev: Option<&[u8]>, contents: &[u8]) -> String {
+    let prev = match prev {
+        Some(prev) => prev,
+        None => return format!("missing: {}", dst.display()),
+    };
+
+    // If it looks like textual contents, do a line-by-line comparison so that
+    // we can tell users what the problem is directly.
+    if let (Ok(utf8_prev), Ok(utf8_contents)) = (str::from_utf8(prev), str::from_utf8(contents)) {
+        if !utf8_prev
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+        {
+            if utf8_prev.lines().eq(utf8_contents.lines()) {
+                return format!("{} differs only in line endings (CRLF vs. LF). If this is a text file, configure git to mark the file as `text eol=lf`.", dst.display());
+            }
+            let name = dst.display().to_string();
+            let diff = similar::TextDiff::from_lines(utf8_prev, utf8_contents)
+                .unified_diff()
+                .header(&name, &name)
+                .to_string();
+            return format!("not up to date: {}\n{diff}", dst.display());
+        }
+    }
+
+    // The contents are binary; just issue a generic error.
+    format!("not up to date: {}", dst.display())
+}
+
+/// Name of the file recording which files were generated into an output
+/// directory.
+///
+/// The file is opt-in: it's created by `--clean` or `--bless`, and only
+/// updated by other runs once it exists.
+///
+/// Files are listed under a `[<generator> <world>]` line for each generator
+/// and world they were generated for, so that several invocations of
+/// `wit-bindgen` can share an output directory.
+const GENERATED_FILES: &str = ".wit-bindgen-files";
+
+/// Files generated within each `--out-dir`, used to find files which were
+/// generated previously but no longer are.
+#[derive(Default)]
+struct OutDirs {
+    dirs: BTreeMap<PathBuf, OutDir>,
+}
+
+#[derive(Default)]
+struct OutDir {
+    /// Names of generated files, relative to the output directory, keyed by
+    /// the generator and world they were generated for.
+    generated: BTreeMap<String, BTreeSet<String>>,
+    check: bool,
+    clean: bool,
+}
+
+impl OutDirs {
+    fn add(&mut self, opt: &Common, inputs: &Inputs, files: &Files) {
+        if let Some(out_dir) = &opt.out_dir {
+            let dir = self.dirs.entry(out_dir.clone()).or_default();
+            dir.generated
+                .entry(format!("{} {}", opt.language, inputs.world))
+                .or_default()
+                .extend(files.iter().map(|(name, _)| name.to_string()));
+            dir.check |= opt.check;
+            dir.clean |= opt.clean || opt.bless;
+        }
+    }
+
+    /// Reports orphaned files with `--check`, or removes them with `--clean`.
+    ///
+    /// Only files previously recorded for the generators and worlds which
+    /// were just run are considered, so nothing is an orphan until a
+    /// [`GENERATED_FILES`] list exists. When the list exists, or with
+    /// `--clean`, the entries for the generators and worlds which were just
+    /// run are updated while entries for others are kept. With `--check` an
+    /// out-of-date list is reported instead.
+    fn finish(&self) -> Result<Vec<String>> {
+        let mut stale = Vec::new();
+        for (out_dir, dir) in self.dirs.iter() {
+            let list = out_dir.join(GENERATED_FILES);
+            let prev = match std::fs::read_to_string(&list) {
+                Ok(prev) => Some(prev),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(e).with_context(|| format!("failed to read {list:?}")),
+            };
+            let mut recorded = parse_generated_files(prev.as_deref().unwrap_or(""))
+                .with_context(|| format!("invalid list of generated files in {list:?}"))?;
+
+            if dir.check || dir.clean {
+                // Files recorded for anything that wasn't just run are still
+                // owned by it, so they're never orphans.
+                let mut generated = HashSet::new();
+                let mut previous = HashSet::new();
+                for (key, names) in recorded.iter() {
+                    let set = if dir.generated.contains_key(key) {
+                        &mut previous
+                    } else {
+                        &mut generated
+                    };
+                    set.extend(names.iter().map(|name| out_dir.join(name)));
+                }
+                generated.extend(
+                    dir.generated
+                        .values()
+                        .flatten()
+                        .map(|name| out_dir.join(name)),
+                );
+                for orphan in orphans(&generated, &previous) {
+                    if dir.check {
+                        stale.push(format!(
+                            "no longer generated: {}\n(use `--bless` to remove it)",
+                            orphan.display()
+                        ));
+                    } else {
+                        eprintln!("Removing {:?}", orphan);
+                        std::fs::remove_file(&orphan)
+                            .with_context(|| format!("failed to remove {:?}", orphan))?;
+                    }
+                }
+            }
+
+            if prev.is_none() && !dir.clean {
+                continue;
+            }
+            for (key, names) in dir.generated.iter() {
+                recorded.insert(key.clone(), names.clone());
+            }
+            let contents = render_generated_files(&recorded);
+            if prev.as_deref() == Some(contents.as_str()) {
+                continue;
+            }
+            if dir.check {
+                stale.push(describe_stale(
+                    &list,
+                    prev.as_deref().map(str::as_bytes),
+                    contents.as_bytes(),
+                ));
+                continue;
+            }
+            std::fs::create_dir_all(out_dir)
+                .with_context(|| format!("failed to create {:?}", out_dir))?;
+            std::fs::write(&list, contents)
+                .with_context(|| format!("failed to write {:?}", list))?;
+        }
+        Ok(stale)
+    }
+}
+
+/// Parses the contents of a [`GENERATED_FILES`] list into the names of the
+/// files recorded for each generator and world.
+///
+/// Comments, and files listed before any `[<generator> <world>]` line, are
+/// ignored. Files must be relative paths within the output directory since
+/// `--clean` removes them.
+fn parse_generated_files(contents: &str) -> Result<BTreeMap<String, BTreeSet<String>>> {
+    let mut recorded = BTreeMap::<String, BTreeSet<String>>::new();
+    let mut current = None;
+    for line in contents.lines() {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        if let Some(key) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = Some(recorded.entry(key.to_string()).or_default());
+            continue;
+        }
+        let within = Path::new(line)
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)));
+        if !within {
+            bail!("`{line}` isn't a relative path within the output directory");
+        }
+        if let Some(names) = &mut current {
+            names.insert(line.to_string());
+        }
+    }
+    Ok(recorded)
+}
+
+/// Renders `recorded` in the format read by [`parse_generated_files`].
+fn render_generated_files(recorded: &BTreeMap<String, BTreeSet<String>>) -> String {
+    let mut contents = String::from(
+        "# Generated by `wit-bindgen`. Lists the files generated into this\n\
+         # directory for each generator and world so that `--check` and\n\
+         # `--clean` can find them once they no longer are.\n",
+    );
+    for (key, names) in recorded.iter() {
+        contents.push_str(&format!("[{key}]\n"));
+        for name in names {
+            contents.push_str(name);
+            contents.push('\n');
+        }
+    }
+    contents
+}
+
+/// Returns files which were previously generated, according to `recorded`,
+/// but are no longer part of `generated`.
+///
+/// Only files recorded by a previous run are considered so that files written
+/// by other tools, or other invocations of `wit-bindgen`, are left alone.
+fn orphans(generated: &HashSet<PathBuf>, recorded: &HashSet<PathBuf>) -> Vec<PathBuf> {
+    let mut orphans = recorded
+        .iter()
+        .filter(|path| !generated.contains(*path) && path.is_file())
+        .cloned()
+        .collect::<Vec<_>>();
+    orphans.sort();
+    orphans
+}
+
+/// Fails with all of the problems found by `--check`, if any.
+fn bail_if_stale(stale: Vec<String>) -> Result<()> {
+    match stale.len() {
+        0 => Ok(()),
+        1 => bail!("{}", stale[0]),
+        n => bail!("{n} files are not up to date:\n\n{}", stale.join("\n")),
+    }
+}
+
+/// Contents of a `wit-bindgen.toml` file used with `wit-bindgen build`.
+///
+/// ```toml
+/// wit = "wit"
+///
+/// [[target]]
+/// language = "rust"
+/// world = "my:app/plugin"
+/// out-dir = "src/bindings"
+/// options = { generate-all = true, with = ["wasi:io/streams=wasi::io::streams"] }
+///
+/// [[target]]
+/// language = "c"
+/// world = "my:app/plugin"
+/// out-dir = "c/bindings"
+/// features = ["experimental"]
+/// ```
+///
+/// All paths are relative to the directory containing the manifest.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct Manifest {
+    /// Default location of WIT files for targets which don't specify one.
+    wit: Option<PathBuf>,
+    #[serde(default)]
+    target: Vec<Target>,
+}
+
+/// A single set of bindings to generate from a [`Manifest`].
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct Target {
+    /// Name of the subcommand to run, such as `rust` or `c`.
+    language: String,
+    wit: Option<PathBuf>,
+    world: Option<String>,
+    out_dir: Option<PathBuf>,
+    #[serde(default)]
+    features: Vec<String>,
+    #[serde(default)]
+    all_features: bool,
+    /// Generator-specific options, named after their command line flags.
+    ///
+    /// `true` passes a flag, `false` omits it, and arrays pass the flag once
+    /// per element.
+    #[serde(default)]
+    options: BTreeMap<String, toml::Value>,
+}
+
+impl Target {
+    /// Translates this target into the equivalent command line and parses it
+    /// the same way a standalone invocation would be.
+    fn parse(&self, manifest: &Manifest, root: &Path) -> Result<(Box<dyn WorldGenerator>, Common)> {
+        let wit = match self.wit.as_ref().or(manifest.wit.as_ref()) {
+            Some(wit) => root.join(wit),
+            None => bail!("no `wit` path specified for target or manifest"),
+        };
+
+        let mut args = vec![
+            "wit-bindgen".to_string(),
+            self.language.clone(),
+            wit.display().to_string(),
+        ];
+        if let Some(world) = &self.world {
+            args.push(format!("--world={world}"));
+        }
+        if let Some(out_dir) = &self.out_dir {
+            args.push(format!("--out-dir={}", root.join(out_dir).display()));
+        }
+        if !self.features.is_empty() {
+            args.push(format!("--features={}", self.features.join(",")));
+        }
+        if self.all_features {
+            args.push("--all-features".to_string());
+        }
+        for (name, value) in self.options.iter() {
+            push_option(&mut args, name, value)?;
+        }
+
+        match Opt::try_parse_from(&args)?.generator() {
+            Some(pair) => Ok(pair),
+            None => bail!("`{}` is not a bindings generator", self.language),
+        }
+    }
+}
+
+fn push_option(args: &mut Vec<String>, name: &str, value: &toml::Value) -> Result<()> {
+    match value {
+        toml::Value::Boolean(true) => args.push(format!("--{name}")),
+        toml::Value::Boolean(false) => {}
+        toml::Value::String(s) => args.push(format!("--{name}={s}")),
+        toml::Value::Integer(i) => args.push(format!("--{name}={i}")),
+        toml::Value::Array(values) => {
+            for value in values {
+                push_option(args, name, value)?;
+            }
+        }
+        _ => bail!("unsupported value for option `{name}`: {value}"),
+    }
     Ok(())
 }
 
+/// Cache of parsed WIT inputs so that targets which share the same WIT path
+/// and features only parse it once.
+#[derive(Default)]
+struct Resolves {
+    cache: HashMap<(PathBuf, Vec<String>, bool), Parsed>,
+}
+
+struct Parsed {
+    resolve: Resolve,
+    pkg: PackageId,
+    /// Every file loaded while parsing, including dependencies.
+    sources: Vec<PathBuf>,
+}
+
+/// What bindings were generated from, as returned by [`gen_world`].
+struct Inputs {
+    world: String,
+    sources: Vec<PathBuf>,
+}
+
+impl Resolves {
+    fn get(&mut self, opts: &Common) -> Result<&Parsed> {
+        let mut features = opts
+            .features
+            .iter()
+            .flat_map(|s| s.split(','))
+            .flat_map(|s| s.split_whitespace())
+            .filter(|f| !f.is_empty())
+            .map(|f| f.to_string())
+            .collect::<Vec<_>>();
+        features.sort();
+        features.dedup();
+
+        let key = (opts.wit.clone(), features, opts.all_features);
+        if !self.cache.contains_key(&key) {
+            let mut resolve = Resolve {
+                all_features: key.2,
+                ..Resolve::default()
+            };
+            resolve.features.extend(key.1.iter().cloned());
+            let (pkg, mut sources) = if key.0 == Path::new("-") {
+                let mut contents = Vec::new();
+                std::io::stdin()
+                    .read_to_end(&mut contents)
+                    .context("failed to read WIT from stdin")?;
+                (push_bytes(&mut resolve, &contents)?, Vec::new())
+            } else {
+                let (pkg, sources) = resolve.push_path(&key.0)?;
+                let sources = sources.paths().map(|p| p.to_path_buf()).collect();
+                (pkg, sources)
+            };
+            sources.sort();
+            self.cache.insert(
+                key.clone(),
+                Parsed {
+                    resolve,
+                    pkg,
+                    sources,
+                },
+            );
+        }
+        Ok(&self.cache[&key])
+    }
+
+    /// Every file loaded while parsing any WIT input so far.
+    fn sources(&self) -> BTreeSet<PathBuf> {
+        self.cache
+            .values()
+            .flat_map(|p| p.sources.iter().cloned())
+            .collect()
+    }
+}
+
+/// Adds the WIT package in `contents`, either as text or in its wasm encoding,
+/// to `resolve`.
+fn push_bytes(resolve: &mut Resolve, contents: &[u8]) -> Result<PackageId> {
+    if contents.starts_with(b"\0asm") {
+        return match wit_component::decode(contents)? {
+            DecodedWasm::WitPackage(decoded, pkg) => {
+                let remap = resolve.merge(decoded)?;
+                Ok(remap.packages[pkg.index()])
+            }
+            DecodedWasm::Component(..) => {
+                bail!("found an actual component instead of an encoded WIT package in wasm")
+            }
+        };
+    }
+    let text = str::from_utf8(contents).context("input WIT is not valid utf-8")?;
+    resolve.push_str("<stdin>", text)
+}
+
 fn attach_with_context(err: Error) -> Error {
     #[cfg(feature = "rust")]
     if let Some(e) = err.downcast_ref::<wit_bindgen_rust::MissingWith>() {
@@ -196,24 +985,25 @@ fn attach_with_context(err: Error) -> Error {
 fn gen_world(
     mut generator: Box<dyn WorldGenerator>,
     opts: &Common,
+    resolves: &mut Resolves,
     files: &mut Files,
-) -> Result<()> {
-    let mut resolve = Resolve::default();
-    resolve.all_features = opts.all_features;
-    for features in opts.features.iter() {
-        for feature in features
-            .split(',')
-            .flat_map(|s| s.split_whitespace())
-            .filter(|f| !f.is_empty())
-        {
-            resolve.features.insert(feature.to_string());
-        }
-    }
-    let (pkg, _files) = resolve.push_path(&opts.wit)?;
-    let world = resolve.select_world(pkg, opts.world.as_deref())?;
-    generator.generate(&resolve, world, files)?;
+) -> Result<Inputs> {
+    let Parsed {
+        resolve,
+        pkg,
+        sources,
+    } = resolves.get(opts)?;
+    let world = resolve.select_world(*pkg, opts.world.as_deref())?;
+    generator.generate(resolve, world, files)?;
 
-    Ok(())
+    let world = &resolve.worlds[world];
+    Ok(Inputs {
+        world: match world.package {
+            Some(pkg) => resolve.id_of_name(pkg, &world.name),
+            None => world.name.clone(),
+        },
+        sources: sources.clone(),
+    })
 }
 
 #[test]
@@ -221,3 +1011,186 @@ fn verify_cli() {
     use clap::CommandFactory;
     Opt::command().debug_assert()
 }
+
+#[test]
+fn manifest_targets() {
+    let manifest: Manifest = toml::from_str(
+        r#"
+            wit = "wit"
+
+            [[target]]
+            language = "c"
+            world = "foo:bar/baz"
+            out-dir = "c"
+            features = ["a", "b"]
+
+            [[target]]
+            language = "markdown"
+            wit = "other.wit"
+            all-features = true
+        "#,
+    )
+    .unwrap();
+
+    let (_, c) = manifest.target[0]
+        .parse(&manifest, Path::new("root"))
+        .unwrap();
+    assert_eq!(c.wit, Path::new("root/wit"));
+    assert_eq!(c.out_dir.as_deref(), Some(Path::new("root/c")));
+    assert_eq!(c.world.as_deref(), Some("foo:bar/baz"));
+    assert_eq!(c.features, ["a,b"]);
+    assert!(!c.all_features);
+
+    let (_, md) = manifest.target[1]
+        .parse(&manifest, Path::new("root"))
+        .unwrap();
+    assert_eq!(md.wit, Path::new("root/other.wit"));
+    assert!(md.out_dir.is_none());
+    assert!(md.all_features);
+}
+
+#[test]
+fn stale_files_are_described() {
+    let dst = Path::new("out/foo.h");
+    assert_eq!(describe_stale(dst, None, b"x"), "missing: out/foo.h");
+    assert!(describe_stale(dst, Some(b"a\r\nb\r\n"), b"a\nb\n").contains("line endings"));
+
+    let msg = describe_stale(dst, Some(b"a\nb\nc\n"), b"a\nB\nc\n");
+    assert!(msg.starts_with("not up to date: out/foo.h\n"));
+    assert!(msg.contains("\n-b\n+B\n"));
+}
+
+#[test]
+fn resolves_record_sources() {
+    let dir = std::env::temp_dir().join(format!("wit-bindgen-sources-{}", std::process::id()));
+    let deps = dir.join("deps");
+    std::fs::create_dir_all(&deps).unwrap();
+    std::fs::write(
+        dir.join("world.wit"),
+        "package a:a; world w { import b:b/i; }",
+    )
+    .unwrap();
+    std::fs::write(deps.join("b.wit"), "package b:b; interface i {}").unwrap();
+
+    let opts = Common::try_parse_from(["wit-bindgen", dir.to_str().unwrap()]).unwrap();
+    let mut resolves = Resolves::default();
+    resolves.get(&opts).unwrap();
+    let sources = resolves.sources().into_iter().collect::<Vec<_>>();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(sources, [deps.join("b.wit"), dir.join("world.wit")]);
+}
+
+#[test]
+fn file_list_json() {
+    let opts = Common::try_parse_from(["wit-bindgen", "wit", "--out-dir=out"]).unwrap();
+    let inputs = Inputs {
+        world: "a:b/c".to_string(),
+        sources: vec!["wit/c.wit".into()],
+    };
+    let mut files = Files::default();
+    files.push("c.h", b"abc");
+
+    let json = serde_json::to_value(FileList::new(&opts, &inputs, &files)).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "world": "a:b/c",
+            "files": [{
+                "path": "out/c.h",
+                "sha256": "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            }],
+            "inputs": ["wit/c.wit"],
+        })
+    );
+}
+
+#[test]
+fn orphans_are_recorded() {
+    let dir = std::env::temp_dir().join(format!("wit-bindgen-orphans-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("kept.h"), "// Generated by `wit-bindgen`").unwrap();
+    std::fs::write(dir.join("unrecorded.h"), "// Generated by `wit-bindgen`").unwrap();
+    std::fs::write(dir.join("recorded.html"), "<h1>").unwrap();
+
+    let generated = HashSet::from([dir.join("kept.h")]);
+    let recorded = HashSet::from([
+        dir.join("kept.h"),
+        dir.join("recorded.html"),
+        dir.join("removed.h"),
+    ]);
+    let orphans = orphans(&generated, &recorded);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(orphans, [dir.join("recorded.html")]);
+}
+
+#[test]
+fn generated_files_are_opt_in() {
+    let dir = std::env::temp_dir().join(format!("wit-bindgen-opt-in-{}", std::process::id()));
+    let out_dir = format!("--out-dir={}", dir.display());
+    let list = dir.join(GENERATED_FILES);
+    let inputs = Inputs {
+        world: "a:b/c".to_string(),
+        sources: Vec::new(),
+    };
+    let mut files = Files::default();
+    files.push("c.h", b"abc");
+    let finish = |files: &Files, flags: &[&str]| {
+        let mut opts =
+            Common::try_parse_from(["wit-bindgen", "wit", out_dir.as_str()].iter().chain(flags))
+                .unwrap();
+        opts.language = "c";
+        let mut out_dirs = OutDirs::default();
+        out_dirs.add(&opts, &inputs, files);
+        out_dirs.finish().unwrap()
+    };
+
+    // Plain runs, and `--check`, don't create the list.
+    assert!(finish(&files, &[]).is_empty());
+    assert!(finish(&files, &["--check"]).is_empty());
+    assert!(!list.exists());
+
+    // `--clean` creates it, after which it's checked and kept up to date.
+    assert!(finish(&files, &["--clean"]).is_empty());
+    assert!(std::fs::read_to_string(&list)
+        .unwrap()
+        .contains("[c a:b/c]\nc.h\n"));
+    assert!(finish(&files, &["--check"]).is_empty());
+    files.push("c.c", b"def");
+    let stale = finish(&files, &["--check"]);
+    assert_eq!(stale.len(), 1);
+    assert!(stale[0].starts_with(&format!("not up to date: {}", list.display())));
+    assert!(finish(&files, &[]).is_empty());
+    let contents = std::fs::read_to_string(&list).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(contents.contains("[c a:b/c]\nc.c\nc.h\n"));
+}
+
+#[test]
+fn generated_files_stay_within_out_dir() {
+    let recorded = parse_generated_files("# comment\n[c a:b/c]\nc.h\nsub/c.c\n").unwrap();
+    assert_eq!(
+        recorded["c a:b/c"],
+        BTreeSet::from(["c.h".to_string(), "sub/c.c".to_string()])
+    );
+
+    for name in ["/etc/passwd", "../c.h", "sub/../../c.h"] {
+        let contents = format!("[c a:b/c]\n{name}\n");
+        assert!(parse_generated_files(&contents).is_err(), "{name}");
+    }
+}
+
+#[test]
+fn wit_from_bytes() {
+    let wit = "package a:a; world w { export f: func(); }";
+    let mut resolve = Resolve::default();
+    let pkg = push_bytes(&mut resolve, wit.as_bytes()).unwrap();
+    assert!(resolve.select_world(pkg, Some("w")).is_ok());
+
+    let wasm = wit_component::encode(&resolve, pkg).unwrap();
+    let mut resolve = Resolve::default();
+    let pkg = push_bytes(&mut resolve, &wasm).unwrap();
+    assert!(resolve.select_world(pkg, Some("w")).is_ok());
+}
@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, fs};
+use wasmtime::{Caller, Config, Engine, Instance, Linker, Module, Store, Trap};
+
+const MODULE: &str = "test:checked-lifts/test";
+
+/// Values returned by the imports of `test` which are out of range for the
+/// types they're lifted into, along with the message `WIT_LIFT_ERROR` gets.
+const BAD_VALUES: &[(&str, i32, &str)] = &[
+    ("bad-u8", 256, "invalid u8"),
+    ("bad-s8", 128, "invalid s8"),
+    ("bad-u16", 65536, "invalid u16"),
+    ("bad-s16", -32769, "invalid s16"),
+    ("bad-char", 0xD800, "invalid char"),
+    ("bad-bool", 2, "invalid bool"),
+    ("bad-enum", 3, "invalid enum discriminant"),
+    ("bad-variant", 2, "invalid variant discriminant"),
+];
+
+// Components can't be handed invalid values by wasmtime's host bindings, so
+// these tests instantiate the guest's core module directly and return bad
+// values from its imports.
+#[test]
+fn run() -> Result<()> {
+    let mut config = Config::new();
+    config.cache_config_load_default()?;
+    config.wasm_backtrace_details(wasmtime::WasmBacktraceDetails::Enable);
+    let engine = Engine::new(&config)?;
+
+    // By default invalid values abort, which traps.
+    let module = Module::from_file(&engine, compile("abort", None)?)?;
+    for (which, (name, _, _)) in BAD_VALUES.iter().enumerate() {
+        let err = check(&engine, &module, which as u32)
+            .err()
+            .with_context(|| format!("lifting the result of `{name}` didn't trap"))?;
+        assert!(err.downcast_ref::<Trap>().is_some(), "{err:?}");
+    }
+
+    // Overriding `WIT_LIFT_ERROR` reports them to the guest instead.
+    let module = Module::from_file(
+        &engine,
+        compile(
+            "override",
+            Some(
+                "WIT_LIFT_ERROR(message)=do { \
+                     extern void wit_lift_error(const char *); \
+                     wit_lift_error(message); \
+                 } while (0)",
+            ),
+        )?,
+    )?;
+    for (which, (name, _, message)) in BAD_VALUES.iter().enumerate() {
+        let reported = check(&engine, &module, which as u32)?;
+        assert_eq!(reported, *message, "lifting the result of `{name}`");
+    }
+
+    Ok(())
+}
+
+/// Calls the guest's `check` export and returns the string it returns.
+fn check(engine: &Engine, module: &Module, which: u32) -> Result<String> {
+    let mut store = Store::new(engine, ());
+    let instance = instantiate(&mut store, module)?;
+    if let Ok(init) = instance.get_typed_func::<(), ()>(&mut store, "_initialize") {
+        init.call(&mut store, ())?;
+    }
+    let check = instance.get_typed_func::<u32, u32>(&mut store, "check")?;
+    let ret = check.call(&mut store, which)? as usize;
+
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    let data = memory.data(&store);
+    let word = |at: usize| u32::from_le_bytes(data[at..at + 4].try_into().unwrap()) as usize;
+    let (ptr, len) = (word(ret), word(ret + 4));
+    Ok(String::from_utf8(data[ptr..ptr + len].to_vec())?)
+}
+
+fn instantiate(store: &mut Store<()>, module: &Module) -> Result<Instance> {
+    let mut linker = Linker::new(store.engine());
+    for (name, value, _) in BAD_VALUES {
+        if *name == "bad-variant" {
+            // Variants with payloads are returned through memory.
+            let value = *value as u8;
+            linker.func_wrap(MODULE, name, move |mut caller: Caller<'_, ()>, ret: u32| {
+                let memory = caller.get_export("memory").unwrap().into_memory().unwrap();
+                memory.data_mut(&mut caller)[ret as usize] = value;
+            })?;
+        } else {
+            let value = *value;
+            linker.func_wrap(MODULE, name, move || value)?;
+        }
+    }
+    linker.define_unknown_imports_as_traps(module)?;
+    linker.instantiate(store, module)
+}
+
+/// Compiles the C guest with checked lifts, defining `define` when compiling
+/// the bindings, and returns the path of the core module.
+fn compile(name: &str, define: Option<&str>) -> Result<PathBuf> {
+    let dir = Path::new("./tests/runtime/checked_lifts");
+    let (resolve, world) = crate::resolve_wit_dir(dir);
+
+    let mut out_dir = env::current_exe()?;
+    out_dir.pop();
+    out_dir.pop();
+    out_dir.pop();
+    out_dir.push("runtime-tests");
+    out_dir.push("checked_lifts");
+    out_dir.push(name);
+    drop(fs::remove_dir_all(&out_dir));
+    fs::create_dir_all(&out_dir)?;
+
+    let mut files = Default::default();
+    let mut opts = wit_bindgen_c::Opts::default();
+    opts.checked_lifts = wit_bindgen_c::Enabled::Yes;
+    opts.build().generate(&resolve, world, &mut files)?;
+    for (file, contents) in files.iter() {
+        fs::write(out_dir.join(file), contents)?;
+    }
+
+    let sdk = PathBuf::from(
+        env::var_os("WASI_SDK_PATH")
+            .expect("point the `WASI_SDK_PATH` environment variable to the path of your wasi-sdk"),
+    );
+    let out_wasm = out_dir.join("checked_lifts.wasm");
+    let mut cmd = Command::new(sdk.join("bin/clang"));
+    cmd.arg("--sysroot").arg(sdk.join("share/wasi-sysroot"));
+    cmd.arg(dir.join("wasm.c"))
+        .arg(out_dir.join("checked_lifts.c"))
+        .arg(out_dir.join("checked_lifts_component_type.o"))
+        .arg("-I")
+        .arg(&out_dir)
+        .arg("-Wall")
+        .arg("-Wextra")
+        .arg("-Werror")
+        .arg("-Wno-unused-parameter")
+        .arg("-mexec-model=reactor")
+        .arg("-g")
+        .arg("-o")
+        .arg(&out_wasm);
+    if let Some(define) = define {
+        cmd.arg(format!("-D{define}"));
+    }
+    let output = cmd.output().context("failed to spawn compiler")?;
+    if !output.status.success() {
+        println!("status: {}", output.status);
+        println!("stdout: ------------------------------------------");
+        println!("{}", String::from_utf8_lossy(&output.stdout));
+        println!("stderr: ------------------------------------------");
+        println!("{}", String::from_utf8_lossy(&output.stderr));
+        panic!("failed to compile");
+    }
+    Ok(out_wasm)
+}
@@ -0,0 +1,42 @@
+#include <checked_lifts.h>
+#include <stddef.h>
+
+static const char *lift_error = NULL;
+
+// Called by `WIT_LIFT_ERROR` when the bindings are compiled with it overridden.
+void wit_lift_error(const char *message) {
+  lift_error = message;
+}
+
+void exports_checked_lifts_check(uint32_t which, checked_lifts_string_t *ret) {
+  lift_error = NULL;
+  switch (which) {
+    case 0:
+      test_checked_lifts_test_bad_u8();
+      break;
+    case 1:
+      test_checked_lifts_test_bad_s8();
+      break;
+    case 2:
+      test_checked_lifts_test_bad_u16();
+      break;
+    case 3:
+      test_checked_lifts_test_bad_s16();
+      break;
+    case 4:
+      test_checked_lifts_test_bad_char();
+      break;
+    case 5:
+      test_checked_lifts_test_bad_bool();
+      break;
+    case 6:
+      test_checked_lifts_test_bad_enum();
+      break;
+    case 7: {
+      test_checked_lifts_test_maybe_t maybe;
+      test_checked_lifts_test_bad_variant(&maybe);
+      break;
+    }
+  }
+  checked_lifts_string_dup(ret, lift_error != NULL ? lift_error : "");
+}
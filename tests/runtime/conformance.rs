@@ -0,0 +1,1347 @@
+//! Cross-generator ABI conformance tests.
+//!
+//! The WIT in `tests/runtime/conformance` describes a set of type shapes, each
+//! with an `echo-<shape>` function taking and returning that shape. For every
+//! enabled backend this module generates a guest which implements those
+//! functions by returning their argument unchanged, and then the host
+//! round-trips randomized values through each guest using wasmtime's dynamic
+//! component API. Any divergence between what a generator lowers and what it
+//! lifts shows up as a mismatch here without needing a hand-written test.
+//!
+//! Resources follow a fixed convention: each resource has a
+//! `constructor(v: u32)` and a `value: func() -> u32` method returning the
+//! constructor's argument, which is how the host identifies handles after
+//! they've gone through a guest.
+//!
+//! Values are generated from a fixed seed so every run is reproducible. Set
+//! `WIT_BINDGEN_CONFORMANCE_SEED` to try other values.
+//!
+//! Guests are generated for every backend. The Go, TeaVM-Java, C# and MoonBit
+//! guests are only built when their toolchain is found, and are otherwise
+//! skipped with a note in the test's output. TeaVM-Java doesn't support
+//! resources yet, so its guest implements the `values` world, which leaves out
+//! the `echo-resources` interface.
+
+use anyhow::{bail, Context, Result};
+use heck::{ToLowerCamelCase, ToSnakeCase, ToUpperCamelCase};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use wasmtime::component::types::{ResourceType, Type};
+use wasmtime::component::{Component, Func, Linker, ResourceAny, ResourceTable, Val};
+use wasmtime::{Config, Engine, Store};
+use wasmtime_wasi::{WasiCtx, WasiCtxBuilder};
+use wit_component::{ComponentEncoder, StringEncoding};
+use wit_parser::{FunctionKind, InterfaceId, Resolve, WorldId, WorldItem};
+
+/// Number of randomized values passed through each echo function.
+const ITERATIONS: usize = 100;
+
+/// How deeply lists may nest before they're forced to be empty.
+const MAX_DEPTH: u32 = 3;
+
+/// Seed used unless `WIT_BINDGEN_CONFORMANCE_SEED` is set.
+const DEFAULT_SEED: u64 = 0x5eed_c0de_ab1c_0ffe;
+
+#[test]
+fn run() -> Result<()> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/runtime/conformance");
+    let mut resolve = Resolve::new();
+    let (pkg, _files) = resolve.push_path(&dir)?;
+    let worlds = Worlds {
+        all: resolve.select_world(pkg, Some("conformance"))?,
+        values: resolve.select_world(pkg, Some("values"))?,
+    };
+
+    let mut out_dir = std::env::current_exe()?;
+    out_dir.pop();
+    out_dir.pop();
+    out_dir.pop();
+    out_dir.push("runtime-tests");
+    out_dir.push("conformance");
+    fs::create_dir_all(&out_dir)?;
+
+    let seed = match std::env::var("WIT_BINDGEN_CONFORMANCE_SEED") {
+        Ok(seed) => seed
+            .parse()
+            .context("invalid WIT_BINDGEN_CONFORMANCE_SEED")?,
+        Err(_) => DEFAULT_SEED,
+    };
+
+    for (wasm, world) in guests(&dir, &resolve, &worlds, &out_dir)? {
+        println!("testing {wasm:?}");
+        round_trip(&wasm, &resolve, world, seed)
+            .with_context(|| format!("conformance failure in {wasm:?} with seed {seed}"))?;
+    }
+    Ok(())
+}
+
+/// Returns the interfaces exported by `world`, all of which are echoed.
+fn exported_interfaces(resolve: &Resolve, world: WorldId) -> Vec<InterfaceId> {
+    resolve.worlds[world]
+        .exports
+        .values()
+        .filter_map(|item| match item {
+            WorldItem::Interface { id, .. } => Some(*id),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The worlds guests are built for.
+struct Worlds {
+    /// Exports every interface.
+    all: WorldId,
+    /// Leaves out shapes containing resources.
+    #[allow(dead_code)] // not used by all generators
+    values: WorldId,
+}
+
+/// Builds an echo guest for every enabled backend whose toolchain is
+/// available, returning the paths of the resulting components along with the
+/// world each implements.
+#[allow(unused_variables, unused_mut)] // not used by all generators
+fn guests(
+    dir: &Path,
+    resolve: &Resolve,
+    worlds: &Worlds,
+    out_dir: &Path,
+) -> Result<Vec<(PathBuf, WorldId)>> {
+    let mut result = Vec::new();
+    let world = worlds.all;
+    let wasi_adapter =
+        fs::read(test_artifacts::ADAPTER).context("failed to read the wasi adapter")?;
+
+    #[cfg(feature = "rust")]
+    result.push((
+        rust_guest(dir, resolve, world, &out_dir.join("rust"), &wasi_adapter)?,
+        world,
+    ));
+
+    #[cfg(feature = "c")]
+    result.push((
+        c_guest(resolve, world, &out_dir.join("c"), &wasi_adapter)?,
+        world,
+    ));
+
+    #[cfg(feature = "cpp")]
+    result.push((
+        cpp_guest(resolve, world, &out_dir.join("cpp"), &wasi_adapter)?,
+        world,
+    ));
+
+    // The Go generator formats its output with `gofmt`.
+    #[cfg(feature = "go")]
+    if toolchain("Go", "tinygo") && toolchain("Go", "gofmt") {
+        result.push((
+            go_guest(resolve, world, &out_dir.join("go"), &wasi_adapter)?,
+            world,
+        ));
+    }
+
+    #[cfg(feature = "teavm-java")]
+    {
+        // The TeaVM jars are downloaded next to `runtime-tests` by
+        // `ci/download-teavm.sh`.
+        let jars = out_dir.ancestors().nth(2).unwrap();
+        let jars = [
+            jars.join("teavm-interop-0.2.8.jar"),
+            jars.join("teavm-cli-0.2.8.jar"),
+        ];
+        if !jars.iter().all(|jar| jar.is_file()) {
+            println!("skipping the TeaVM-Java conformance guest: run ci/download-teavm.sh first");
+        } else if toolchain("TeaVM-Java", "javac") {
+            let world = worlds.values;
+            result.push((
+                java_guest(
+                    resolve,
+                    world,
+                    &out_dir.join("teavm-java"),
+                    &jars,
+                    &wasi_adapter,
+                )?,
+                world,
+            ));
+        }
+    }
+
+    // Like the other C# runtime tests this relies on NativeAOT-LLVM, which is
+    // only available on Windows.
+    #[cfg(feature = "csharp")]
+    if cfg!(windows) && toolchain("C#", dotnet()) {
+        result.push((
+            csharp_guest(resolve, world, &out_dir.join("csharp"))?,
+            world,
+        ));
+    }
+
+    #[cfg(feature = "moonbit")]
+    if toolchain("MoonBit", "moon") {
+        result.push((
+            moonbit_guest(resolve, world, &out_dir.join("moonbit"), &wasi_adapter)?,
+            world,
+        ));
+    }
+
+    Ok(result)
+}
+
+/// Returns whether `program` can be run to build the `backend` guest, noting
+/// that the guest is skipped if it can't.
+#[allow(dead_code)] // not used by all generators
+fn toolchain(backend: &str, program: impl AsRef<std::ffi::OsStr>) -> bool {
+    let program = program.as_ref();
+    if Command::new(program).arg("--version").output().is_ok() {
+        return true;
+    }
+    println!("skipping the {backend} conformance guest: `{program:?}` wasn't found");
+    false
+}
+
+#[cfg(feature = "rust")]
+fn rust_guest(
+    dir: &Path,
+    resolve: &Resolve,
+    world: WorldId,
+    out_dir: &Path,
+    wasi_adapter: &[u8],
+) -> Result<PathBuf> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let src_dir = out_dir.join("src");
+    fs::create_dir_all(&src_dir)?;
+
+    let mut src = String::new();
+    writeln!(
+        src,
+        "wit_bindgen::generate!({{ path: {:?}, world: {:?} }});",
+        dir.display().to_string(),
+        resolve.worlds[world].name,
+    )?;
+    src.push_str("struct Component;\nexport!(Component);\n");
+    for iface in exported_interfaces(resolve, world) {
+        let iface = &resolve.interfaces[iface];
+        let pkg = &resolve.packages[iface.package.unwrap()].name;
+        let module = format!(
+            "exports::{}::{}::{}",
+            pkg.namespace.to_snake_case(),
+            pkg.name.to_snake_case(),
+            iface.name.as_ref().unwrap().to_snake_case(),
+        );
+
+        let mut guest = format!("impl {module}::Guest for Component {{\n");
+        for (name, func) in iface.functions.iter() {
+            match &func.kind {
+                FunctionKind::Freestanding => {
+                    let ty = echoed_type(resolve, name, func)?;
+                    let ty = format!("{module}::{}", ty.to_upper_camel_case());
+                    writeln!(
+                        guest,
+                        "    fn {}(x: {ty}) -> {ty} {{ x }}",
+                        name.to_snake_case()
+                    )?;
+                }
+                FunctionKind::Constructor(id) => {
+                    let resource = resolve.types[*id]
+                        .name
+                        .as_ref()
+                        .unwrap()
+                        .to_upper_camel_case();
+                    writeln!(guest, "    type {resource} = {resource}Impl;")?;
+                    writeln!(
+                        src,
+                        "\
+struct {resource}Impl(u32);
+
+impl {module}::Guest{resource} for {resource}Impl {{
+    fn new(v: u32) -> Self {{ Self(v) }}
+    fn value(&self) -> u32 {{ self.0 }}
+}}"
+                    )?;
+                }
+                _ => {}
+            }
+        }
+        guest.push_str("}\n");
+        src.push_str(&guest);
+    }
+    fs::write(src_dir.join("lib.rs"), src)?;
+
+    fs::write(
+        out_dir.join("Cargo.toml"),
+        format!(
+            "\
+[package]
+name = \"conformance\"
+version = \"0.0.0\"
+edition = \"2021\"
+publish = false
+
+[lib]
+crate-type = [\"cdylib\"]
+
+[dependencies]
+wit-bindgen = {{ path = {:?} }}
+
+[workspace]
+",
+            root.join("crates/guest-rust").display().to_string(),
+        ),
+    )?;
+    // Reuse the versions already locked for this repository.
+    fs::copy(root.join("Cargo.lock"), out_dir.join("Cargo.lock"))?;
+
+    let target_dir = out_dir.join("target");
+    let mut cmd = Command::new("cargo");
+    cmd.arg("build")
+        .arg("--target")
+        .arg("wasm32-wasip1")
+        .current_dir(out_dir)
+        .env("CARGO_TARGET_DIR", &target_dir)
+        .env_remove("CARGO_ENCODED_RUSTFLAGS");
+    run_compiler(cmd)?;
+
+    let module = fs::read(target_dir.join("wasm32-wasip1/debug/conformance.wasm"))?;
+    let component = ComponentEncoder::default()
+        .module(&module)?
+        .validate(true)
+        .adapter("wasi_snapshot_preview1", wasi_adapter)?
+        .realloc_via_memory_grow(true)
+        .encode()?;
+    let dst = out_dir.join("rust.component.wasm");
+    fs::write(&dst, component)?;
+    Ok(dst)
+}
+
+#[cfg(feature = "c")]
+fn c_guest(
+    resolve: &Resolve,
+    world: WorldId,
+    out_dir: &Path,
+    wasi_adapter: &[u8],
+) -> Result<PathBuf> {
+    drop(fs::remove_dir_all(out_dir));
+    fs::create_dir_all(out_dir)?;
+
+    let mut files = Default::default();
+    wit_bindgen_c::Opts::default()
+        .build()
+        .generate(resolve, world, &mut files)?;
+    for (file, contents) in files.iter() {
+        fs::write(out_dir.join(file), contents)?;
+    }
+
+    let snake = resolve.worlds[world].name.to_snake_case();
+    let mut src = format!("#include \"{snake}.h\"\n#include <stdlib.h>\n");
+    for iface in exported_interfaces(resolve, world) {
+        let iface = &resolve.interfaces[iface];
+        let pkg = &resolve.packages[iface.package.unwrap()].name;
+        let prefix = format!(
+            "exports_{}_{}_{}",
+            pkg.namespace.to_snake_case(),
+            pkg.name.to_snake_case(),
+            iface.name.as_ref().unwrap().to_snake_case(),
+        );
+        for (name, func) in iface.functions.iter() {
+            match &func.kind {
+                FunctionKind::Freestanding => {
+                    let ty = echoed_type(resolve, name, func)?;
+                    let ty = format!("{prefix}_{}_t", ty.to_snake_case());
+                    writeln!(
+                        src,
+                        "void {prefix}_{}({ty} *x, {ty} *ret) {{ *ret = *x; }}",
+                        name.to_snake_case()
+                    )?;
+                }
+                FunctionKind::Constructor(id) => {
+                    let resource = resolve.types[*id].name.as_ref().unwrap().to_snake_case();
+                    let rep = format!("{prefix}_{resource}_t");
+                    writeln!(
+                        src,
+                        "\
+struct {rep} {{ uint32_t v; }};
+
+{prefix}_own_{resource}_t {prefix}_constructor_{resource}(uint32_t v) {{
+    {rep} *rep = malloc(sizeof({rep}));
+    rep->v = v;
+    return {prefix}_{resource}_new(rep);
+}}
+
+uint32_t {prefix}_method_{resource}_value({prefix}_borrow_{resource}_t self) {{
+    return self->v;
+}}
+
+void {prefix}_{resource}_destructor({rep} *rep) {{
+    free(rep);
+}}"
+                    )?;
+                }
+                _ => {}
+            }
+        }
+    }
+    let guest = out_dir.join("guest.c");
+    fs::write(&guest, src)?;
+
+    let sdk = PathBuf::from(
+        std::env::var_os("WASI_SDK_PATH")
+            .expect("point the `WASI_SDK_PATH` environment variable to the path of your wasi-sdk"),
+    );
+    let out_wasm = out_dir.join("c.wasm");
+    let mut cmd = Command::new(sdk.join("bin/clang"));
+    cmd.arg("--sysroot")
+        .arg(sdk.join("share/wasi-sysroot"))
+        .arg(&guest)
+        .arg(out_dir.join(format!("{snake}.c")))
+        .arg(out_dir.join(format!("{snake}_component_type.o")))
+        .arg("-I")
+        .arg(out_dir)
+        .arg("-Wall")
+        .arg("-Wextra")
+        .arg("-Werror")
+        .arg("-Wno-unused-parameter")
+        .arg("-mexec-model=reactor")
+        .arg("-g")
+        .arg("-o")
+        .arg(&out_wasm);
+    run_compiler(cmd)?;
+
+    let module = fs::read(&out_wasm)?;
+    let component = ComponentEncoder::default()
+        .module(&module)?
+        .validate(true)
+        .adapter("wasi_snapshot_preview1", wasi_adapter)?
+        .encode()?;
+    let dst = out_dir.join("c.component.wasm");
+    fs::write(&dst, component)?;
+    Ok(dst)
+}
+
+#[cfg(feature = "cpp")]
+fn cpp_guest(
+    resolve: &Resolve,
+    world: WorldId,
+    out_dir: &Path,
+    wasi_adapter: &[u8],
+) -> Result<PathBuf> {
+    drop(fs::remove_dir_all(out_dir));
+    fs::create_dir_all(out_dir)?;
+
+    let mut files = Default::default();
+    wit_bindgen_cpp::Opts::default()
+        .build()
+        .generate(resolve, world, &mut files)?;
+    for (file, contents) in files.iter() {
+        fs::write(out_dir.join(file), contents)?;
+    }
+
+    let snake = resolve.worlds[world].name.to_snake_case();
+    let mut src = format!("#include \"{snake}.hpp\"\n");
+    for iface in exported_interfaces(resolve, world) {
+        let iface = &resolve.interfaces[iface];
+        let pkg = &resolve.packages[iface.package.unwrap()].name;
+        let namespace = format!(
+            "exports::{}::{}::{}",
+            pkg.namespace.to_snake_case(),
+            pkg.name.to_snake_case(),
+            iface.name.as_ref().unwrap().to_snake_case(),
+        );
+        writeln!(src, "\nnamespace {namespace} {{")?;
+        for (name, func) in iface.functions.iter() {
+            match &func.kind {
+                FunctionKind::Freestanding => {
+                    let ty = echoed_type(resolve, name, func)?;
+                    let ty = ty.to_upper_camel_case();
+                    // Values owning resources are passed by value, everything
+                    // else by reference.
+                    let param = if has_own(resolve, &func.params[0].1) {
+                        format!("{ty} x")
+                    } else {
+                        format!("const {ty} &x")
+                    };
+                    writeln!(
+                        src,
+                        "{ty} {}({param}) {{ return x; }}",
+                        name.to_snake_case()
+                    )?;
+                }
+                FunctionKind::Constructor(id) => {
+                    let resource = resolve.types[*id]
+                        .name
+                        .as_ref()
+                        .unwrap()
+                        .to_upper_camel_case();
+                    writeln!(
+                        src,
+                        "\
+class {resource}Impl : public {resource} {{
+  public:
+  explicit {resource}Impl(uint32_t v) : v_(v) {{}}
+  uint32_t value() override {{ return v_; }}
+
+  private:
+  uint32_t v_;
+}};
+
+{resource}::Own {resource}::create(uint32_t v) {{
+  return {resource}::Own(std::make_unique<{resource}Impl>(v));
+}}"
+                    )?;
+                }
+                _ => {}
+            }
+        }
+        writeln!(src, "}}  // namespace {namespace}")?;
+    }
+    let guest = out_dir.join("guest.cpp");
+    fs::write(&guest, src)?;
+
+    let sdk = PathBuf::from(
+        std::env::var_os("WASI_SDK_PATH")
+            .expect("point the `WASI_SDK_PATH` environment variable to the path of your wasi-sdk"),
+    );
+    let c_obj = out_dir.join(format!("{snake}.o"));
+    let mut cmd = Command::new(sdk.join("bin/clang"));
+    cmd.arg("--sysroot")
+        .arg(sdk.join("share/wasi-sysroot"))
+        .arg(out_dir.join(format!("{snake}.c")))
+        .arg("-I")
+        .arg(out_dir)
+        .arg("-Wall")
+        .arg("-Wextra")
+        .arg("-Werror")
+        .arg("-Wno-unused-parameter")
+        .arg("-g")
+        .arg("-c")
+        .arg("-o")
+        .arg(&c_obj);
+    run_compiler(cmd)?;
+
+    let out_wasm = out_dir.join("cpp.wasm");
+    let mut cmd = Command::new(sdk.join("bin/clang++"));
+    cmd.arg("--sysroot")
+        .arg(sdk.join("share/wasi-sysroot"))
+        .arg(&guest)
+        .arg(out_dir.join(format!("{snake}.cpp")))
+        .arg(&c_obj)
+        .arg(out_dir.join(format!("{snake}_component_type.o")))
+        .arg("-I")
+        .arg(out_dir)
+        .arg("-std=c++23")
+        .arg("-fno-exceptions")
+        .arg("-Wall")
+        .arg("-Wextra")
+        .arg("-Werror")
+        .arg("-Wno-unused-parameter")
+        .arg("-mexec-model=reactor")
+        .arg("-g")
+        .arg("-o")
+        .arg(&out_wasm);
+    run_compiler(cmd)?;
+
+    let module = fs::read(&out_wasm)?;
+    let component = ComponentEncoder::default()
+        .module(&module)?
+        .validate(true)
+        .adapter("wasi_snapshot_preview1", wasi_adapter)?
+        .encode()?;
+    let dst = out_dir.join("cpp.component.wasm");
+    fs::write(&dst, component)?;
+    Ok(dst)
+}
+
+/// Whether `ty` owns a resource, in which case the C++ bindings pass it by
+/// value rather than by reference.
+#[cfg(feature = "cpp")]
+fn has_own(resolve: &Resolve, ty: &wit_parser::Type) -> bool {
+    use wit_parser::{Handle, TypeDefKind};
+
+    let wit_parser::Type::Id(id) = ty else {
+        return false;
+    };
+    match &resolve.types[*id].kind {
+        TypeDefKind::Handle(Handle::Own(_)) => true,
+        TypeDefKind::Record(r) => r.fields.iter().any(|f| has_own(resolve, &f.ty)),
+        TypeDefKind::Variant(v) => v
+            .cases
+            .iter()
+            .any(|c| c.ty.as_ref().is_some_and(|ty| has_own(resolve, ty))),
+        TypeDefKind::Tuple(t) => t.types.iter().any(|ty| has_own(resolve, ty)),
+        TypeDefKind::Result(r) => {
+            r.ok.iter()
+                .chain(r.err.iter())
+                .any(|ty| has_own(resolve, ty))
+        }
+        TypeDefKind::Option(ty) | TypeDefKind::List(ty) | TypeDefKind::Type(ty) => {
+            has_own(resolve, ty)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(feature = "go")]
+fn go_guest(
+    resolve: &Resolve,
+    world: WorldId,
+    out_dir: &Path,
+    wasi_adapter: &[u8],
+) -> Result<PathBuf> {
+    drop(fs::remove_dir_all(out_dir));
+    let gen_dir = out_dir.join("gen");
+    fs::create_dir_all(&gen_dir)?;
+
+    let mut files = Default::default();
+    wit_bindgen_go::Opts::default()
+        .build()
+        .generate(resolve, world, &mut files)?;
+    for (file, contents) in files.iter() {
+        fs::write(gen_dir.join(file), contents)?;
+    }
+
+    let snake = resolve.worlds[world].name.to_snake_case();
+    let mut src = format!("package main\n\nimport (\n\t. \"wit_{snake}_go/gen\"\n)\n");
+    let mut init = String::new();
+    for iface in exported_interfaces(resolve, world) {
+        let iface = &resolve.interfaces[iface];
+        let pkg = &resolve.packages[iface.package.unwrap()].name;
+        let prefix = format!(
+            "Exports{}{}{}",
+            pkg.namespace.to_upper_camel_case(),
+            pkg.name.to_upper_camel_case(),
+            iface.name.as_ref().unwrap().to_upper_camel_case(),
+        );
+        let guest = format!("{prefix}Impl");
+        writeln!(init, "\tSet{prefix}({guest}{{}})")?;
+        writeln!(src, "\ntype {guest} struct{{}}")?;
+        for (name, func) in iface.functions.iter() {
+            match &func.kind {
+                FunctionKind::Freestanding => {
+                    let ty = echoed_type(resolve, name, func)?;
+                    let ty = format!("{prefix}{}", ty.to_upper_camel_case());
+                    writeln!(
+                        src,
+                        "\nfunc ({guest}) {}(x {ty}) {ty} {{ return x }}",
+                        name.to_upper_camel_case()
+                    )?;
+                }
+                FunctionKind::Constructor(id) => {
+                    let resource = resolve.types[*id]
+                        .name
+                        .as_ref()
+                        .unwrap()
+                        .to_upper_camel_case();
+                    writeln!(
+                        src,
+                        "
+type {resource}Impl struct {{ v uint32 }}
+
+func ({guest}) Constructor{resource}(v uint32) {prefix}{resource} {{
+	return &{resource}Impl{{v}}
+}}
+
+func (r *{resource}Impl) Method{resource}Value() uint32 {{ return r.v }}"
+                    )?;
+                }
+                _ => {}
+            }
+        }
+    }
+    writeln!(src, "\nfunc init() {{\n{init}}}\n\nfunc main() {{}}")?;
+    fs::write(out_dir.join(format!("{snake}.go")), src)?;
+    fs::write(
+        out_dir.join("go.mod"),
+        format!("module wit_{snake}_go\n\ngo 1.20"),
+    )?;
+
+    let out_wasm = out_dir.join("go.wasm");
+    let mut cmd = Command::new("tinygo");
+    cmd.arg("build")
+        .arg("-target=wasi")
+        .arg("-o")
+        .arg(&out_wasm)
+        .arg(format!("{snake}.go"))
+        .current_dir(out_dir);
+    run_compiler(cmd)?;
+
+    let mut module = fs::read(&out_wasm)?;
+    embed_component_type(&mut module, resolve, world, StringEncoding::UTF8)?;
+    let component = ComponentEncoder::default()
+        .module(&module)?
+        .validate(true)
+        .adapter("wasi_snapshot_preview1", wasi_adapter)?
+        .encode()?;
+    let dst = out_dir.join("go.component.wasm");
+    fs::write(&dst, component)?;
+    Ok(dst)
+}
+
+#[cfg(feature = "teavm-java")]
+fn java_guest(
+    resolve: &Resolve,
+    world: WorldId,
+    out_dir: &Path,
+    [interop_jar, cli_jar]: &[PathBuf; 2],
+    wasi_adapter: &[u8],
+) -> Result<PathBuf> {
+    drop(fs::remove_dir_all(out_dir));
+    let java_dir = out_dir.join("src/main/java");
+
+    let mut files = Default::default();
+    wit_bindgen_teavm_java::Opts::default()
+        .build()
+        .generate(resolve, world, &mut files)?;
+    let mut sources = Vec::new();
+    for (file, contents) in files.iter() {
+        let dst = java_dir.join(file);
+        fs::create_dir_all(dst.parent().unwrap())?;
+        fs::write(&dst, contents)?;
+        sources.push(dst);
+    }
+
+    for iface in exported_interfaces(resolve, world) {
+        let iface = &resolve.interfaces[iface];
+        let pkg = &resolve.packages[iface.package.unwrap()].name;
+        let package = format!(
+            "wit.exports.{}.{}",
+            pkg.namespace.to_snake_case(),
+            pkg.name.to_snake_case(),
+        );
+        let class = iface.name.as_ref().unwrap().to_upper_camel_case();
+        let mut src = format!("package {package};\n\npublic class {class}Impl {{\n");
+        for (name, func) in iface.functions.iter() {
+            if let FunctionKind::Freestanding = func.kind {
+                let ty = echoed_type(resolve, name, func)?;
+                let ty = format!("{class}.{}", ty.to_upper_camel_case());
+                writeln!(
+                    src,
+                    "    public static {ty} {}({ty} x) {{ return x; }}",
+                    name.to_lower_camel_case()
+                )?;
+            }
+        }
+        src.push_str("}\n");
+        let dst = java_dir
+            .join(package.replace('.', "/"))
+            .join(format!("{class}Impl.java"));
+        fs::write(&dst, src)?;
+        sources.push(dst);
+    }
+    let main = java_dir.join("Main.java");
+    fs::write(
+        &main,
+        include_bytes!("../../crates/teavm-java/tests/Main.java"),
+    )?;
+    sources.push(main);
+
+    let classes = out_dir.join("target/classes");
+    let mut cmd = Command::new("javac");
+    cmd.arg("-cp")
+        .arg(interop_jar)
+        .arg("-d")
+        .arg(&classes)
+        .args(&sources);
+    run_compiler(cmd)?;
+
+    let wasm_dir = out_dir.join("target/generated/wasm/teavm-wasm");
+    let mut cmd = Command::new("java");
+    cmd.arg("-jar")
+        .arg(cli_jar)
+        .arg("-p")
+        .arg(&classes)
+        .arg("-d")
+        .arg(&wasm_dir)
+        .arg("-t")
+        .arg("wasm")
+        .arg("-g")
+        .arg("-O")
+        .arg("1");
+    for file in &sources {
+        let class = file.strip_prefix(&java_dir)?.with_extension("");
+        cmd.arg("--preserve-class")
+            .arg(class.to_str().unwrap().replace('/', "."));
+    }
+    cmd.arg("Main");
+    run_compiler(cmd)?;
+
+    let module = fs::read(wasm_dir.join("classes.wasm"))?;
+    let component = ComponentEncoder::default()
+        .module(&module)?
+        .validate(true)
+        .adapter("wasi_snapshot_preview1", wasi_adapter)?
+        .encode()?;
+    let dst = out_dir.join("teavm-java.component.wasm");
+    fs::write(&dst, component)?;
+    Ok(dst)
+}
+
+/// Returns the `dotnet` executable, found in `DOTNET_ROOT` if it's set.
+#[cfg(feature = "csharp")]
+fn dotnet() -> PathBuf {
+    match std::env::var_os("DOTNET_ROOT") {
+        Some(root) => Path::new(&root).join("dotnet"),
+        None => "dotnet".into(),
+    }
+}
+
+#[cfg(feature = "csharp")]
+fn csharp_guest(resolve: &Resolve, world: WorldId, out_dir: &Path) -> Result<PathBuf> {
+    drop(fs::remove_dir_all(out_dir));
+    fs::create_dir_all(out_dir)?;
+
+    let mut files = Default::default();
+    wit_bindgen_csharp::Opts::default()
+        .build()
+        .generate(resolve, world, &mut files)?;
+    for (file, contents) in files.iter() {
+        fs::write(out_dir.join(file), contents)?;
+    }
+
+    let world_name = &resolve.worlds[world].name;
+    let camel = format!("{}World", world_name.to_upper_camel_case());
+    let mut src = String::new();
+    for iface in exported_interfaces(resolve, world) {
+        let iface = &resolve.interfaces[iface];
+        let pkg = &resolve.packages[iface.package.unwrap()].name;
+        let class = iface.name.as_ref().unwrap().to_upper_camel_case();
+        writeln!(
+            src,
+            "namespace {camel}.wit.exports.{}.{}\n{{\n    public class {class}Impl : I{class}\n    {{",
+            pkg.namespace.to_snake_case(),
+            pkg.name.to_snake_case(),
+        )?;
+        for (name, func) in iface.functions.iter() {
+            match &func.kind {
+                FunctionKind::Freestanding => {
+                    let ty = echoed_type(resolve, name, func)?;
+                    let ty = format!("I{class}.{}", ty.to_upper_camel_case());
+                    writeln!(
+                        src,
+                        "        public static {ty} {}({ty} x) {{ return x; }}",
+                        name.to_upper_camel_case()
+                    )?;
+                }
+                FunctionKind::Constructor(id) => {
+                    let resource = resolve.types[*id]
+                        .name
+                        .as_ref()
+                        .unwrap()
+                        .to_upper_camel_case();
+                    writeln!(
+                        src,
+                        "\
+        public class {resource} : I{class}.{resource}, I{class}.I{resource}
+        {{
+            private uint v;
+
+            public {resource}(uint v) {{ this.v = v; }}
+
+            public uint Value() {{ return v; }}
+        }}"
+                    )?;
+                }
+                _ => {}
+            }
+        }
+        src.push_str("    }\n}\n\n");
+    }
+    fs::write(out_dir.join("Guest.cs"), src)?;
+
+    let assembly_name = "csharp-conformance";
+    let mut csproj =
+        wit_bindgen_csharp::CSProject::new(out_dir.to_path_buf(), assembly_name, world_name);
+    csproj.aot();
+    csproj.generate()?;
+
+    let out_wasm = out_dir.join(assembly_name);
+    let mut cmd = Command::new(dotnet());
+    cmd.current_dir(out_dir)
+        .arg("publish")
+        .arg(out_dir.join(format!("{camel}.csproj")))
+        .arg("-r")
+        .arg("wasi-wasm")
+        .arg("-c")
+        .arg("Debug")
+        .arg("/p:PlatformTarget=AnyCPU")
+        .arg("/p:MSBuildEnableWorkloadResolver=false")
+        .arg("--self-contained")
+        .arg("/p:UseAppHost=false")
+        .arg("-o")
+        .arg(&out_wasm);
+    run_compiler(cmd)?;
+
+    // NativeAOT-LLVM produces a component directly.
+    Ok(out_wasm.join(format!("{assembly_name}.wasm")))
+}
+
+#[cfg(feature = "moonbit")]
+fn moonbit_guest(
+    resolve: &Resolve,
+    world: WorldId,
+    out_dir: &Path,
+    wasi_adapter: &[u8],
+) -> Result<PathBuf> {
+    drop(fs::remove_dir_all(out_dir));
+
+    let mut files = Default::default();
+    wit_bindgen_moonbit::Opts {
+        gen_dir: "gen".to_string(),
+        ..Default::default()
+    }
+    .build()
+    .generate(resolve, world, &mut files)?;
+    for (file, contents) in files.iter() {
+        let dst = out_dir.join(file);
+        fs::create_dir_all(dst.parent().unwrap())?;
+        fs::write(&dst, contents)?;
+    }
+
+    // Each exported interface is implemented by replacing its generated stub.
+    for iface in exported_interfaces(resolve, world) {
+        let iface = &resolve.interfaces[iface];
+        let suffix = format!(
+            "/{}/stub.mbt",
+            iface.name.as_ref().unwrap().to_lower_camel_case()
+        );
+        let stub = files
+            .iter()
+            .map(|(file, _)| file)
+            .find(|file| file.starts_with("gen/interface/") && file.ends_with(&suffix))
+            .with_context(|| format!("no stub generated for `{suffix}`"))?;
+
+        let mut src = String::new();
+        for (name, func) in iface.functions.iter() {
+            match &func.kind {
+                FunctionKind::Freestanding => {
+                    let ty = echoed_type(resolve, name, func)?;
+                    let ty = ty.to_upper_camel_case();
+                    writeln!(
+                        src,
+                        "pub fn {}(x : {ty}) -> {ty} {{\n  x\n}}\n",
+                        name.to_snake_case()
+                    )?;
+                }
+                FunctionKind::Constructor(id) => {
+                    let name = resolve.types[*id].name.as_ref().unwrap();
+                    let resource = name.to_upper_camel_case();
+                    let snake = name.to_snake_case();
+                    writeln!(
+                        src,
+                        "\
+// The value each live `{resource}` was constructed with, keyed by its rep.
+let {snake}_values : Map[Int, UInt] = {{}}
+
+let next_{snake}_rep : Ref[Int] = {{ val: 0 }}
+
+pub fn {resource}::dtor(self : {resource}) -> Unit {{
+  let {resource}(rep) = self
+  {snake}_values.remove(rep)
+}}
+
+pub fn {resource}::{snake}(v : UInt) -> {resource} {{
+  next_{snake}_rep.val += 1
+  {snake}_values[next_{snake}_rep.val] = v
+  {resource}::new(next_{snake}_rep.val)
+}}
+
+pub fn {resource}::value(self : {resource}) -> UInt {{
+  let {resource}(rep) = self
+  {snake}_values.get(rep).unwrap()
+}}
+"
+                    )?;
+                }
+                _ => {}
+            }
+        }
+        fs::write(out_dir.join(stub), src)?;
+    }
+
+    let mut cmd = Command::new("moon");
+    cmd.arg("build")
+        .arg("--target")
+        .arg("wasm")
+        .arg("--debug")
+        .arg("--source-dir")
+        .arg(out_dir);
+    run_compiler(cmd)?;
+
+    let mut module = fs::read(out_dir.join("target/wasm/debug/build/gen/gen.wasm"))?;
+    embed_component_type(&mut module, resolve, world, StringEncoding::UTF16)?;
+    let component = ComponentEncoder::default()
+        .module(&module)?
+        .validate(true)
+        .adapter("wasi_snapshot_preview1", wasi_adapter)?
+        .encode()?;
+    let dst = out_dir.join("moonbit.component.wasm");
+    fs::write(&dst, component)?;
+    Ok(dst)
+}
+
+/// Appends the `component-type` custom section describing `world` to
+/// `module`, for toolchains which don't embed it themselves.
+#[allow(dead_code)] // not used by all generators
+fn embed_component_type(
+    module: &mut Vec<u8>,
+    resolve: &Resolve,
+    world: WorldId,
+    encoding: StringEncoding,
+) -> Result<()> {
+    use wasm_encoder::{Encode, Section};
+
+    let encoded = wit_component::metadata::encode(resolve, world, encoding, None)?;
+    let section = wasm_encoder::CustomSection {
+        name: std::borrow::Cow::Borrowed("component-type"),
+        data: std::borrow::Cow::Borrowed(&encoded),
+    };
+    module.push(section.id());
+    section.encode(module);
+    Ok(())
+}
+
+/// Checks that `func` is `echo-<ty>: func(x: <ty>) -> <ty>` and returns the
+/// name of `<ty>`.
+#[allow(dead_code)] // not used by all generators
+fn echoed_type<'a>(
+    resolve: &'a Resolve,
+    name: &str,
+    func: &wit_parser::Function,
+) -> Result<&'a str> {
+    let results = func.results.iter_types().collect::<Vec<_>>();
+    let ty = match (&func.params[..], &results[..]) {
+        ([(_, wit_parser::Type::Id(param))], [wit_parser::Type::Id(result)]) if param == result => {
+            *param
+        }
+        _ => bail!("`{name}` must take and return the same named type"),
+    };
+    match &resolve.types[ty].name {
+        Some(ty) => Ok(ty),
+        None => bail!("`{name}` must take and return the same named type"),
+    }
+}
+
+#[allow(dead_code)] // not used by all generators
+fn run_compiler(mut cmd: Command) -> Result<()> {
+    let command = format!("{cmd:?}");
+    let output = cmd
+        .output()
+        .with_context(|| format!("failed to spawn `{command}`"))?;
+    if !output.status.success() {
+        println!("status: {}", output.status);
+        println!("stdout: ------------------------------------------");
+        println!("{}", String::from_utf8_lossy(&output.stdout));
+        println!("stderr: ------------------------------------------");
+        println!("{}", String::from_utf8_lossy(&output.stderr));
+        bail!("failed to compile: `{command}`");
+    }
+    Ok(())
+}
+
+/// Instantiates `wasm` and passes `ITERATIONS` random values through each echo
+/// function it exports.
+fn round_trip(wasm: &Path, resolve: &Resolve, world: WorldId, seed: u64) -> Result<()> {
+    let mut config = Config::new();
+    config.cache_config_load_default()?;
+    config.wasm_backtrace_details(wasmtime::WasmBacktraceDetails::Enable);
+    config.wasm_component_model(true);
+    let engine = Engine::new(&config)?;
+
+    let component = Component::from_file(&engine, wasm)?;
+    let mut linker = Linker::new(&engine);
+    wasmtime_wasi::add_to_linker_sync(&mut linker)?;
+    let wasi: WasiCtx = WasiCtxBuilder::new().inherit_stdout().args(&[""]).build();
+    let data = crate::Wasi((), crate::MyCtx {}, ResourceTable::new(), wasi);
+    let mut store = Store::new(&engine, data);
+    let instance = linker.instantiate(&mut store, &component)?;
+
+    for iface in exported_interfaces(resolve, world) {
+        let name = resolve.id_of(iface).unwrap();
+        let export = instance
+            .get_export(&mut store, None, &name)
+            .with_context(|| format!("missing export `{name}`"))?;
+
+        let mut gen = Generator {
+            rng: Rng(seed | 1),
+            constructors: Vec::new(),
+            values: Vec::new(),
+        };
+        let mut echoes = Vec::new();
+        for (func_name, func) in resolve.interfaces[iface].functions.iter() {
+            let index = instance
+                .get_export(&mut store, Some(&export), func_name)
+                .with_context(|| format!("missing export `{func_name}`"))?;
+            let f = instance.get_func(&mut store, &index).unwrap();
+            match &func.kind {
+                FunctionKind::Freestanding => echoes.push((func_name, f)),
+                FunctionKind::Constructor(_) => {
+                    let ty = match &f.results(&store)[..] {
+                        [Type::Own(ty)] => *ty,
+                        _ => bail!("unexpected signature for `{func_name}`"),
+                    };
+                    gen.constructors.push((ty, f));
+                }
+                FunctionKind::Method(_) if func_name.ends_with(".value") => {
+                    let ty = match &f.params(&store)[..] {
+                        [Type::Borrow(ty)] => *ty,
+                        _ => bail!("unexpected signature for `{func_name}`"),
+                    };
+                    gen.values.push((ty, f));
+                }
+                _ => {}
+            }
+        }
+
+        for (func_name, func) in echoes {
+            let ty = match &func.params(&store)[..] {
+                [ty] => ty.clone(),
+                _ => bail!("`{func_name}` must take a single parameter"),
+            };
+            for _ in 0..ITERATIONS {
+                let (arg, expected) = gen.arbitrary(&mut store, &ty, 0)?;
+                let mut results = [Val::Bool(false)];
+                func.call(&mut store, &[arg], &mut results)?;
+                func.post_return(&mut store)?;
+                let [result] = results;
+                let actual = gen.observe(&mut store, result)?;
+                if actual != expected {
+                    bail!(
+                        "`{func_name}` did not echo its argument\n\
+                         expected: {expected:?}\n  \
+                         actual: {actual:?}"
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+type TestStore = Store<crate::Wasi<()>>;
+
+/// Produces random values for component types.
+struct Generator {
+    rng: Rng,
+    /// The `[constructor]` of each exported resource.
+    constructors: Vec<(ResourceType, Func)>,
+    /// The `value` method of each exported resource.
+    values: Vec<(ResourceType, Func)>,
+}
+
+impl Generator {
+    /// Returns a random value of type `ty` along with the value expected back
+    /// from an echo of it.
+    ///
+    /// The two are identical except that resources in the expected value are
+    /// replaced with the `u32` they were constructed with, since handles change
+    /// as they move in and out of a guest.
+    fn arbitrary(&mut self, store: &mut TestStore, ty: &Type, depth: u32) -> Result<(Val, Val)> {
+        let val = match ty {
+            Type::Bool => Val::Bool(self.rng.next() & 1 == 1),
+            Type::S8 => Val::S8(self.rng.next() as i8),
+            Type::U8 => Val::U8(self.rng.next() as u8),
+            Type::S16 => Val::S16(self.rng.next() as i16),
+            Type::U16 => Val::U16(self.rng.next() as u16),
+            Type::S32 => Val::S32(self.rng.next() as i32),
+            Type::U32 => Val::U32(self.rng.next() as u32),
+            Type::S64 => Val::S64(self.rng.next() as i64),
+            Type::U64 => Val::U64(self.rng.next()),
+            Type::Float32 => Val::Float32(f32::from_bits(self.rng.next() as u32)),
+            Type::Float64 => Val::Float64(f64::from_bits(self.rng.next())),
+            Type::Char => Val::Char(self.char()),
+            Type::String => {
+                let len = self.rng.below(8);
+                Val::String((0..len).map(|_| self.char()).collect())
+            }
+            Type::List(list) => {
+                let len = if depth < MAX_DEPTH {
+                    self.rng.below(4)
+                } else {
+                    0
+                };
+                let ty = list.ty();
+                let (vals, expected) = self.many(store, (0..len).map(|_| ty.clone()), depth)?;
+                return Ok((Val::List(vals), Val::List(expected)));
+            }
+            Type::Record(record) => {
+                let names = record
+                    .fields()
+                    .map(|f| f.name.to_string())
+                    .collect::<Vec<_>>();
+                let (vals, expected) = self.many(store, record.fields().map(|f| f.ty), depth)?;
+                return Ok((
+                    Val::Record(names.iter().cloned().zip(vals).collect()),
+                    Val::Record(names.into_iter().zip(expected).collect()),
+                ));
+            }
+            Type::Tuple(tuple) => {
+                let (vals, expected) = self.many(store, tuple.types(), depth)?;
+                return Ok((Val::Tuple(vals), Val::Tuple(expected)));
+            }
+            Type::Variant(variant) => {
+                let cases = variant.cases().collect::<Vec<_>>();
+                let case = &cases[self.rng.below(cases.len())];
+                let (val, expected) = self.optional(store, case.ty.as_ref(), depth)?;
+                return Ok((
+                    Val::Variant(case.name.to_string(), val),
+                    Val::Variant(case.name.to_string(), expected),
+                ));
+            }
+            Type::Enum(e) => {
+                let names = e.names().collect::<Vec<_>>();
+                Val::Enum(names[self.rng.below(names.len())].to_string())
+            }
+            Type::Option(option) => {
+                let (val, expected) = if self.rng.next() & 1 == 1 {
+                    self.optional(store, Some(&option.ty()), depth)?
+                } else {
+                    (None, None)
+                };
+                return Ok((Val::Option(val), Val::Option(expected)));
+            }
+            Type::Result(result) => {
+                return Ok(if self.rng.next() & 1 == 1 {
+                    let (val, expected) = self.optional(store, result.ok().as_ref(), depth)?;
+                    (Val::Result(Ok(val)), Val::Result(Ok(expected)))
+                } else {
+                    let (val, expected) = self.optional(store, result.err().as_ref(), depth)?;
+                    (Val::Result(Err(val)), Val::Result(Err(expected)))
+                });
+            }
+            Type::Flags(flags) => Val::Flags(
+                flags
+                    .names()
+                    .filter(|_| self.rng.next() & 1 == 1)
+                    .map(|name| name.to_string())
+                    .collect(),
+            ),
+            Type::Own(ty) => {
+                let v = self.rng.next() as u32;
+                let ctor =
+                    lookup(&self.constructors, *ty).context("no constructor found for resource")?;
+                let mut results = [Val::Bool(false)];
+                ctor.call(&mut *store, &[Val::U32(v)], &mut results)?;
+                ctor.post_return(&mut *store)?;
+                let [resource] = results;
+                return Ok((resource, Val::U32(v)));
+            }
+            Type::Borrow(_) => bail!("borrowed handles can't be echoed"),
+        };
+        Ok((val.clone(), val))
+    }
+
+    fn many(
+        &mut self,
+        store: &mut TestStore,
+        tys: impl Iterator<Item = Type>,
+        depth: u32,
+    ) -> Result<(Vec<Val>, Vec<Val>)> {
+        let mut vals = Vec::new();
+        let mut expected = Vec::new();
+        for ty in tys {
+            let (val, e) = self.arbitrary(store, &ty, depth + 1)?;
+            vals.push(val);
+            expected.push(e);
+        }
+        Ok((vals, expected))
+    }
+
+    fn optional(
+        &mut self,
+        store: &mut TestStore,
+        ty: Option<&Type>,
+        depth: u32,
+    ) -> Result<(Option<Box<Val>>, Option<Box<Val>>)> {
+        Ok(match ty {
+            Some(ty) => {
+                let (val, expected) = self.arbitrary(store, ty, depth + 1)?;
+                (Some(Box::new(val)), Some(Box::new(expected)))
+            }
+            None => (None, None),
+        })
+    }
+
+    /// Converts a value returned from a guest into the form produced as the
+    /// expected value by `arbitrary`, dropping any resources within it.
+    fn observe(&mut self, store: &mut TestStore, val: Val) -> Result<Val> {
+        Ok(match val {
+            Val::List(vals) => Val::List(self.observe_all(store, vals)?),
+            Val::Tuple(vals) => Val::Tuple(self.observe_all(store, vals)?),
+            Val::Record(fields) => {
+                let (names, vals): (Vec<_>, Vec<_>) = fields.into_iter().unzip();
+                Val::Record(
+                    names
+                        .into_iter()
+                        .zip(self.observe_all(store, vals)?)
+                        .collect(),
+                )
+            }
+            Val::Variant(name, val) => Val::Variant(name, self.observe_optional(store, val)?),
+            Val::Option(val) => Val::Option(self.observe_optional(store, val)?),
+            Val::Result(Ok(val)) => Val::Result(Ok(self.observe_optional(store, val)?)),
+            Val::Result(Err(val)) => Val::Result(Err(self.observe_optional(store, val)?)),
+            Val::Resource(resource) => self.observe_resource(store, resource)?,
+            val => val,
+        })
+    }
+
+    fn observe_all(&mut self, store: &mut TestStore, vals: Vec<Val>) -> Result<Vec<Val>> {
+        vals.into_iter().map(|v| self.observe(store, v)).collect()
+    }
+
+    fn observe_optional(
+        &mut self,
+        store: &mut TestStore,
+        val: Option<Box<Val>>,
+    ) -> Result<Option<Box<Val>>> {
+        Ok(match val {
+            Some(val) => Some(Box::new(self.observe(store, *val)?)),
+            None => None,
+        })
+    }
+
+    fn observe_resource(&mut self, store: &mut TestStore, resource: ResourceAny) -> Result<Val> {
+        let value =
+            lookup(&self.values, resource.ty()).context("no `value` method found for resource")?;
+        let mut results = [Val::Bool(false)];
+        value.call(&mut *store, &[Val::Resource(resource)], &mut results)?;
+        value.post_return(&mut *store)?;
+        resource.resource_drop(&mut *store)?;
+        let [result] = results;
+        Ok(result)
+    }
+
+    fn char(&mut self) -> char {
+        loop {
+            // Bias towards ASCII so that strings are readable in failure
+            // messages while still covering multi-byte encodings.
+            let limit = if self.rng.next() & 1 == 1 {
+                0x80
+            } else {
+                0x11_0000
+            };
+            if let Some(c) = char::from_u32(self.rng.below(limit) as u32) {
+                return c;
+            }
+        }
+    }
+}
+
+fn lookup(funcs: &[(ResourceType, Func)], ty: ResourceType) -> Option<Func> {
+    funcs.iter().find(|(t, _)| *t == ty).map(|(_, f)| *f)
+}
+
+/// A small xorshift generator, which is plenty for picking test values and
+/// keeps runs reproducible from a single seed.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
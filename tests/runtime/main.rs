@@ -13,6 +13,9 @@ use wasmtime_wasi::{WasiCtx, WasiCtxBuilder, WasiView};
 use wit_component::{ComponentEncoder, StringEncoding};
 use wit_parser::{Resolve, WorldId, WorldItem};
 
+#[cfg(feature = "c")]
+mod checked_lifts;
+mod conformance;
 mod flavorful;
 mod lists;
 mod many_arguments;
@@ -771,7 +774,7 @@ fn tests(name: &str, dir_name: &str) -> Result<Vec<PathBuf>> {
 }
 
 #[allow(dead_code)] // not used by all generators
-fn resolve_wit_dir(dir: &PathBuf) -> (Resolve, WorldId) {
+fn resolve_wit_dir(dir: &Path) -> (Resolve, WorldId) {
     let mut resolve = Resolve::new();
     let (pkg, _files) = resolve.push_path(dir).unwrap();
     let world = resolve.select_world(pkg, None).unwrap();